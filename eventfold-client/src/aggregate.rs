@@ -0,0 +1,37 @@
+//! [`StreamStore`] over gRPC, so a [`Repository`](eventfold_db::Repository)
+//! can run against a remote server.
+
+use eventfold_db::{AppendResult, ExpectedVersion, ProposedEvent, RecordedEvent, StreamStore};
+use uuid::Uuid;
+
+use crate::client::Client;
+use crate::error::ClientError;
+
+impl StreamStore for Client {
+    type Error = ClientError;
+
+    async fn read_stream(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        max_count: u64,
+    ) -> Result<Vec<RecordedEvent>, ClientError> {
+        match Client::read_stream(self, stream_id, from_version, max_count).await {
+            Err(e) if e.code() == Some(tonic::Code::NotFound) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    async fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+    ) -> Result<AppendResult, ClientError> {
+        Client::append(self, stream_id, expected_version, events).await
+    }
+
+    fn is_conflict(error: &ClientError) -> bool {
+        error.is_wrong_expected_version()
+    }
+}
@@ -0,0 +1,441 @@
+//! The typed EventfoldDB client.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use eventfold_db::proto;
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::{AppendResult, ExpectedVersion, ProposedEvent, RecordedEvent, StreamInfo};
+use futures_core::Stream;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use uuid::Uuid;
+
+use crate::convert::{
+    expected_version_to_proto, proposed_to_proto, recorded_from_proto, stream_info_from_proto,
+};
+use crate::error::ClientError;
+use crate::options::{ClientOptions, RetryPolicy, TlsOptions};
+use crate::subscription::{Subscription, SubscriptionTarget};
+
+/// Metadata key the server uses to suggest a retry delay on `RESOURCE_EXHAUSTED`.
+const RETRY_PUSHBACK_HEADER: &str = "grpc-retry-pushback-ms";
+
+/// Events yielded one at a time by the paginated reads.
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<RecordedEvent, ClientError>> + Send>>;
+
+/// Adds the bearer token to every request.
+#[derive(Debug, Clone)]
+pub(crate) struct BearerToken(Option<MetadataValue<Ascii>>);
+
+impl Interceptor for BearerToken {
+    fn call(
+        &mut self,
+        mut request: tonic::Request<()>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        if let Some(token) = &self.0 {
+            request
+                .metadata_mut()
+                .insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
+}
+
+/// Generated client with the bearer token interceptor.
+pub(crate) type Inner = EventStoreClient<InterceptedService<Channel, BearerToken>>;
+
+/// A connection to an EventfoldDB server.
+///
+/// Cloning is cheap and clones share the underlying HTTP/2 connection, so a
+/// single `Client` can be shared across tasks. Every method takes `&self`.
+///
+/// Unary calls are retried on transient failures according to the
+/// [`RetryPolicy`] in the [`ClientOptions`].
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: Inner,
+    retry: RetryPolicy,
+    request_timeout: Option<Duration>,
+}
+
+impl Client {
+    /// Connect to the server described by `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidConfig`] if the endpoint, TLS material,
+    /// or token is malformed, and [`ClientError::ConnectionFailed`] if the
+    /// server cannot be reached.
+    pub async fn connect(options: ClientOptions) -> Result<Client, ClientError> {
+        let mut endpoint = Endpoint::from_shared(options.endpoint.clone())
+            .map_err(|e| ClientError::InvalidConfig(describe(&e)))?;
+        if let Some(timeout) = options.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(tls) = &options.tls {
+            endpoint = endpoint
+                .tls_config(tls_config(tls))
+                .map_err(|e| ClientError::InvalidConfig(describe(&e)))?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| ClientError::ConnectionFailed(describe(&e)))?;
+        Client::from_channel(channel, &options)
+    }
+
+    /// Build a client on an existing channel, using the token, timeout, and
+    /// retry settings of `options`. The endpoint and TLS settings are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidConfig`] if the token cannot be sent as a
+    /// header value.
+    pub fn from_channel(channel: Channel, options: &ClientOptions) -> Result<Client, ClientError> {
+        let token = options
+            .token
+            .as_ref()
+            .map(|token| {
+                format!("Bearer {token}")
+                    .parse::<MetadataValue<Ascii>>()
+                    .map_err(|_| {
+                        ClientError::InvalidConfig("token is not a valid header value".into())
+                    })
+            })
+            .transpose()?;
+        Ok(Client {
+            inner: EventStoreClient::with_interceptor(channel, BearerToken(token)),
+            retry: options.retry,
+            request_timeout: options.request_timeout,
+        })
+    }
+
+    /// Append `events` to `stream_id` atomically.
+    ///
+    /// Retried on transient failures with the same event IDs, so a retry of
+    /// an append that did reach the log returns the original positions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Grpc`] with `FAILED_PRECONDITION` if
+    /// `expected_version` does not match (see
+    /// [`ClientError::is_wrong_expected_version`]), or with the status of any
+    /// other failure once retries are exhausted.
+    pub async fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+    ) -> Result<AppendResult, ClientError> {
+        let message = proto::AppendRequest {
+            stream_id: stream_id.to_string(),
+            expected_version: Some(expected_version_to_proto(expected_version)),
+            events: events.iter().map(proposed_to_proto).collect(),
+        };
+        let response = self
+            .call(
+                |mut inner, request| async move { inner.append(request).await },
+                message,
+            )
+            .await?;
+        Ok(AppendResult {
+            first_stream_version: response.first_stream_version,
+            last_stream_version: response.last_stream_version,
+            first_global_position: response.first_global_position,
+            last_global_position: response.last_global_position,
+        })
+    }
+
+    /// Read up to `max_count` events of `stream_id` from `from_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Grpc`] with `NOT_FOUND` if the stream does not
+    /// exist.
+    pub async fn read_stream(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        max_count: u64,
+    ) -> Result<Vec<RecordedEvent>, ClientError> {
+        let message = proto::ReadStreamRequest {
+            stream_id: stream_id.to_string(),
+            from_version,
+            max_count,
+        };
+        let response = self
+            .call(
+                |mut inner, request| async move { inner.read_stream(request).await },
+                message,
+            )
+            .await?;
+        response
+            .events
+            .into_iter()
+            .map(recorded_from_proto)
+            .collect()
+    }
+
+    /// Read up to `max_count` events from the global log from `from_position`.
+    pub async fn read_all(
+        &self,
+        from_position: u64,
+        max_count: u64,
+    ) -> Result<Vec<RecordedEvent>, ClientError> {
+        let message = proto::ReadAllRequest {
+            from_position,
+            max_count,
+            from_timestamp: None,
+        };
+        let response = self
+            .call(
+                |mut inner, request| async move { inner.read_all(request).await },
+                message,
+            )
+            .await?;
+        response
+            .events
+            .into_iter()
+            .map(recorded_from_proto)
+            .collect()
+    }
+
+    /// Every event of `stream_id` from `from_version` to the current end,
+    /// fetched in pages of `page_size`.
+    ///
+    /// The stream ends after the first short page. Events appended while it
+    /// runs may or may not be included; use [`Client::subscribe_stream`] to
+    /// keep following the stream.
+    pub fn read_stream_paged(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        page_size: u64,
+    ) -> EventStream {
+        let client = self.clone();
+        let page_size = page_size.max(1);
+        Box::pin(async_stream::try_stream! {
+            let mut next = from_version;
+            loop {
+                let page = client.read_stream(stream_id, next, page_size).await?;
+                let full = page.len() as u64 == page_size;
+                for event in page {
+                    next = event.stream_version + 1;
+                    yield event;
+                }
+                if !full {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Every event of the global log from `from_position` to the current end,
+    /// fetched in pages of `page_size`.
+    ///
+    /// The stream ends after the first short page. Use
+    /// [`Client::subscribe_all`] to keep following the log.
+    pub fn read_all_paged(&self, from_position: u64, page_size: u64) -> EventStream {
+        let client = self.clone();
+        let page_size = page_size.max(1);
+        Box::pin(async_stream::try_stream! {
+            let mut next = from_position;
+            loop {
+                let page = client.read_all(next, page_size).await?;
+                let full = page.len() as u64 == page_size;
+                for event in page {
+                    next = event.global_position + 1;
+                    yield event;
+                }
+                if !full {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// The first global position recorded at or after `timestamp` (Unix
+    /// milliseconds), or the next position to be assigned if every event is
+    /// older.
+    pub async fn get_position_at(&self, timestamp: u64) -> Result<u64, ClientError> {
+        let message = proto::GetPositionAtRequest { timestamp };
+        let response = self
+            .call(
+                |mut inner, request| async move { inner.get_position_at(request).await },
+                message,
+            )
+            .await?;
+        Ok(response.position)
+    }
+
+    /// Every stream with its event count and latest version, sorted by ID.
+    pub async fn list_streams(&self) -> Result<Vec<StreamInfo>, ClientError> {
+        let response = self
+            .call(
+                |mut inner, request| async move { inner.list_streams(request).await },
+                proto::ListStreamsRequest {},
+            )
+            .await?;
+        response
+            .streams
+            .into_iter()
+            .map(stream_info_from_proto)
+            .collect()
+    }
+
+    /// Follow the global log from `from_position`: historical events, a
+    /// `CaughtUp` marker, then live events.
+    ///
+    /// The subscription reconnects on its own; see [`Subscription`].
+    pub fn subscribe_all(&self, from_position: u64) -> Subscription {
+        Subscription::new(self.clone(), SubscriptionTarget::All { from_position })
+    }
+
+    /// Follow one stream from `from_version`, like [`Client::subscribe_all`].
+    pub fn subscribe_stream(&self, stream_id: Uuid, from_version: u64) -> Subscription {
+        Subscription::new(
+            self.clone(),
+            SubscriptionTarget::Stream {
+                stream_id,
+                from_version,
+            },
+        )
+    }
+
+    /// The generated client, for the subscription task.
+    pub(crate) fn inner(&self) -> Inner {
+        self.inner.clone()
+    }
+
+    /// The retry policy, for the subscription task.
+    pub(crate) fn retry(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Send a unary call built from `message`, retrying transient failures.
+    async fn call<M, T, F, Fut>(&self, send: F, message: M) -> Result<T, ClientError>
+    where
+        M: Clone,
+        F: Fn(Inner, tonic::Request<M>) -> Fut,
+        Fut: Future<Output = Result<tonic::Response<T>, tonic::Status>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let mut request = tonic::Request::new(message.clone());
+            if let Some(timeout) = self.request_timeout {
+                request.set_timeout(timeout);
+            }
+            let status = match send(self.inner.clone(), request).await {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) => status,
+            };
+            if attempt >= self.retry.max_attempts || !is_retryable(&status) {
+                return Err(status.into());
+            }
+            let delay = retry_pushback(&status).unwrap_or_else(|| self.retry.backoff(attempt));
+            tracing::debug!(
+                code = ?status.code(),
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "retrying EventfoldDB call"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Whether a unary call that failed with `status` may be retried.
+fn is_retryable(status: &tonic::Status) -> bool {
+    matches!(
+        status.code(),
+        tonic::Code::Unavailable | tonic::Code::ResourceExhausted | tonic::Code::DeadlineExceeded
+    )
+}
+
+/// The retry delay the server suggested, if any.
+pub(crate) fn retry_pushback(status: &tonic::Status) -> Option<Duration> {
+    let millis = status
+        .metadata()
+        .get(RETRY_PUSHBACK_HEADER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+/// Build the tonic TLS configuration for `tls`.
+fn tls_config(tls: &TlsOptions) -> ClientTlsConfig {
+    let mut config = ClientTlsConfig::new();
+    if let Some(ca_pem) = &tls.ca_pem {
+        config = config.ca_certificate(Certificate::from_pem(ca_pem));
+    }
+    if let Some((cert_pem, key_pem)) = &tls.identity {
+        config = config.identity(Identity::from_pem(cert_pem, key_pem));
+    }
+    if let Some(domain_name) = &tls.domain_name {
+        config = config.domain_name(domain_name);
+    }
+    config
+}
+
+/// An error message including its sources, since tonic's transport errors
+/// only say "transport error" at the top level.
+fn describe(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_codes_are_retryable() {
+        assert!(is_retryable(&tonic::Status::unavailable("down")));
+        assert!(is_retryable(&tonic::Status::resource_exhausted("quota")));
+        assert!(is_retryable(&tonic::Status::deadline_exceeded("slow")));
+        assert!(!is_retryable(&tonic::Status::failed_precondition(
+            "wrong expected version"
+        )));
+        assert!(!is_retryable(&tonic::Status::permission_denied("no")));
+    }
+
+    #[test]
+    fn retry_pushback_is_read_from_metadata() {
+        let mut status = tonic::Status::resource_exhausted("quota");
+        status
+            .metadata_mut()
+            .insert(RETRY_PUSHBACK_HEADER, "250".parse().expect("valid value"));
+        assert_eq!(retry_pushback(&status), Some(Duration::from_millis(250)));
+        assert_eq!(retry_pushback(&tonic::Status::unavailable("down")), None);
+    }
+
+    #[tokio::test]
+    async fn connect_without_server_fails() {
+        let options = ClientOptions::new("http://[::1]:1").with_retry(RetryPolicy::none());
+        match Client::connect(options).await {
+            Err(ClientError::ConnectionFailed(_)) => {}
+            other => panic!("expected ConnectionFailed, got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_endpoint_is_invalid_config() {
+        match Client::connect(ClientOptions::new("not a uri")).await {
+            Err(ClientError::InvalidConfig(_)) => {}
+            other => panic!("expected InvalidConfig, got: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Conversions between the generated protobuf messages and the domain types
+//! of `eventfold_db`.
+
+use bytes::Bytes;
+use eventfold_db::proto;
+use eventfold_db::{AuditInfo, ExpectedVersion, ProposedEvent, RecordedEvent, StreamInfo};
+use uuid::Uuid;
+
+use crate::error::ClientError;
+
+/// Convert a domain `ProposedEvent` to its protobuf form.
+pub(crate) fn proposed_to_proto(event: &ProposedEvent) -> proto::ProposedEvent {
+    proto::ProposedEvent {
+        event_id: event.event_id.to_string(),
+        event_type: event.event_type.clone(),
+        metadata: event.metadata.to_vec(),
+        payload: event.payload.to_vec(),
+    }
+}
+
+/// Convert a domain `ExpectedVersion` to its protobuf form.
+pub(crate) fn expected_version_to_proto(expected: ExpectedVersion) -> proto::ExpectedVersion {
+    use proto::expected_version::Kind;
+    let kind = match expected {
+        ExpectedVersion::Any => Kind::Any(proto::Empty {}),
+        ExpectedVersion::NoStream => Kind::NoStream(proto::Empty {}),
+        ExpectedVersion::Exact(version) => Kind::Exact(version),
+    };
+    proto::ExpectedVersion { kind: Some(kind) }
+}
+
+/// Convert a protobuf `RecordedEvent` from the server to the domain type.
+///
+/// # Errors
+///
+/// Returns [`ClientError::InvalidResponse`] if an ID is not a valid UUID.
+pub(crate) fn recorded_from_proto(
+    event: proto::RecordedEvent,
+) -> Result<RecordedEvent, ClientError> {
+    Ok(RecordedEvent {
+        event_id: parse_uuid("event_id", &event.event_id)?,
+        stream_id: parse_uuid("stream_id", &event.stream_id)?,
+        stream_version: event.stream_version,
+        global_position: event.global_position,
+        recorded_at: event.recorded_at,
+        event_type: event.event_type,
+        metadata: Bytes::from(event.metadata),
+        payload: Bytes::from(event.payload),
+        audit: event.audited.then(|| AuditInfo {
+            principal: (!event.principal.is_empty()).then_some(event.principal),
+            client_addr: (!event.client_addr.is_empty()).then_some(event.client_addr),
+        }),
+    })
+}
+
+/// Convert a protobuf `StreamInfo` from the server to the domain type.
+///
+/// # Errors
+///
+/// Returns [`ClientError::InvalidResponse`] if the stream ID is not a valid
+/// UUID.
+pub(crate) fn stream_info_from_proto(info: proto::StreamInfo) -> Result<StreamInfo, ClientError> {
+    Ok(StreamInfo {
+        stream_id: parse_uuid("stream_id", &info.stream_id)?,
+        event_count: info.event_count,
+        latest_version: info.latest_version,
+    })
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, ClientError> {
+    Uuid::parse_str(value)
+        .map_err(|e| ClientError::InvalidResponse(format!("{field} {value:?}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_event_round_trips_through_proto() {
+        let event = RecordedEvent {
+            event_id: Uuid::new_v4(),
+            stream_id: Uuid::new_v4(),
+            stream_version: 3,
+            global_position: 42,
+            recorded_at: 1_700_000_000_000,
+            event_type: "OrderPlaced".to_string(),
+            metadata: Bytes::from_static(b"{}"),
+            payload: Bytes::from_static(&[0xFF, 0x00]),
+            audit: Some(AuditInfo {
+                principal: Some("svc".to_string()),
+                client_addr: None,
+            }),
+        };
+        let proto = eventfold_db::service::recorded_to_proto(&event);
+        assert_eq!(recorded_from_proto(proto).expect("valid event"), event);
+    }
+
+    #[test]
+    fn malformed_uuid_is_an_invalid_response() {
+        let proto = proto::RecordedEvent {
+            event_id: "not-a-uuid".to_string(),
+            ..Default::default()
+        };
+        match recorded_from_proto(proto) {
+            Err(ClientError::InvalidResponse(msg)) => assert!(msg.contains("event_id")),
+            other => panic!("expected InvalidResponse, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn expected_version_maps_every_kind() {
+        use proto::expected_version::Kind;
+        assert_eq!(
+            expected_version_to_proto(ExpectedVersion::Exact(7)).kind,
+            Some(Kind::Exact(7))
+        );
+        assert_eq!(
+            expected_version_to_proto(ExpectedVersion::NoStream).kind,
+            Some(Kind::NoStream(proto::Empty {}))
+        );
+        assert_eq!(
+            expected_version_to_proto(ExpectedVersion::Any).kind,
+            Some(Kind::Any(proto::Empty {}))
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Error types for the EventfoldDB client.
+
+/// Unified error type for all client operations.
+///
+/// Server-side failures arrive as [`ClientError::Grpc`] with the status the
+/// server mapped from its own error, e.g. `FAILED_PRECONDITION` for a wrong
+/// expected version or `NOT_FOUND` for a missing stream; see
+/// [`ClientError::code`].
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The endpoint, TLS material, or token in the options is unusable.
+    #[error("invalid client configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Could not connect to the server.
+    #[error("connection failed: {0}")]
+    ConnectionFailed(String),
+
+    /// The server or transport returned an error status.
+    #[error("gRPC error: {0}")]
+    Grpc(#[from] tonic::Status),
+
+    /// The server sent a response the client could not interpret, such as an
+    /// event with a malformed UUID.
+    #[error("invalid response from server: {0}")]
+    InvalidResponse(String),
+}
+
+impl ClientError {
+    /// The gRPC status code, for errors returned by the server.
+    pub fn code(&self) -> Option<tonic::Code> {
+        match self {
+            ClientError::Grpc(status) => Some(status.code()),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if an append failed its expected version check.
+    pub fn is_wrong_expected_version(&self) -> bool {
+        self.code() == Some(tonic::Code::FailedPrecondition)
+            && self.to_string().contains("wrong expected version")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grpc_error_exposes_code() {
+        let err = ClientError::from(tonic::Status::not_found("stream not found: x"));
+        assert_eq!(err.code(), Some(tonic::Code::NotFound));
+        assert!(err.to_string().contains("stream not found"));
+        assert_eq!(ClientError::InvalidConfig("bad".into()).code(), None);
+    }
+
+    #[test]
+    fn wrong_expected_version_is_recognized() {
+        let err = ClientError::from(tonic::Status::failed_precondition(
+            "wrong expected version: expected 0, actual 1",
+        ));
+        assert!(err.is_wrong_expected_version());
+        let read_only = ClientError::from(tonic::Status::failed_precondition(
+            "store is open read-only",
+        ));
+        assert!(!read_only.is_wrong_expected_version());
+    }
+}
@@ -0,0 +1,80 @@
+//! Async Rust client for EventfoldDB.
+//!
+//! A typed wrapper over the generated gRPC stubs: requests and responses use
+//! the domain types of `eventfold_db` ([`ProposedEvent`], [`RecordedEvent`],
+//! [`ExpectedVersion`]) instead of protobuf messages with string UUIDs, and
+//! the connection handles authentication, retries, and reconnecting
+//! subscriptions.
+//!
+//! # Quick Start
+//!
+//! ```ignore
+//! use eventfold_client::{Client, ClientOptions, ExpectedVersion, ProposedEvent};
+//! use futures::StreamExt;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), eventfold_client::ClientError> {
+//!     let client = Client::connect(
+//!         ClientOptions::new("http://127.0.0.1:2113").with_token("<jwt>"),
+//!     )
+//!     .await?;
+//!
+//!     let stream_id = uuid::Uuid::new_v4();
+//!     let event = ProposedEvent {
+//!         event_id: uuid::Uuid::new_v4(),
+//!         event_type: "OrderPlaced".to_string(),
+//!         metadata: bytes::Bytes::new(),
+//!         payload: bytes::Bytes::from_static(b"{\"item\": \"widget\"}"),
+//!     };
+//!     client
+//!         .append(stream_id, ExpectedVersion::NoStream, vec![event])
+//!         .await?;
+//!
+//!     let mut events = client.read_stream_paged(stream_id, 0, 500);
+//!     while let Some(event) = events.next().await {
+//!         println!("{}", event?.event_type);
+//!     }
+//!
+//!     let mut subscription = client
+//!         .subscribe_all(0)
+//!         .on_checkpoint(|position| println!("resume from {position}"));
+//!     while let Some(message) = subscription.next().await {
+//!         println!("{:?}", message?);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Key Types
+//!
+//! - [`Client`] -- Cloneable connection with one method per RPC, plus paged
+//!   reads that yield events as a stream.
+//! - [`ClientOptions`] -- Endpoint, [`TlsOptions`], bearer token, timeouts,
+//!   and the [`RetryPolicy`] applied to transient failures.
+//! - [`Subscription`] -- Catch-up-then-live stream of [`SubscriptionMessage`]s
+//!   that re-subscribes from its last position after a disconnect or lag.
+//! - [`Repository`] -- Load-fold-append loop for an [`Aggregate`], retrying
+//!   on conflicts. `Client` implements [`StreamStore`], so
+//!   `Repository::new(client)` works against a remote server.
+//! - [`ClientError`] -- Configuration, connection, and server errors.
+
+// `ClientError::Grpc` carries a `tonic::Status` unboxed so callers can match on
+// it directly, which makes every `Result<_, ClientError>` trip
+// clippy::result_large_err.
+#![allow(clippy::result_large_err)]
+
+mod aggregate;
+mod client;
+mod convert;
+pub mod error;
+pub mod options;
+mod subscription;
+
+pub use client::{Client, EventStream};
+pub use error::ClientError;
+pub use eventfold_db::{
+    Aggregate, AppendResult, AuditInfo, ConflictPolicy, ExpectedVersion, Loaded, ProposedEvent,
+    RecordedEvent, Repository, RepositoryError, StreamInfo, StreamStore, SubscriptionMessage,
+};
+pub use options::{ClientOptions, RetryPolicy, TlsOptions};
+pub use subscription::Subscription;
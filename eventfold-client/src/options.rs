@@ -0,0 +1,160 @@
+//! Connection, authentication, and retry options.
+
+use std::time::Duration;
+
+/// How to reach and authenticate to an EventfoldDB server.
+///
+/// `ClientOptions::new(endpoint)` connects over plaintext without a token and
+/// with the default [`RetryPolicy`].
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Server URI, e.g. `http://127.0.0.1:2113` or `https://events.internal:2113`.
+    pub endpoint: String,
+    /// TLS settings. `None` connects over plaintext.
+    pub tls: Option<TlsOptions>,
+    /// JWT sent as `authorization: Bearer <token>` on every call.
+    pub token: Option<String>,
+    /// Limit on establishing the connection. `None` uses tonic's default.
+    pub connect_timeout: Option<Duration>,
+    /// Deadline sent with each unary call as `grpc-timeout`. `None` waits
+    /// indefinitely. Not applied to subscriptions.
+    pub request_timeout: Option<Duration>,
+    /// How failed calls are retried.
+    pub retry: RetryPolicy,
+}
+
+impl ClientOptions {
+    /// Options for `endpoint` with everything else at its default.
+    pub fn new(endpoint: impl Into<String>) -> ClientOptions {
+        ClientOptions {
+            endpoint: endpoint.into(),
+            tls: None,
+            token: None,
+            connect_timeout: None,
+            request_timeout: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Connect over TLS.
+    pub fn with_tls(mut self, tls: TlsOptions) -> ClientOptions {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Authenticate every call with the JWT `token`.
+    pub fn with_token(mut self, token: impl Into<String>) -> ClientOptions {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Send `timeout` as the deadline of every unary call.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> ClientOptions {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Replace the retry policy.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> ClientOptions {
+        self.retry = retry;
+        self
+    }
+}
+
+/// TLS settings for the connection.
+///
+/// The client is built without a bundled root store, so `ca_pem` is needed to
+/// verify the server certificate. Adding `identity` turns on mutual TLS for
+/// servers started with `EVENTFOLD_TLS_CA`.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    /// PEM-encoded CA certificate used to verify the server.
+    pub ca_pem: Option<Vec<u8>>,
+    /// PEM-encoded client certificate and key for mutual TLS:
+    /// `(cert_pem, key_pem)`.
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+    /// Name to verify the server certificate against, when it differs from
+    /// the endpoint host.
+    pub domain_name: Option<String>,
+}
+
+/// Retry behavior for calls that fail with a transient status.
+///
+/// `UNAVAILABLE`, `RESOURCE_EXHAUSTED`, and `DEADLINE_EXCEEDED` are retried.
+/// Appends are safe to retry: the events keep their IDs, and the server
+/// answers a repeated batch from its idempotency index instead of writing it
+/// twice.
+///
+/// An append can hit its deadline in two ways, told apart by the status
+/// message. One still queued when the deadline passed was dropped unwritten
+/// ("nothing was committed"). One the server was already writing may still
+/// commit ("the outcome is unknown"), so when retries run out on it, read the
+/// stream or append the same events again before treating it as failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts per call, including the first. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubles on each further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based).
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn builder_methods_set_fields() {
+        let options = ClientOptions::new("http://127.0.0.1:2113")
+            .with_token("jwt")
+            .with_request_timeout(Duration::from_secs(2))
+            .with_retry(RetryPolicy::none());
+        assert_eq!(options.token.as_deref(), Some("jwt"));
+        assert_eq!(options.request_timeout, Some(Duration::from_secs(2)));
+        assert_eq!(options.retry.max_attempts, 1);
+        assert!(options.tls.is_none());
+    }
+}
@@ -0,0 +1,240 @@
+//! Subscriptions that survive disconnects.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use eventfold_db::proto;
+use eventfold_db::{RecordedEvent, SubscriptionMessage};
+use futures_core::Stream;
+use uuid::Uuid;
+
+use crate::client::{Client, retry_pushback};
+use crate::convert::recorded_from_proto;
+use crate::error::ClientError;
+
+type MessageStream = Pin<Box<dyn Stream<Item = Result<SubscriptionMessage, ClientError>> + Send>>;
+
+/// What a [`Subscription`] follows.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SubscriptionTarget {
+    /// The global log, resuming by global position.
+    All { from_position: u64 },
+    /// One stream, resuming by stream version.
+    Stream { stream_id: Uuid, from_version: u64 },
+}
+
+impl SubscriptionTarget {
+    /// Where to start the first subscribe call.
+    fn start(&self) -> u64 {
+        match self {
+            SubscriptionTarget::All { from_position } => *from_position,
+            SubscriptionTarget::Stream { from_version, .. } => *from_version,
+        }
+    }
+
+    /// Where to resume after `event` has been handled.
+    fn resume_after(&self, event: &RecordedEvent) -> u64 {
+        match self {
+            SubscriptionTarget::All { .. } => event.global_position + 1,
+            SubscriptionTarget::Stream { .. } => event.stream_version + 1,
+        }
+    }
+}
+
+/// A live subscription: historical events, a `CaughtUp` marker, then new
+/// events as they are appended.
+///
+/// When the connection drops, the server ends the stream, or the server
+/// terminates a lagging subscriber, the subscription re-subscribes from the
+/// position after the last event it yielded, backing off per the client's
+/// [`RetryPolicy`](crate::RetryPolicy). The attempt count resets whenever a
+/// message arrives, so only consecutive failures count towards
+/// `max_attempts`; past that the last error is yielded and the stream ends.
+/// Other errors, such as `PERMISSION_DENIED`, end it immediately.
+///
+/// Each re-subscribe replays history from the resume position, so a
+/// `CaughtUp` marker is yielded again once it has caught up. No event is
+/// yielded twice.
+pub struct Subscription {
+    inner: MessageStream,
+    target: SubscriptionTarget,
+    on_checkpoint: Option<Box<dyn FnMut(u64) + Send>>,
+    pending_checkpoint: Option<u64>,
+}
+
+impl Subscription {
+    pub(crate) fn new(client: Client, target: SubscriptionTarget) -> Subscription {
+        Subscription {
+            inner: Box::pin(run(client, target)),
+            target,
+            on_checkpoint: None,
+            pending_checkpoint: None,
+        }
+    }
+
+    /// Call `f` with the resume position each time an event has been handled.
+    ///
+    /// An event counts as handled once the consumer polls for the next
+    /// message, so persisting the position in `f` never skips an event that
+    /// was still being processed. The position is the global position (for
+    /// [`Client::subscribe_all`]) or stream version (for
+    /// [`Client::subscribe_stream`]) to pass when subscribing again.
+    pub fn on_checkpoint(mut self, f: impl FnMut(u64) + Send + 'static) -> Subscription {
+        self.on_checkpoint = Some(Box::new(f));
+        self
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<SubscriptionMessage, ClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(position) = this.pending_checkpoint.take() {
+            if let Some(f) = this.on_checkpoint.as_mut() {
+                f(position);
+            }
+        }
+        let item = this.inner.as_mut().poll_next(cx);
+        if let Poll::Ready(Some(Ok(SubscriptionMessage::Event(event)))) = &item {
+            this.pending_checkpoint = Some(this.target.resume_after(event));
+        }
+        item
+    }
+}
+
+impl std::fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("target", &self.target)
+            .field("pending_checkpoint", &self.pending_checkpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether a subscription that failed with `status` should re-subscribe.
+fn is_resubscribable(status: &tonic::Status) -> bool {
+    match status.code() {
+        // The server ends a lagging subscriber with ABORTED.
+        tonic::Code::Unavailable
+        | tonic::Code::Unknown
+        | tonic::Code::ResourceExhausted
+        | tonic::Code::Aborted => true,
+        _ => false,
+    }
+}
+
+/// Open a server subscription for `target` starting at `from`.
+async fn open(
+    client: &Client,
+    target: SubscriptionTarget,
+    from: u64,
+) -> Result<tonic::Streaming<proto::SubscribeResponse>, tonic::Status> {
+    let mut inner = client.inner();
+    let response = match target {
+        SubscriptionTarget::All { .. } => {
+            inner
+                .subscribe_all(proto::SubscribeAllRequest {
+                    from_position: from,
+                    from_timestamp: None,
+                })
+                .await?
+        }
+        SubscriptionTarget::Stream { stream_id, .. } => {
+            inner
+                .subscribe_stream(proto::SubscribeStreamRequest {
+                    stream_id: stream_id.to_string(),
+                    from_version: from,
+                })
+                .await?
+        }
+    };
+    Ok(response.into_inner())
+}
+
+/// The message stream behind a [`Subscription`], re-subscribing on
+/// transient failures.
+fn run(
+    client: Client,
+    target: SubscriptionTarget,
+) -> impl Stream<Item = Result<SubscriptionMessage, ClientError>> + Send {
+    async_stream::stream! {
+        let retry = client.retry();
+        let mut next = target.start();
+        let mut failures = 0;
+        loop {
+            let status = match open(&client, target, next).await {
+                Ok(mut messages) => loop {
+                    match messages.message().await {
+                        Ok(Some(response)) => {
+                            failures = 0;
+                            match response.content {
+                                Some(proto::subscribe_response::Content::Event(event)) => {
+                                    match recorded_from_proto(event) {
+                                        Ok(event) => {
+                                            next = target.resume_after(&event);
+                                            yield Ok(SubscriptionMessage::Event(Arc::new(event)));
+                                        }
+                                        Err(e) => {
+                                            yield Err(e);
+                                            return;
+                                        }
+                                    }
+                                }
+                                Some(proto::subscribe_response::Content::CaughtUp(_)) => {
+                                    yield Ok(SubscriptionMessage::CaughtUp);
+                                }
+                                None => {
+                                    yield Err(ClientError::InvalidResponse(
+                                        "subscription message without content".into(),
+                                    ));
+                                    return;
+                                }
+                            }
+                        }
+                        Ok(None) => break tonic::Status::unavailable("subscription ended by server"),
+                        Err(status) => break status,
+                    }
+                },
+                Err(status) => status,
+            };
+
+            failures += 1;
+            if failures >= retry.max_attempts || !is_resubscribable(&status) {
+                yield Err(ClientError::Grpc(status));
+                return;
+            }
+            let delay = retry_pushback(&status).unwrap_or_else(|| retry.backoff(failures));
+            tracing::debug!(
+                code = ?status.code(),
+                from = next,
+                delay_ms = delay.as_millis() as u64,
+                "re-subscribing to EventfoldDB"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lag_and_disconnects_resubscribe() {
+        assert!(is_resubscribable(&tonic::Status::unavailable("down")));
+        assert!(is_resubscribable(&tonic::Status::aborted(
+            "subscription lagged: re-subscribe from last checkpoint"
+        )));
+        assert!(!is_resubscribable(&tonic::Status::invalid_argument(
+            "subscription lagged: re-subscribe from last checkpoint"
+        )));
+        assert!(!is_resubscribable(&tonic::Status::invalid_argument(
+            "invalid stream_id"
+        )));
+        assert!(!is_resubscribable(&tonic::Status::unauthenticated(
+            "no token"
+        )));
+    }
+}
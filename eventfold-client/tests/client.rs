@@ -0,0 +1,643 @@
+//! Integration tests for `eventfold_client::Client` against an in-process
+//! EventfoldDB server on an ephemeral port.
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventfold_client::{
+    Aggregate, Client, ClientError, ClientOptions, ConflictPolicy, ExpectedVersion, ProposedEvent,
+    RecordedEvent, Repository, RetryPolicy, Subscription, SubscriptionMessage, TlsOptions,
+};
+use eventfold_db::auth::JwtInterceptor;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::{Broker, EventfoldService, Store, spawn_writer};
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use tempfile::TempDir;
+use tonic::server::NamedService;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::server::Router;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tower_service::Service;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An event store service over a fresh log in a temp directory, which must
+/// outlive the server.
+fn new_service(broker_capacity: usize) -> (EventStoreServer<EventfoldService>, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    let broker = Broker::new(broker_capacity);
+    let (writer_handle, read_index, _join_handle) = spawn_writer(
+        store,
+        64,
+        broker.clone(),
+        NonZeroUsize::new(128).expect("nonzero"),
+    );
+    let service = EventStoreServer::new(EventfoldService::new(writer_handle, read_index, broker));
+    (service, dir)
+}
+
+/// Serve `router` on an ephemeral port and return its address.
+async fn serve(router: Router) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        router
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+
+    // Give the server a moment to start accepting connections.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    addr
+}
+
+/// Spin up an in-process server and return its address and the temp
+/// directory holding the event log. With `secret`, the server requires a JWT.
+async fn start_server(broker_capacity: usize, secret: Option<&str>) -> (SocketAddr, TempDir) {
+    let (service, dir) = new_service(broker_capacity);
+    let router = match secret {
+        Some(secret) => Server::builder().add_service(InterceptedService::new(
+            service,
+            JwtInterceptor::new(secret),
+        )),
+        None => Server::builder().add_service(service),
+    };
+    (serve(router).await, dir)
+}
+
+fn options(addr: SocketAddr) -> ClientOptions {
+    ClientOptions::new(format!("http://[::1]:{}", addr.port()))
+}
+
+async fn connect(addr: SocketAddr) -> Client {
+    Client::connect(options(addr))
+        .await
+        .expect("client connect should succeed")
+}
+
+fn make_event(event_type: &str) -> ProposedEvent {
+    ProposedEvent {
+        event_id: uuid::Uuid::new_v4(),
+        event_type: event_type.to_string(),
+        metadata: Bytes::new(),
+        payload: Bytes::from_static(b"{}"),
+    }
+}
+
+fn make_events(count: usize) -> Vec<ProposedEvent> {
+    (0..count).map(|i| make_event(&format!("E{i}"))).collect()
+}
+
+/// The next subscription message, failing the test on error, end of
+/// stream, or timeout.
+async fn next_message(subscription: &mut Subscription) -> SubscriptionMessage {
+    tokio::time::timeout(TIMEOUT, subscription.next())
+        .await
+        .expect("should not time out")
+        .expect("stream should not end")
+        .expect("message should succeed")
+}
+
+/// Mint an HS256 JWT with `sub = "test"` valid for an hour.
+fn mint_token(secret: &str) -> String {
+    #[derive(serde::Serialize)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before Unix epoch")
+        .as_secs();
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        &Claims {
+            sub: "test".to_string(),
+            exp: now + 3600,
+        },
+        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .expect("JWT encoding should not fail in tests")
+}
+
+#[tokio::test]
+async fn append_then_read_returns_domain_types() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    let stream_id = uuid::Uuid::new_v4();
+    let events = make_events(3);
+
+    let result = client
+        .append(stream_id, ExpectedVersion::NoStream, events.clone())
+        .await
+        .expect("append should succeed");
+    assert_eq!(result.first_stream_version, 0);
+    assert_eq!(result.last_stream_version, 2);
+    assert_eq!(result.last_global_position, 2);
+
+    let read = client
+        .read_stream(stream_id, 0, 100)
+        .await
+        .expect("read_stream should succeed");
+    assert_eq!(read.len(), 3);
+    for (recorded, proposed) in read.iter().zip(&events) {
+        assert_eq!(recorded.stream_id, stream_id);
+        assert_eq!(recorded.event_id, proposed.event_id);
+        assert_eq!(recorded.event_type, proposed.event_type);
+        assert_eq!(recorded.payload, proposed.payload);
+    }
+
+    let all = client
+        .read_all(1, 100)
+        .await
+        .expect("read_all should succeed");
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].global_position, 1);
+
+    let streams = client
+        .list_streams()
+        .await
+        .expect("list_streams should succeed");
+    assert_eq!(streams.len(), 1);
+    assert_eq!(streams[0].stream_id, stream_id);
+    assert_eq!(streams[0].event_count, 3);
+}
+
+#[tokio::test]
+async fn server_errors_keep_their_status() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    let stream_id = uuid::Uuid::new_v4();
+    client
+        .append(stream_id, ExpectedVersion::NoStream, make_events(1))
+        .await
+        .expect("first append should succeed");
+
+    let err = client
+        .append(stream_id, ExpectedVersion::NoStream, make_events(1))
+        .await
+        .expect_err("second NoStream append should fail");
+    assert!(err.is_wrong_expected_version(), "got: {err}");
+
+    let err = client
+        .read_stream(uuid::Uuid::new_v4(), 0, 10)
+        .await
+        .expect_err("unknown stream should fail");
+    assert_eq!(err.code(), Some(tonic::Code::NotFound));
+}
+
+#[tokio::test]
+async fn paged_reads_yield_every_event_in_order() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    let stream_id = uuid::Uuid::new_v4();
+    client
+        .append(stream_id, ExpectedVersion::NoStream, make_events(25))
+        .await
+        .expect("append should succeed");
+
+    let versions: Vec<u64> = client
+        .read_stream_paged(stream_id, 3, 10)
+        .map(|event| event.expect("page should succeed").stream_version)
+        .collect()
+        .await;
+    assert_eq!(versions, (3..25).collect::<Vec<_>>());
+
+    // An exact multiple of the page size ends with one empty page.
+    let positions: Vec<u64> = client
+        .read_all_paged(5, 10)
+        .map(|event| event.expect("page should succeed").global_position)
+        .collect()
+        .await;
+    assert_eq!(positions, (5..25).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn get_position_at_maps_timestamps_to_positions() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    client
+        .append(uuid::Uuid::new_v4(), ExpectedVersion::Any, make_events(2))
+        .await
+        .expect("append should succeed");
+
+    assert_eq!(client.get_position_at(0).await.expect("lookup"), 0);
+    assert_eq!(client.get_position_at(u64::MAX).await.expect("lookup"), 2);
+}
+
+#[tokio::test]
+async fn subscription_resumes_after_lag_without_duplicates() {
+    // A broadcast capacity of 4 makes the server drop the subscriber as
+    // lagged when 20 events are published at once.
+    let (addr, _dir) = start_server(4, None).await;
+    let client = connect(addr).await;
+    let checkpoints = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&checkpoints);
+    let mut subscription = client
+        .subscribe_all(0)
+        .on_checkpoint(move |position| recorded.lock().expect("lock").push(position));
+
+    assert!(matches!(
+        next_message(&mut subscription).await,
+        SubscriptionMessage::CaughtUp
+    ));
+
+    client
+        .append(
+            uuid::Uuid::new_v4(),
+            ExpectedVersion::NoStream,
+            make_events(20),
+        )
+        .await
+        .expect("append should succeed");
+
+    let mut positions = Vec::new();
+    while positions.len() < 20 {
+        if let SubscriptionMessage::Event(event) = next_message(&mut subscription).await {
+            positions.push(event.global_position);
+        }
+    }
+    assert_eq!(positions, (0..20).collect::<Vec<_>>());
+
+    // The last event counts as handled once the next message is polled for.
+    let _ = tokio::time::timeout(Duration::from_millis(100), subscription.next()).await;
+    let checkpoints = checkpoints.lock().expect("lock");
+    assert_eq!(checkpoints.last(), Some(&20));
+    assert!(checkpoints.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[tokio::test]
+async fn stream_subscription_catches_up_then_goes_live() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    let stream_id = uuid::Uuid::new_v4();
+    client
+        .append(stream_id, ExpectedVersion::NoStream, make_events(2))
+        .await
+        .expect("append should succeed");
+
+    let mut subscription = client.subscribe_stream(stream_id, 1);
+    match next_message(&mut subscription).await {
+        SubscriptionMessage::Event(event) => assert_eq!(event.stream_version, 1),
+        other => panic!("expected historical event, got: {other:?}"),
+    }
+    assert!(matches!(
+        next_message(&mut subscription).await,
+        SubscriptionMessage::CaughtUp
+    ));
+
+    client
+        .append(stream_id, ExpectedVersion::Exact(1), make_events(1))
+        .await
+        .expect("live append should succeed");
+    match next_message(&mut subscription).await {
+        SubscriptionMessage::Event(event) => assert_eq!(event.stream_version, 2),
+        other => panic!("expected live event, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn token_is_sent_as_bearer_authorization() {
+    let secret = "client-test-secret";
+    let (addr, _dir) = start_server(1024, Some(secret)).await;
+
+    let anonymous = Client::connect(options(addr).with_retry(RetryPolicy::none()))
+        .await
+        .expect("client connect should succeed");
+    let err = anonymous
+        .list_streams()
+        .await
+        .expect_err("call without a token should fail");
+    assert_eq!(err.code(), Some(tonic::Code::Unauthenticated));
+
+    let authed = Client::connect(options(addr).with_token(mint_token(secret)))
+        .await
+        .expect("client connect should succeed");
+    authed
+        .append(uuid::Uuid::new_v4(), ExpectedVersion::Any, make_events(1))
+        .await
+        .expect("append with a token should succeed");
+}
+
+/// Wraps the service and replaces the response to the first `Append` with
+/// `UNAVAILABLE` after the server has handled it, as if the connection
+/// dropped before the reply arrived.
+#[derive(Clone)]
+struct LoseFirstAppendResponse {
+    inner: EventStoreServer<EventfoldService>,
+    lost: Arc<AtomicBool>,
+}
+
+impl NamedService for LoseFirstAppendResponse {
+    const NAME: &'static str = <EventStoreServer<EventfoldService> as NamedService>::NAME;
+}
+
+impl Service<http::Request<tonic::body::Body>> for LoseFirstAppendResponse {
+    type Response = http::Response<tonic::body::Body>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Service::<http::Request<tonic::body::Body>>::poll_ready(&mut self.inner, cx)
+    }
+
+    fn call(&mut self, request: http::Request<tonic::body::Body>) -> Self::Future {
+        let lose =
+            request.uri().path().ends_with("/Append") && !self.lost.swap(true, Ordering::SeqCst);
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            if lose {
+                return Ok(tonic::Status::unavailable("connection reset").into_http());
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[tokio::test]
+async fn append_retry_after_lost_response_reuses_event_ids() {
+    let (service, _dir) = new_service(1024);
+    let lost = Arc::new(AtomicBool::new(false));
+    let addr = serve(Server::builder().add_service(LoseFirstAppendResponse {
+        inner: service,
+        lost: Arc::clone(&lost),
+    }))
+    .await;
+    let client = Client::connect(options(addr).with_retry(RetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(10),
+    }))
+    .await
+    .expect("client connect should succeed");
+    let stream_id = uuid::Uuid::new_v4();
+    let events = make_events(3);
+
+    // The first attempt is written but its response is lost. The retry
+    // carries the same IDs, so the server answers it from the dedup index
+    // instead of the NoStream check failing or the events being written twice.
+    let result = client
+        .append(stream_id, ExpectedVersion::NoStream, events.clone())
+        .await
+        .expect("retried append should succeed");
+    assert!(lost.load(Ordering::SeqCst), "first response should be lost");
+    assert_eq!(result.first_stream_version, 0);
+    assert_eq!(result.last_stream_version, 2);
+
+    let read = client
+        .read_stream(stream_id, 0, 100)
+        .await
+        .expect("read_stream should succeed");
+    let ids: Vec<_> = read.iter().map(|event| event.event_id).collect();
+    let expected: Vec<_> = events.iter().map(|event| event.event_id).collect();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn repeated_append_is_answered_with_the_original_positions() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let client = connect(addr).await;
+    let stream_id = uuid::Uuid::new_v4();
+    client
+        .append(stream_id, ExpectedVersion::Any, make_events(1))
+        .await
+        .expect("first append should succeed");
+    let events = make_events(2);
+
+    let first = client
+        .append(stream_id, ExpectedVersion::Exact(0), events.clone())
+        .await
+        .expect("append should succeed");
+    let repeated = client
+        .append(stream_id, ExpectedVersion::Exact(0), events)
+        .await
+        .expect("repeated append should hit the dedup index");
+    assert_eq!(repeated, first);
+    assert_eq!(repeated.first_global_position, 1);
+
+    let streams = client
+        .list_streams()
+        .await
+        .expect("list_streams should succeed");
+    assert_eq!(streams[0].event_count, 3);
+}
+
+/// A CA and a server certificate for `localhost` and the loopback
+/// addresses, plus a client certificate signed by the same CA, all PEM.
+struct Certs {
+    ca: Vec<u8>,
+    server: (Vec<u8>, Vec<u8>),
+    client: (Vec<u8>, Vec<u8>),
+}
+
+fn generate_certs() -> Certs {
+    let ca_key = rcgen::KeyPair::generate().expect("CA key generation should succeed");
+    let mut ca_params = rcgen::CertificateParams::new(vec!["EventfoldDB Test CA".into()])
+        .expect("CA params should be valid");
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca_cert = ca_params
+        .self_signed(&ca_key)
+        .expect("CA self-signing should succeed");
+
+    let server_key = rcgen::KeyPair::generate().expect("server key generation should succeed");
+    let mut server_params = rcgen::CertificateParams::new(vec!["localhost".into()])
+        .expect("server params should be valid");
+    server_params.subject_alt_names = vec![
+        rcgen::SanType::IpAddress(std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)),
+        rcgen::SanType::DnsName("localhost".try_into().expect("localhost is valid DNS")),
+    ];
+    let server_cert = server_params
+        .signed_by(&server_key, &ca_cert, &ca_key)
+        .expect("server cert signing should succeed");
+
+    let client_key = rcgen::KeyPair::generate().expect("client key generation should succeed");
+    let client_cert = rcgen::CertificateParams::new(vec!["EventfoldDB Test Client".into()])
+        .expect("client params should be valid")
+        .signed_by(&client_key, &ca_cert, &ca_key)
+        .expect("client cert signing should succeed");
+
+    Certs {
+        ca: ca_cert.pem().into_bytes(),
+        server: (
+            server_cert.pem().into_bytes(),
+            server_key.serialize_pem().into_bytes(),
+        ),
+        client: (
+            client_cert.pem().into_bytes(),
+            client_key.serialize_pem().into_bytes(),
+        ),
+    }
+}
+
+/// Start a TLS server with `certs`, requiring client certificates signed by
+/// the CA when `mutual`.
+async fn start_tls_server(certs: &Certs, mutual: bool) -> (SocketAddr, TempDir) {
+    let (service, dir) = new_service(1024);
+    let (cert, key) = &certs.server;
+    let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+    if mutual {
+        tls = tls.client_ca_root(Certificate::from_pem(&certs.ca));
+    }
+    let router = Server::builder()
+        .tls_config(tls)
+        .expect("TLS config should be valid")
+        .add_service(service);
+    (serve(router).await, dir)
+}
+
+fn tls_options(addr: SocketAddr, tls: TlsOptions) -> ClientOptions {
+    ClientOptions::new(format!("https://[::1]:{}", addr.port()))
+        .with_tls(tls)
+        .with_retry(RetryPolicy::none())
+}
+
+#[tokio::test]
+async fn tls_options_verify_the_server_with_the_given_ca() {
+    let certs = generate_certs();
+    let (addr, _dir) = start_tls_server(&certs, false).await;
+
+    let client = Client::connect(tls_options(
+        addr,
+        TlsOptions {
+            ca_pem: Some(certs.ca.clone()),
+            domain_name: Some("localhost".into()),
+            ..TlsOptions::default()
+        },
+    ))
+    .await
+    .expect("TLS connect should succeed");
+    client
+        .append(uuid::Uuid::new_v4(), ExpectedVersion::Any, make_events(1))
+        .await
+        .expect("append over TLS should succeed");
+
+    // A CA that did not sign the server certificate is refused.
+    let other = generate_certs();
+    let result = Client::connect(tls_options(
+        addr,
+        TlsOptions {
+            ca_pem: Some(other.ca),
+            domain_name: Some("localhost".into()),
+            ..TlsOptions::default()
+        },
+    ))
+    .await;
+    match result {
+        Ok(client) => {
+            client
+                .list_streams()
+                .await
+                .expect_err("call to a server signed by another CA should fail");
+        }
+        Err(err) => assert!(
+            matches!(err, ClientError::ConnectionFailed(_)),
+            "got: {err}"
+        ),
+    }
+}
+
+#[tokio::test]
+async fn tls_identity_is_presented_for_mutual_tls() {
+    let certs = generate_certs();
+    let (addr, _dir) = start_tls_server(&certs, true).await;
+
+    let client = Client::connect(tls_options(
+        addr,
+        TlsOptions {
+            ca_pem: Some(certs.ca.clone()),
+            identity: Some(certs.client.clone()),
+            domain_name: Some("localhost".into()),
+        },
+    ))
+    .await
+    .expect("mTLS connect should succeed");
+    client
+        .append(uuid::Uuid::new_v4(), ExpectedVersion::Any, make_events(1))
+        .await
+        .expect("append with a client certificate should succeed");
+
+    // Without an identity the server rejects the handshake, either while
+    // connecting or on the first call.
+    let result = Client::connect(tls_options(
+        addr,
+        TlsOptions {
+            ca_pem: Some(certs.ca.clone()),
+            domain_name: Some("localhost".into()),
+            ..TlsOptions::default()
+        },
+    ))
+    .await;
+    if let Ok(client) = result {
+        client
+            .list_streams()
+            .await
+            .expect_err("call without a client certificate should fail");
+    }
+}
+
+/// Number of `Incremented` events seen.
+#[derive(Debug, Default)]
+struct Counter(u64);
+
+impl Aggregate for Counter {
+    type Command = ();
+    type Error = std::convert::Infallible;
+
+    fn apply(&mut self, event: &RecordedEvent) {
+        if event.event_type == "Incremented" {
+            self.0 += 1;
+        }
+    }
+
+    fn handle(&self, _: &()) -> Result<Vec<ProposedEvent>, Self::Error> {
+        Ok(vec![make_event("Incremented")])
+    }
+}
+
+#[tokio::test]
+async fn repository_over_grpc_retries_concurrent_commands() {
+    let (addr, _dir) = start_server(1024, None).await;
+    let repo = Repository::<Counter, _>::new(connect(addr).await).with_conflict_policy(
+        ConflictPolicy::Retry {
+            max_retries: 10,
+            backoff: Duration::from_millis(5),
+        },
+    );
+    let stream_id = uuid::Uuid::new_v4();
+
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let repo = repo.clone();
+            tokio::spawn(async move { repo.execute(stream_id, &()).await })
+        })
+        .collect();
+    let mut versions = Vec::new();
+    for task in tasks {
+        let result = task
+            .await
+            .expect("task should not panic")
+            .expect("execute should succeed")
+            .expect("the command produces an event");
+        assert_eq!(result.first_stream_version, result.last_stream_version);
+        versions.push(result.first_stream_version);
+    }
+    versions.sort_unstable();
+    assert_eq!(versions, (0..8).collect::<Vec<_>>());
+
+    let loaded = repo.load(stream_id).await.expect("load should succeed");
+    assert_eq!(loaded.state.0, 8);
+    assert_eq!(loaded.version, Some(7));
+}
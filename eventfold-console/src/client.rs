@@ -115,6 +115,7 @@ impl Client {
             .read_all(ReadAllRequest {
                 from_position,
                 max_count,
+                from_timestamp: None,
             })
             .await?
             .into_inner();
@@ -206,7 +207,10 @@ impl Client {
     ) -> Result<Streaming<SubscribeResponse>, ConsoleError> {
         let stream = self
             .inner
-            .subscribe_all(SubscribeAllRequest { from_position })
+            .subscribe_all(SubscribeAllRequest {
+                from_position,
+                from_timestamp: None,
+            })
             .await?
             .into_inner();
         Ok(stream)
@@ -253,19 +257,17 @@ pub async fn spawn_subscription(
         let _ = tx.blocking_send(SubscriptionMsg::Error(e.to_string()));
     }) {
         let Some(resp) = resp else { break };
-        match resp.content {
+        let msg = match resp.content {
             Some(eventfold_db::proto::subscribe_response::Content::Event(proto_event)) => {
-                let event = proto_to_event_record(proto_event);
-                if tx.send(SubscriptionMsg::Event(event)).await.is_err() {
-                    return; // channel closed, render loop exited
-                }
+                SubscriptionMsg::Event(proto_to_event_record(proto_event))
             }
             Some(eventfold_db::proto::subscribe_response::Content::CaughtUp(_)) => {
-                if tx.send(SubscriptionMsg::CaughtUp).await.is_err() {
-                    return;
-                }
+                SubscriptionMsg::CaughtUp
             }
-            None => {}
+            None => continue,
+        };
+        if tx.send(msg).await.is_err() {
+            return; // channel closed, render loop exited
         }
     }
 }
@@ -301,6 +303,7 @@ mod tests {
             event_type: "OrderPlaced".into(),
             metadata: vec![1, 2, 3],
             payload: vec![4, 5, 6],
+            ..Default::default()
         };
         let record = proto_to_event_record(proto);
         assert_eq!(record.event_id, "eid-1");
@@ -0,0 +1,385 @@
+//! Per-operation and per-stream authorization from JWT claims.
+//!
+//! [`JwtInterceptor`](crate::auth::JwtInterceptor) only establishes who a
+//! caller is. An [`AclPolicy`] decides what they may do: each RPC is an
+//! [`Operation`], and a request is allowed when some [`AclRule`] matching one of
+//! the caller's roles grants that operation, optionally only for certain
+//! streams. Everything not granted is denied with `PERMISSION_DENIED`.
+//!
+//! Policies are JSON documents:
+//!
+//! ```json
+//! {
+//!   "roles_claim": "roles",
+//!   "rules": [
+//!     { "roles": ["projector"], "operations": ["ReadStream", "ReadAll", "SubscribeAll"] },
+//!     { "roles": ["orders"], "operations": ["Append", "ReadStream"], "streams_claim": "streams" }
+//!   ]
+//! }
+//! ```
+//!
+//! Roles are read from the `roles_claim` claim (default `roles`), which may be
+//! an array of strings or a space-separated string such as an OAuth `scope`.
+//! A rule's `streams` lists fixed stream IDs; its `streams_claim` names a claim
+//! listing the stream IDs the caller owns. A rule with either restriction only
+//! grants operations on a single stream.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use metrics::counter;
+use uuid::Uuid;
+
+use crate::auth::AuthenticatedClaims;
+use crate::error::Error;
+
+/// Role that matches every authenticated caller.
+pub const ANY_ROLE: &str = "*";
+
+/// An RPC an ACL rule can grant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+pub enum Operation {
+    /// `Append` to one stream.
+    Append,
+    /// `ReadStream` on one stream.
+    ReadStream,
+    /// `ReadAll` across every stream.
+    ReadAll,
+    /// `SubscribeAll` across every stream.
+    SubscribeAll,
+    /// `SubscribeStream` on one stream.
+    SubscribeStream,
+    /// `ListStreams`.
+    ListStreams,
+    /// `ListHoles`.
+    ListHoles,
+    /// `GetPositionAt`.
+    GetPositionAt,
+    /// `Backup` of the whole log.
+    Backup,
+}
+
+impl Operation {
+    /// The RPC name, as used in policies and metric labels.
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Append => "Append",
+            Operation::ReadStream => "ReadStream",
+            Operation::ReadAll => "ReadAll",
+            Operation::SubscribeAll => "SubscribeAll",
+            Operation::SubscribeStream => "SubscribeStream",
+            Operation::ListStreams => "ListStreams",
+            Operation::ListHoles => "ListHoles",
+            Operation::GetPositionAt => "GetPositionAt",
+            Operation::Backup => "Backup",
+        }
+    }
+}
+
+/// One grant in an [`AclPolicy`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclRule {
+    /// Roles the rule applies to. [`ANY_ROLE`] matches every caller.
+    pub roles: Vec<String>,
+    /// Operations the rule grants.
+    pub operations: HashSet<Operation>,
+    /// Fixed stream IDs the rule is limited to.
+    pub streams: Option<Vec<Uuid>>,
+    /// Claim listing the stream IDs the rule is limited to.
+    pub streams_claim: Option<String>,
+}
+
+impl AclRule {
+    /// Whether the rule limits which streams it applies to.
+    fn is_stream_scoped(&self) -> bool {
+        self.streams.is_some() || self.streams_claim.is_some()
+    }
+
+    /// Whether the rule grants `operation` on `stream` to a caller holding
+    /// `roles` and `claims`.
+    fn grants(
+        &self,
+        roles: &[&str],
+        claims: &AuthenticatedClaims,
+        operation: Operation,
+        stream: Option<Uuid>,
+    ) -> bool {
+        if !self.operations.contains(&operation) {
+            return false;
+        }
+        if !self
+            .roles
+            .iter()
+            .any(|role| role == ANY_ROLE || roles.contains(&role.as_str()))
+        {
+            return false;
+        }
+        if !self.is_stream_scoped() {
+            return true;
+        }
+        let Some(stream) = stream else {
+            return false;
+        };
+        if self.streams.as_ref().is_some_and(|s| s.contains(&stream)) {
+            return true;
+        }
+        self.streams_claim.as_ref().is_some_and(|claim| {
+            claim_values(claims, claim)
+                .iter()
+                .any(|value| value.parse::<Uuid>().ok() == Some(stream))
+        })
+    }
+}
+
+/// Rules deciding which authenticated callers may perform which operations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclPolicy {
+    /// Claim holding the caller's roles.
+    pub roles_claim: String,
+    /// Grants, checked in order. A request is allowed if any rule grants it.
+    pub rules: Vec<AclRule>,
+}
+
+/// On-disk form of an [`AclPolicy`].
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPolicy {
+    #[serde(default = "default_roles_claim")]
+    roles_claim: String,
+    rules: Vec<RawRule>,
+}
+
+/// On-disk form of an [`AclRule`]; stream IDs are still strings.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    roles: Vec<String>,
+    operations: HashSet<Operation>,
+    #[serde(default)]
+    streams: Option<Vec<String>>,
+    #[serde(default)]
+    streams_claim: Option<String>,
+}
+
+fn default_roles_claim() -> String {
+    "roles".to_string()
+}
+
+impl AclPolicy {
+    /// Parse a policy from its JSON form.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if the JSON does not describe a
+    /// policy, names an unknown operation, or lists a stream ID that is not a
+    /// UUID.
+    pub fn from_json(json: &str) -> Result<AclPolicy, Error> {
+        let raw: RawPolicy = serde_json::from_str(json)
+            .map_err(|e| Error::InvalidArgument(format!("invalid ACL policy: {e}")))?;
+        let rules = raw
+            .rules
+            .into_iter()
+            .map(|rule| {
+                let streams = rule
+                    .streams
+                    .map(|streams| {
+                        streams
+                            .iter()
+                            .map(|s| {
+                                s.parse::<Uuid>().map_err(|e| {
+                                    Error::InvalidArgument(format!(
+                                        "invalid stream ID {s:?} in ACL policy: {e}"
+                                    ))
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .transpose()?;
+                Ok(AclRule {
+                    roles: rule.roles,
+                    operations: rule.operations,
+                    streams,
+                    streams_claim: rule.streams_claim,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(AclPolicy {
+            roles_claim: raw.roles_claim,
+            rules,
+        })
+    }
+
+    /// Read a policy from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file cannot be read, and the errors of
+    /// [`AclPolicy::from_json`] otherwise.
+    pub fn load(path: &Path) -> Result<AclPolicy, Error> {
+        AclPolicy::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Decide whether the caller holding `claims` may perform `operation`,
+    /// on `stream` for single-stream operations.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PermissionDenied`] if no rule grants the request, or
+    /// if the request carries no claims at all.
+    pub fn check(
+        &self,
+        claims: Option<&AuthenticatedClaims>,
+        operation: Operation,
+        stream: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let allowed = claims.is_some_and(|claims| {
+            let roles = claim_values(claims, &self.roles_claim);
+            let roles: Vec<&str> = roles.iter().map(String::as_str).collect();
+            self.rules
+                .iter()
+                .any(|rule| rule.grants(&roles, claims, operation, stream))
+        });
+        if allowed {
+            return Ok(());
+        }
+        counter!("eventfold_acl_denials_total", "operation" => operation.name()).increment(1);
+        let target = match stream {
+            Some(stream) => format!(" on stream {stream}"),
+            None => String::new(),
+        };
+        Err(Error::PermissionDenied(format!(
+            "{} not permitted{target}",
+            operation.name()
+        )))
+    }
+}
+
+/// The string values of `claim`: each string in an array, or each
+/// whitespace-separated word of a string.
+fn claim_values(claims: &AuthenticatedClaims, claim: &str) -> Vec<String> {
+    match claims.0.get(claim) {
+        Some(serde_json::Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+        Some(serde_json::Value::Array(values)) => values
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(value: serde_json::Value) -> AuthenticatedClaims {
+        match value {
+            serde_json::Value::Object(map) => AuthenticatedClaims(map),
+            other => panic!("claims must be an object, got {other}"),
+        }
+    }
+
+    const POLICY: &str = r#"{
+        "rules": [
+            { "roles": ["projector"], "operations": ["ReadStream", "ReadAll", "SubscribeAll"] },
+            { "roles": ["orders"], "operations": ["Append", "ReadStream"], "streams_claim": "streams" },
+            { "roles": ["*"], "operations": ["ListStreams"] }
+        ]
+    }"#;
+
+    #[test]
+    fn projector_can_only_read() {
+        let policy = AclPolicy::from_json(POLICY).expect("valid policy");
+        let projector = claims(serde_json::json!({ "sub": "p", "roles": ["projector"] }));
+        let stream = Uuid::new_v4();
+
+        policy
+            .check(Some(&projector), Operation::SubscribeAll, None)
+            .expect("projector may subscribe");
+        policy
+            .check(Some(&projector), Operation::ReadStream, Some(stream))
+            .expect("projector may read streams");
+        match policy.check(Some(&projector), Operation::Append, Some(stream)) {
+            Err(Error::PermissionDenied(msg)) => {
+                assert!(msg.contains("Append"), "got: {msg}");
+                assert!(msg.contains(&stream.to_string()), "got: {msg}");
+            }
+            other => panic!("expected PermissionDenied, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn service_can_only_append_to_streams_in_its_claim() {
+        let policy = AclPolicy::from_json(POLICY).expect("valid policy");
+        let own = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let service = claims(serde_json::json!({
+            "sub": "orders-service",
+            "roles": "orders",
+            "streams": [own.to_string()],
+        }));
+
+        policy
+            .check(Some(&service), Operation::Append, Some(own))
+            .expect("append to own stream");
+        assert!(
+            policy
+                .check(Some(&service), Operation::Append, Some(other))
+                .is_err()
+        );
+        // A stream-scoped rule never grants operations across all streams.
+        assert!(
+            policy
+                .check(Some(&service), Operation::ReadAll, None)
+                .is_err()
+        );
+        // The wildcard rule still applies.
+        policy
+            .check(Some(&service), Operation::ListStreams, None)
+            .expect("every caller may list streams");
+    }
+
+    #[test]
+    fn fixed_streams_and_scope_claim() {
+        let stream = Uuid::new_v4();
+        let policy = AclPolicy::from_json(&format!(
+            r#"{{
+                "roles_claim": "scope",
+                "rules": [{{ "roles": ["events:write"], "operations": ["Append"], "streams": ["{stream}"] }}]
+            }}"#
+        ))
+        .expect("valid policy");
+        let caller = claims(serde_json::json!({ "sub": "c", "scope": "events:read events:write" }));
+
+        policy
+            .check(Some(&caller), Operation::Append, Some(stream))
+            .expect("listed stream");
+        assert!(
+            policy
+                .check(Some(&caller), Operation::Append, Some(Uuid::new_v4()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn requests_without_claims_are_denied() {
+        let policy = AclPolicy::from_json(POLICY).expect("valid policy");
+        assert!(matches!(
+            policy.check(None, Operation::ListStreams, None),
+            Err(Error::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        for json in [
+            r#"{ "rules": [{ "roles": ["a"], "operations": ["Delete"] }] }"#,
+            r#"{ "rules": [{ "roles": ["a"], "operations": ["Append"], "streams": ["nope"] }] }"#,
+            r#"{ "rules": [], "default": "allow" }"#,
+        ] {
+            assert!(
+                matches!(AclPolicy::from_json(json), Err(Error::InvalidArgument(_))),
+                "policy should be rejected: {json}"
+            );
+        }
+    }
+}
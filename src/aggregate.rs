@@ -0,0 +1,603 @@
+//! Load-fold-append helper for event-sourced aggregates.
+//!
+//! The usual command handling loop is: read a stream, fold its events into the
+//! aggregate's state, decide which new events the command produces, and append
+//! them with `ExpectedVersion::Exact` of the version that was read. If another
+//! writer appended in between, the append fails with a wrong expected version
+//! and the whole loop has to run again against the new state.
+//!
+//! [`Repository`] runs that loop for any [`Aggregate`], retrying conflicts per
+//! its [`ConflictPolicy`] after a short, randomized backoff so that writers
+//! racing for the same stream do not collide again in lockstep. It talks to
+//! the store through [`StreamStore`], which is implemented by [`EmbeddedStore`]
+//! for an in-process writer and read index, and by `eventfold_client::Client`
+//! for a remote server.
+
+use std::future::Future;
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::reader::ReadIndex;
+use crate::types::{AppendResult, ExpectedVersion, ProposedEvent, RecordedEvent};
+use crate::writer::WriterHandle;
+
+/// Default number of events fetched per read while loading an aggregate.
+const DEFAULT_PAGE_SIZE: u64 = 1000;
+
+/// Largest multiple of [`ConflictPolicy::Retry`]'s `backoff` waited before a
+/// retry.
+const MAX_BACKOFF_FACTOR: u32 = 32;
+
+/// State rebuilt from one stream's events that decides which new events a
+/// command produces.
+///
+/// `handle` may be called more than once for the same command when the append
+/// conflicts, each time on freshly loaded state, so it should only compute
+/// events and leave side effects to the caller.
+pub trait Aggregate: Default + Send {
+    /// Input to [`Aggregate::handle`].
+    type Command: Sync;
+    /// Why a command was rejected.
+    type Error: Send;
+
+    /// Fold one recorded event into the state.
+    fn apply(&mut self, event: &RecordedEvent);
+
+    /// Decide the events `command` produces against the current state.
+    /// Returning no events makes [`Repository::execute`] a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns the aggregate's own error to reject the command; nothing is
+    /// appended.
+    fn handle(&self, command: &Self::Command) -> Result<Vec<ProposedEvent>, Self::Error>;
+}
+
+/// Stream reads and appends used by a [`Repository`].
+pub trait StreamStore: Send + Sync {
+    /// Error returned by the store.
+    type Error: Send;
+
+    /// Read up to `max_count` events of `stream_id` from `from_version`. A
+    /// stream that does not exist reads as empty.
+    fn read_stream(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        max_count: u64,
+    ) -> impl Future<Output = Result<Vec<RecordedEvent>, Self::Error>> + Send;
+
+    /// Append `events`, which are never empty, to `stream_id` and return the
+    /// positions they were given.
+    fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+    ) -> impl Future<Output = Result<AppendResult, Self::Error>> + Send;
+
+    /// Whether `error` is an expected version mismatch.
+    fn is_conflict(error: &Self::Error) -> bool;
+}
+
+/// A [`StreamStore`] over the writer task and read index of an embedded
+/// store, as returned by [`spawn_writer`](crate::writer::spawn_writer).
+#[derive(Clone)]
+pub struct EmbeddedStore {
+    writer: WriterHandle,
+    read_index: ReadIndex,
+}
+
+impl EmbeddedStore {
+    /// Create a store that appends through `writer` and reads from
+    /// `read_index`.
+    pub fn new(writer: WriterHandle, read_index: ReadIndex) -> EmbeddedStore {
+        EmbeddedStore { writer, read_index }
+    }
+}
+
+impl StreamStore for EmbeddedStore {
+    type Error = Error;
+
+    async fn read_stream(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        max_count: u64,
+    ) -> Result<Vec<RecordedEvent>, Error> {
+        match self
+            .read_index
+            .read_stream(stream_id, from_version, max_count)
+        {
+            Err(Error::StreamNotFound { .. }) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    async fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+    ) -> Result<AppendResult, Error> {
+        let recorded = self
+            .writer
+            .append(stream_id, expected_version, events)
+            .await?;
+        AppendResult::from_recorded(&recorded)
+            .ok_or_else(|| Error::InvalidArgument("events must not be empty".into()))
+    }
+
+    fn is_conflict(error: &Error) -> bool {
+        matches!(error, Error::WrongExpectedVersion { .. })
+    }
+}
+
+/// What [`Repository::execute`] does when its append hits a wrong expected
+/// version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Return [`RepositoryError::Conflict`] straight away.
+    Fail,
+    /// Reload the aggregate and handle the command again, up to
+    /// `max_retries` times after the first attempt.
+    ///
+    /// Each retry first waits a random delay between half and all of
+    /// `backoff`, doubled for every earlier retry and capped at 32 times
+    /// `backoff`, so concurrent writers to one stream spread out instead of
+    /// conflicting again. A zero `backoff` retries immediately.
+    Retry {
+        /// Reloads allowed after the first conflicting append.
+        max_retries: u32,
+        /// Upper bound of the delay before the first retry.
+        backoff: Duration,
+    },
+}
+
+impl Default for ConflictPolicy {
+    fn default() -> ConflictPolicy {
+        ConflictPolicy::Retry {
+            max_retries: 3,
+            backoff: Duration::from_millis(10),
+        }
+    }
+}
+
+/// The delay before retry number `retry` (1-based) under a `backoff` of
+/// [`ConflictPolicy::Retry`], picked with `random` bits from between half and
+/// all of the exponential delay.
+fn conflict_backoff(backoff: Duration, retry: u32, random: u64) -> Duration {
+    let factor = 2u32
+        .saturating_pow(retry.saturating_sub(1))
+        .min(MAX_BACKOFF_FACTOR);
+    let ceiling = backoff.saturating_mul(factor).as_micros() as u64;
+    let floor = ceiling / 2;
+    Duration::from_micros(floor + random % (ceiling - floor + 1))
+}
+
+/// Error returned by [`Repository`] operations.
+///
+/// `D` is the aggregate's [`Aggregate::Error`] and `S` the store's
+/// [`StreamStore::Error`].
+#[derive(Debug, thiserror::Error)]
+pub enum RepositoryError<D, S> {
+    /// The aggregate rejected the command.
+    #[error("command rejected: {0}")]
+    Rejected(D),
+
+    /// Reading or appending failed for a reason other than a conflict.
+    #[error("store error: {0}")]
+    Store(S),
+
+    /// Every attempt lost the race against another writer to the stream.
+    #[error("append conflicted {attempts} times; giving up")]
+    Conflict {
+        /// Appends attempted, all of which conflicted.
+        attempts: u32,
+    },
+}
+
+/// An aggregate loaded from its stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<A> {
+    /// State after folding every event of the stream.
+    pub state: A,
+    /// Version of the last event folded, or `None` if the stream does not
+    /// exist.
+    pub version: Option<u64>,
+}
+
+impl<A> Loaded<A> {
+    /// The expected version that appends only if the stream is unchanged.
+    pub fn expected_version(&self) -> ExpectedVersion {
+        match self.version {
+            Some(version) => ExpectedVersion::Exact(version),
+            None => ExpectedVersion::NoStream,
+        }
+    }
+}
+
+/// Loads aggregates of type `A` from a [`StreamStore`] and appends the events
+/// their commands produce, one stream per aggregate instance.
+///
+/// Every call loads from the store; nothing is cached between calls.
+pub struct Repository<A, S> {
+    store: S,
+    conflict_policy: ConflictPolicy,
+    page_size: u64,
+    _aggregate: PhantomData<fn() -> A>,
+}
+
+impl<A: Aggregate, S: StreamStore> Repository<A, S> {
+    /// Create a repository over `store` with the default [`ConflictPolicy`].
+    pub fn new(store: S) -> Repository<A, S> {
+        Repository {
+            store,
+            conflict_policy: ConflictPolicy::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+            _aggregate: PhantomData,
+        }
+    }
+
+    /// Replace the conflict policy.
+    pub fn with_conflict_policy(mut self, policy: ConflictPolicy) -> Repository<A, S> {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Read streams `page_size` events at a time while loading (minimum 1).
+    pub fn with_page_size(mut self, page_size: u64) -> Repository<A, S> {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// The underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Fold every event of `stream_id` into a fresh `A`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RepositoryError::Store`] if a read fails.
+    pub async fn load(
+        &self,
+        stream_id: Uuid,
+    ) -> Result<Loaded<A>, RepositoryError<A::Error, S::Error>> {
+        let mut loaded = Loaded {
+            state: A::default(),
+            version: None,
+        };
+        loop {
+            let from_version = loaded.version.map_or(0, |version| version + 1);
+            let page = self
+                .store
+                .read_stream(stream_id, from_version, self.page_size)
+                .await
+                .map_err(RepositoryError::Store)?;
+            let full = page.len() as u64 == self.page_size;
+            for event in &page {
+                loaded.state.apply(event);
+                loaded.version = Some(event.stream_version);
+            }
+            if !full {
+                return Ok(loaded);
+            }
+        }
+    }
+
+    /// Load `stream_id`, handle `command`, and append the resulting events
+    /// expecting the version that was loaded.
+    ///
+    /// On a conflict the aggregate is reloaded and the command handled again,
+    /// after a backoff, as the [`ConflictPolicy`] allows. Returns the positions
+    /// of the appended events, or `None` if the command produced none.
+    ///
+    /// # Errors
+    ///
+    /// - [`RepositoryError::Rejected`] if `handle` rejects the command.
+    /// - [`RepositoryError::Conflict`] if the conflict policy is exhausted.
+    /// - [`RepositoryError::Store`] if a read or append fails otherwise.
+    pub async fn execute(
+        &self,
+        stream_id: Uuid,
+        command: &A::Command,
+    ) -> Result<Option<AppendResult>, RepositoryError<A::Error, S::Error>> {
+        let (max_attempts, backoff) = match self.conflict_policy {
+            ConflictPolicy::Fail => (1, Duration::ZERO),
+            ConflictPolicy::Retry {
+                max_retries,
+                backoff,
+            } => (max_retries.saturating_add(1), backoff),
+        };
+        let mut attempts = 0;
+        loop {
+            let loaded = self.load(stream_id).await?;
+            let events = loaded
+                .state
+                .handle(command)
+                .map_err(RepositoryError::Rejected)?;
+            if events.is_empty() {
+                return Ok(None);
+            }
+
+            attempts += 1;
+            match self
+                .store
+                .append(stream_id, loaded.expected_version(), events)
+                .await
+            {
+                Ok(result) => return Ok(Some(result)),
+                Err(e) if S::is_conflict(&e) => {
+                    if attempts >= max_attempts {
+                        return Err(RepositoryError::Conflict { attempts });
+                    }
+                    let delay =
+                        conflict_backoff(backoff, attempts, RandomState::new().hash_one(attempts));
+                    tracing::debug!(
+                        %stream_id,
+                        attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        "append conflicted; reloading aggregate"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(RepositoryError::Store(e)),
+            }
+        }
+    }
+}
+
+impl<A, S: Clone> Clone for Repository<A, S> {
+    fn clone(&self) -> Repository<A, S> {
+        Repository {
+            store: self.store.clone(),
+            conflict_policy: self.conflict_policy,
+            page_size: self.page_size,
+            _aggregate: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::Broker;
+    use crate::store::Store;
+    use crate::writer::spawn_writer;
+    use bytes::Bytes;
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// A counter that refuses to go above a limit.
+    #[derive(Debug, Default, Clone, PartialEq)]
+    struct Counter {
+        value: u64,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct LimitReached;
+
+    impl Aggregate for Counter {
+        type Command = u64;
+        type Error = LimitReached;
+
+        fn apply(&mut self, event: &RecordedEvent) {
+            if event.event_type == "Incremented" {
+                self.value += 1;
+            }
+        }
+
+        fn handle(&self, limit: &u64) -> Result<Vec<ProposedEvent>, LimitReached> {
+            if self.value >= *limit {
+                return Err(LimitReached);
+            }
+            Ok(vec![incremented()])
+        }
+    }
+
+    fn incremented() -> ProposedEvent {
+        ProposedEvent {
+            event_id: Uuid::new_v4(),
+            event_type: "Incremented".to_string(),
+            metadata: Bytes::new(),
+            payload: Bytes::new(),
+        }
+    }
+
+    fn embedded() -> EmbeddedStore {
+        let (writer, read_index, _join) = spawn_writer(
+            Store::in_memory(),
+            16,
+            Broker::new(16),
+            NonZeroUsize::new(64).expect("nonzero"),
+        );
+        EmbeddedStore::new(writer, read_index)
+    }
+
+    /// Appends an event of its own to the stream right before the first
+    /// append it forwards, so that append conflicts.
+    #[derive(Clone)]
+    struct Interfering {
+        inner: EmbeddedStore,
+        interfered: std::sync::Arc<AtomicBool>,
+    }
+
+    impl StreamStore for Interfering {
+        type Error = Error;
+
+        async fn read_stream(
+            &self,
+            stream_id: Uuid,
+            from_version: u64,
+            max_count: u64,
+        ) -> Result<Vec<RecordedEvent>, Error> {
+            self.inner
+                .read_stream(stream_id, from_version, max_count)
+                .await
+        }
+
+        async fn append(
+            &self,
+            stream_id: Uuid,
+            expected_version: ExpectedVersion,
+            events: Vec<ProposedEvent>,
+        ) -> Result<AppendResult, Error> {
+            if !self.interfered.swap(true, Ordering::SeqCst) {
+                self.inner
+                    .append(stream_id, ExpectedVersion::Any, vec![incremented()])
+                    .await?;
+            }
+            self.inner.append(stream_id, expected_version, events).await
+        }
+
+        fn is_conflict(error: &Error) -> bool {
+            EmbeddedStore::is_conflict(error)
+        }
+    }
+
+    fn interfering() -> Interfering {
+        Interfering {
+            inner: embedded(),
+            interfered: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_appends_and_load_folds() {
+        let repo = Repository::<Counter, _>::new(embedded()).with_page_size(2);
+        let stream_id = Uuid::new_v4();
+
+        let missing = repo.load(stream_id).await.expect("load should succeed");
+        assert_eq!(missing.version, None);
+        assert_eq!(missing.expected_version(), ExpectedVersion::NoStream);
+
+        for _ in 0..5 {
+            repo.execute(stream_id, &10)
+                .await
+                .expect("execute should succeed");
+        }
+        let loaded = repo.load(stream_id).await.expect("load should succeed");
+        assert_eq!(loaded.state, Counter { value: 5 });
+        assert_eq!(loaded.version, Some(4));
+    }
+
+    #[tokio::test]
+    async fn command_without_events_appends_nothing() {
+        let repo = Repository::<Counter, _>::new(embedded());
+        let stream_id = Uuid::new_v4();
+
+        #[derive(Default)]
+        struct Idle;
+        impl Aggregate for Idle {
+            type Command = ();
+            type Error = std::convert::Infallible;
+            fn apply(&mut self, _: &RecordedEvent) {}
+            fn handle(&self, _: &()) -> Result<Vec<ProposedEvent>, Self::Error> {
+                Ok(Vec::new())
+            }
+        }
+        let idle = Repository::<Idle, _>::new(repo.store().clone());
+        let result = idle
+            .execute(stream_id, &())
+            .await
+            .expect("execute should succeed");
+        assert_eq!(result, None);
+        assert_eq!(repo.load(stream_id).await.expect("load").version, None);
+    }
+
+    #[tokio::test]
+    async fn rejected_command_appends_nothing() {
+        let repo = Repository::<Counter, _>::new(embedded());
+        let stream_id = Uuid::new_v4();
+        repo.execute(stream_id, &1)
+            .await
+            .expect("first increment should succeed");
+
+        match repo.execute(stream_id, &1).await {
+            Err(RepositoryError::Rejected(LimitReached)) => {}
+            other => panic!("expected Rejected, got: {other:?}"),
+        }
+        let loaded = repo.load(stream_id).await.expect("load should succeed");
+        assert_eq!(loaded.version, Some(0));
+    }
+
+    #[tokio::test]
+    async fn conflict_is_retried_on_reloaded_state() {
+        let repo = Repository::<Counter, _>::new(interfering());
+        let stream_id = Uuid::new_v4();
+
+        let result = repo
+            .execute(stream_id, &10)
+            .await
+            .expect("retry should succeed")
+            .expect("the command produces an event");
+        // The interfering event took version 0; the retried append expected it.
+        assert_eq!(result.first_stream_version, 1);
+        assert_eq!(result.last_stream_version, 1);
+    }
+
+    #[tokio::test]
+    async fn conflict_retry_waits_for_the_backoff() {
+        let backoff = Duration::from_millis(40);
+        let repo = Repository::<Counter, _>::new(interfering()).with_conflict_policy(
+            ConflictPolicy::Retry {
+                max_retries: 1,
+                backoff,
+            },
+        );
+
+        let started = std::time::Instant::now();
+        repo.execute(Uuid::new_v4(), &10)
+            .await
+            .expect("retry should succeed");
+        assert!(started.elapsed() >= backoff / 2);
+    }
+
+    #[test]
+    fn conflict_backoff_is_jittered_exponential_and_capped() {
+        let backoff = Duration::from_millis(10);
+        assert_eq!(conflict_backoff(backoff, 1, 0), Duration::from_millis(5));
+        assert_eq!(
+            conflict_backoff(backoff, 1, 5_000),
+            Duration::from_millis(10)
+        );
+        assert_eq!(conflict_backoff(backoff, 3, 0), Duration::from_millis(20));
+        assert_eq!(
+            conflict_backoff(backoff, 3, 20_000),
+            Duration::from_millis(40)
+        );
+        for random in [0, 1, 12_345, u64::MAX] {
+            let delay = conflict_backoff(backoff, 40, random);
+            assert!(delay >= Duration::from_millis(160) && delay <= Duration::from_millis(320));
+        }
+        assert_eq!(conflict_backoff(Duration::ZERO, 2, 99), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn conflict_fails_under_fail_policy() {
+        let repo =
+            Repository::<Counter, _>::new(interfering()).with_conflict_policy(ConflictPolicy::Fail);
+
+        match repo.execute(Uuid::new_v4(), &10).await {
+            Err(RepositoryError::Conflict { attempts: 1 }) => {}
+            other => panic!("expected Conflict, got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn retried_command_is_decided_on_fresh_state() {
+        // The interfering increment uses up the limit, so the retry rejects.
+        let repo = Repository::<Counter, _>::new(interfering());
+
+        match repo.execute(Uuid::new_v4(), &1).await {
+            Err(RepositoryError::Rejected(LimitReached)) => {}
+            other => panic!("expected Rejected, got: {other:?}"),
+        }
+    }
+}
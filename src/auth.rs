@@ -1,16 +1,148 @@
 //! JWT authentication interceptor for EventfoldDB.
 //!
 //! This module provides [`JwtInterceptor`], a [`tonic::service::Interceptor`]
-//! implementation that validates HS256 JSON Web Tokens on incoming gRPC requests.
+//! implementation that validates JSON Web Tokens on incoming gRPC requests.
 //! When wired into the tonic server, every request must carry a valid
 //! `authorization: Bearer <token>` metadata header or it is rejected with
-//! `UNAUTHENTICATED` before reaching service logic.
+//! `UNAUTHENTICATED` before reaching service logic. Accepted requests carry the
+//! token's subject as an [`AuthenticatedSubject`] request extension and all of
+//! its claims as [`AuthenticatedClaims`], which [`crate::acl`] uses for
+//! authorization.
+//!
+//! Tokens can be verified with an HS256 shared secret, with RS256, ES256, or
+//! EdDSA public keys read from PEM files, or with the keys in a JWKS document
+//! on disk (see [`JwtKeySource`]). Key files are re-read by
+//! [`JwtInterceptor::spawn_reload`] when they change, so keys can be rotated
+//! without a restart.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, JwkSet, KeyAlgorithm, PublicKeyUse};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+
+use crate::error::Error;
+
+/// Where a [`JwtInterceptor`] gets its verification keys.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JwtKeySource {
+    /// HS256 shared secret.
+    Secret(String),
+    /// PEM-encoded public keys, all used with `algorithm`. A token is
+    /// accepted if any of them verifies it, which allows rotating keys by
+    /// listing the old and new key side by side.
+    PublicKeys {
+        /// PEM files holding one public key each.
+        paths: Vec<PathBuf>,
+        /// `RS256`, `ES256`, or `EdDSA`, matching the key type.
+        algorithm: Algorithm,
+    },
+    /// A JWKS document (`{"keys": [...]}`). Tokens are matched to keys by
+    /// their `kid` header.
+    Jwks(PathBuf),
+}
+
+impl JwtKeySource {
+    /// Key files to watch for changes. Empty for a shared secret.
+    fn paths(&self) -> Vec<&Path> {
+        match self {
+            JwtKeySource::Secret(_) => Vec::new(),
+            JwtKeySource::PublicKeys { paths, .. } => paths.iter().map(PathBuf::as_path).collect(),
+            JwtKeySource::Jwks(path) => vec![path.as_path()],
+        }
+    }
+}
+
+/// Keys and claim checks for a [`JwtInterceptor`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct JwtConfig {
+    /// Where verification keys come from.
+    pub keys: JwtKeySource,
+    /// Accepted `iss` values. When non-empty, tokens must carry one of them.
+    pub issuers: Vec<String>,
+    /// Accepted `aud` values. When non-empty, tokens must name one of them.
+    pub audiences: Vec<String>,
+    /// Clock skew tolerated when checking `exp` and `nbf`.
+    pub leeway: Duration,
+}
+
+impl JwtConfig {
+    /// Verify tokens with `keys`, without issuer or audience checks and with
+    /// no leeway.
+    pub fn new(keys: JwtKeySource) -> Self {
+        Self {
+            keys,
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            leeway: Duration::ZERO,
+        }
+    }
+
+    /// Claim checks shared by every key; the algorithm is set per key.
+    fn validation(&self) -> Validation {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_exp = true;
+        validation.validate_nbf = true;
+        // Tokens minted for other services may name them; only check `aud`
+        // when audiences are configured.
+        validation.validate_aud = !self.audiences.is_empty();
+        validation.leeway = self.leeway.as_secs();
+        let mut required = vec!["exp", "sub"];
+        if !self.issuers.is_empty() {
+            validation.set_issuer(&self.issuers);
+            required.push("iss");
+        }
+        if !self.audiences.is_empty() {
+            validation.set_audience(&self.audiences);
+            required.push("aud");
+        }
+        validation.set_required_spec_claims(&required);
+        validation
+    }
+}
 
-/// Holds the decoded signing key and validation config for HS256 JWT verification.
+/// One key a token may be verified with.
+#[derive(Clone)]
+struct VerifyingKey {
+    /// Key ID from the JWKS. `None` matches tokens with any `kid`.
+    kid: Option<String>,
+    /// The only algorithm this key accepts.
+    algorithm: Algorithm,
+    key: DecodingKey,
+    /// The configured claim checks, restricted to `algorithm`.
+    validation: Validation,
+}
+
+impl VerifyingKey {
+    fn new(kid: Option<String>, algorithm: Algorithm, key: DecodingKey, base: &Validation) -> Self {
+        let mut validation = base.clone();
+        validation.algorithms = vec![algorithm];
+        Self {
+            kid,
+            algorithm,
+            key,
+            validation,
+        }
+    }
+
+    /// Whether a token with `header` may be checked against this key.
+    fn matches(&self, header: &jsonwebtoken::Header) -> bool {
+        header.alg == self.algorithm
+            && match (&self.kid, &header.kid) {
+                (Some(kid), Some(token_kid)) => kid == token_kid,
+                (Some(_), None) => false,
+                (None, _) => true,
+            }
+    }
+}
+
+/// Holds the verification keys and validation config for JWT verification.
 ///
 /// Implements [`tonic::service::Interceptor`] so it can be plugged directly into
 /// a tonic `InterceptedService` wrapper. The struct is `Clone` as required by
-/// tonic for per-connection cloning.
+/// tonic for per-connection cloning; clones share the key set, so a reload is
+/// seen by every connection.
 ///
 /// # Examples
 ///
@@ -21,49 +153,227 @@
 /// ```
 #[derive(Clone)]
 pub struct JwtInterceptor {
-    decoding_key: jsonwebtoken::DecodingKey,
-    validation: jsonwebtoken::Validation,
+    config: Arc<JwtConfig>,
+    keys: Arc<RwLock<Arc<Vec<VerifyingKey>>>>,
 }
 
 impl JwtInterceptor {
     /// Construct a new `JwtInterceptor` from an HS256 shared secret.
     ///
-    /// The secret is used to build a [`jsonwebtoken::DecodingKey`] and a
-    /// [`jsonwebtoken::Validation`] configured for HS256 with `exp` validation
-    /// enabled. Both `exp` and `sub` are required claims.
+    /// Tokens are validated for HS256 with `exp` validation enabled. Both
+    /// `exp` and `sub` are required claims.
     ///
     /// # Arguments
     ///
     /// * `secret` - The shared HS256 signing secret as a UTF-8 string.
     pub fn new(secret: &str) -> Self {
-        let decoding_key = jsonwebtoken::DecodingKey::from_secret(secret.as_bytes());
-        let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256);
-        validation.validate_exp = true;
-        validation.leeway = 0;
-        validation.required_spec_claims = ["exp", "sub"].iter().map(|s| (*s).to_string()).collect();
-        Self {
-            decoding_key,
-            validation,
+        Self::from_config(JwtConfig::new(JwtKeySource::Secret(secret.to_string())))
+            .expect("a shared secret needs no key files")
+    }
+
+    /// Construct a `JwtInterceptor` from `config`, reading any key files.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidKeyFile`] if a key file cannot be read or
+    /// holds no usable key.
+    pub fn from_config(config: JwtConfig) -> Result<Self, Error> {
+        let keys = load_keys(&config)?;
+        Ok(Self {
+            config: Arc::new(config),
+            keys: Arc::new(RwLock::new(Arc::new(keys))),
+        })
+    }
+
+    /// Re-read the key files and swap in the new keys.
+    ///
+    /// On error the current keys stay in use.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidKeyFile`] if a key file cannot be read or
+    /// holds no usable key.
+    pub fn reload(&self) -> Result<(), Error> {
+        let keys = load_keys(&self.config)?;
+        *self.keys.write().expect("JWT key set lock poisoned") = Arc::new(keys);
+        Ok(())
+    }
+
+    /// Spawn a task that checks the key files every `interval` and reloads
+    /// them when their modification time changes.
+    ///
+    /// Reload errors are logged and the current keys stay in use. Returns
+    /// `None` for a shared secret, which has no files to watch.
+    pub fn spawn_reload(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        if self.config.keys.paths().is_empty() {
+            return None;
         }
+        let interceptor = self.clone();
+        let mut last_modified = self.modified();
+        Some(tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick.tick().await;
+            loop {
+                tick.tick().await;
+                let modified = interceptor.modified();
+                if modified == last_modified {
+                    continue;
+                }
+                match interceptor.reload() {
+                    Ok(()) => {
+                        last_modified = modified;
+                        tracing::info!("Reloaded JWT verification keys");
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to reload JWT keys; keeping current keys");
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Modification times of the key files; `None` for files that cannot be
+    /// read.
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        self.config
+            .keys
+            .paths()
+            .into_iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+}
+
+/// Read the verification keys described by `config`.
+fn load_keys(config: &JwtConfig) -> Result<Vec<VerifyingKey>, Error> {
+    let base = config.validation();
+    match &config.keys {
+        JwtKeySource::Secret(secret) => Ok(vec![VerifyingKey::new(
+            None,
+            Algorithm::HS256,
+            DecodingKey::from_secret(secret.as_bytes()),
+            &base,
+        )]),
+        JwtKeySource::PublicKeys { paths, algorithm } => paths
+            .iter()
+            .map(|path| {
+                let pem = std::fs::read(path).map_err(|e| invalid_key(path, e))?;
+                let key = match algorithm {
+                    Algorithm::RS256
+                    | Algorithm::RS384
+                    | Algorithm::RS512
+                    | Algorithm::PS256
+                    | Algorithm::PS384
+                    | Algorithm::PS512 => DecodingKey::from_rsa_pem(&pem),
+                    Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(&pem),
+                    Algorithm::EdDSA => DecodingKey::from_ed_pem(&pem),
+                    Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                        return Err(invalid_key(path, "HMAC algorithms need a shared secret"));
+                    }
+                }
+                .map_err(|e| invalid_key(path, e))?;
+                Ok(VerifyingKey::new(None, *algorithm, key, &base))
+            })
+            .collect(),
+        JwtKeySource::Jwks(path) => {
+            let contents = std::fs::read(path).map_err(|e| invalid_key(path, e))?;
+            let set: JwkSet =
+                serde_json::from_slice(&contents).map_err(|e| invalid_key(path, e))?;
+            let mut keys = Vec::new();
+            for jwk in &set.keys {
+                let kid = jwk.common.key_id.clone();
+                if matches!(jwk.common.public_key_use, Some(PublicKeyUse::Encryption)) {
+                    continue;
+                }
+                let Some(algorithm) = jwk_algorithm(jwk) else {
+                    tracing::warn!(kid = ?kid, path = %path.display(), "Skipping unsupported JWK");
+                    continue;
+                };
+                let key = DecodingKey::from_jwk(jwk).map_err(|e| invalid_key(path, e))?;
+                keys.push(VerifyingKey::new(kid, algorithm, key, &base));
+            }
+            if keys.is_empty() {
+                return Err(invalid_key(path, "no usable signing keys"));
+            }
+            Ok(keys)
+        }
+    }
+}
+
+/// The signing algorithm for a public JWK: its `alg` member, or the default
+/// for its key type. Symmetric keys and unknown curves are not supported.
+fn jwk_algorithm(jwk: &jsonwebtoken::jwk::Jwk) -> Option<Algorithm> {
+    let default = match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Algorithm::RS256,
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Algorithm::ES256,
+            EllipticCurve::P384 => Algorithm::ES384,
+            _ => return None,
+        },
+        AlgorithmParameters::OctetKeyPair(params) => match params.curve {
+            EllipticCurve::Ed25519 => Algorithm::EdDSA,
+            _ => return None,
+        },
+        AlgorithmParameters::OctetKey(_) => return None,
+    };
+    match jwk.common.key_algorithm {
+        None => Some(default),
+        Some(KeyAlgorithm::RS256) => Some(Algorithm::RS256),
+        Some(KeyAlgorithm::RS384) => Some(Algorithm::RS384),
+        Some(KeyAlgorithm::RS512) => Some(Algorithm::RS512),
+        Some(KeyAlgorithm::PS256) => Some(Algorithm::PS256),
+        Some(KeyAlgorithm::PS384) => Some(Algorithm::PS384),
+        Some(KeyAlgorithm::PS512) => Some(Algorithm::PS512),
+        Some(KeyAlgorithm::ES256) => Some(Algorithm::ES256),
+        Some(KeyAlgorithm::ES384) => Some(Algorithm::ES384),
+        Some(KeyAlgorithm::EdDSA) => Some(Algorithm::EdDSA),
+        Some(_) => None,
+    }
+}
+
+fn invalid_key(path: &Path, detail: impl std::fmt::Display) -> Error {
+    Error::InvalidKeyFile {
+        path: path.to_path_buf(),
+        detail: detail.to_string(),
     }
 }
 
+/// The `sub` claim of the token a request was authenticated with.
+///
+/// Inserted into the request extensions by [`JwtInterceptor`], so handlers
+/// can attribute requests to a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedSubject(pub String);
+
+/// Every claim of the token a request was authenticated with.
+///
+/// Inserted into the request extensions by [`JwtInterceptor`] next to
+/// [`AuthenticatedSubject`], so handlers can make decisions on roles, scopes,
+/// or any custom claim.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AuthenticatedClaims(pub serde_json::Map<String, serde_json::Value>);
+
 /// Internal claims struct used for JWT token decoding.
 ///
-/// Only `sub` (subject) and `exp` (expiration) are extracted; the store does
-/// not act on the subject value beyond requiring its presence.
+/// `sub` (subject) and `exp` (expiration) are typed; everything else is kept
+/// as JSON. `iss` and `aud` are checked by [`Validation`].
 #[derive(serde::Deserialize)]
 struct Claims {
     /// Subject claim -- identifies the token holder.
-    #[allow(dead_code)]
     sub: String,
     /// Expiration timestamp (seconds since Unix epoch).
-    #[allow(dead_code)]
     exp: u64,
+    /// All other claims.
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
 }
 
 impl tonic::service::Interceptor for JwtInterceptor {
-    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> {
+    fn call(
+        &mut self,
+        mut request: tonic::Request<()>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
         // Step 1: Read the `authorization` metadata key.
         let token_str = match request.metadata().get("authorization") {
             Some(value) => match value.to_str() {
@@ -94,14 +404,43 @@ impl tonic::service::Interceptor for JwtInterceptor {
             }
         };
 
-        // Step 3: Decode and validate the JWT.
-        match jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &self.validation) {
-            Ok(_) => Ok(request),
+        // Step 3: Pick the keys the header allows and validate against each.
+        let header = match jsonwebtoken::decode_header(token) {
+            Ok(header) => header,
             Err(e) => {
                 tracing::debug!("rejected: {e}");
-                Err(tonic::Status::unauthenticated(e.to_string()))
+                return Err(tonic::Status::unauthenticated(e.to_string()));
+            }
+        };
+        let keys = Arc::clone(&self.keys.read().expect("JWT key set lock poisoned"));
+        let mut last_error = None;
+        for key in keys.iter().filter(|key| key.matches(&header)) {
+            match jsonwebtoken::decode::<Claims>(token, &key.key, &key.validation) {
+                Ok(data) => {
+                    let Claims {
+                        sub,
+                        exp,
+                        mut other,
+                    } = data.claims;
+                    other.insert("sub".to_string(), sub.clone().into());
+                    other.insert("exp".to_string(), exp.into());
+                    let extensions = request.extensions_mut();
+                    extensions.insert(AuthenticatedSubject(sub));
+                    extensions.insert(AuthenticatedClaims(other));
+                    return Ok(request);
+                }
+                Err(e) => last_error = Some(e),
             }
         }
+        let message = match last_error {
+            Some(e) => e.to_string(),
+            None => format!(
+                "no verification key for alg {:?} and kid {:?}",
+                header.alg, header.kid
+            ),
+        };
+        tracing::debug!("rejected: {message}");
+        Err(tonic::Status::unauthenticated(message))
     }
 }
 
@@ -185,6 +524,43 @@ mod tests {
         assert!(result.is_ok(), "expected Ok, got: {:?}", result.err());
     }
 
+    #[test]
+    fn valid_token_attaches_subject() {
+        let mut interceptor = JwtInterceptor::new("secret");
+        let token = encode_token("secret", "billing-service", now_secs() + 3600);
+        let mut request = tonic::Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {token}").parse().expect("valid ASCII"),
+        );
+        let request = interceptor.call(request).expect("token should be accepted");
+        assert_eq!(
+            request.extensions().get::<AuthenticatedSubject>(),
+            Some(&AuthenticatedSubject("billing-service".into()))
+        );
+    }
+
+    #[test]
+    fn valid_token_attaches_all_claims() {
+        let mut interceptor = JwtInterceptor::new("secret");
+        let exp = now_secs() + 3600;
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &serde_json::json!({ "sub": "projector-1", "exp": exp, "roles": ["projector"] }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .expect("encoding should not fail in tests");
+
+        let request = call_with(&mut interceptor, &token).expect("token should be accepted");
+        let claims = request
+            .extensions()
+            .get::<AuthenticatedClaims>()
+            .expect("claims should be attached");
+        assert_eq!(claims.0["sub"], "projector-1");
+        assert_eq!(claims.0["exp"], exp);
+        assert_eq!(claims.0["roles"], serde_json::json!(["projector"]));
+    }
+
     #[test]
     fn wrong_secret_returns_unauthenticated() {
         // Token signed with "wrong-secret", interceptor configured with "correct-secret".
@@ -214,4 +590,302 @@ mod tests {
         let status = result.unwrap_err();
         assert_eq!(status.code(), tonic::Code::Unauthenticated);
     }
+
+    /// Helper: send `token` through `interceptor` as a bearer token.
+    #[allow(clippy::result_large_err)] // mirrors `Interceptor::call`
+    fn call_with(
+        interceptor: &mut JwtInterceptor,
+        token: &str,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        let mut request = tonic::Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {token}").parse().expect("valid ASCII"),
+        );
+        interceptor.call(request)
+    }
+
+    /// Helper: sign `claims` with a PKCS#8 PEM private key.
+    fn sign(
+        algorithm: Algorithm,
+        kid: Option<&str>,
+        private_pem: &str,
+        claims: &serde_json::Value,
+    ) -> String {
+        let key = match algorithm {
+            Algorithm::ES256 => jsonwebtoken::EncodingKey::from_ec_pem(private_pem.as_bytes()),
+            Algorithm::EdDSA => jsonwebtoken::EncodingKey::from_ed_pem(private_pem.as_bytes()),
+            other => panic!("unsupported test algorithm {other:?}"),
+        }
+        .expect("valid private key");
+        let mut header = jsonwebtoken::Header::new(algorithm);
+        header.kid = kid.map(str::to_string);
+        jsonwebtoken::encode(&header, claims, &key).expect("encoding should not fail in tests")
+    }
+
+    /// Helper: claims for `sub` valid for an hour.
+    fn claims(sub: &str) -> serde_json::Value {
+        serde_json::json!({ "sub": sub, "exp": now_secs() + 3600 })
+    }
+
+    /// Helper: a public JWK for a P-256 key pair.
+    fn ec_jwk(key_pair: &rcgen::KeyPair, kid: &str) -> serde_json::Value {
+        use base64::Engine;
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        // Uncompressed SEC1 point: 0x04 || x || y.
+        let raw = key_pair.public_key_raw();
+        serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "kid": kid,
+            "use": "sig",
+            "x": b64.encode(&raw[1..33]),
+            "y": b64.encode(&raw[33..65]),
+        })
+    }
+
+    /// Helper: a public JWK for an Ed25519 key pair.
+    fn ed_jwk(key_pair: &rcgen::KeyPair, kid: &str) -> serde_json::Value {
+        use base64::Engine;
+        serde_json::json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "kid": kid,
+            "alg": "EdDSA",
+            "x": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(key_pair.public_key_raw()),
+        })
+    }
+
+    fn write_jwks(path: &Path, keys: &[serde_json::Value]) {
+        std::fs::write(path, serde_json::json!({ "keys": keys }).to_string()).expect("write JWKS");
+    }
+
+    #[test]
+    fn es256_and_eddsa_pem_keys_verify_tokens() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        for (alg, signature_alg) in [
+            (Algorithm::ES256, &rcgen::PKCS_ECDSA_P256_SHA256),
+            (Algorithm::EdDSA, &rcgen::PKCS_ED25519),
+        ] {
+            let key_pair = rcgen::KeyPair::generate_for(signature_alg).expect("keygen");
+            let path = dir.path().join(format!("{alg:?}.pem"));
+            std::fs::write(&path, key_pair.public_key_pem()).expect("write PEM");
+            let mut interceptor =
+                JwtInterceptor::from_config(JwtConfig::new(JwtKeySource::PublicKeys {
+                    paths: vec![path],
+                    algorithm: alg,
+                }))
+                .expect("PEM key should load");
+
+            let token = sign(alg, None, &key_pair.serialize_pem(), &claims("svc"));
+            let request = call_with(&mut interceptor, &token).expect("token should be accepted");
+            assert_eq!(
+                request.extensions().get::<AuthenticatedSubject>(),
+                Some(&AuthenticatedSubject("svc".into()))
+            );
+
+            // An HS256 token can never be checked against a public key.
+            let forged = encode_token("whatever", "svc", now_secs() + 3600);
+            let status = call_with(&mut interceptor, &forged).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        }
+    }
+
+    #[test]
+    fn jwks_selects_key_by_kid() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("jwks.json");
+        let ec = rcgen::KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256).expect("keygen");
+        let ed = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).expect("keygen");
+        write_jwks(&path, &[ec_jwk(&ec, "ec-1"), ed_jwk(&ed, "ed-1")]);
+        let mut interceptor = JwtInterceptor::from_config(JwtConfig::new(JwtKeySource::Jwks(path)))
+            .expect("JWKS should load");
+
+        let ec_token = sign(
+            Algorithm::ES256,
+            Some("ec-1"),
+            &ec.serialize_pem(),
+            &claims("a"),
+        );
+        call_with(&mut interceptor, &ec_token).expect("ES256 token should be accepted");
+        let ed_token = sign(
+            Algorithm::EdDSA,
+            Some("ed-1"),
+            &ed.serialize_pem(),
+            &claims("b"),
+        );
+        call_with(&mut interceptor, &ed_token).expect("EdDSA token should be accepted");
+
+        let unknown = sign(
+            Algorithm::ES256,
+            Some("ec-2"),
+            &ec.serialize_pem(),
+            &claims("a"),
+        );
+        let status = call_with(&mut interceptor, &unknown).unwrap_err();
+        assert!(
+            status.message().contains("no verification key"),
+            "got: {}",
+            status.message()
+        );
+    }
+
+    #[test]
+    fn jwks_without_signing_keys_is_rejected() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("jwks.json");
+        write_jwks(
+            &path,
+            &[serde_json::json!({ "kty": "oct", "k": "c2VjcmV0" })],
+        );
+
+        match JwtInterceptor::from_config(JwtConfig::new(JwtKeySource::Jwks(path.clone()))) {
+            Err(Error::InvalidKeyFile { path: bad, .. }) => assert_eq!(bad, path),
+            Err(other) => panic!("expected InvalidKeyFile, got: {other:?}"),
+            Ok(_) => panic!("a JWKS with only symmetric keys should be rejected"),
+        }
+    }
+
+    #[test]
+    fn reload_picks_up_rotated_jwks() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("jwks.json");
+        let old = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).expect("keygen");
+        let new = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).expect("keygen");
+        write_jwks(&path, &[ed_jwk(&old, "old")]);
+        let mut interceptor =
+            JwtInterceptor::from_config(JwtConfig::new(JwtKeySource::Jwks(path.clone())))
+                .expect("JWKS should load");
+        let new_token = sign(
+            Algorithm::EdDSA,
+            Some("new"),
+            &new.serialize_pem(),
+            &claims("s"),
+        );
+        assert!(call_with(&mut interceptor, &new_token).is_err());
+
+        write_jwks(&path, &[ed_jwk(&new, "new")]);
+        interceptor.reload().expect("reload should succeed");
+        call_with(&mut interceptor, &new_token).expect("rotated key should be accepted");
+
+        // A broken file keeps the last good keys.
+        std::fs::write(&path, "not json").expect("write");
+        assert!(interceptor.reload().is_err());
+        call_with(&mut interceptor, &new_token).expect("previous keys stay in use");
+    }
+
+    #[test]
+    fn issuer_audience_and_leeway_are_enforced() {
+        let config = JwtConfig {
+            issuers: vec!["https://idp.example".into()],
+            audiences: vec!["eventfold".into()],
+            leeway: Duration::from_secs(60),
+            ..JwtConfig::new(JwtKeySource::Secret("secret".into()))
+        };
+        let mut interceptor = JwtInterceptor::from_config(config).expect("config");
+        let token = |claims: serde_json::Value| {
+            jsonwebtoken::encode(
+                &jsonwebtoken::Header::new(Algorithm::HS256),
+                &claims,
+                &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+            )
+            .expect("encoding should not fail in tests")
+        };
+
+        let good = token(serde_json::json!({
+            "sub": "s", "exp": now_secs() - 30, "iss": "https://idp.example", "aud": "eventfold",
+        }));
+        call_with(&mut interceptor, &good).expect("expiry within leeway should be accepted");
+
+        for bad in [
+            serde_json::json!({ "sub": "s", "exp": now_secs() + 60, "aud": "eventfold" }),
+            serde_json::json!({
+                "sub": "s", "exp": now_secs() + 60, "iss": "https://evil.example", "aud": "eventfold",
+            }),
+            serde_json::json!({
+                "sub": "s", "exp": now_secs() + 60, "iss": "https://idp.example", "aud": "billing",
+            }),
+            serde_json::json!({
+                "sub": "s", "exp": now_secs() - 120, "iss": "https://idp.example", "aud": "eventfold",
+            }),
+        ] {
+            let status = call_with(&mut interceptor, &token(bad.clone())).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated, "claims: {bad}");
+        }
+    }
+
+    #[test]
+    fn token_is_rejected_before_its_nbf() {
+        let mut interceptor = JwtInterceptor::new("secret");
+        let token = |nbf: u64| {
+            jsonwebtoken::encode(
+                &jsonwebtoken::Header::new(Algorithm::HS256),
+                &serde_json::json!({ "sub": "s", "exp": now_secs() + 3600, "nbf": nbf }),
+                &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+            )
+            .expect("encoding should not fail in tests")
+        };
+
+        call_with(&mut interceptor, &token(now_secs() - 60)).expect("past nbf is accepted");
+        let status = call_with(&mut interceptor, &token(now_secs() + 3600)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn aud_is_ignored_without_configured_audiences() {
+        let mut interceptor = JwtInterceptor::new("secret");
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &serde_json::json!({ "sub": "s", "exp": now_secs() + 3600, "aud": "billing" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .expect("encoding should not fail in tests");
+
+        call_with(&mut interceptor, &token).expect("aud is not checked");
+    }
+
+    #[tokio::test]
+    async fn spawn_reload_watches_key_files() {
+        assert!(
+            JwtInterceptor::new("secret")
+                .spawn_reload(Duration::from_millis(10))
+                .is_none()
+        );
+
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("jwks.json");
+        let old = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).expect("keygen");
+        let new = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).expect("keygen");
+        write_jwks(&path, &[ed_jwk(&old, "old")]);
+        let mut interceptor =
+            JwtInterceptor::from_config(JwtConfig::new(JwtKeySource::Jwks(path.clone())))
+                .expect("JWKS should load");
+        let task = interceptor
+            .spawn_reload(Duration::from_millis(10))
+            .expect("key files are watched");
+
+        // Make sure the rewrite gets a different modification time.
+        let later = SystemTime::now() + Duration::from_secs(5);
+        write_jwks(&path, &[ed_jwk(&new, "new")]);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later))
+            .expect("set mtime");
+
+        let token = sign(
+            Algorithm::EdDSA,
+            Some("new"),
+            &new.serialize_pem(),
+            &claims("s"),
+        );
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while call_with(&mut interceptor, &token).is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("reload task should pick up the new key");
+        task.abort();
+    }
 }
@@ -0,0 +1,542 @@
+//! Storage backends behind [`Store`](crate::store::Store).
+//!
+//! A backend is the byte sink the store appends encoded batches to. The store
+//! keeps all format knowledge (file header, batch envelopes, recovery) and the
+//! in-memory index; the backend only has to append bytes at the end, make them
+//! durable on request, and report its length.
+//!
+//! Two backends ship with the crate:
+//!
+//! - [`FileBackend`] -- the append-only log file. This is what
+//!   [`Store::open`](crate::store::Store::open) uses.
+//! - [`MemoryBackend`] -- a growable byte buffer with no durability at all,
+//!   used by [`Store::in_memory`](crate::store::Store::in_memory) so tests and
+//!   embedders can run the writer, broker, and gRPC service without touching
+//!   the filesystem.
+
+use std::fs::File;
+use std::io;
+#[cfg(not(unix))]
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::codec;
+
+/// Byte sink for the encoded event log.
+///
+/// Implementations receive whole batch envelopes from
+/// [`Store::append`](crate::store::Store::append), one call per batch, and
+/// must append them in call order. The store decides when to call
+/// [`sync_all`](StorageBackend::sync_all) or
+/// [`sync_data`](StorageBackend::sync_data) based on its
+/// [`Durability`](crate::store::Durability) mode.
+pub trait StorageBackend: Send {
+    /// Append `buf` to the end of the log.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from the underlying medium. After an error the
+    /// store treats the tail of the log as unknown.
+    fn append(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Make all appended bytes and the log's metadata durable.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from the underlying medium.
+    fn sync_all(&mut self) -> io::Result<()>;
+
+    /// Make all appended bytes durable, skipping metadata not needed to read
+    /// them back.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from the underlying medium.
+    fn sync_data(&mut self) -> io::Result<()>;
+
+    /// Returns the number of bytes in the log, including the file header.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from the underlying medium.
+    fn size(&self) -> io::Result<u64>;
+}
+
+/// [`StorageBackend`] over an append-only log file.
+///
+/// Batches are written at the tracked logical end of the log with positioned
+/// writes, so the file may be longer than the log. With preallocation enabled
+/// the file is grown ahead of the writer in fixed-size, zero-filled extents
+/// (`fallocate(2)` on Linux), which keeps the inode size stable across most
+/// fsyncs. Each batch written into preallocated space is followed by a log
+/// end marker recording the logical end, which recovery checks; it falls back
+/// to treating an all-zero tail as unwritten space for logs without one, see
+/// [`Store::open`](crate::store::Store::open).
+pub struct FileBackend {
+    /// Log file handle, opened read+write.
+    file: File,
+    /// Logical end of the log: the offset the next batch is written at.
+    end: u64,
+    /// Physical length of the file, including preallocated space.
+    allocated: u64,
+    /// Extent size to preallocate in. Zero disables preallocation.
+    extent: u64,
+    /// io_uring submission path, when enabled.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    ring: Option<uring::Ring>,
+}
+
+impl FileBackend {
+    /// Wrap an open log file whose log ends at byte offset `end`.
+    ///
+    /// The file must contain a valid header and complete batches up to `end`;
+    /// anything after `end` is overwritten by later appends.
+    /// [`Store::open`](crate::store::Store::open) guarantees this before it
+    /// builds a backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from reading the file's metadata.
+    pub fn new(file: File, end: u64) -> io::Result<FileBackend> {
+        let allocated = file.metadata()?.len();
+        Ok(FileBackend {
+            file,
+            end,
+            allocated,
+            extent: 0,
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            ring: None,
+        })
+    }
+
+    /// Preallocate the file in extents of `extent` bytes. Zero disables
+    /// preallocation.
+    pub fn with_preallocation(mut self, extent: u64) -> FileBackend {
+        self.extent = extent;
+        self
+    }
+
+    /// Submit writes and syncs through an io_uring instead of `pwrite(2)` and
+    /// `fsync(2)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `io_uring_setup(2)` if the kernel does not
+    /// support io_uring or it is disabled (common in containers).
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub fn with_io_uring(mut self) -> io::Result<FileBackend> {
+        self.ring = Some(uring::Ring::new()?);
+        Ok(self)
+    }
+
+    /// Returns the physical length of the file, including preallocated space.
+    pub fn allocated(&self) -> u64 {
+        self.allocated
+    }
+
+    /// Grow the file so that it is at least `needed` bytes long, rounding up
+    /// to a whole number of extents. A no-op when preallocation is off.
+    fn reserve(&mut self, needed: u64) -> io::Result<()> {
+        if self.extent == 0 || needed <= self.allocated {
+            return Ok(());
+        }
+        let target = needed.div_ceil(self.extent) * self.extent;
+        allocate(&self.file, self.allocated, target)?;
+        self.allocated = target;
+        Ok(())
+    }
+
+    /// Write all of `buf` at byte offset `offset`.
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        if let Some(ring) = self.ring.as_mut() {
+            return ring.write_all_at(&self.file, buf, offset);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileExt;
+            self.file.write_all_at(buf, offset)
+        }
+
+        #[cfg(not(unix))]
+        {
+            self.file.seek(SeekFrom::Start(offset))?;
+            self.file.write_all(buf)
+        }
+    }
+}
+
+/// Extend `file` from `from` to `to` bytes with zero-filled, allocated space.
+///
+/// Uses `fallocate(2)` on Linux so the blocks are reserved up front, and falls
+/// back to `set_len` (a sparse extension) where that is unsupported.
+fn allocate(file: &File, from: u64, to: u64) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        // SAFETY: `fallocate` only reads its integer arguments, and the fd is
+        // owned by `file` for the duration of the call.
+        let rc = unsafe {
+            libc::fallocate(
+                file.as_raw_fd(),
+                0,
+                from as libc::off_t,
+                (to - from) as libc::off_t,
+            )
+        };
+        if rc == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EOPNOTSUPP) {
+            return Err(err);
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = from;
+    file.set_len(to)
+}
+
+impl StorageBackend for FileBackend {
+    fn append(&mut self, buf: &[u8]) -> io::Result<()> {
+        let end = self.end + buf.len() as u64;
+        let written = if self.extent == 0 {
+            self.write_at(buf, self.end)?;
+            end
+        } else {
+            // The log no longer ends at EOF, so follow the batch with a
+            // marker recording where it does end. The next append starts at
+            // `end` and overwrites the marker.
+            let mut block = Vec::with_capacity(buf.len() + codec::LOG_END_SIZE);
+            block.extend_from_slice(buf);
+            block.extend_from_slice(&codec::encode_log_end(end));
+            let written = end + codec::LOG_END_SIZE as u64;
+            self.reserve(written)?;
+            self.write_at(&block, self.end)?;
+            written
+        };
+        self.end = end;
+        if written > self.allocated {
+            self.allocated = written;
+        }
+        Ok(())
+    }
+
+    fn sync_all(&mut self) -> io::Result<()> {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        if let Some(ring) = self.ring.as_mut() {
+            return ring.fsync(&self.file, false);
+        }
+        self.file.sync_all()
+    }
+
+    fn sync_data(&mut self) -> io::Result<()> {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        if let Some(ring) = self.ring.as_mut() {
+            return ring.fsync(&self.file, true);
+        }
+        self.file.sync_data()
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.end)
+    }
+}
+
+/// Minimal synchronous io_uring wrapper for the writer task.
+///
+/// The writer already serializes appends, so each operation is submitted
+/// alone and waited for before returning. The win over plain syscalls is
+/// modest; the ring exists so the write path can later batch submissions.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring {
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+
+    use io_uring::{IoUring, opcode, types};
+
+    /// Submission queue depth. Only one operation is ever in flight.
+    const ENTRIES: u32 = 4;
+
+    /// An io_uring instance owned by a single [`FileBackend`](super::FileBackend).
+    pub(super) struct Ring {
+        ring: IoUring,
+    }
+
+    impl Ring {
+        /// Set up a new ring.
+        pub(super) fn new() -> io::Result<Ring> {
+            Ok(Ring {
+                ring: IoUring::new(ENTRIES)?,
+            })
+        }
+
+        /// Submit `entry`, wait for its completion, and return its result.
+        fn run(&mut self, entry: io_uring::squeue::Entry) -> io::Result<u32> {
+            // SAFETY: every buffer referenced by `entry` is borrowed by the
+            // caller until this function returns, and we wait for the
+            // completion before returning.
+            unsafe {
+                self.ring
+                    .submission()
+                    .push(&entry)
+                    .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+            }
+            self.ring.submit_and_wait(1)?;
+            let cqe = self
+                .ring
+                .completion()
+                .next()
+                .ok_or_else(|| io::Error::other("io_uring completion missing"))?;
+            let res = cqe.result();
+            if res < 0 {
+                return Err(io::Error::from_raw_os_error(-res));
+            }
+            Ok(res as u32)
+        }
+
+        /// Write all of `buf` at `offset`, resubmitting after short writes.
+        pub(super) fn write_all_at(
+            &mut self,
+            file: &File,
+            mut buf: &[u8],
+            mut offset: u64,
+        ) -> io::Result<()> {
+            let fd = types::Fd(file.as_raw_fd());
+            while !buf.is_empty() {
+                let len = buf.len().min(u32::MAX as usize) as u32;
+                let entry = opcode::Write::new(fd, buf.as_ptr(), len)
+                    .offset(offset)
+                    .build();
+                let written = self.run(entry)? as usize;
+                if written == 0 {
+                    return Err(io::ErrorKind::WriteZero.into());
+                }
+                buf = &buf[written..];
+                offset += written as u64;
+            }
+            Ok(())
+        }
+
+        /// Fsync the file, or fdatasync it when `data_only` is set.
+        pub(super) fn fsync(&mut self, file: &File, data_only: bool) -> io::Result<()> {
+            let flags = if data_only {
+                types::FsyncFlags::DATASYNC
+            } else {
+                types::FsyncFlags::empty()
+            };
+            let entry = opcode::Fsync::new(types::Fd(file.as_raw_fd()))
+                .flags(flags)
+                .build();
+            self.run(entry).map(|_| ())
+        }
+    }
+}
+
+/// [`StorageBackend`] that keeps the encoded log in a byte buffer.
+///
+/// Syncs are no-ops and nothing survives the process. The buffer holds exactly
+/// the bytes a [`FileBackend`] would have written, so [`MemoryBackend::bytes`]
+/// can be written out and opened with [`Store::open`](crate::store::Store::open).
+#[derive(Debug, Clone)]
+pub struct MemoryBackend {
+    /// File header followed by every appended batch.
+    data: Vec<u8>,
+}
+
+impl MemoryBackend {
+    /// Create an empty log containing only the file header.
+    pub fn new() -> MemoryBackend {
+        MemoryBackend {
+            data: codec::encode_header().to_vec(),
+        }
+    }
+
+    /// Returns the encoded log, header included.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        MemoryBackend::new()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn append(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.data.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn sync_all(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_data(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+}
+
+/// [`MemoryBackend`] whose syncs always fail, standing in for a disk that
+/// returns `EIO`.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct FailingSyncBackend(MemoryBackend);
+
+#[cfg(test)]
+impl StorageBackend for FailingSyncBackend {
+    fn append(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.0.append(buf)
+    }
+
+    fn sync_all(&mut self) -> io::Result<()> {
+        Err(io::Error::other("EIO"))
+    }
+
+    fn sync_data(&mut self) -> io::Result<()> {
+        Err(io::Error::other("EIO"))
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        self.0.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn memory_backend_starts_with_file_header() {
+        let backend = MemoryBackend::new();
+        assert_eq!(backend.bytes(), codec::encode_header());
+        assert_eq!(
+            backend.size().expect("size"),
+            codec::encode_header().len() as u64
+        );
+    }
+
+    #[test]
+    fn memory_backend_appends_in_order() {
+        let mut backend = MemoryBackend::new();
+        backend.append(b"abc").expect("append");
+        backend.append(b"de").expect("append");
+        backend.sync_all().expect("sync");
+
+        let header_len = codec::encode_header().len();
+        assert_eq!(&backend.bytes()[header_len..], b"abcde");
+        assert_eq!(backend.size().expect("size"), header_len as u64 + 5);
+    }
+
+    #[test]
+    fn file_backend_appends_at_end() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .expect("open");
+        file.write_all(b"head").expect("write");
+
+        let mut backend = FileBackend::new(file, 4).expect("backend");
+        backend.append(b"tail").expect("append");
+        backend.sync_data().expect("sync");
+
+        assert_eq!(backend.size().expect("size"), 8);
+        assert_eq!(std::fs::read(&path).expect("read"), b"headtail");
+    }
+
+    #[test]
+    fn file_backend_preallocates_whole_extents() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .expect("open");
+        file.write_all(b"head").expect("write");
+
+        let mut backend = FileBackend::new(file, 4)
+            .expect("backend")
+            .with_preallocation(4096);
+        backend.append(b"tail").expect("append");
+
+        assert_eq!(backend.size().expect("size"), 8);
+        assert_eq!(backend.allocated(), 4096);
+        let data = std::fs::read(&path).expect("read");
+        assert_eq!(data.len(), 4096);
+        assert_eq!(&data[..8], b"headtail");
+        assert_eq!(&data[8..24], &codec::encode_log_end(8));
+        assert!(data[24..].iter().all(|&b| b == 0));
+
+        // Crossing the extent boundary grows by whole extents, and the next
+        // batch overwrites the previous end marker.
+        backend.append(&[7u8; 5000]).expect("append");
+        assert_eq!(backend.allocated(), 8192);
+        assert_eq!(backend.size().expect("size"), 5008);
+        let data = std::fs::read(&path).expect("read");
+        assert!(data[8..5008].iter().all(|&b| b == 7));
+        assert_eq!(&data[5008..5024], &codec::encode_log_end(5008));
+    }
+
+    #[test]
+    fn file_backend_overwrites_past_logical_end() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        std::fs::write(&path, b"headjunkjunk").expect("write");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .expect("open");
+
+        let mut backend = FileBackend::new(file, 4).expect("backend");
+        backend.append(b"tail").expect("append");
+
+        assert_eq!(std::fs::read(&path).expect("read"), b"headtailjunk");
+        assert_eq!(backend.size().expect("size"), 8);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    #[test]
+    fn io_uring_backend_writes_and_syncs() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        std::fs::write(&path, b"head").expect("write");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .expect("open");
+
+        let backend = FileBackend::new(file, 4).expect("backend");
+        let mut backend = match backend.with_io_uring() {
+            Ok(backend) => backend,
+            // io_uring is often disabled in sandboxes and containers.
+            Err(e) => {
+                eprintln!("skipping: io_uring unavailable: {e}");
+                return;
+            }
+        };
+        backend.append(b"tail").expect("append");
+        backend.sync_data().expect("fdatasync");
+        backend.sync_all().expect("fsync");
+
+        assert_eq!(std::fs::read(&path).expect("read"), b"headtail");
+    }
+}
@@ -0,0 +1,457 @@
+//! Online backups of the event log.
+//!
+//! A [`BackupSource`] takes a consistent, point-in-time copy of a running
+//! store's log while the writer task keeps appending. Consistency comes from
+//! the append-only format: the source reads the committed byte length from the
+//! in-memory [`EventLog`] under a read lock, and every byte before that offset
+//! belongs to a batch that has already been fsynced and indexed. Bytes written
+//! afterwards are never part of the copy, so a backup can never contain a torn
+//! final batch.
+//!
+//! A copy can be cut at any global position; the cut is rounded down to the
+//! nearest batch boundary because batches are atomic on disk. [`restore`]
+//! writes a copy to disk and verifies it by opening it with [`Store::open`].
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::codec::{self, DecodeOutcome};
+use crate::data_dir::sync_parent;
+use crate::error::Error;
+use crate::store::{self, EventLog, Store};
+use crate::types::LogHole;
+
+/// A point-in-time cut of the log that a backup copies.
+///
+/// # Fields
+///
+/// * `len` - Number of bytes of the log file in the copy.
+/// * `next_position` - First global position *not* contained in the copy.
+/// * `holes` - Holes that fall inside the copy and must be restored with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Number of bytes of the log file in the copy.
+    pub len: u64,
+    /// First global position not contained in the copy.
+    pub next_position: u64,
+    /// Holes that fall inside the copy and must be restored with it.
+    pub holes: Vec<LogHole>,
+}
+
+/// Handle for taking consistent backups of a running store.
+///
+/// Obtained from [`Store::backup_source`] before the store is moved into the
+/// writer task. Cloning is cheap; all clones share the same event log.
+#[derive(Clone, Debug)]
+pub struct BackupSource {
+    /// Path of the live log file.
+    path: PathBuf,
+    /// Shared in-memory event log, used to find the committed end of the file.
+    log: Arc<RwLock<EventLog>>,
+}
+
+impl BackupSource {
+    /// Create a backup source for the log at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the live log file.
+    /// * `log` - The event log shared with the store that owns `path`.
+    ///
+    /// # Returns
+    ///
+    /// A new `BackupSource`.
+    pub fn new(path: PathBuf, log: Arc<RwLock<EventLog>>) -> BackupSource {
+        BackupSource { path, log }
+    }
+
+    /// Returns the path of the live log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Choose the cut for a backup containing events below `to_position`.
+    ///
+    /// With `to_position` unset (or at or past the head), the cut is the
+    /// current committed end of the log. Otherwise the log is scanned for the
+    /// last batch boundary at or below `to_position`.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_position` - Exclusive upper bound on the global positions to
+    ///   copy, or `None` for everything committed so far.
+    ///
+    /// # Returns
+    ///
+    /// The [`Snapshot`] describing the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the log file cannot be read.
+    /// Returns [`Error::CorruptRecord`] if the committed part of the log no
+    /// longer decodes.
+    pub fn snapshot(&self, to_position: Option<u64>) -> Result<Snapshot, Error> {
+        let (log_len, next_position, holes) = {
+            let log = self.log.read().expect("EventLog RwLock poisoned");
+            (log.log_len, log.next_position(), log.holes.clone())
+        };
+
+        let snapshot = match to_position {
+            Some(to) if to < next_position => {
+                let mut data = Vec::with_capacity(log_len as usize);
+                File::open(&self.path)?
+                    .take(log_len)
+                    .read_to_end(&mut data)?;
+                let (len, next_position) = batch_boundary(&data, &holes, to)?;
+                Snapshot {
+                    len: len as u64,
+                    next_position,
+                    holes: holes
+                        .into_iter()
+                        .filter(|h| h.end_offset <= len as u64)
+                        .collect(),
+                }
+            }
+            _ => Snapshot {
+                len: log_len,
+                next_position,
+                holes,
+            },
+        };
+        Ok(snapshot)
+    }
+
+    /// Open a reader over the bytes of `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - A cut previously returned by [`BackupSource::snapshot`].
+    ///
+    /// # Returns
+    ///
+    /// A reader yielding exactly `snapshot.len` bytes of the log file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the log file cannot be opened.
+    pub fn open_snapshot(&self, snapshot: &Snapshot) -> Result<std::io::Take<File>, Error> {
+        Ok(File::open(&self.path)?.take(snapshot.len))
+    }
+
+    /// Write a verified backup of the log to `dest`.
+    ///
+    /// Takes a snapshot, copies it with [`restore`], and returns the snapshot
+    /// that was written. Appends continue while the copy is made.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest` - Path of the backup log file. Must not exist.
+    /// * `to_position` - Exclusive upper bound on the global positions to
+    ///   copy, or `None` for everything committed so far.
+    ///
+    /// # Returns
+    ///
+    /// The [`Snapshot`] that was copied.
+    ///
+    /// # Errors
+    ///
+    /// See [`BackupSource::snapshot`] and [`restore`].
+    pub fn backup_to_path(&self, dest: &Path, to_position: Option<u64>) -> Result<Snapshot, Error> {
+        let snapshot = self.snapshot(to_position)?;
+        let reader = self.open_snapshot(&snapshot)?;
+        restore(dest, reader, &snapshot)?;
+        Ok(snapshot)
+    }
+}
+
+/// Write a backup to `dest` and verify it opens as a store.
+///
+/// Copies `data` to a temporary file next to `dest`, fsyncs it, renames it into
+/// place, writes the holes sidecar if the snapshot has holes, and fsyncs the
+/// directory. The copy is then opened with [`Store::open_read_only`]; the
+/// restore fails if the copy has a torn tail or does not end at
+/// `snapshot.next_position`, and the copy is removed again.
+///
+/// # Arguments
+///
+/// * `dest` - Path of the restored log file. Must not exist.
+/// * `data` - The backup bytes, e.g. from [`BackupSource::open_snapshot`] or a
+///   `Backup` RPC stream.
+/// * `snapshot` - Description of the backup, used for verification.
+///
+/// # Returns
+///
+/// The restored copy, opened read-only. Open it again with [`Store::open`] to
+/// append to it.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidArgument`] if `dest` already exists.
+/// Returns [`Error::Io`] if writing the copy fails.
+/// Returns [`Error::CorruptRecord`] if the copy fails verification.
+pub fn restore(dest: &Path, mut data: impl Read, snapshot: &Snapshot) -> Result<Store, Error> {
+    if dest.exists() {
+        return Err(Error::InvalidArgument(format!(
+            "backup destination already exists: {}",
+            dest.display()
+        )));
+    }
+
+    let mut tmp_name = dest.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    let mut file = File::create(&tmp_path)?;
+    let copied = std::io::copy(&mut data, &mut file)?;
+    file.flush()?;
+    file.sync_all()?;
+    drop(file);
+    if copied != snapshot.len {
+        std::fs::remove_file(&tmp_path)?;
+        return Err(Error::CorruptRecord {
+            position: snapshot.next_position,
+            detail: format!("backup is {copied} bytes, expected {} bytes", snapshot.len),
+        });
+    }
+    std::fs::rename(&tmp_path, dest)?;
+
+    // Leave nothing behind on failure, so the same destination can be retried.
+    let verified = verify_restored(dest, snapshot);
+    if verified.is_err() {
+        let _ = std::fs::remove_file(dest);
+        let _ = std::fs::remove_file(store::holes_path(dest));
+    }
+    verified
+}
+
+/// Write the holes sidecar for a copy renamed into place at `dest`, make the
+/// rename durable, and check that the copy opens and ends where `snapshot`
+/// says.
+fn verify_restored(dest: &Path, snapshot: &Snapshot) -> Result<Store, Error> {
+    if !snapshot.holes.is_empty() {
+        store::write_holes(&store::holes_path(dest), &snapshot.holes)?;
+    }
+    sync_parent(dest)?;
+
+    // Read-only, so no lock sidecar is left next to the copy.
+    let restored = Store::open_read_only(dest)?;
+    if restored.log_file_len()? != snapshot.len
+        || restored.global_position() != snapshot.next_position
+    {
+        return Err(Error::CorruptRecord {
+            position: restored.global_position(),
+            detail: format!(
+                "restored backup ends at position {}, expected {}",
+                restored.global_position(),
+                snapshot.next_position
+            ),
+        });
+    }
+    Ok(restored)
+}
+
+/// Find the last batch boundary in `data` that keeps every copied position
+/// below `to_position`.
+///
+/// # Returns
+///
+/// `(offset, next_position)`: the byte offset of the boundary and the first
+/// global position after it.
+///
+/// # Errors
+///
+/// Returns [`Error::CorruptRecord`] if a batch in `data` does not decode.
+fn batch_boundary(data: &[u8], holes: &[LogHole], to_position: u64) -> Result<(usize, u64), Error> {
+    let mut offset = store::HEADER_SIZE;
+    let mut next_position = 0;
+
+    while offset < data.len() {
+        // Holes are skipped whole, like batches.
+        if let Some(hole) = holes.iter().find(|h| h.start_offset == offset as u64) {
+            let hole_end = hole.first_position + hole.missing_count;
+            if hole_end > to_position {
+                break;
+            }
+            offset = hole.end_offset as usize;
+            next_position = hole_end;
+            continue;
+        }
+
+        let corrupt = |detail: &str| Error::CorruptRecord {
+            position: next_position,
+            detail: format!("{detail} at byte offset {offset} while scanning for backup cut"),
+        };
+        let Ok(DecodeOutcome::Complete {
+            value: header,
+            consumed,
+        }) = codec::decode_batch_header(&data[offset..])
+        else {
+            return Err(corrupt("undecodable batch header"));
+        };
+        let batch_end = header.first_global_pos + u64::from(header.record_count);
+        if batch_end > to_position {
+            break;
+        }
+
+        let mut end = offset + consumed;
+        for _ in 0..header.record_count {
+            match codec::decode_record(&data[end..]) {
+                Ok(DecodeOutcome::Complete { consumed, .. }) => end += consumed,
+                _ => return Err(corrupt("undecodable record")),
+            }
+        }
+        offset = end + codec::BATCH_FOOTER_SIZE;
+        next_position = batch_end;
+    }
+
+    Ok((offset.min(data.len()), next_position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ExpectedVersion, ProposedEvent};
+    use bytes::Bytes;
+    use uuid::Uuid;
+
+    /// Helper: build a `ProposedEvent` with the given event type.
+    fn proposed(event_type: &str) -> ProposedEvent {
+        ProposedEvent {
+            event_id: Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            metadata: Bytes::new(),
+            payload: Bytes::from_static(b"{}"),
+        }
+    }
+
+    /// Helper: open a store and append `batches` single-event batches to one
+    /// stream, then a two-event batch. Positions: 0..batches, then two more.
+    fn store_with_batches(path: &Path, batches: u64) -> Store {
+        let mut store = Store::open(path).expect("open should succeed");
+        let stream_id = Uuid::new_v4();
+        for _ in 0..batches {
+            store
+                .append(stream_id, ExpectedVersion::Any, 0, vec![proposed("One")])
+                .expect("append should succeed");
+        }
+        store
+            .append(
+                stream_id,
+                ExpectedVersion::Any,
+                0,
+                vec![proposed("Pair"), proposed("Pair")],
+            )
+            .expect("append should succeed");
+        store
+    }
+
+    #[test]
+    fn backup_to_path_copies_full_log() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store_with_batches(&dir.path().join("events.log"), 3);
+        let source = store
+            .backup_source()
+            .expect("file store has a backup source");
+
+        let dest = dir.path().join("backup.log");
+        let snapshot = source
+            .backup_to_path(&dest, None)
+            .expect("backup should succeed");
+        assert_eq!(snapshot.next_position, 5);
+        assert_eq!(snapshot.len, store.log_file_len().expect("len"));
+
+        let restored = Store::open(&dest).expect("backup should open");
+        assert_eq!(restored.read_all(0, 100), store.read_all(0, 100));
+    }
+
+    #[test]
+    fn snapshot_cut_rounds_down_to_batch_boundary() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store_with_batches(&dir.path().join("events.log"), 3);
+        let source = store
+            .backup_source()
+            .expect("file store has a backup source");
+
+        // Position 4 falls inside the final two-event batch (positions 3, 4).
+        let snapshot = source.snapshot(Some(4)).expect("snapshot should succeed");
+        assert_eq!(snapshot.next_position, 3);
+
+        let dest = dir.path().join("backup.log");
+        let restored = restore(
+            &dest,
+            source.open_snapshot(&snapshot).expect("open snapshot"),
+            &snapshot,
+        )
+        .expect("restore should succeed");
+        assert_eq!(restored.global_position(), 3);
+        assert_eq!(restored.read_all(0, 100), store.read_all(0, 3));
+    }
+
+    #[test]
+    fn snapshot_ignores_appends_after_the_cut() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let mut store = store_with_batches(&dir.path().join("events.log"), 1);
+        let source = store
+            .backup_source()
+            .expect("file store has a backup source");
+        let snapshot = source.snapshot(None).expect("snapshot should succeed");
+
+        // An append after the snapshot must not leak into the copy.
+        store
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::Any,
+                0,
+                vec![proposed("Late")],
+            )
+            .expect("append should succeed");
+
+        let dest = dir.path().join("backup.log");
+        let restored = restore(
+            &dest,
+            source.open_snapshot(&snapshot).expect("open snapshot"),
+            &snapshot,
+        )
+        .expect("restore should succeed");
+        assert_eq!(restored.global_position(), 3);
+    }
+
+    #[test]
+    fn restore_rejects_existing_destination() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store_with_batches(&dir.path().join("events.log"), 1);
+        let dest = dir.path().join("backup.log");
+        std::fs::write(&dest, b"occupied").expect("write dest");
+
+        match store
+            .backup_source()
+            .expect("file store has a backup source")
+            .backup_to_path(&dest, None)
+        {
+            Err(Error::InvalidArgument(msg)) => assert!(msg.contains("already exists")),
+            other => panic!("expected InvalidArgument, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn restore_rejects_short_copy() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store_with_batches(&dir.path().join("events.log"), 1);
+        let source = store
+            .backup_source()
+            .expect("file store has a backup source");
+        let snapshot = source.snapshot(None).expect("snapshot should succeed");
+
+        let truncated = source
+            .open_snapshot(&snapshot)
+            .expect("open snapshot")
+            .take(snapshot.len - 1);
+        let dest = dir.path().join("backup.log");
+        match restore(&dest, truncated, &snapshot) {
+            Err(Error::CorruptRecord { .. }) => {}
+            other => panic!("expected CorruptRecord, got: {:?}", other.map(|_| ())),
+        }
+        assert!(!dest.exists());
+    }
+}
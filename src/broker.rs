@@ -103,11 +103,11 @@ const CATCHUP_BATCH_SIZE: u64 = 500;
 ///
 /// A stream yielding `Result<SubscriptionMessage, Error>`. The stream yields `Event` variants
 /// during catch-up and live phases, a single `CaughtUp` marker between them, and terminates
-/// with `Err(Error::InvalidArgument(...))` if the broadcast receiver lags.
+/// with `Err(Error::SubscriptionLagged)` if the broadcast receiver lags.
 ///
 /// # Errors
 ///
-/// Yields `Error::InvalidArgument` if the broadcast receiver falls behind and the channel
+/// Yields `Error::SubscriptionLagged` if the broadcast receiver falls behind and the channel
 /// reports a lag. The consumer should re-subscribe from their last processed position.
 pub async fn subscribe_all(
     read_index: ReadIndex,
@@ -156,9 +156,7 @@ pub async fn subscribe_all(
                 }
                 Err(broadcast::error::RecvError::Lagged(_)) => {
                     // Step 5: Lag termination.
-                    yield Err(Error::InvalidArgument(
-                        "subscription lagged: re-subscribe from last checkpoint".into(),
-                    ));
+                    yield Err(Error::SubscriptionLagged);
                     return;
                 }
                 Err(broadcast::error::RecvError::Closed) => {
@@ -191,11 +189,11 @@ pub async fn subscribe_all(
 ///
 /// A stream yielding `Result<SubscriptionMessage, Error>`. The stream yields `Event` variants
 /// during catch-up and live phases, a single `CaughtUp` marker between them, and terminates
-/// with `Err(Error::InvalidArgument(...))` if the broadcast receiver lags.
+/// with `Err(Error::SubscriptionLagged)` if the broadcast receiver lags.
 ///
 /// # Errors
 ///
-/// Yields `Error::InvalidArgument` if the broadcast receiver falls behind and the channel
+/// Yields `Error::SubscriptionLagged` if the broadcast receiver falls behind and the channel
 /// reports a lag. The consumer should re-subscribe from their last processed stream version.
 pub async fn subscribe_stream(
     read_index: ReadIndex,
@@ -264,9 +262,7 @@ pub async fn subscribe_stream(
                 }
                 Err(broadcast::error::RecvError::Lagged(_)) => {
                     // Step 5: Lag termination.
-                    yield Err(Error::InvalidArgument(
-                        "subscription lagged: re-subscribe from last checkpoint".into(),
-                    ));
+                    yield Err(Error::SubscriptionLagged);
                     return;
                 }
                 Err(broadcast::error::RecvError::Closed) => {
@@ -297,6 +293,7 @@ mod tests {
             event_type: event_type.to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         }
     }
 
@@ -860,7 +857,7 @@ mod tests {
 
     // AC-11: subscribe_all -- lag termination. Create broker with capacity 4,
     // start subscription on empty store, append 10 events without polling the
-    // stream, then poll. The stream should yield Err(Error::InvalidArgument)
+    // stream, then poll. The stream should yield Err(Error::SubscriptionLagged)
     // and then None (stream ends).
     #[tokio::test]
     async fn ac11_subscribe_all_lag_termination() {
@@ -897,7 +894,7 @@ mod tests {
         //    after -- catch-up reads the current index, so it may see some events).
         // 2. Emit CaughtUp.
         // 3. Start draining broadcast receiver which will have lagged.
-        // 4. Yield Err(Error::InvalidArgument(...)).
+        // 4. Yield Err(Error::SubscriptionLagged).
         // 5. Return None (stream ends).
         let mut got_error = false;
         let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
@@ -912,8 +909,8 @@ mod tests {
                 Some(Ok(SubscriptionMessage::CaughtUp)) => continue,
                 Some(Err(ref e)) => {
                     assert!(
-                        matches!(e, crate::error::Error::InvalidArgument(_)),
-                        "expected InvalidArgument error, got: {e:?}"
+                        matches!(e, crate::error::Error::SubscriptionLagged),
+                        "expected SubscriptionLagged error, got: {e:?}"
                     );
                     got_error = true;
                 }
@@ -0,0 +1,151 @@
+//! Wall-clock source for server-assigned timestamps.
+//!
+//! The writer stamps every batch with `recorded_at` from a [`Clock`]. Servers
+//! use [`SystemClock`]; tests and embedders that need exact, repeatable
+//! timestamps pass a [`ManualClock`] to
+//! [`spawn_writer_with_clock`](crate::writer::spawn_writer_with_clock) and move
+//! it by hand.
+//!
+//! The clock is the writer's only source of nondeterminism. It assigns no
+//! IDs: event IDs come from the caller's
+//! [`ProposedEvent`](crate::types::ProposedEvent), and global positions and
+//! stream versions are sequential. With a [`ManualClock`], the same appends
+//! always produce the same recorded events.
+//!
+//! Readings are turned into `recorded_at` values by a stamper, which keeps
+//! them non-decreasing along the log.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use metrics::counter;
+
+/// A source of wall-clock time in Unix epoch milliseconds.
+///
+/// The writer reads it once per batch. It need not be monotonic: readings
+/// that go backwards are clamped to the latest `recorded_at` in the log.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Current time in Unix epoch milliseconds.
+    fn now_millis(&self) -> u64;
+}
+
+/// The system wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock before Unix epoch")
+            .as_millis() as u64
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one clone and hand another
+/// to the writer.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    millis: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Create a clock reading `millis` Unix epoch milliseconds.
+    pub fn new(millis: u64) -> ManualClock {
+        ManualClock {
+            millis: Arc::new(AtomicU64::new(millis)),
+        }
+    }
+
+    /// Set the time to `millis`. Moving backwards is allowed, to simulate a
+    /// clock step.
+    pub fn set(&self, millis: u64) {
+        self.millis.store(millis, Ordering::SeqCst);
+    }
+
+    /// Move the time forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        self.millis
+            .fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> u64 {
+        self.millis.load(Ordering::SeqCst)
+    }
+}
+
+/// Turns [`Clock`] readings into non-decreasing `recorded_at` values.
+///
+/// A reading earlier than the last stamp, or than the floor it was created
+/// with, is clamped to it, logged, and counted in
+/// `eventfold_clock_clamps_total`. The writer and the re-appending import
+/// both stamp through one, seeded with the latest `recorded_at` in the log.
+#[derive(Debug)]
+pub(crate) struct Stamper {
+    clock: Arc<dyn Clock>,
+    last: u64,
+}
+
+impl Stamper {
+    /// Create a stamper that never returns less than `floor`.
+    pub(crate) fn new(clock: Arc<dyn Clock>, floor: u64) -> Stamper {
+        Stamper { clock, last: floor }
+    }
+
+    /// The next `recorded_at`: the clock's reading, but never earlier than the
+    /// previous stamp.
+    pub(crate) fn stamp(&mut self) -> u64 {
+        let now = self.clock.now_millis();
+        if now < self.last {
+            counter!("eventfold_clock_clamps_total").increment(1);
+            tracing::warn!(
+                now,
+                last_recorded_at = self.last,
+                behind_ms = self.last - now,
+                "wall clock went backwards; keeping the last recorded_at"
+            );
+        }
+        self.last = now.max(self.last);
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_clones_share_time() {
+        let clock = ManualClock::new(1_000);
+        let shared = clock.clone();
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(shared.now_millis(), 3_000);
+        shared.set(500);
+        assert_eq!(clock.now_millis(), 500);
+    }
+
+    #[test]
+    fn stamper_never_goes_backwards() {
+        let clock = ManualClock::new(1_000);
+        let mut stamper = Stamper::new(Arc::new(clock.clone()), 2_000);
+
+        assert_eq!(stamper.stamp(), 2_000);
+        clock.set(3_000);
+        assert_eq!(stamper.stamp(), 3_000);
+        clock.set(2_500);
+        assert_eq!(stamper.stamp(), 3_000);
+        clock.set(4_000);
+        assert_eq!(stamper.stamp(), 4_000);
+    }
+
+    #[test]
+    fn system_clock_is_after_2020() {
+        assert!(SystemClock.now_millis() > 1_577_836_800_000);
+    }
+}
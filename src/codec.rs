@@ -7,12 +7,23 @@
 //! The file header is a fixed 8-byte sequence (magic number + format version).
 //! Each record is a length-prefixed, CRC32-checksummed binary frame containing
 //! a single [`RecordedEvent`].
+//!
+//! A record may end with an optional audit section after the payload holding
+//! the event's [`AuditInfo`]. Records written without auditing are unchanged,
+//! and decoders that predate the section ignore it, so it does not change the
+//! format version.
+//!
+//! A log with preallocated space also carries a log end marker directly after
+//! its last batch, recording the byte offset the log ends at. The next batch
+//! overwrites it. Decoders that predate the marker treat it as a torn tail and
+//! drop it, which loses nothing, so it does not change the format version
+//! either.
 
 use bytes::Bytes;
 use uuid::Uuid;
 
 use crate::error::Error;
-use crate::types::RecordedEvent;
+use crate::types::{AuditInfo, RecordedEvent};
 
 /// Magic bytes identifying an EventfoldDB log file (ASCII "EFDB").
 const MAGIC: [u8; 4] = [0x45, 0x46, 0x44, 0x42];
@@ -26,6 +37,9 @@ pub(crate) const BATCH_HEADER_MAGIC: [u8; 4] = [0x45, 0x46, 0x42, 0x42];
 /// Magic bytes identifying a batch footer (ASCII "EFBF").
 pub(crate) const BATCH_FOOTER_MAGIC: [u8; 4] = [0x45, 0x46, 0x42, 0x46];
 
+/// Magic bytes identifying a log end marker (ASCII "EFLE").
+pub(crate) const LOG_END_MAGIC: [u8; 4] = [0x45, 0x46, 0x4C, 0x45];
+
 /// Result of attempting to decode a value from a byte buffer.
 ///
 /// Distinguishes between a successfully decoded value and a buffer that does
@@ -136,11 +150,17 @@ const FIXED_BODY_SIZE: usize = 8 + 8 + 16 + 8 + 16 + 2 + 4 + 4 + 4;
 /// Size of the length prefix field in bytes.
 const LENGTH_PREFIX_SIZE: usize = 4;
 
+/// Size of the optional audit section excluding its strings:
+/// principal_len(2) + client_addr_len(2).
+const AUDIT_FIXED_SIZE: usize = 2 + 2;
+
 /// Encode a [`RecordedEvent`] into the binary on-disk format.
 ///
 /// The returned buffer contains the length prefix, all record fields, and a
 /// trailing CRC32 checksum. The caller can append this directly to the log
-/// file.
+/// file. If the event carries [`AuditInfo`], an audit section (principal and
+/// client address, each a `u16` length followed by UTF-8 bytes, empty when
+/// unknown) follows the payload.
 ///
 /// # Arguments
 ///
@@ -151,7 +171,13 @@ const LENGTH_PREFIX_SIZE: usize = 4;
 /// A `Vec<u8>` containing the complete binary record.
 pub fn encode_record(event: &RecordedEvent) -> Vec<u8> {
     let et_bytes = event.event_type.as_bytes();
-    let body_len = FIXED_BODY_SIZE + et_bytes.len() + event.metadata.len() + event.payload.len();
+    let audit_len = event.audit.as_ref().map_or(0, |audit| {
+        AUDIT_FIXED_SIZE
+            + audit.principal.as_deref().map_or(0, str::len)
+            + audit.client_addr.as_deref().map_or(0, str::len)
+    });
+    let body_len =
+        FIXED_BODY_SIZE + et_bytes.len() + event.metadata.len() + event.payload.len() + audit_len;
     let total_len = LENGTH_PREFIX_SIZE + body_len;
 
     let mut buf = Vec::with_capacity(total_len);
@@ -159,7 +185,7 @@ pub fn encode_record(event: &RecordedEvent) -> Vec<u8> {
     // record_length: byte count from global_position through checksum (inclusive).
     buf.extend_from_slice(&(body_len as u32).to_le_bytes());
 
-    // -- Begin body (CRC32 covers from here through payload) --
+    // -- Begin body (CRC32 covers from here through the audit section) --
     buf.extend_from_slice(&event.global_position.to_le_bytes());
     buf.extend_from_slice(&event.recorded_at.to_le_bytes());
     buf.extend_from_slice(event.stream_id.as_bytes());
@@ -171,6 +197,13 @@ pub fn encode_record(event: &RecordedEvent) -> Vec<u8> {
     buf.extend_from_slice(&event.metadata);
     buf.extend_from_slice(&(event.payload.len() as u32).to_le_bytes());
     buf.extend_from_slice(&event.payload);
+    if let Some(audit) = &event.audit {
+        for field in [&audit.principal, &audit.client_addr] {
+            let bytes = field.as_deref().unwrap_or_default().as_bytes();
+            buf.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+            buf.extend_from_slice(bytes);
+        }
+    }
     // -- End body --
 
     // CRC32 over the body (everything after record_length, before checksum).
@@ -313,6 +346,29 @@ pub fn decode_record(buf: &[u8]) -> Result<DecodeOutcome<RecordedEvent>, Error>
 
     // payload (raw bytes)
     let pay_bytes = read_bytes!(payload_len);
+
+    // Optional audit section: principal and client address, each a u16
+    // length followed by UTF-8 bytes.
+    let audit = if cursor < protected.len() {
+        let mut fields = [None, None];
+        for field in &mut fields {
+            let len_bytes = read_bytes!(2);
+            let len = u16::from_le_bytes(len_bytes.try_into().expect("2 bytes for u16")) as usize;
+            let bytes = read_bytes!(len);
+            let value = std::str::from_utf8(bytes).map_err(|e| Error::CorruptRecord {
+                position: 0,
+                detail: format!("invalid UTF-8 in audit field: {e}"),
+            })?;
+            *field = (!value.is_empty()).then(|| value.to_string());
+        }
+        let [principal, client_addr] = fields;
+        Some(AuditInfo {
+            principal,
+            client_addr,
+        })
+    } else {
+        None
+    };
     // Cursor is intentionally not read after the last field; suppress the warning.
     let _ = cursor;
 
@@ -325,6 +381,7 @@ pub fn decode_record(buf: &[u8]) -> Result<DecodeOutcome<RecordedEvent>, Error>
         event_type: event_type.to_string(),
         metadata: Bytes::copy_from_slice(meta_bytes),
         payload: Bytes::copy_from_slice(pay_bytes),
+        audit,
     };
 
     Ok(DecodeOutcome::Complete {
@@ -451,6 +508,73 @@ pub fn decode_batch_footer(buf: &[u8]) -> Result<DecodeOutcome<BatchFooter>, Err
     })
 }
 
+/// Size of a log end marker on disk in bytes (magic 4 + end 8 + crc 4).
+pub(crate) const LOG_END_SIZE: usize = 16;
+
+/// Encode a log end marker as a fixed 16-byte array.
+///
+/// The marker consists of the 4-byte magic (`EFLE`), followed by the logical
+/// end of the log as `u64` LE and a CRC32 over the preceding 12 bytes as
+/// `u32` LE.
+///
+/// # Arguments
+///
+/// * `end` - Byte offset the log ends at, which is also where the marker is
+///   written.
+///
+/// # Returns
+///
+/// A 16-byte array containing the encoded marker.
+pub fn encode_log_end(end: u64) -> [u8; 16] {
+    let mut buf = [0u8; LOG_END_SIZE];
+    buf[0..4].copy_from_slice(&LOG_END_MAGIC);
+    buf[4..12].copy_from_slice(&end.to_le_bytes());
+    let crc = crc32fast::hash(&buf[0..12]);
+    buf[12..16].copy_from_slice(&crc.to_le_bytes());
+    buf
+}
+
+/// Decode a log end marker from the start of a byte buffer.
+///
+/// Returns [`DecodeOutcome::Incomplete`] if the buffer has fewer than 16
+/// bytes.
+///
+/// # Arguments
+///
+/// * `buf` - A byte slice starting at the beginning of a log end marker.
+///
+/// # Returns
+///
+/// A [`DecodeOutcome<u64>`] holding the recorded end offset on success.
+///
+/// # Errors
+///
+/// Returns [`Error::CorruptRecord`] if the magic bytes are wrong or the CRC
+/// does not match.
+pub fn decode_log_end(buf: &[u8]) -> Result<DecodeOutcome<u64>, Error> {
+    if buf.len() < LOG_END_SIZE {
+        return Ok(DecodeOutcome::Incomplete);
+    }
+    if buf[0..4] != LOG_END_MAGIC {
+        return Err(Error::CorruptRecord {
+            position: 0,
+            detail: "wrong log end marker magic bytes".to_string(),
+        });
+    }
+    let stored_crc = u32::from_le_bytes([buf[12], buf[13], buf[14], buf[15]]);
+    if stored_crc != crc32fast::hash(&buf[0..12]) {
+        return Err(Error::CorruptRecord {
+            position: 0,
+            detail: "log end marker CRC mismatch".to_string(),
+        });
+    }
+    let end = u64::from_le_bytes(buf[4..12].try_into().expect("slice is exactly 8 bytes"));
+    Ok(DecodeOutcome::Complete {
+        value: end,
+        consumed: LOG_END_SIZE,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -466,6 +590,7 @@ mod tests {
             event_type: "TestEvent".to_string(),
             metadata: bytes::Bytes::new(),
             payload: bytes::Bytes::from_static(b"{}"),
+            audit: None,
         };
         let outcome = DecodeOutcome::Complete {
             value: event,
@@ -507,6 +632,7 @@ mod tests {
             event_type: event_type.to_string(),
             metadata: bytes::Bytes::copy_from_slice(metadata),
             payload: bytes::Bytes::copy_from_slice(payload),
+            audit: None,
         }
     }
 
@@ -801,6 +927,75 @@ mod tests {
         );
     }
 
+    // Audit section
+
+    #[test]
+    fn round_trip_preserves_audit_info() {
+        let mut event = make_event(3, 1, "OrderPlaced", b"meta", b"{}");
+        event.audit = Some(AuditInfo {
+            principal: Some("billing-service".to_string()),
+            client_addr: Some("10.0.0.7:51234".to_string()),
+        });
+        let buf = encode_record(&event);
+        assert_eq!(
+            buf.len(),
+            encode_record(&make_event(3, 1, "OrderPlaced", b"meta", b"{}")).len()
+                + AUDIT_FIXED_SIZE
+                + "billing-service".len()
+                + "10.0.0.7:51234".len()
+        );
+        match decode_record(&buf).expect("decode should succeed") {
+            DecodeOutcome::Complete { value, consumed } => {
+                assert_eq!(value, event);
+                assert_eq!(consumed, buf.len());
+            }
+            DecodeOutcome::Incomplete => panic!("expected Complete, got Incomplete"),
+        }
+    }
+
+    #[test]
+    fn audit_with_unknown_fields_round_trips_as_empty_audit() {
+        let mut event = make_event(0, 0, "Anonymous", b"", b"");
+        event.audit = Some(AuditInfo {
+            principal: None,
+            client_addr: Some("[::1]:4000".to_string()),
+        });
+        let buf = encode_record(&event);
+        match decode_record(&buf).expect("decode should succeed") {
+            DecodeOutcome::Complete { value, .. } => assert_eq!(value, event),
+            DecodeOutcome::Incomplete => panic!("expected Complete, got Incomplete"),
+        }
+
+        event.audit = Some(AuditInfo::default());
+        match decode_record(&encode_record(&event)).expect("decode should succeed") {
+            DecodeOutcome::Complete { value, .. } => {
+                assert_eq!(value.audit, Some(AuditInfo::default()));
+            }
+            DecodeOutcome::Incomplete => panic!("expected Complete, got Incomplete"),
+        }
+    }
+
+    #[test]
+    fn truncated_audit_section_returns_corrupt_record() {
+        let event = make_event(0, 0, "AB", b"", b"");
+        let mut buf = encode_record(&event);
+        // Splice a lone principal length (claiming 5 bytes) before the
+        // checksum, then fix up the length prefix and CRC.
+        let crc_offset = buf.len() - 4;
+        buf.truncate(crc_offset);
+        buf.extend_from_slice(&5u16.to_le_bytes());
+        let body_len = (buf.len() - 4 + 4) as u32;
+        buf[0..4].copy_from_slice(&body_len.to_le_bytes());
+        let crc = crc32fast::hash(&buf[4..]);
+        buf.extend_from_slice(&crc.to_le_bytes());
+
+        let result = decode_record(&buf);
+        assert!(
+            matches!(result, Err(Error::CorruptRecord { .. })),
+            "expected CorruptRecord for truncated audit section, got: {result:?}"
+        );
+    }
+
     // AC-1: Header encoding
 
     #[test]
@@ -1026,4 +1221,32 @@ mod tests {
     fn fixed_body_size_is_70() {
         assert_eq!(FIXED_BODY_SIZE, 70);
     }
+
+    #[test]
+    fn log_end_marker_round_trip() {
+        let buf = encode_log_end(0x0123_4567_89AB_CDEF);
+        assert_eq!(&buf[0..4], b"EFLE");
+        match decode_log_end(&buf).expect("decode should succeed") {
+            DecodeOutcome::Complete { value, consumed } => {
+                assert_eq!(value, 0x0123_4567_89AB_CDEF);
+                assert_eq!(consumed, LOG_END_SIZE);
+            }
+            DecodeOutcome::Incomplete => panic!("expected Complete"),
+        }
+        assert!(matches!(
+            decode_log_end(&buf[..15]),
+            Ok(DecodeOutcome::Incomplete)
+        ));
+    }
+
+    #[test]
+    fn log_end_marker_rejects_a_flipped_bit() {
+        let mut buf = encode_log_end(4096);
+        buf[5] ^= 0x01;
+        let result = decode_log_end(&buf);
+        assert!(
+            matches!(result, Err(Error::CorruptRecord { .. })),
+            "expected CorruptRecord, got: {result:?}"
+        );
+    }
 }
@@ -0,0 +1,433 @@
+//! Data directory layout.
+//!
+//! A server owns one data directory. Its contents are described by a
+//! versioned manifest, `manifest.json`, which lists the log files; everything
+//! else in the directory is sidecar state belonging to a log (the holes file,
+//! the lock file) and is named after it:
+//!
+//! ```text
+//! <data>/
+//!   manifest.json
+//!   events.log
+//!   events.log.holes   (only after a repair-mode open skipped damage)
+//!   events.log.lock
+//! ```
+//!
+//! Earlier releases pointed `EVENTFOLD_DATA` at the log file itself. A
+//! writable [`DataDir::open`] on such a path migrates it into a directory of
+//! the same name. The migration is a sequence of renames through a
+//! `<path>.migrating` staging directory, so a crash part-way through is
+//! finished by the next open.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::lock::{LogLock, lock_path};
+use crate::store::holes_path;
+
+/// Name of the manifest file inside a data directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name given to the log file of a newly created or migrated data directory.
+pub const LOG_FILE: &str = "events.log";
+
+/// Layout version written by this release. Directories with a newer version
+/// are refused rather than misread.
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// Suffix of the staging directory used while migrating a single-file layout.
+const MIGRATING_SUFFIX: &str = ".migrating";
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the directory layout.
+    pub layout_version: u32,
+    /// Log files, relative to the data directory. This release reads and
+    /// writes exactly one.
+    pub log_files: Vec<String>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Manifest {
+            layout_version: LAYOUT_VERSION,
+            log_files: vec![LOG_FILE.to_string()],
+        }
+    }
+}
+
+/// An opened data directory.
+#[derive(Debug, Clone)]
+pub struct DataDir {
+    /// The directory holding the manifest and logs.
+    root: PathBuf,
+    /// The parsed manifest.
+    manifest: Manifest,
+    /// Whether this open migrated a single-file layout.
+    migrated: bool,
+}
+
+impl DataDir {
+    /// Open the data directory at `root`, creating or migrating it as needed.
+    ///
+    /// * A missing path becomes a new directory with a fresh manifest.
+    /// * An existing empty directory is given a fresh manifest.
+    /// * A regular file is treated as a log from the single-file layout and
+    ///   moved into a new directory at the same path, together with its holes
+    ///   sidecar. A server still running on that file is detected through its
+    ///   lock and blocks the migration.
+    /// * A leftover `<root>.migrating` directory from an interrupted
+    ///   migration is completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidDataDir`] if the manifest is unreadable, has a
+    /// newer layout version, or lists other than one log file, or if `root`
+    /// is a non-empty directory without a manifest.
+    /// Returns [`Error::LogLocked`] if a legacy log file is in use.
+    /// Returns [`Error::Io`] on filesystem errors.
+    pub fn open(root: &Path) -> Result<DataDir, Error> {
+        let staging = staging_path(root);
+        if root.is_file() || (!root.exists() && staging.is_dir()) {
+            migrate(root, &staging)?;
+            let mut dir = DataDir::load(root)?;
+            dir.migrated = true;
+            return Ok(dir);
+        }
+
+        if !root.exists() {
+            std::fs::create_dir_all(root)?;
+            write_manifest(root, &Manifest::default())?;
+            sync_parent(root)?;
+        } else if !root.join(MANIFEST_FILE).exists() {
+            if std::fs::read_dir(root)?.next().is_some() {
+                return Err(Error::InvalidDataDir {
+                    path: root.to_path_buf(),
+                    detail: format!("directory is not empty and has no {MANIFEST_FILE}"),
+                });
+            }
+            write_manifest(root, &Manifest::default())?;
+        }
+        DataDir::load(root)
+    }
+
+    /// Open the data directory at `root` without creating, migrating, or
+    /// writing anything.
+    ///
+    /// A regular file is served in place as a single-file layout, which lets
+    /// a read-only server run directly on a restored backup.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] with kind `NotFound` if `root` does not exist.
+    /// Returns [`Error::InvalidDataDir`] under the same conditions as
+    /// [`DataDir::open`], or if the directory has no manifest.
+    pub fn open_read_only(root: &Path) -> Result<DataDir, Error> {
+        if root.is_file() {
+            let (parent, name) = match (root.parent(), root.file_name()) {
+                (Some(parent), Some(name)) => (parent, name),
+                _ => {
+                    return Err(Error::InvalidDataDir {
+                        path: root.to_path_buf(),
+                        detail: "log file path has no parent directory".into(),
+                    });
+                }
+            };
+            return Ok(DataDir {
+                root: parent.to_path_buf(),
+                manifest: Manifest {
+                    layout_version: LAYOUT_VERSION,
+                    log_files: vec![name.to_string_lossy().into_owned()],
+                },
+                migrated: false,
+            });
+        }
+        if !root.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("data directory {} does not exist", root.display()),
+            )
+            .into());
+        }
+        DataDir::load(root)
+    }
+
+    /// Read and validate the manifest of an existing directory.
+    fn load(root: &Path) -> Result<DataDir, Error> {
+        let invalid = |detail: String| Error::InvalidDataDir {
+            path: root.to_path_buf(),
+            detail,
+        };
+        let bytes = match std::fs::read(root.join(MANIFEST_FILE)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(invalid(format!("missing {MANIFEST_FILE}")));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let manifest: Manifest = serde_json::from_slice(&bytes)
+            .map_err(|e| invalid(format!("malformed {MANIFEST_FILE}: {e}")))?;
+
+        if manifest.layout_version > LAYOUT_VERSION {
+            return Err(invalid(format!(
+                "layout version {} is newer than the supported version {LAYOUT_VERSION}",
+                manifest.layout_version
+            )));
+        }
+        if manifest.log_files.len() != 1 {
+            return Err(invalid(format!(
+                "expected exactly one log file, manifest lists {}",
+                manifest.log_files.len()
+            )));
+        }
+
+        Ok(DataDir {
+            root: root.to_path_buf(),
+            manifest,
+            migrated: false,
+        })
+    }
+
+    /// The directory holding the manifest and logs.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The parsed manifest.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Path of the log file to open the [`Store`](crate::Store) on.
+    pub fn log_path(&self) -> PathBuf {
+        self.root.join(&self.manifest.log_files[0])
+    }
+
+    /// Whether this open migrated a single-file layout into the directory.
+    pub fn migrated(&self) -> bool {
+        self.migrated
+    }
+}
+
+/// Returns the staging directory used while migrating the log at `root`.
+fn staging_path(root: &Path) -> PathBuf {
+    let mut name = root.as_os_str().to_owned();
+    name.push(MIGRATING_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Move a single-file layout at `root` into a data directory at the same
+/// path. Every step is skipped if it has already happened, so rerunning this
+/// after a crash finishes the job.
+fn migrate(root: &Path, staging: &Path) -> Result<(), Error> {
+    // Refuse to move a log out from under a running server.
+    let lock = LogLock::acquire(root)?;
+
+    if !staging.exists() {
+        std::fs::create_dir(staging)?;
+    }
+    let log = staging.join(LOG_FILE);
+    if root.is_file() {
+        std::fs::rename(root, &log)?;
+    }
+    let holes = holes_path(root);
+    if holes.exists() {
+        std::fs::rename(&holes, holes_path(&log))?;
+    }
+    write_manifest(staging, &Manifest::default())?;
+    File::open(staging)?.sync_all()?;
+
+    std::fs::rename(staging, root)?;
+    sync_parent(root)?;
+
+    // The old lock file now guards nothing; the store locks the moved log.
+    drop(lock);
+    match std::fs::remove_file(lock_path(root)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Atomically write `manifest` into the directory `dir`.
+fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), Error> {
+    let json = serde_json::to_vec_pretty(manifest).map_err(std::io::Error::other)?;
+    let tmp_path = dir.join(format!("{MANIFEST_FILE}.tmp"));
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&json)?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, dir.join(MANIFEST_FILE))?;
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Fsync the parent directory of `path` so a create or rename of `path` is
+/// durable.
+pub(crate) fn sync_parent(path: &Path) -> Result<(), Error> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+    use crate::types::{ExpectedVersion, ProposedEvent};
+
+    fn append_one(store: &mut Store) {
+        store
+            .append(
+                uuid::Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                0,
+                vec![ProposedEvent {
+                    event_id: uuid::Uuid::new_v4(),
+                    event_type: "Migrated".to_string(),
+                    metadata: bytes::Bytes::new(),
+                    payload: bytes::Bytes::from_static(b"{}"),
+                }],
+            )
+            .expect("append should succeed");
+    }
+
+    #[test]
+    fn open_creates_directory_with_manifest() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("data");
+
+        let dir = DataDir::open(&root).expect("open should succeed");
+
+        assert!(root.is_dir());
+        assert!(!dir.migrated());
+        assert_eq!(dir.manifest(), &Manifest::default());
+        assert_eq!(dir.log_path(), root.join(LOG_FILE));
+        let reopened = DataDir::open(&root).expect("reopen should succeed");
+        assert_eq!(reopened.manifest(), dir.manifest());
+    }
+
+    #[test]
+    fn open_migrates_single_file_layout() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("events.db");
+        {
+            let mut store = Store::open(&root).expect("open should succeed");
+            append_one(&mut store);
+            append_one(&mut store);
+        }
+        std::fs::write(
+            holes_path(&root),
+            "# start_offset end_offset first_position missing_count\n",
+        )
+        .expect("write holes");
+
+        let dir = DataDir::open(&root).expect("migration should succeed");
+
+        assert!(dir.migrated());
+        assert!(root.is_dir());
+        assert!(root.join(MANIFEST_FILE).exists());
+        assert!(holes_path(&dir.log_path()).exists());
+        assert!(!staging_path(&root).exists());
+        assert!(!lock_path(&root).exists());
+        let store = Store::open(&dir.log_path()).expect("open migrated log");
+        assert_eq!(store.global_position(), 2);
+    }
+
+    #[test]
+    fn open_finishes_interrupted_migration() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("events.db");
+        {
+            let mut store = Store::open(&root).expect("open should succeed");
+            append_one(&mut store);
+        }
+        // Simulate a crash after the log was moved into the staging directory.
+        let staging = staging_path(&root);
+        std::fs::create_dir(&staging).expect("create staging");
+        std::fs::rename(&root, staging.join(LOG_FILE)).expect("move log");
+
+        let dir = DataDir::open(&root).expect("migration should resume");
+
+        assert!(dir.migrated());
+        let store = Store::open(&dir.log_path()).expect("open migrated log");
+        assert_eq!(store.global_position(), 1);
+    }
+
+    #[test]
+    fn migration_refuses_log_held_by_running_store() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("events.db");
+        let _running = Store::open(&root).expect("open should succeed");
+
+        match DataDir::open(&root) {
+            Err(Error::LogLocked { .. }) => {}
+            other => panic!("expected LogLocked, got: {other:?}"),
+        }
+        assert!(root.is_file());
+    }
+
+    #[test]
+    fn open_rejects_newer_layout_version() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("data");
+        std::fs::create_dir(&root).expect("create dir");
+        std::fs::write(
+            root.join(MANIFEST_FILE),
+            r#"{"layout_version": 99, "log_files": ["events.log"]}"#,
+        )
+        .expect("write manifest");
+
+        match DataDir::open(&root) {
+            Err(Error::InvalidDataDir { detail, .. }) => {
+                assert!(detail.contains("99"), "detail: {detail}")
+            }
+            other => panic!("expected InvalidDataDir, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn open_rejects_non_empty_directory_without_manifest() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        std::fs::write(tmp.path().join("unrelated.txt"), "x").expect("write file");
+
+        let result = DataDir::open(tmp.path());
+        assert!(
+            matches!(result, Err(Error::InvalidDataDir { .. })),
+            "expected InvalidDataDir, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn open_read_only_serves_single_file_in_place() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("events.db");
+        drop(Store::open(&root).expect("open should succeed"));
+
+        let dir = DataDir::open_read_only(&root).expect("read-only open should succeed");
+
+        assert!(root.is_file());
+        assert_eq!(dir.log_path(), root);
+        assert!(!dir.migrated());
+    }
+
+    #[test]
+    fn open_read_only_does_not_create_directory() {
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let root = tmp.path().join("missing");
+
+        match DataDir::open_read_only(&root) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("expected NotFound, got: {other:?}"),
+        }
+        assert!(!root.exists());
+    }
+}
@@ -129,6 +129,7 @@ mod tests {
             event_type: "TestEvent".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         }
     }
 
@@ -0,0 +1,243 @@
+//! Low-disk-space guard for appends.
+//!
+//! Running out of space in the middle of a batch leaves a torn write that is
+//! only cleaned up by recovery on the next start, and after the failed write
+//! the writer refuses all further appends. [`DiskGuard`] samples the free space
+//! on the data volume and, while it is below a low watermark, lets the service
+//! reject appends with `RESOURCE_EXHAUSTED` before they reach the writer.
+//! Reads are unaffected, and appends are accepted again as soon as space is
+//! freed.
+
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use metrics::{counter, gauge};
+
+use crate::error::Error;
+
+/// Tracks free space on the volume holding a path against a low watermark.
+///
+/// Cloning shares the same state. The free-space figure only changes when
+/// [`DiskGuard::refresh`] runs, either directly or from the task started by
+/// [`DiskGuard::spawn_monitor`].
+#[derive(Debug, Clone)]
+pub struct DiskGuard {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// Any path on the monitored volume.
+    path: PathBuf,
+    /// Appends are rejected while free space is below this many bytes.
+    min_free: u64,
+    /// Free bytes at the last sample.
+    free: AtomicU64,
+    /// Whether the last sample was below `min_free`.
+    low: tokio::sync::watch::Sender<bool>,
+}
+
+impl DiskGuard {
+    /// Start guarding the volume that holds `path`, taking a first sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Any existing path on the volume to watch, normally the data
+    ///   directory.
+    /// * `min_free` - Free bytes below which appends are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the free space of `path` cannot be read.
+    pub fn new(path: impl Into<PathBuf>, min_free: NonZeroU64) -> Result<DiskGuard, Error> {
+        let guard = DiskGuard {
+            inner: Arc::new(Inner {
+                path: path.into(),
+                min_free: min_free.get(),
+                free: AtomicU64::new(u64::MAX),
+                low: tokio::sync::watch::Sender::new(false),
+            }),
+        };
+        guard.refresh()?;
+        Ok(guard)
+    }
+
+    /// Re-sample free space and update the low-space state and metrics.
+    ///
+    /// Returns the number of free bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the free space cannot be read. The previous
+    /// sample is kept.
+    pub fn refresh(&self) -> Result<u64, Error> {
+        let free = free_space(&self.inner.path)?;
+        self.inner.free.store(free, Ordering::Relaxed);
+        let low = free < self.inner.min_free;
+        self.inner.low.send_if_modified(|current| {
+            if *current == low {
+                return false;
+            }
+            if low {
+                tracing::warn!(
+                    free_bytes = free,
+                    min_free_bytes = self.inner.min_free,
+                    "Free disk space below low watermark -- rejecting appends"
+                );
+            } else {
+                tracing::info!(
+                    free_bytes = free,
+                    "Free disk space recovered -- accepting appends"
+                );
+            }
+            *current = low;
+            true
+        });
+        gauge!("eventfold_disk_free_bytes").set(free as f64);
+        gauge!("eventfold_disk_low").set(if low { 1.0 } else { 0.0 });
+        Ok(free)
+    }
+
+    /// Check whether an append may proceed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DiskSpaceLow`] if the last sample was below the low
+    /// watermark.
+    pub fn check(&self) -> Result<(), Error> {
+        if !self.is_low() {
+            return Ok(());
+        }
+        counter!("eventfold_disk_rejections_total").increment(1);
+        Err(Error::DiskSpaceLow {
+            free: self.free(),
+            min_free: self.inner.min_free,
+        })
+    }
+
+    /// Whether the last sample was below the low watermark.
+    pub fn is_low(&self) -> bool {
+        *self.inner.low.borrow()
+    }
+
+    /// Free bytes at the last sample.
+    pub fn free(&self) -> u64 {
+        self.inner.free.load(Ordering::Relaxed)
+    }
+
+    /// Returns a receiver notified whenever free space crosses the low
+    /// watermark in either direction.
+    pub fn watch_low(&self) -> tokio::sync::watch::Receiver<bool> {
+        self.inner.low.subscribe()
+    }
+
+    /// Spawn a task that calls [`DiskGuard::refresh`] every `interval`.
+    ///
+    /// Sampling errors are logged and the previous sample is kept. The task
+    /// runs until aborted.
+    pub fn spawn_monitor(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let guard = self.clone();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tick.tick().await;
+                if let Err(e) = guard.refresh() {
+                    tracing::warn!(error = %e, "Failed to read free disk space");
+                }
+            }
+        })
+    }
+}
+
+/// Bytes available to unprivileged writers on the volume holding `path`.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Result<u64, Error> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+        Error::InvalidArgument(format!("path contains a NUL byte: {}", path.display()))
+    })?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` points to
+    // writable memory large enough for a `statvfs`.
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    if rc != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // SAFETY: `statvfs` returned 0, so it initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+    #[allow(clippy::unnecessary_cast)] // field widths differ between platforms
+    Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Free space is only measured on Unix; elsewhere the volume is always
+/// reported as unlimited.
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Result<u64, Error> {
+    Ok(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_space_of_tempdir_is_positive() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        assert!(free_space(dir.path()).expect("statvfs should succeed") > 0);
+    }
+
+    #[test]
+    fn free_space_of_missing_path_fails() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let result = free_space(&dir.path().join("missing"));
+        assert!(matches!(result, Err(Error::Io(_))), "got: {result:?}");
+    }
+
+    #[test]
+    fn guard_below_watermark_rejects_appends() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        // No volume has this much free space.
+        let guard = DiskGuard::new(dir.path(), NonZeroU64::MAX).expect("guard");
+
+        assert!(guard.is_low());
+        match guard.check() {
+            Err(Error::DiskSpaceLow { free, min_free }) => {
+                assert_eq!(free, guard.free());
+                assert_eq!(min_free, u64::MAX);
+            }
+            other => panic!("expected DiskSpaceLow, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn guard_above_watermark_admits_appends() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let guard = DiskGuard::new(dir.path(), NonZeroU64::MIN).expect("guard");
+
+        assert!(!guard.is_low());
+        guard.check().expect("append should be admitted");
+        assert!(!*guard.watch_low().borrow());
+    }
+
+    #[tokio::test]
+    async fn monitor_refreshes_the_sample() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let guard = DiskGuard::new(dir.path(), NonZeroU64::MIN).expect("guard");
+        guard.inner.free.store(0, Ordering::Relaxed);
+
+        let monitor = guard.spawn_monitor(Duration::from_millis(10));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while guard.free() == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("monitor should take a new sample");
+        monitor.abort();
+    }
+}
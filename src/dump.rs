@@ -0,0 +1,1009 @@
+//! Decoding dump of a raw log file, for debugging.
+//!
+//! Unlike [`crate::export`], which reads events through a recovered store,
+//! [`dump`] walks the bytes of a log file with the [`codec`] decoders and
+//! reports what is actually on disk: the file header, every batch with its
+//! byte offset and CRC check, the records inside it, damaged ranges, the log
+//! end marker, and preallocated space at the end. Damaged ranges are skipped by
+//! resynchronizing on the next fully valid batch, the same way recovery does,
+//! so a dump never stops at the first bad byte.
+//!
+//! Output is either human-readable text or JSON lines, see [`DumpFormat`].
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::codec::{self, BATCH_FOOTER_SIZE, DecodeOutcome};
+use crate::error::Error;
+use crate::types::{MAX_EVENT_SIZE, RecordedEvent};
+
+/// Size of the file header at the start of every log.
+const FILE_HEADER_SIZE: usize = 8;
+
+/// Output format of a [`dump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// One line per header, batch, end marker, and damaged or preallocated
+    /// range, with records indented below their batch.
+    Text,
+    /// One JSON object per entry, told apart by its `kind` field: `header`,
+    /// `batch`, `record`, `damaged`, `end`, or `preallocated`.
+    JsonLines,
+}
+
+/// How record metadata and payloads are printed.
+///
+/// Each format falls back to the next one when the bytes do not fit it:
+/// JSON to UTF-8 to hex. JSON lines output names the encoding used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// Parsed JSON, embedded as is in JSON lines output.
+    Json,
+    /// UTF-8 text.
+    Utf8,
+    /// Lowercase hex.
+    Hex,
+}
+
+/// Which records a dump prints.
+///
+/// `DumpFilter::default()` selects every record. A batch is printed when at
+/// least one of its records is selected; damaged ranges, the end marker, and
+/// preallocated ranges are always printed.
+#[derive(Debug, Clone, Default)]
+pub struct DumpFilter {
+    /// Streams to print. `None` prints every stream.
+    pub streams: Option<HashSet<Uuid>>,
+    /// Event types to print. `None` prints every type.
+    pub event_types: Option<HashSet<String>>,
+    /// First global position to print (inclusive).
+    pub from_position: u64,
+    /// Global position to stop at (exclusive). `None` prints to the end.
+    pub to_position: Option<u64>,
+    /// Earliest `recorded_at` to print, in Unix milliseconds (inclusive).
+    pub since: Option<u64>,
+    /// Latest `recorded_at` to print, in Unix milliseconds (exclusive).
+    pub until: Option<u64>,
+}
+
+impl DumpFilter {
+    /// Returns `true` if `event` passes every condition of the filter.
+    fn matches(&self, event: &RecordedEvent) -> bool {
+        event.global_position >= self.from_position
+            && self.to_position.is_none_or(|to| event.global_position < to)
+            && self.since.is_none_or(|since| event.recorded_at >= since)
+            && self.until.is_none_or(|until| event.recorded_at < until)
+            && self
+                .streams
+                .as_ref()
+                .is_none_or(|streams| streams.contains(&event.stream_id))
+            && self
+                .event_types
+                .as_ref()
+                .is_none_or(|types| types.contains(&event.event_type))
+    }
+}
+
+/// Counts of what a [`dump`] found in the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DumpSummary {
+    /// Valid batches in the log, printed or not.
+    pub batches: u64,
+    /// Records printed after filtering.
+    pub records: u64,
+    /// Damaged byte ranges.
+    pub damaged: u64,
+}
+
+/// A batch decoded from the log, with the byte range of each record.
+struct Batch {
+    /// Number of records the batch header announces.
+    record_count: u32,
+    /// Global position of the first record.
+    first_position: u64,
+    /// Offset and length of each record, with the decoded event.
+    records: Vec<(usize, usize, RecordedEvent)>,
+    /// Total length of the batch including header and footer.
+    len: usize,
+}
+
+/// How many bytes a [`Window`] reads at a time while scanning.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// A sliding window over a log read sequentially from `reader`.
+///
+/// Holds the bytes from the oldest offset still needed, usually the start of
+/// the batch being decoded, to the furthest offset read so far, so memory is
+/// bounded by the largest batch rather than the file.
+struct Window<R> {
+    reader: R,
+    /// Bytes from file offset `start` onward.
+    buf: Vec<u8>,
+    /// File offset of `buf[0]`.
+    start: usize,
+    /// Whether `reader` has hit the end of the file.
+    eof: bool,
+}
+
+impl<R: Read> Window<R> {
+    fn new(reader: R) -> Window<R> {
+        Window {
+            reader,
+            buf: Vec::new(),
+            start: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the `len` bytes at file offset `at`, fewer only at the end of
+    /// the file. `at` must not be before the last [`Window::discard`].
+    fn get(&mut self, at: usize, len: usize) -> std::io::Result<&[u8]> {
+        let from = at - self.start;
+        let want = from + len;
+        if self.buf.len() < want && !self.eof {
+            let missing = (want - self.buf.len()) as u64;
+            let read = (&mut self.reader)
+                .take(missing)
+                .read_to_end(&mut self.buf)?;
+            self.eof = (read as u64) < missing;
+        }
+        let end = want.min(self.buf.len());
+        Ok(&self.buf[from.min(end)..end])
+    }
+
+    /// Drop the bytes before file offset `at`.
+    fn discard(&mut self, at: usize) {
+        let n = (at - self.start).min(self.buf.len());
+        self.buf.drain(..n);
+        self.start = at;
+    }
+
+    /// Returns the length of the file, reading and dropping the rest of it.
+    fn file_len(&mut self) -> std::io::Result<usize> {
+        let mut end = self.start + self.buf.len();
+        self.buf.clear();
+        if !self.eof {
+            end += std::io::copy(&mut self.reader, &mut std::io::sink())? as usize;
+            self.eof = true;
+        }
+        self.start = end;
+        Ok(end)
+    }
+
+    /// Scan past the damage at `offset` for the next fully valid batch, the
+    /// way [`crate::store::next_valid_batch`] does in memory.
+    ///
+    /// # Returns
+    ///
+    /// `(end, true)` if a valid batch starts at `end`, or `(end, false)` if
+    /// none follows, with `end` just past the last non-zero byte at or after
+    /// `offset`, so the zeros after it can be reported as preallocated.
+    fn skip_damage(&mut self, offset: usize) -> std::io::Result<(usize, bool)> {
+        let first = self.get(offset, 1)?;
+        let mut end = if first.first().is_some_and(|b| *b != 0) {
+            offset + 1
+        } else {
+            offset
+        };
+        let mut probe = offset + 1;
+        loop {
+            self.discard(probe);
+            let chunk = self.get(probe, READ_CHUNK)?;
+            if chunk.is_empty() {
+                return Ok((end, false));
+            }
+            // Only a byte that could start the batch header magic needs a
+            // full decode.
+            let candidate = chunk
+                .iter()
+                .position(|b| *b == codec::BATCH_HEADER_MAGIC[0]);
+            let scanned = candidate.unwrap_or(chunk.len());
+            if let Some(i) = chunk[..scanned].iter().rposition(|b| *b != 0) {
+                end = probe + i + 1;
+            }
+            probe += scanned;
+            if candidate.is_some() {
+                if decode_batch(self, probe)?.is_ok() {
+                    return Ok((probe, true));
+                }
+                end = probe + 1;
+                probe += 1;
+            }
+        }
+    }
+}
+
+/// Decode the batch starting at `offset` in `window`, checking its CRC.
+///
+/// A record longer than [`MAX_EVENT_SIZE`], which the store never writes,
+/// counts as damage, so a garbage length never makes the window read ahead
+/// further than that.
+///
+/// # Returns
+///
+/// The batch, or a description of the first problem found: a bad or
+/// truncated header, record, or footer, or a CRC mismatch.
+///
+/// # Errors
+///
+/// Returns an I/O error if reading the log fails.
+fn decode_batch<R: Read>(
+    window: &mut Window<R>,
+    offset: usize,
+) -> std::io::Result<Result<Batch, String>> {
+    let header_bytes = window.get(offset, codec::BATCH_HEADER_SIZE)?;
+    let (header, mut end) = match codec::decode_batch_header(header_bytes) {
+        Ok(DecodeOutcome::Complete { value, consumed }) => (value, offset + consumed),
+        Ok(DecodeOutcome::Incomplete) => return Ok(Err("truncated batch header".to_string())),
+        Err(e) => return Ok(Err(e.to_string())),
+    };
+    // The count comes from disk, so the vector only grows with the records
+    // actually decoded.
+    let mut records = Vec::new();
+    for index in 0..header.record_count {
+        let truncated = || format!("truncated record {index} of {}", header.record_count);
+        let Some(prefix) = window.get(end, 4)?.first_chunk::<4>() else {
+            return Ok(Err(truncated()));
+        };
+        let len = 4 + u32::from_le_bytes(*prefix) as usize;
+        if len > MAX_EVENT_SIZE {
+            return Ok(Err(format!(
+                "record {index}: length {len} exceeds the {MAX_EVENT_SIZE}-byte limit"
+            )));
+        }
+        match codec::decode_record(window.get(end, len)?) {
+            Ok(DecodeOutcome::Complete { value, consumed }) => {
+                records.push((end, consumed, value));
+                end += consumed;
+            }
+            Ok(DecodeOutcome::Incomplete) => return Ok(Err(truncated())),
+            Err(e) => return Ok(Err(format!("record {index}: {e}"))),
+        }
+    }
+    let footer = match codec::decode_batch_footer(window.get(end, BATCH_FOOTER_SIZE)?) {
+        Ok(DecodeOutcome::Complete { value, .. }) => value,
+        Ok(DecodeOutcome::Incomplete) => return Ok(Err("truncated batch footer".to_string())),
+        Err(e) => return Ok(Err(e.to_string())),
+    };
+    let crc = crc32fast::hash(window.get(offset, end - offset)?);
+    if footer.batch_crc != crc {
+        return Ok(Err(format!(
+            "batch CRC mismatch: stored {:08x}, computed {crc:08x}",
+            footer.batch_crc
+        )));
+    }
+    Ok(Ok(Batch {
+        record_count: header.record_count,
+        first_position: header.first_global_pos,
+        records,
+        len: end + BATCH_FOOTER_SIZE - offset,
+    }))
+}
+
+/// Dump the raw log file contents in `data` to `out`.
+///
+/// # Arguments
+///
+/// * `data` - The complete contents of a log file.
+/// * `filter` - Which records to print.
+/// * `format` - Text or JSON lines.
+/// * `payload` - How to print metadata and payloads.
+/// * `out` - Destination writer. Flushed before returning.
+///
+/// # Returns
+///
+/// A [`DumpSummary`] of what was found.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidHeader`] if `data` does not start with a valid file
+/// header. Returns [`Error::Io`] if writing to `out` fails. Damage after the
+/// header is reported in the output, not as an error.
+pub fn dump(
+    data: &[u8],
+    filter: &DumpFilter,
+    format: DumpFormat,
+    payload: PayloadFormat,
+    out: impl Write,
+) -> Result<DumpSummary, Error> {
+    dump_from(data, filter, format, payload, out)
+}
+
+/// Dump the log file at `path` to `out`, like [`dump`].
+///
+/// The file is read once, front to back, through a buffer that holds at most
+/// about one batch, so logs larger than RAM can be dumped. It is only ever
+/// read, so this is safe next to a live server: batches the server appends
+/// while the dump runs may or may not be included, and a torn tail it
+/// truncates just ends the dump early.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the file cannot be opened or read, and otherwise
+/// the same errors as [`dump`].
+pub fn dump_file(
+    path: &Path,
+    filter: &DumpFilter,
+    format: DumpFormat,
+    payload: PayloadFormat,
+    out: impl Write,
+) -> Result<DumpSummary, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    dump_from(reader, filter, format, payload, out)
+}
+
+/// Dump the log read sequentially from `reader`; see [`dump`].
+fn dump_from(
+    reader: impl Read,
+    filter: &DumpFilter,
+    format: DumpFormat,
+    payload: PayloadFormat,
+    mut out: impl Write,
+) -> Result<DumpSummary, Error> {
+    let mut window = Window::new(reader);
+    let header = window.get(0, FILE_HEADER_SIZE)?;
+    let Some(header) = header.first_chunk::<FILE_HEADER_SIZE>() else {
+        return Err(Error::InvalidHeader(format!(
+            "log is {} bytes, shorter than the {FILE_HEADER_SIZE}-byte file header",
+            header.len()
+        )));
+    };
+    let version = codec::decode_header(header)?;
+    let mut printer = Printer {
+        out: &mut out,
+        format,
+        payload,
+    };
+    printer.header(version)?;
+
+    let mut summary = DumpSummary::default();
+    let mut offset = FILE_HEADER_SIZE;
+    loop {
+        window.discard(offset);
+        let head = window.get(offset, codec::LOG_END_SIZE)?;
+        if head.is_empty() {
+            break;
+        }
+        // Everything past the end marker is preallocated space.
+        if let Ok(DecodeOutcome::Complete { value, consumed }) = codec::decode_log_end(head) {
+            printer.log_end(offset, value)?;
+            offset += consumed;
+            break;
+        }
+        match decode_batch(&mut window, offset)? {
+            Ok(batch) => {
+                summary.batches += 1;
+                let selected: Vec<_> = batch
+                    .records
+                    .iter()
+                    .filter(|(_, _, event)| filter.matches(event))
+                    .collect();
+                if !selected.is_empty() {
+                    printer.batch(offset, &batch)?;
+                    for (record_offset, len, event) in selected {
+                        printer.record(*record_offset, *len, event)?;
+                        summary.records += 1;
+                    }
+                }
+                offset += batch.len;
+            }
+            Err(detail) => {
+                // Without a later batch, the damage runs to the last non-zero
+                // byte, and the zeros after it are preallocated. For logs
+                // without an end marker that is all an all-zero tail is.
+                let (end, resumes) = window.skip_damage(offset)?;
+                if end > offset {
+                    printer.damaged(offset, end - offset, &detail)?;
+                    summary.damaged += 1;
+                }
+                offset = end;
+                if !resumes {
+                    break;
+                }
+            }
+        }
+    }
+    let file_len = window.file_len()?;
+    if offset < file_len {
+        printer.preallocated(offset, file_len - offset)?;
+    }
+    out.flush()?;
+    Ok(summary)
+}
+
+/// Writes dump entries in one [`DumpFormat`].
+struct Printer<'a, W> {
+    out: &'a mut W,
+    format: DumpFormat,
+    payload: PayloadFormat,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn json_line(&mut self, value: Value) -> Result<(), Error> {
+        serde_json::to_writer(&mut *self.out, &value).map_err(std::io::Error::from)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn header(&mut self, version: u32) -> Result<(), Error> {
+        match self.format {
+            DumpFormat::Text => writeln!(self.out, "header offset=0 version={version}")?,
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "header",
+                "offset": 0,
+                "version": version,
+            }))?,
+        }
+        Ok(())
+    }
+
+    fn batch(&mut self, offset: usize, batch: &Batch) -> Result<(), Error> {
+        match self.format {
+            DumpFormat::Text => writeln!(
+                self.out,
+                "batch offset={offset} len={} records={} first_position={}",
+                batch.len, batch.record_count, batch.first_position
+            )?,
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "batch",
+                "offset": offset,
+                "len": batch.len,
+                "record_count": batch.record_count,
+                "first_position": batch.first_position,
+            }))?,
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, offset: usize, len: usize, event: &RecordedEvent) -> Result<(), Error> {
+        let principal = event.audit.as_ref().and_then(|a| a.principal.as_deref());
+        let client_addr = event.audit.as_ref().and_then(|a| a.client_addr.as_deref());
+        let (metadata_encoding, metadata) = render_bytes(&event.metadata, self.payload);
+        let (payload_encoding, payload) = render_bytes(&event.payload, self.payload);
+        match self.format {
+            DumpFormat::Text => {
+                write!(
+                    self.out,
+                    "  record offset={offset} len={len} position={} stream={} version={} \
+                     type={} event_id={} recorded_at={} ({})",
+                    event.global_position,
+                    event.stream_id,
+                    event.stream_version,
+                    event.event_type,
+                    event.event_id,
+                    event.recorded_at,
+                    format_timestamp(event.recorded_at),
+                )?;
+                if let Some(principal) = principal {
+                    write!(self.out, " principal={principal}")?;
+                }
+                if let Some(client_addr) = client_addr {
+                    write!(self.out, " client_addr={client_addr}")?;
+                }
+                writeln!(self.out)?;
+                if !event.metadata.is_empty() {
+                    writeln!(self.out, "    metadata: {}", text(&metadata))?;
+                }
+                if !event.payload.is_empty() {
+                    writeln!(self.out, "    payload: {}", text(&payload))?;
+                }
+            }
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "record",
+                "offset": offset,
+                "len": len,
+                "global_position": event.global_position,
+                "stream_id": event.stream_id.to_string(),
+                "stream_version": event.stream_version,
+                "event_id": event.event_id.to_string(),
+                "event_type": event.event_type,
+                "recorded_at": event.recorded_at,
+                "recorded_at_utc": format_timestamp(event.recorded_at),
+                "principal": principal,
+                "client_addr": client_addr,
+                "metadata_encoding": metadata_encoding,
+                "metadata": metadata,
+                "payload_encoding": payload_encoding,
+                "payload": payload,
+            }))?,
+        }
+        Ok(())
+    }
+
+    fn damaged(&mut self, offset: usize, len: usize, detail: &str) -> Result<(), Error> {
+        match self.format {
+            DumpFormat::Text => writeln!(self.out, "damaged offset={offset} len={len}: {detail}")?,
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "damaged",
+                "offset": offset,
+                "len": len,
+                "detail": detail,
+            }))?,
+        }
+        Ok(())
+    }
+
+    fn log_end(&mut self, offset: usize, recorded_end: u64) -> Result<(), Error> {
+        match self.format {
+            DumpFormat::Text => {
+                writeln!(self.out, "end offset={offset} recorded_end={recorded_end}")?
+            }
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "end",
+                "offset": offset,
+                "recorded_end": recorded_end,
+            }))?,
+        }
+        Ok(())
+    }
+
+    fn preallocated(&mut self, offset: usize, len: usize) -> Result<(), Error> {
+        match self.format {
+            DumpFormat::Text => writeln!(self.out, "preallocated offset={offset} len={len}")?,
+            DumpFormat::JsonLines => self.json_line(json!({
+                "kind": "preallocated",
+                "offset": offset,
+                "len": len,
+            }))?,
+        }
+        Ok(())
+    }
+}
+
+/// Render `bytes` in `format`, falling back from JSON to UTF-8 to hex.
+///
+/// # Returns
+///
+/// The name of the encoding used and the rendered value.
+fn render_bytes(bytes: &[u8], format: PayloadFormat) -> (&'static str, Value) {
+    if format == PayloadFormat::Json {
+        if let Ok(value) = serde_json::from_slice::<Value>(bytes) {
+            return ("json", value);
+        }
+    }
+    if format != PayloadFormat::Hex {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return ("utf8", Value::String(text.to_string()));
+        }
+    }
+    let hex = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    ("hex", Value::String(hex))
+}
+
+/// Text form of a value from [`render_bytes`]: strings unquoted, JSON compact.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Format Unix milliseconds as an RFC 3339 UTC timestamp, e.g.
+/// `2023-11-14T22:13:20.000Z`.
+fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bytes::Bytes;
+
+    use crate::store::Store;
+    use crate::types::{ExpectedVersion, ProposedEvent};
+
+    fn proposed(event_type: &str, payload: &'static [u8]) -> ProposedEvent {
+        ProposedEvent {
+            event_id: Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            metadata: Bytes::new(),
+            payload: Bytes::from_static(payload),
+        }
+    }
+
+    /// Helper: a log with a batch of two events in stream A at time 10 and one
+    /// event in stream B at time 20. Returns the raw file bytes.
+    fn seeded_log(dir: &tempfile::TempDir) -> (Vec<u8>, Uuid, Uuid) {
+        let path = dir.path().join("events.log");
+        let mut store = Store::open(&path).expect("open should succeed");
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        store
+            .append(
+                a,
+                ExpectedVersion::NoStream,
+                10,
+                vec![
+                    proposed("Opened", b"{\"n\":1}"),
+                    proposed("Renamed", b"plain text"),
+                ],
+            )
+            .expect("append should succeed");
+        store
+            .append(
+                b,
+                ExpectedVersion::NoStream,
+                20,
+                vec![proposed("Opened", &[0xFF, 0x00])],
+            )
+            .expect("append should succeed");
+        drop(store);
+        (std::fs::read(&path).expect("read log"), a, b)
+    }
+
+    fn dump_json(data: &[u8], filter: &DumpFilter) -> (DumpSummary, Vec<Value>) {
+        let mut out = Vec::new();
+        let summary = dump(
+            data,
+            filter,
+            DumpFormat::JsonLines,
+            PayloadFormat::Json,
+            &mut out,
+        )
+        .expect("dump should succeed");
+        let lines = String::from_utf8(out)
+            .expect("output is UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line is JSON"))
+            .collect();
+        (summary, lines)
+    }
+
+    fn kinds(lines: &[Value]) -> Vec<&str> {
+        lines
+            .iter()
+            .map(|line| line["kind"].as_str().expect("kind is a string"))
+            .collect()
+    }
+
+    #[test]
+    fn dump_lists_batches_and_records_with_offsets() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, a, _) = seeded_log(&dir);
+
+        let (summary, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(
+            summary,
+            DumpSummary {
+                batches: 2,
+                records: 3,
+                damaged: 0
+            }
+        );
+        assert_eq!(
+            kinds(&lines),
+            ["header", "batch", "record", "record", "batch", "record"]
+        );
+        assert_eq!(lines[0]["version"], 3);
+        assert_eq!(lines[1]["offset"], 8);
+        assert_eq!(lines[2]["offset"], 8 + codec::BATCH_HEADER_SIZE);
+        assert_eq!(lines[2]["stream_id"], a.to_string());
+        assert_eq!(lines[2]["recorded_at_utc"], "1970-01-01T00:00:00.010Z");
+        // The second batch starts where the first one ends.
+        let first_end = lines[1]["offset"].as_u64().unwrap() + lines[1]["len"].as_u64().unwrap();
+        assert_eq!(lines[4]["offset"].as_u64(), Some(first_end));
+        assert_eq!(lines[4]["first_position"], 2);
+    }
+
+    #[test]
+    fn payloads_fall_back_from_json_to_utf8_to_hex() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, _, _) = seeded_log(&dir);
+
+        let (_, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(lines[2]["payload_encoding"], "json");
+        assert_eq!(lines[2]["payload"], json!({"n": 1}));
+        assert_eq!(lines[3]["payload_encoding"], "utf8");
+        assert_eq!(lines[3]["payload"], "plain text");
+        assert_eq!(lines[5]["payload_encoding"], "hex");
+        assert_eq!(lines[5]["payload"], "ff00");
+
+        assert_eq!(
+            render_bytes(b"{\"n\":1}", PayloadFormat::Hex),
+            ("hex", json!("7b226e223a317d"))
+        );
+    }
+
+    #[test]
+    fn filter_selects_records_and_their_batches() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, a, b) = seeded_log(&dir);
+
+        let by_stream = DumpFilter {
+            streams: Some(HashSet::from([b])),
+            ..DumpFilter::default()
+        };
+        let (summary, lines) = dump_json(&data, &by_stream);
+        assert_eq!(kinds(&lines), ["header", "batch", "record"]);
+        assert_eq!(summary.batches, 2);
+        assert_eq!(summary.records, 1);
+
+        let by_type = DumpFilter {
+            event_types: Some(HashSet::from(["Opened".to_string()])),
+            ..DumpFilter::default()
+        };
+        let (_, lines) = dump_json(&data, &by_type);
+        assert_eq!(
+            kinds(&lines),
+            ["header", "batch", "record", "batch", "record"]
+        );
+
+        let by_position = DumpFilter {
+            from_position: 1,
+            to_position: Some(2),
+            ..DumpFilter::default()
+        };
+        let (_, lines) = dump_json(&data, &by_position);
+        assert_eq!(kinds(&lines), ["header", "batch", "record"]);
+        assert_eq!(lines[2]["global_position"], 1);
+        assert_eq!(lines[2]["stream_id"], a.to_string());
+
+        let by_time = DumpFilter {
+            since: Some(15),
+            until: Some(25),
+            ..DumpFilter::default()
+        };
+        let (_, lines) = dump_json(&data, &by_time);
+        assert_eq!(kinds(&lines), ["header", "batch", "record"]);
+        assert_eq!(lines[2]["recorded_at"], 20);
+    }
+
+    #[test]
+    fn damaged_batch_is_reported_and_skipped() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (mut data, _, _) = seeded_log(&dir);
+        // Flip a byte inside the first record of the first batch.
+        data[8 + codec::BATCH_HEADER_SIZE + 10] ^= 0xFF;
+        data.extend_from_slice(&[0; 64]);
+
+        let (summary, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(summary.damaged, 1);
+        assert_eq!(
+            kinds(&lines),
+            ["header", "damaged", "batch", "record", "preallocated"]
+        );
+        assert_eq!(lines[1]["offset"], 8);
+        assert_eq!(lines[1]["len"], lines[2]["offset"].as_u64().unwrap() - 8);
+        assert_eq!(lines[4]["len"], 64);
+    }
+
+    #[test]
+    fn garbage_record_count_is_damage_not_an_allocation() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (mut data, _, _) = seeded_log(&dir);
+        let end = data.len();
+        data.extend_from_slice(&codec::encode_batch_header(0xFFFF_FFFF, 3));
+        data.extend_from_slice(&[0; 64]);
+
+        let (summary, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(summary.batches, 2);
+        assert_eq!(summary.damaged, 1);
+        assert_eq!(lines[lines.len() - 2]["kind"], "damaged");
+        assert_eq!(lines[lines.len() - 2]["offset"], end);
+        // The damage ends at the header's last non-zero byte.
+        let damaged_len = lines[lines.len() - 2]["len"].as_u64().unwrap() as usize;
+        assert!(damaged_len <= codec::BATCH_HEADER_SIZE, "got {damaged_len}");
+        assert_eq!(lines[lines.len() - 1]["kind"], "preallocated");
+        assert_eq!(lines[lines.len() - 1]["offset"], end + damaged_len);
+        assert_eq!(
+            data.len(),
+            end + damaged_len + lines[lines.len() - 1]["len"].as_u64().unwrap() as usize
+        );
+    }
+
+    #[test]
+    fn dump_file_resumes_after_damage_longer_than_a_read() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, _, _) = seeded_log(&dir);
+        let (_, lines) = dump_json(&data, &DumpFilter::default());
+        let second = lines[4]["offset"].as_u64().unwrap() as usize;
+        let garbage = 3 * READ_CHUNK + 5;
+        let mut damaged = data[..second].to_vec();
+        damaged.extend(std::iter::repeat_n(0x5A, garbage));
+        damaged.extend_from_slice(&data[second..]);
+        damaged.extend_from_slice(&vec![0; 2 * READ_CHUNK]);
+        let path = dir.path().join("damaged.log");
+        std::fs::write(&path, &damaged).expect("write log");
+        let mut out = Vec::new();
+
+        let summary = dump_file(
+            &path,
+            &DumpFilter::default(),
+            DumpFormat::JsonLines,
+            PayloadFormat::Json,
+            &mut out,
+        )
+        .expect("dump should succeed");
+
+        assert_eq!(summary.batches, 2);
+        assert_eq!(summary.damaged, 1);
+        let lines: Vec<Value> = String::from_utf8(out)
+            .expect("output is UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line is JSON"))
+            .collect();
+        assert_eq!(
+            kinds(&lines),
+            [
+                "header",
+                "batch",
+                "record",
+                "record",
+                "damaged",
+                "batch",
+                "record",
+                "preallocated"
+            ]
+        );
+        assert_eq!(lines[4]["offset"], second);
+        assert_eq!(lines[4]["len"], garbage);
+        assert_eq!(lines[5]["offset"], second + garbage);
+        assert_eq!(lines[7]["len"], 2 * READ_CHUNK);
+    }
+
+    #[test]
+    fn preallocated_range_starts_at_the_end_of_a_batch_ending_in_zero() {
+        // About one batch in 256 has a CRC whose last byte on disk is zero.
+        // The batch header has to vary: a record ends in its own CRC, so with
+        // a fixed header and record length the batch CRC is constant.
+        let batch = (0u64..)
+            .find_map(|n| {
+                let event = RecordedEvent {
+                    event_id: Uuid::nil(),
+                    stream_id: Uuid::nil(),
+                    stream_version: 0,
+                    global_position: n,
+                    recorded_at: 0,
+                    event_type: "Opened".to_string(),
+                    metadata: Bytes::new(),
+                    payload: Bytes::from_static(b"{}"),
+                    audit: None,
+                };
+                let mut batch = codec::encode_batch_header(1, n).to_vec();
+                batch.extend_from_slice(&codec::encode_record(&event));
+                let crc = crc32fast::hash(&batch);
+                batch.extend_from_slice(&codec::encode_batch_footer(crc));
+                (batch.last() == Some(&0)).then_some(batch)
+            })
+            .expect("some batch ends in a zero byte");
+        let mut data = codec::encode_header().to_vec();
+        data.extend_from_slice(&batch);
+        data.extend_from_slice(&[0; 64]);
+
+        let (summary, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(summary.damaged, 0);
+        assert_eq!(kinds(&lines), ["header", "batch", "record", "preallocated"]);
+        assert_eq!(lines[3]["offset"], FILE_HEADER_SIZE + batch.len());
+        assert_eq!(lines[3]["len"], 64);
+    }
+
+    #[test]
+    fn end_marker_bounds_the_preallocated_range() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (mut data, _, _) = seeded_log(&dir);
+        let end = data.len();
+        data.extend_from_slice(&codec::encode_log_end(end as u64));
+        // Stale bytes past the marker are unwritten space, not damage.
+        data.extend_from_slice(&[0, 0, 7, 0]);
+
+        let (summary, lines) = dump_json(&data, &DumpFilter::default());
+
+        assert_eq!(summary.damaged, 0);
+        assert_eq!(lines[lines.len() - 2]["kind"], "end");
+        assert_eq!(lines[lines.len() - 2]["offset"], end);
+        assert_eq!(lines[lines.len() - 2]["recorded_end"], end);
+        assert_eq!(lines[lines.len() - 1]["kind"], "preallocated");
+        assert_eq!(lines[lines.len() - 1]["offset"], end + codec::LOG_END_SIZE);
+        assert_eq!(lines[lines.len() - 1]["len"], 4);
+    }
+
+    #[test]
+    fn text_format_prints_one_line_per_entry() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, _, _) = seeded_log(&dir);
+        let mut out = Vec::new();
+
+        dump(
+            &data,
+            &DumpFilter::default(),
+            DumpFormat::Text,
+            PayloadFormat::Utf8,
+            &mut out,
+        )
+        .expect("dump should succeed");
+
+        let text = String::from_utf8(out).expect("output is UTF-8");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "header offset=0 version=3");
+        assert!(lines[1].starts_with("batch offset=8 "), "got: {text}");
+        assert!(lines[2].contains("type=Opened"), "got: {text}");
+        assert_eq!(lines[3], "    payload: {\"n\":1}");
+    }
+
+    #[test]
+    fn dump_file_matches_dumping_the_bytes() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (data, _, _) = seeded_log(&dir);
+        let (expected_summary, expected_lines) = dump_json(&data, &DumpFilter::default());
+        let mut out = Vec::new();
+
+        let summary = dump_file(
+            &dir.path().join("events.log"),
+            &DumpFilter::default(),
+            DumpFormat::JsonLines,
+            PayloadFormat::Json,
+            &mut out,
+        )
+        .expect("dump should succeed");
+
+        assert_eq!(summary, expected_summary);
+        let dumped: Vec<Value> = String::from_utf8(out)
+            .expect("output is UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line is JSON"))
+            .collect();
+        assert_eq!(dumped, expected_lines);
+    }
+
+    #[test]
+    fn short_or_foreign_file_is_rejected() {
+        let result = dump(
+            b"EFDB",
+            &DumpFilter::default(),
+            DumpFormat::Text,
+            PayloadFormat::Json,
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(Error::InvalidHeader(_))));
+
+        let result = dump(
+            b"not a log file",
+            &DumpFilter::default(),
+            DumpFormat::Text,
+            PayloadFormat::Json,
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(Error::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn format_timestamp_renders_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(1_700_000_000_123),
+            "2023-11-14T22:13:20.123Z"
+        );
+        assert_eq!(
+            format_timestamp(951_782_400_000),
+            "2000-02-29T00:00:00.000Z"
+        );
+    }
+}
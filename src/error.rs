@@ -18,6 +18,16 @@ use uuid::Uuid;
 /// - `InvalidHeader` -> `DATA_LOSS`
 /// - `EventTooLarge` -> `INVALID_ARGUMENT`
 /// - `InvalidArgument` -> `INVALID_ARGUMENT`
+/// - `ReadOnly` -> `FAILED_PRECONDITION`
+/// - `LogLocked` -> `UNAVAILABLE`
+/// - `InvalidDataDir` -> `INTERNAL`
+/// - `DeadlineExceeded` -> `DEADLINE_EXCEEDED`
+/// - `DeadlineExceededInFlight` -> `DEADLINE_EXCEEDED`
+/// - `WriterFailed` -> `UNAVAILABLE`
+/// - `DiskSpaceLow` -> `RESOURCE_EXHAUSTED`
+/// - `InvalidKeyFile` -> `INTERNAL`
+/// - `PermissionDenied` -> `PERMISSION_DENIED`
+/// - `SubscriptionLagged` -> `ABORTED`
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// Optimistic concurrency check failed: the stream's current version does not
@@ -66,6 +76,77 @@ pub enum Error {
     /// A request argument is invalid.
     #[error("invalid argument: {0}")]
     InvalidArgument(String),
+
+    /// The store was opened read-only and cannot accept appends.
+    #[error("store is open read-only")]
+    ReadOnly,
+
+    /// Another open store holds the exclusive lock on this log file.
+    #[error("log file {} is locked by another process{}", path.display(), match pid {
+        Some(pid) => format!(" (pid {pid})"),
+        None => String::new(),
+    })]
+    LogLocked {
+        /// Path of the locked log file.
+        path: std::path::PathBuf,
+        /// PID recorded by the lock holder, if it could be read.
+        pid: Option<u32>,
+    },
+
+    /// The data directory or its manifest is unusable.
+    #[error("invalid data directory {}: {detail}", path.display())]
+    InvalidDataDir {
+        /// Path of the data directory.
+        path: std::path::PathBuf,
+        /// Human-readable description of the problem.
+        detail: String,
+    },
+
+    /// The request's deadline passed before the writer started on it. Nothing
+    /// was written, so the append can be retried as is.
+    #[error("deadline exceeded before the append was written; nothing was committed")]
+    DeadlineExceeded,
+
+    /// The request's deadline passed while the writer was working on it. The
+    /// events may still be committed; retrying with the same event IDs is
+    /// answered from the idempotency index if they were.
+    #[error("deadline exceeded while the append was being written; the outcome is unknown")]
+    DeadlineExceededInFlight,
+
+    /// An earlier write or fsync failed, so the store refuses appends until
+    /// it is reopened and the log recovered.
+    #[error("writer stopped after a fatal I/O error ({0}); restart to recover")]
+    WriterFailed(String),
+
+    /// Free space on the data volume is below the configured low watermark.
+    /// Nothing was written; the append can be retried once space is freed.
+    #[error("free disk space {free} bytes is below the {min_free} byte low watermark")]
+    DiskSpaceLow {
+        /// Free bytes at the last sample.
+        free: u64,
+        /// The configured low watermark in bytes.
+        min_free: u64,
+    },
+
+    /// A JWT verification key or TLS certificate file could not be read or
+    /// holds no usable key.
+    #[error("invalid key file {}: {detail}", path.display())]
+    InvalidKeyFile {
+        /// The PEM or JWKS file.
+        path: std::path::PathBuf,
+        /// Human-readable description of the problem.
+        detail: String,
+    },
+
+    /// The authenticated caller is not allowed to perform the operation.
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// A live subscriber fell further behind than the broker buffers, so its
+    /// subscription was ended. Re-subscribing from the last checkpoint
+    /// resumes it without gaps.
+    #[error("subscription lagged: re-subscribe from last checkpoint")]
+    SubscriptionLagged,
 }
 
 #[cfg(test)]
@@ -175,7 +256,87 @@ mod tests {
         );
     }
 
-    // AC-5: All seven variants implement Debug (format via {:?} produces non-empty strings).
+    #[test]
+    fn read_only_display() {
+        assert_eq!(Error::ReadOnly.to_string(), "store is open read-only");
+    }
+
+    #[test]
+    fn log_locked_display_names_path_and_pid() {
+        let err = Error::LogLocked {
+            path: "/data/events.log".into(),
+            pid: Some(4242),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("/data/events.log"), "missing path in: {msg}");
+        assert!(msg.contains("pid 4242"), "missing pid in: {msg}");
+    }
+
+    #[test]
+    fn invalid_data_dir_display_names_path_and_detail() {
+        let err = Error::InvalidDataDir {
+            path: "/data".into(),
+            detail: "missing manifest.json".into(),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("/data"), "missing path in: {msg}");
+        assert!(
+            msg.contains("missing manifest.json"),
+            "missing detail in: {msg}"
+        );
+    }
+
+    #[test]
+    fn deadline_exceeded_display_says_nothing_was_committed() {
+        let msg = Error::DeadlineExceeded.to_string();
+        assert!(msg.contains("deadline exceeded"), "got: {msg}");
+        assert!(msg.contains("nothing was committed"), "got: {msg}");
+    }
+
+    #[test]
+    fn deadline_exceeded_in_flight_display_says_the_outcome_is_unknown() {
+        let msg = Error::DeadlineExceededInFlight.to_string();
+        assert!(msg.contains("deadline exceeded"), "got: {msg}");
+        assert!(msg.contains("outcome is unknown"), "got: {msg}");
+    }
+
+    #[test]
+    fn writer_failed_display_includes_cause() {
+        let msg = Error::WriterFailed("I/O error: No space left on device".into()).to_string();
+        assert!(msg.contains("No space left on device"), "got: {msg}");
+        assert!(msg.contains("restart"), "got: {msg}");
+    }
+
+    #[test]
+    fn disk_space_low_display_includes_free_and_watermark() {
+        let msg = Error::DiskSpaceLow {
+            free: 1024,
+            min_free: 4096,
+        }
+        .to_string();
+        assert!(msg.contains("1024"), "got: {msg}");
+        assert!(msg.contains("4096"), "got: {msg}");
+    }
+
+    #[test]
+    fn invalid_key_file_display_includes_path_and_detail() {
+        let msg = Error::InvalidKeyFile {
+            path: "/etc/eventfold/jwks.json".into(),
+            detail: "no usable signing keys".into(),
+        }
+        .to_string();
+        assert!(msg.contains("/etc/eventfold/jwks.json"), "got: {msg}");
+        assert!(msg.contains("no usable signing keys"), "got: {msg}");
+    }
+
+    #[test]
+    fn permission_denied_display_includes_reason() {
+        let msg = Error::PermissionDenied("Append not permitted".into()).to_string();
+        assert!(msg.contains("permission denied"), "got: {msg}");
+        assert!(msg.contains("Append not permitted"), "got: {msg}");
+    }
+
+    // AC-5: All variants implement Debug (format via {:?} produces non-empty strings).
 
     #[test]
     fn all_variants_debug_non_empty() {
@@ -199,6 +360,28 @@ mod tests {
                 max: 65_536,
             },
             Error::InvalidArgument("empty".into()),
+            Error::ReadOnly,
+            Error::LogLocked {
+                path: "/data/events.log".into(),
+                pid: None,
+            },
+            Error::InvalidDataDir {
+                path: "/data".into(),
+                detail: "bad manifest".into(),
+            },
+            Error::DeadlineExceeded,
+            Error::DeadlineExceededInFlight,
+            Error::WriterFailed("fsync failed".into()),
+            Error::DiskSpaceLow {
+                free: 0,
+                min_free: 1,
+            },
+            Error::InvalidKeyFile {
+                path: "/keys/jwt.pem".into(),
+                detail: "bad PEM".into(),
+            },
+            Error::PermissionDenied("Backup not permitted".into()),
+            Error::SubscriptionLagged,
         ];
 
         for (i, variant) in variants.iter().enumerate() {
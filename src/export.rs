@@ -0,0 +1,785 @@
+//! Export and import of the event log in portable formats.
+//!
+//! Two formats are supported, both one event per entry in global position
+//! order:
+//!
+//! - [`ExportFormat::Ndjson`] -- newline-delimited JSON. Every field of the
+//!   [`RecordedEvent`] is kept; UUIDs are hyphenated strings and `metadata` /
+//!   `payload` are standard base64 so that arbitrary bytes round-trip.
+//! - [`ExportFormat::Protobuf`] -- a stream of `eventfold.RecordedEvent`
+//!   messages, each prefixed with its length as a protobuf varint (the
+//!   "length-delimited" framing used by `writeDelimitedTo` and
+//!   `prost::Message::encode_length_delimited`).
+//!
+//! Export reads through [`ReadIndex::read_all`], so it can run against a live
+//! store. Import writes through [`Store::append`] in one of two modes, see
+//! [`ImportMode`].
+
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytes::Bytes;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::clock::{Stamper, SystemClock};
+use crate::error::Error;
+use crate::proto;
+use crate::reader::ReadIndex;
+use crate::service::recorded_to_proto;
+use crate::store::Store;
+use crate::types::{AuditInfo, ExpectedVersion, ProposedEvent, RecordedEvent};
+
+/// Number of events fetched from the read index per page during export.
+const EXPORT_PAGE_SIZE: u64 = 1000;
+
+/// Most events appended as one batch by a re-appending import.
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Upper bound on a single length-delimited protobuf entry during import.
+/// Generous compared to `MAX_EVENT_SIZE` so that only garbage input trips it.
+const MAX_PROTOBUF_ENTRY: u64 = 16 * 1024 * 1024;
+
+/// Serialization format for export and import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Newline-delimited JSON, one event object per line.
+    Ndjson,
+    /// Varint length-prefixed protobuf `RecordedEvent` messages.
+    Protobuf,
+}
+
+/// Which events an export includes.
+///
+/// `ExportFilter::default()` selects the whole log.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// First global position to export (inclusive).
+    pub from_position: u64,
+    /// Global position to stop at (exclusive). `None` exports to the head.
+    pub to_position: Option<u64>,
+    /// Streams to export. `None` exports every stream.
+    pub streams: Option<HashSet<Uuid>>,
+}
+
+impl ExportFilter {
+    /// Returns `true` if `event` belongs to the selected streams.
+    fn matches_stream(&self, event: &RecordedEvent) -> bool {
+        self.streams
+            .as_ref()
+            .is_none_or(|streams| streams.contains(&event.stream_id))
+    }
+}
+
+/// How [`import`] assigns positions to imported events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep every event's original global position, stream version, and
+    /// timestamp. The target store must be empty and the input must be a
+    /// complete, contiguous export starting at position 0.
+    PreservePositions,
+    /// Append every event to its stream at the end of an existing store. The
+    /// store assigns new positions and versions; events whose IDs already
+    /// exist in the store are skipped, so re-running an import is harmless.
+    /// Events get the current time as `recorded_at`, clamped so it never
+    /// precedes an event already in the store.
+    Reappend,
+}
+
+/// Outcome of an [`import`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportSummary {
+    /// Number of events written to the store.
+    pub imported: u64,
+    /// Number of events skipped because their IDs were already present.
+    pub skipped: u64,
+}
+
+/// JSON representation of a [`RecordedEvent`] used by the NDJSON format.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonEvent {
+    global_position: u64,
+    /// Hyphenated UUID.
+    stream_id: String,
+    stream_version: u64,
+    /// Hyphenated UUID.
+    event_id: String,
+    event_type: String,
+    recorded_at: u64,
+    /// Standard base64.
+    metadata: String,
+    /// Standard base64.
+    payload: String,
+    /// Whether the event carries audit information. Omitted when `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    audited: bool,
+    /// Audited principal, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    principal: Option<String>,
+    /// Audited client address, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_addr: Option<String>,
+}
+
+/// Export events from `read_index` to `out`.
+///
+/// Pages through the global log with [`ReadIndex::read_all`], applying
+/// `filter`, and writes each selected event in `format`. Events appended while
+/// the export runs are included up to the filter's `to_position`.
+///
+/// # Arguments
+///
+/// * `read_index` - Log to export from.
+/// * `filter` - Position range and stream selection.
+/// * `format` - Output format.
+/// * `out` - Destination writer. Flushed before returning.
+///
+/// # Returns
+///
+/// The number of events written.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if writing to `out` fails.
+pub fn export(
+    read_index: &ReadIndex,
+    filter: &ExportFilter,
+    format: ExportFormat,
+    mut out: impl Write,
+) -> Result<u64, Error> {
+    let mut cursor = filter.from_position;
+    let mut written = 0;
+
+    'pages: loop {
+        let page = read_index.read_all(cursor, EXPORT_PAGE_SIZE);
+        let Some(last) = page.last() else {
+            break;
+        };
+        cursor = last.global_position + 1;
+
+        for event in &page {
+            if filter
+                .to_position
+                .is_some_and(|to| event.global_position >= to)
+            {
+                break 'pages;
+            }
+            if !filter.matches_stream(event) {
+                continue;
+            }
+            write_event(&mut out, event, format)?;
+            written += 1;
+        }
+    }
+
+    out.flush()?;
+    Ok(written)
+}
+
+/// Import events from `input` into `store`.
+///
+/// # Arguments
+///
+/// * `store` - Target store.
+/// * `input` - Exported events in `format`.
+/// * `format` - Input format.
+/// * `mode` - Whether to keep original positions, see [`ImportMode`].
+///
+/// # Returns
+///
+/// An [`ImportSummary`] with the number of imported and skipped events.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidArgument`] if the input is malformed, or, in
+/// [`ImportMode::PreservePositions`], if the store is not empty or the input
+/// positions are not contiguous from 0.
+/// Returns [`Error::WrongExpectedVersion`] if preserved stream versions do not
+/// line up. Returns [`Error::Io`] if reading the input or writing the store
+/// fails. Events imported before an error stay in the store.
+pub fn import(
+    store: &mut Store,
+    input: impl BufRead,
+    format: ExportFormat,
+    mode: ImportMode,
+) -> Result<ImportSummary, Error> {
+    let events = read_events(input, format);
+    match mode {
+        ImportMode::PreservePositions => import_preserving(store, events),
+        ImportMode::Reappend => import_reappending(store, events),
+    }
+}
+
+/// Import for [`ImportMode::PreservePositions`].
+///
+/// Consecutive events of the same stream with the same timestamp and audit
+/// information are appended as one batch, which keeps the original
+/// `recorded_at` and `audit` values intact.
+fn import_preserving(
+    store: &mut Store,
+    events: impl Iterator<Item = Result<RecordedEvent, Error>>,
+) -> Result<ImportSummary, Error> {
+    if store.global_position() != 0 {
+        return Err(Error::InvalidArgument(
+            "preserving positions requires an empty store".to_string(),
+        ));
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut pending: Vec<RecordedEvent> = Vec::new();
+    for event in events {
+        let event = event?;
+        let expected_position = summary.imported + pending.len() as u64;
+        if event.global_position != expected_position {
+            return Err(Error::InvalidArgument(format!(
+                "cannot preserve positions: expected position {expected_position}, found {}",
+                event.global_position
+            )));
+        }
+        let same_batch = pending.last().is_some_and(|prev| {
+            prev.stream_id == event.stream_id
+                && prev.recorded_at == event.recorded_at
+                && prev.audit == event.audit
+        });
+        if !same_batch {
+            summary.imported += append_preserved(store, &mut pending)?;
+        }
+        pending.push(event);
+    }
+    summary.imported += append_preserved(store, &mut pending)?;
+    Ok(summary)
+}
+
+/// Append `batch` (events of one stream) with its original stream versions,
+/// timestamp, and audit information, then clear it.
+fn append_preserved(store: &mut Store, batch: &mut Vec<RecordedEvent>) -> Result<u64, Error> {
+    let Some(first) = batch.first() else {
+        return Ok(0);
+    };
+    let expected = match first.stream_version {
+        0 => ExpectedVersion::NoStream,
+        v => ExpectedVersion::Exact(v - 1),
+    };
+    let (stream_id, recorded_at) = (first.stream_id, first.recorded_at);
+    let audit = first.audit.clone();
+    let proposed = batch.drain(..).map(to_proposed).collect::<Vec<_>>();
+    let count = proposed.len() as u64;
+    store.append_audited(stream_id, expected, recorded_at, proposed, audit)?;
+    Ok(count)
+}
+
+/// Import for [`ImportMode::Reappend`].
+///
+/// Consecutive new events of the same stream with the same audit information
+/// are appended as one batch, up to [`IMPORT_BATCH_SIZE`] events, so a large
+/// import is not one fsync per event. Every batch is stamped with the current
+/// time, but never earlier than the latest `recorded_at` already in the log,
+/// keeping timestamps non-decreasing as the writer does.
+fn import_reappending(
+    store: &mut Store,
+    events: impl Iterator<Item = Result<RecordedEvent, Error>>,
+) -> Result<ImportSummary, Error> {
+    let (mut seen, latest_recorded_at) = {
+        let log = store.log();
+        let log = log.read().expect("EventLog RwLock poisoned");
+        let seen: HashSet<Uuid> = log.events.iter().map(|e| e.event_id).collect();
+        (seen, log.latest_recorded_at())
+    };
+    let recorded_at = Stamper::new(Arc::new(SystemClock), latest_recorded_at).stamp();
+
+    let mut summary = ImportSummary::default();
+    let mut pending: Vec<RecordedEvent> = Vec::new();
+    for event in events {
+        let event = event?;
+        if !seen.insert(event.event_id) {
+            summary.skipped += 1;
+            continue;
+        }
+        let same_batch = pending.len() < IMPORT_BATCH_SIZE
+            && pending
+                .last()
+                .is_some_and(|prev| prev.stream_id == event.stream_id && prev.audit == event.audit);
+        if !same_batch {
+            summary.imported += append_reappended(store, recorded_at, &mut pending)?;
+        }
+        pending.push(event);
+    }
+    summary.imported += append_reappended(store, recorded_at, &mut pending)?;
+    Ok(summary)
+}
+
+/// Append `batch` (events of one stream) at the end of the stream, then clear
+/// it.
+fn append_reappended(
+    store: &mut Store,
+    recorded_at: u64,
+    batch: &mut Vec<RecordedEvent>,
+) -> Result<u64, Error> {
+    let Some(first) = batch.first() else {
+        return Ok(0);
+    };
+    let (stream_id, audit) = (first.stream_id, first.audit.clone());
+    let proposed = batch.drain(..).map(to_proposed).collect::<Vec<_>>();
+    let count = proposed.len() as u64;
+    store.append_audited(
+        stream_id,
+        ExpectedVersion::Any,
+        recorded_at,
+        proposed,
+        audit,
+    )?;
+    Ok(count)
+}
+
+/// Strip server-assigned fields from a recorded event.
+fn to_proposed(event: RecordedEvent) -> ProposedEvent {
+    ProposedEvent {
+        event_id: event.event_id,
+        event_type: event.event_type,
+        metadata: event.metadata,
+        payload: event.payload,
+    }
+}
+
+/// Write one event to `out` in `format`.
+fn write_event(
+    out: &mut impl Write,
+    event: &RecordedEvent,
+    format: ExportFormat,
+) -> Result<(), Error> {
+    match format {
+        ExportFormat::Ndjson => {
+            let json = JsonEvent {
+                global_position: event.global_position,
+                stream_id: event.stream_id.to_string(),
+                stream_version: event.stream_version,
+                event_id: event.event_id.to_string(),
+                event_type: event.event_type.clone(),
+                recorded_at: event.recorded_at,
+                metadata: BASE64.encode(&event.metadata),
+                payload: BASE64.encode(&event.payload),
+                audited: event.audit.is_some(),
+                principal: event.audit.as_ref().and_then(|a| a.principal.clone()),
+                client_addr: event.audit.as_ref().and_then(|a| a.client_addr.clone()),
+            };
+            serde_json::to_writer(&mut *out, &json).map_err(std::io::Error::from)?;
+            out.write_all(b"\n")?;
+        }
+        ExportFormat::Protobuf => {
+            out.write_all(&recorded_to_proto(event).encode_length_delimited_to_vec())?;
+        }
+    }
+    Ok(())
+}
+
+/// Decode events from `input` in `format`, one at a time.
+fn read_events<R: BufRead>(input: R, format: ExportFormat) -> EventReader<R> {
+    EventReader {
+        input,
+        format,
+        entry: 0,
+    }
+}
+
+/// Iterator over the events of an export, produced by [`read_events`].
+struct EventReader<R> {
+    /// Remaining export input.
+    input: R,
+    /// Format of `input`.
+    format: ExportFormat,
+    /// One-based number of the last entry read, for error messages.
+    entry: u64,
+}
+
+impl<R: BufRead> EventReader<R> {
+    /// Read the next NDJSON line, skipping blank lines.
+    fn next_json(&mut self) -> Result<Option<RecordedEvent>, Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.entry += 1;
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        let json: JsonEvent = serde_json::from_str(&line).map_err(|e| self.malformed(e))?;
+        let metadata = BASE64
+            .decode(&json.metadata)
+            .map_err(|e| self.malformed(e))?;
+        let payload = BASE64
+            .decode(&json.payload)
+            .map_err(|e| self.malformed(e))?;
+        Ok(Some(RecordedEvent {
+            event_id: self.parse_uuid("event_id", &json.event_id)?,
+            stream_id: self.parse_uuid("stream_id", &json.stream_id)?,
+            stream_version: json.stream_version,
+            global_position: json.global_position,
+            recorded_at: json.recorded_at,
+            event_type: json.event_type,
+            metadata: Bytes::from(metadata),
+            payload: Bytes::from(payload),
+            audit: json.audited.then_some(AuditInfo {
+                principal: json.principal,
+                client_addr: json.client_addr,
+            }),
+        }))
+    }
+
+    /// Read the next length-delimited protobuf message.
+    fn next_protobuf(&mut self) -> Result<Option<RecordedEvent>, Error> {
+        // Clean end of input is only allowed between entries.
+        if self.input.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        self.entry += 1;
+
+        let mut len: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let mut byte = [0u8; 1];
+            self.input.read_exact(&mut byte)?;
+            len |= u64::from(byte[0] & 0x7F) << shift;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift >= 64 {
+                return Err(self.malformed("length prefix is not a valid varint"));
+            }
+        }
+        if len > MAX_PROTOBUF_ENTRY {
+            return Err(self.malformed(format!("entry of {len} bytes is too large")));
+        }
+
+        let mut buf = vec![0u8; len as usize];
+        self.input.read_exact(&mut buf)?;
+        let msg = proto::RecordedEvent::decode(buf.as_slice()).map_err(|e| self.malformed(e))?;
+        Ok(Some(RecordedEvent {
+            event_id: self.parse_uuid("event_id", &msg.event_id)?,
+            stream_id: self.parse_uuid("stream_id", &msg.stream_id)?,
+            stream_version: msg.stream_version,
+            global_position: msg.global_position,
+            recorded_at: msg.recorded_at,
+            event_type: msg.event_type,
+            metadata: Bytes::from(msg.metadata),
+            payload: Bytes::from(msg.payload),
+            audit: msg.audited.then(|| AuditInfo {
+                principal: (!msg.principal.is_empty()).then_some(msg.principal),
+                client_addr: (!msg.client_addr.is_empty()).then_some(msg.client_addr),
+            }),
+        }))
+    }
+
+    /// Parse a UUID field of the current entry.
+    fn parse_uuid(&self, field: &str, value: &str) -> Result<Uuid, Error> {
+        Uuid::parse_str(value).map_err(|e| self.malformed(format!("{field}: {e}")))
+    }
+
+    /// Build the error for a malformed entry.
+    fn malformed(&self, detail: impl std::fmt::Display) -> Error {
+        Error::InvalidArgument(format!("malformed import entry {}: {detail}", self.entry))
+    }
+}
+
+impl<R: BufRead> Iterator for EventReader<R> {
+    type Item = Result<RecordedEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match self.format {
+            ExportFormat::Ndjson => self.next_json(),
+            ExportFormat::Protobuf => self.next_protobuf(),
+        };
+        next.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: build a `ProposedEvent` with a non-UTF-8 payload so base64
+    /// round-tripping is exercised.
+    fn proposed(event_type: &str) -> ProposedEvent {
+        ProposedEvent {
+            event_id: Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            metadata: Bytes::from_static(b"{\"trace\":1}"),
+            payload: Bytes::from_static(&[0xFF, 0x00, 0x7B]),
+        }
+    }
+
+    /// Helper: a store with two streams. A has 3 events (one batch of 2, then
+    /// one), B has 2 single-event batches. Positions: A0 A1 B0 A2 B1.
+    fn seeded_store(dir: &tempfile::TempDir) -> (Store, Uuid, Uuid) {
+        let mut store = Store::open(&dir.path().join("source.log")).expect("open should succeed");
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        store
+            .append(
+                a,
+                ExpectedVersion::NoStream,
+                10,
+                vec![proposed("A"), proposed("A")],
+            )
+            .expect("append should succeed");
+        store
+            .append(b, ExpectedVersion::NoStream, 20, vec![proposed("B")])
+            .expect("append should succeed");
+        store
+            .append(a, ExpectedVersion::Exact(1), 30, vec![proposed("A")])
+            .expect("append should succeed");
+        // One audited batch, so round trips cover the audit fields.
+        store
+            .append_audited(
+                b,
+                ExpectedVersion::Exact(0),
+                40,
+                vec![proposed("B")],
+                Some(AuditInfo {
+                    principal: Some("svc".to_string()),
+                    client_addr: None,
+                }),
+            )
+            .expect("append should succeed");
+        (store, a, b)
+    }
+
+    /// Helper: export the whole store in `format`.
+    fn export_all(store: &Store, format: ExportFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        let read_index = ReadIndex::new(store.log());
+        let written = export(&read_index, &ExportFilter::default(), format, &mut out)
+            .expect("export should succeed");
+        assert_eq!(written, store.global_position());
+        out
+    }
+
+    #[test]
+    fn ndjson_round_trip_preserves_positions() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, _, _) = seeded_store(&dir);
+        let data = export_all(&source, ExportFormat::Ndjson);
+        assert_eq!(data.iter().filter(|&&b| b == b'\n').count(), 5);
+
+        let mut target = Store::open(&dir.path().join("target.log")).expect("open should succeed");
+        let summary = import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::PreservePositions,
+        )
+        .expect("import should succeed");
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 5,
+                skipped: 0
+            }
+        );
+        assert_eq!(target.read_all(0, 100), source.read_all(0, 100));
+    }
+
+    #[test]
+    fn protobuf_round_trip_preserves_positions() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, _, _) = seeded_store(&dir);
+        let data = export_all(&source, ExportFormat::Protobuf);
+
+        let mut target = Store::open(&dir.path().join("target.log")).expect("open should succeed");
+        import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Protobuf,
+            ImportMode::PreservePositions,
+        )
+        .expect("import should succeed");
+        assert_eq!(target.read_all(0, 100), source.read_all(0, 100));
+    }
+
+    #[test]
+    fn export_filters_by_range_and_stream() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, a, _) = seeded_store(&dir);
+        let read_index = ReadIndex::new(source.log());
+
+        let filter = ExportFilter {
+            from_position: 1,
+            to_position: Some(4),
+            streams: Some(HashSet::from([a])),
+        };
+        let mut out = Vec::new();
+        let written = export(&read_index, &filter, ExportFormat::Protobuf, &mut out)
+            .expect("export should succeed");
+        assert_eq!(written, 2);
+
+        let positions: Vec<u64> = read_events(out.as_slice(), ExportFormat::Protobuf)
+            .map(|e| e.expect("decode should succeed").global_position)
+            .collect();
+        assert_eq!(positions, vec![1, 3]);
+    }
+
+    #[test]
+    fn preserve_positions_rejects_non_empty_store() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (mut source, _, _) = seeded_store(&dir);
+        let data = export_all(&source, ExportFormat::Ndjson);
+
+        match import(
+            &mut source,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::PreservePositions,
+        ) {
+            Err(Error::InvalidArgument(msg)) => assert!(msg.contains("empty store")),
+            other => panic!("expected InvalidArgument, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn preserve_positions_rejects_gaps() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, _, b) = seeded_store(&dir);
+        let filter = ExportFilter {
+            streams: Some(HashSet::from([b])),
+            ..ExportFilter::default()
+        };
+        let mut data = Vec::new();
+        export(
+            &ReadIndex::new(source.log()),
+            &filter,
+            ExportFormat::Ndjson,
+            &mut data,
+        )
+        .expect("export should succeed");
+
+        let mut target = Store::open(&dir.path().join("target.log")).expect("open should succeed");
+        match import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::PreservePositions,
+        ) {
+            Err(Error::InvalidArgument(msg)) => assert!(msg.contains("expected position 0")),
+            other => panic!("expected InvalidArgument, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reappend_assigns_new_positions_and_skips_known_ids() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, a, _) = seeded_store(&dir);
+        let data = export_all(&source, ExportFormat::Ndjson);
+
+        let mut target = Store::open(&dir.path().join("target.log")).expect("open should succeed");
+        target
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                0,
+                vec![proposed("Existing")],
+            )
+            .expect("append should succeed");
+
+        let first = import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::Reappend,
+        )
+        .expect("import should succeed");
+        assert_eq!(
+            first,
+            ImportSummary {
+                imported: 5,
+                skipped: 0
+            }
+        );
+        assert_eq!(target.global_position(), 6);
+        assert_eq!(target.stream_version(&a), Some(2));
+
+        // Importing the same export again is a no-op.
+        let second = import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::Reappend,
+        )
+        .expect("import should succeed");
+        assert_eq!(
+            second,
+            ImportSummary {
+                imported: 0,
+                skipped: 5
+            }
+        );
+        assert_eq!(target.global_position(), 6);
+    }
+
+    #[test]
+    fn reappend_batches_per_stream_and_keeps_timestamps_ascending() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let (source, _, _) = seeded_store(&dir);
+        let data = export_all(&source, ExportFormat::Ndjson);
+
+        // An existing event stamped far in the future sets the floor.
+        let future = 4_000_000_000_000;
+        let target_path = dir.path().join("target.log");
+        let mut target = Store::open(&target_path).expect("open should succeed");
+        target
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                future,
+                vec![proposed("Existing")],
+            )
+            .expect("append should succeed");
+
+        import(
+            &mut target,
+            data.as_slice(),
+            ExportFormat::Ndjson,
+            ImportMode::Reappend,
+        )
+        .expect("import should succeed");
+        assert!(
+            target
+                .read_all(0, 10)
+                .iter()
+                .all(|e| e.recorded_at == future)
+        );
+
+        // A0 A1 | B0 | A2 | B1 (audited), after the existing batch.
+        let bytes = std::fs::read(&target_path).expect("read target log");
+        let summary = crate::dump::dump(
+            &bytes,
+            &crate::dump::DumpFilter::default(),
+            crate::dump::DumpFormat::Text,
+            crate::dump::PayloadFormat::Hex,
+            &mut std::io::sink(),
+        )
+        .expect("dump should succeed");
+        assert_eq!(summary.batches, 5);
+    }
+
+    #[test]
+    fn malformed_ndjson_reports_entry_number() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let mut target = Store::open(&dir.path().join("target.log")).expect("open should succeed");
+        let input = b"\n{not json}\n";
+        match import(
+            &mut target,
+            &input[..],
+            ExportFormat::Ndjson,
+            ImportMode::Reappend,
+        ) {
+            Err(Error::InvalidArgument(msg)) => assert!(msg.contains("entry 2"), "got: {msg}"),
+            other => panic!("expected InvalidArgument, got: {other:?}"),
+        }
+    }
+}
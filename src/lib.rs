@@ -48,7 +48,8 @@
 //! # Key Types
 //!
 //! - [`Store`] -- Storage engine that owns the append-only log file and in-memory
-//!   index. Open or create a store with [`Store::open`].
+//!   index. Open or create a store with [`Store::open`], or use
+//!   [`Store::in_memory`] for tests that should not touch the filesystem.
 //! - [`WriterHandle`] -- Cloneable handle for submitting append requests to the
 //!   single writer task via a bounded channel.
 //! - [`ReadIndex`] -- Shared, read-only handle to the in-memory event log for
@@ -61,6 +62,13 @@
 //!   stream version, and timestamp.
 //! - [`ExpectedVersion`] -- Optimistic concurrency control: `Any`, `NoStream`,
 //!   or `Exact(version)`.
+//! - [`Clock`] -- Source of the `recorded_at` timestamps the writer assigns.
+//!   [`SystemClock`] by default, or a [`ManualClock`] for deterministic tests
+//!   via [`spawn_writer_with_clock`]. The writer assigns no event IDs, so
+//!   with a `ManualClock` its output is fully deterministic.
+//! - [`Repository`] -- Load-fold-append loop for an [`Aggregate`] that retries
+//!   the append on a wrong expected version, over an [`EmbeddedStore`] or any
+//!   other [`StreamStore`].
 //! - [`Error`] -- Unified error enum for all operations, with variants that map
 //!   to gRPC status codes.
 //!
@@ -69,38 +77,81 @@
 //! This crate ships both a library and a standalone server binary. The library
 //! exposes the storage engine, writer, reader, broker, and gRPC service types
 //! so they can be embedded into custom applications. The binary
-//! (`eventfold-db`) is a thin wrapper that reads configuration from the
-//! environment, opens the store, and starts the gRPC server -- suitable for
-//! running EventfoldDB as a standalone service.
+//! (`eventfold-db`) is a thin wrapper that reads configuration from a config
+//! file, the environment, and flags, opens the store, and starts the gRPC
+//! server -- suitable for running EventfoldDB as a standalone service. Its
+//! offline subcommands (`verify`, `dump`, `stats`, `export`, `import`,
+//! `backup`, `compact`) work on a data directory through the same library
+//! APIs.
+//!
+//! The gRPC service, JWT authentication and ACLs, TLS, the Prometheus
+//! exporter, export/import, and the binary sit behind the default `server`
+//! feature. With `default-features = false` the crate is the storage engine,
+//! the domain types, and the generated protobuf types, which is all the
+//! `eventfold-client` crate depends on.
 
+#[cfg(feature = "server")]
+pub mod acl;
+pub mod aggregate;
+#[cfg(feature = "server")]
 pub mod auth;
+pub mod backend;
+pub mod backup;
 pub mod broker;
+pub mod clock;
 pub mod codec;
+pub mod data_dir;
 pub(crate) mod dedup;
+pub mod disk;
+pub mod dump;
 pub mod error;
+#[cfg(feature = "server")]
+pub mod export;
+pub(crate) mod lock;
 /// Prometheus metrics infrastructure for EventfoldDB.
+#[cfg(feature = "server")]
 pub mod metrics;
 /// Generated protobuf types for the EventfoldDB gRPC API.
 pub mod proto {
     tonic::include_proto!("eventfold");
 }
+#[cfg(feature = "server")]
+pub mod quota;
 pub mod reader;
+#[cfg(feature = "server")]
 pub mod service;
 pub mod store;
+#[cfg(feature = "server")]
+pub mod tls;
 pub mod types;
 pub mod writer;
 
+#[cfg(feature = "server")]
+pub use acl::{AclPolicy, Operation};
+pub use aggregate::{
+    Aggregate, ConflictPolicy, EmbeddedStore, Loaded, Repository, RepositoryError, StreamStore,
+};
+pub use backend::{FileBackend, MemoryBackend, StorageBackend};
+pub use backup::BackupSource;
 pub use broker::{Broker, subscribe_all, subscribe_stream};
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use codec::DecodeOutcome;
+pub use data_dir::{DataDir, Manifest};
+pub use disk::DiskGuard;
 pub use error::Error;
+#[cfg(feature = "server")]
+pub use quota::{QuotaConfig, Quotas};
 pub use reader::ReadIndex;
+#[cfg(feature = "server")]
 pub use service::EventfoldService;
-pub use store::Store;
+pub use store::{Durability, Store, StoreOptions};
+#[cfg(feature = "server")]
+pub use tls::ReloadableTls;
 pub use types::{
-    ExpectedVersion, MAX_EVENT_SIZE, MAX_EVENT_TYPE_LEN, ProposedEvent, RecordedEvent, StreamInfo,
-    SubscriptionMessage,
+    AppendResult, AuditInfo, ExpectedVersion, LogHole, MAX_EVENT_SIZE, MAX_EVENT_TYPE_LEN,
+    ProposedEvent, RecordedEvent, StreamInfo, SubscriptionMessage,
 };
-pub use writer::{WriterHandle, spawn_writer};
+pub use writer::{RequestState, WriterHandle, WriterStatus, spawn_writer, spawn_writer_with_clock};
 
 #[cfg(test)]
 mod tests {
@@ -129,6 +180,7 @@ mod tests {
             event_type: "TestEvent".to_string(),
             metadata: bytes::Bytes::new(),
             payload: bytes::Bytes::new(),
+            audit: None,
         };
         assert_eq!(event.global_position, 0);
     }
@@ -172,6 +224,7 @@ mod tests {
         assert!(req.expected_version.is_none());
     }
 
+    #[cfg(feature = "server")]
     #[test]
     fn eventfold_service_accessible_at_crate_root() {
         // Verify that `crate::EventfoldService` resolves and that its constructor
@@ -190,6 +243,7 @@ mod tests {
         assert_eq!(info.event_count, 3);
     }
 
+    #[cfg(feature = "server")]
     #[test]
     fn event_store_server_accessible_via_proto() {
         // Verify that the tonic-generated EventStoreServer is reachable through
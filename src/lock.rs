@@ -0,0 +1,122 @@
+//! Exclusive lock guarding a log file against a second writer.
+//!
+//! Two processes appending to the same log would interleave batches and
+//! corrupt it. A writable [`Store`](crate::store::Store) therefore holds an
+//! advisory `flock(2)` on a `<log>.lock` file next to the log for as long as
+//! it is open. The lock file also records the holder's PID so the losing
+//! process can say who owns the log. The kernel drops the lock when the
+//! holder exits, so a stale lock file left by a crash never blocks a restart.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+/// Returns the path of the lock file guarding the log at `path`.
+pub(crate) fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// A held exclusive lock on a log file. Released on drop.
+#[derive(Debug)]
+pub(crate) struct LogLock {
+    /// Open handle to the lock file; closing it releases the `flock`.
+    _file: File,
+}
+
+impl LogLock {
+    /// Take the exclusive lock for the log at `path` without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LogLocked`] if another open store holds the lock,
+    /// with the PID recorded in the lock file when it can be read.
+    /// Returns [`Error::Io`] if the lock file cannot be created or written.
+    pub(crate) fn acquire(path: &Path) -> Result<LogLock, Error> {
+        let lock_file = lock_path(path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_file)?;
+
+        if !try_lock_exclusive(&file)? {
+            let mut contents = String::new();
+            let pid = file
+                .read_to_string(&mut contents)
+                .ok()
+                .and_then(|_| contents.trim().parse::<u32>().ok());
+            return Err(Error::LogLocked {
+                path: path.to_path_buf(),
+                pid,
+            });
+        }
+
+        // Record our PID for whoever loses the race next.
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+
+        Ok(LogLock { _file: file })
+    }
+}
+
+/// Try to take an exclusive `flock` on `file`. Returns `Ok(false)` if another
+/// open file description holds it.
+#[cfg(unix)]
+fn try_lock_exclusive(file: &File) -> Result<bool, Error> {
+    use std::os::fd::AsRawFd;
+    // SAFETY: `flock` only takes the descriptor and flags; the fd is owned by
+    // `file` for the duration of the call.
+    let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if rc == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(err.into())
+    }
+}
+
+/// Advisory locking is only implemented on Unix; elsewhere the lock always
+/// succeeds.
+#[cfg(not(unix))]
+fn try_lock_exclusive(_file: &File) -> Result<bool, Error> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_acquire_fails_and_names_holder_pid() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+
+        let _held = LogLock::acquire(&path).expect("first lock should succeed");
+        match LogLock::acquire(&path) {
+            Err(Error::LogLocked { path: locked, pid }) => {
+                assert_eq!(locked, path);
+                assert_eq!(pid, Some(std::process::id()));
+            }
+            other => panic!("expected LogLocked, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn lock_is_released_on_drop() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+
+        drop(LogLock::acquire(&path).expect("first lock should succeed"));
+        LogLock::acquire(&path).expect("lock should be free after drop");
+        assert!(lock_path(&path).exists());
+    }
+}
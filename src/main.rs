@@ -1,45 +1,65 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
 use std::net::SocketAddr;
-use std::num::NonZeroUsize;
-use std::path::PathBuf;
-
-use eventfold_db::auth::JwtInterceptor;
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use eventfold_db::auth::{JwtConfig, JwtInterceptor, JwtKeySource};
+use eventfold_db::codec;
+use eventfold_db::dump::{self, DumpFilter, DumpFormat, PayloadFormat};
+use eventfold_db::export::{self, ExportFilter, ExportFormat, ImportMode};
 use eventfold_db::proto::event_store_server::EventStoreServer;
-use eventfold_db::{Broker, EventfoldService, Store, spawn_writer};
+use eventfold_db::store;
+use eventfold_db::tls::TlsConfig;
+use eventfold_db::{
+    AclPolicy, Broker, DataDir, DecodeOutcome, DiskGuard, Durability, Error, EventfoldService,
+    QuotaConfig, Quotas, ReadIndex, ReloadableTls, Store, StoreOptions, WriterStatus, spawn_writer,
+};
 use tonic::service::interceptor::InterceptedService;
+use uuid::Uuid;
 
-/// Optional TLS configuration parsed from environment variables.
-///
-/// Present only when `EVENTFOLD_TLS_CERT` and `EVENTFOLD_TLS_KEY` are both set.
-/// When `EVENTFOLD_TLS_CA` is also set, the server requires client certificates (mTLS).
-#[derive(Debug, Clone, PartialEq)]
-struct TlsConfig {
-    /// Path to the PEM-encoded server certificate file.
-    cert_path: PathBuf,
-    /// Path to the PEM-encoded server private key file.
-    key_path: PathBuf,
-    /// Path to the PEM-encoded CA certificate for client verification (mTLS).
-    /// When `Some`, the server requires and verifies client certificates.
-    ca_path: Option<PathBuf>,
-}
-
-/// Server configuration parsed from environment variables.
+/// Server configuration, merged from a TOML config file, environment
+/// variables, and command-line flags (see [`Cli`]).
 ///
 /// # Environment Variables
 ///
+/// Every variable can also be set in the config file under its name without
+/// the `EVENTFOLD_` prefix, in lowercase (`EVENTFOLD_TLS_CERT` is `tls_cert`,
+/// or `cert` in a `[tls]` table), or with `--set <key>=<value>`.
+///
 /// | Variable                    | Required | Default      | Description                          |
 /// |-----------------------------|----------|--------------|--------------------------------------|
-/// | `EVENTFOLD_DATA`            | Yes      | --           | Path to the append-only log file     |
+/// | `EVENTFOLD_DATA`            | Yes      | --           | Data directory; a log file is migrated into one |
 /// | `EVENTFOLD_LISTEN`          | No       | `[::]:2113`  | Socket address to listen on          |
 /// | `EVENTFOLD_BROKER_CAPACITY` | No       | `4096`       | Broadcast channel buffer size        |
 /// | `EVENTFOLD_DEDUP_CAPACITY`  | No       | `65536`      | Max event IDs in dedup index         |
-/// | `EVENTFOLD_TLS_CERT`        | No       | --           | PEM cert path (enables TLS)          |
+/// | `EVENTFOLD_TLS_CERT`        | No       | --           | PEM cert path (enables TLS); TLS files reload on change or `SIGHUP` |
 /// | `EVENTFOLD_TLS_KEY`         | No       | --           | PEM key path (required with CERT)    |
 /// | `EVENTFOLD_TLS_CA`          | No       | --           | PEM CA path (enables mTLS)           |
 /// | `EVENTFOLD_METRICS_LISTEN`  | No       | `[::]:9090`  | Metrics HTTP address; empty disables |
 /// | `EVENTFOLD_JWT_SECRET`      | No       | --           | HS256 JWT signing secret; auth disabled when unset |
+/// | `EVENTFOLD_JWT_PUBLIC_KEYS` | No       | --           | Comma-separated PEM public keys (enables auth) |
+/// | `EVENTFOLD_JWT_ALGORITHM`   | No       | `RS256`      | `RS256`, `ES256`, or `EdDSA` for the PEM keys |
+/// | `EVENTFOLD_JWT_JWKS`        | No       | --           | JWKS file, keys picked by `kid` (enables auth) |
+/// | `EVENTFOLD_JWT_ISSUER`      | No       | --           | Comma-separated accepted `iss` values |
+/// | `EVENTFOLD_JWT_AUDIENCE`    | No       | --           | Comma-separated accepted `aud` values |
+/// | `EVENTFOLD_JWT_LEEWAY_SECS` | No       | `0`          | Clock skew allowed on `exp`/`nbf`    |
+/// | `EVENTFOLD_ACL_FILE`        | No       | --           | JSON ACL policy (requires JWT auth)  |
+/// | `EVENTFOLD_AUDIT`           | No       | `false`      | Record principal and client address on appended events |
+/// | `EVENTFOLD_REPAIR`          | No       | `false`      | Skip mid-file corruption on open (`true`/`1`) |
+/// | `EVENTFOLD_DURABILITY`      | No       | `fsync-per-batch` | `fsync-per-batch`, `fdatasync`, `interval`, or `none` |
+/// | `EVENTFOLD_SYNC_INTERVAL_MS`| No       | `100`        | Fsync period in `interval` mode      |
+/// | `EVENTFOLD_PREALLOCATE_MB`  | No       | `64`         | Log file preallocation extent; `0` disables |
+/// | `EVENTFOLD_IO_URING`        | No       | `false`      | io_uring write path (`io-uring` feature, Linux) |
+/// | `EVENTFOLD_READ_ONLY`       | No       | `false`      | Serve an existing log without writing to it |
+/// | `EVENTFOLD_QUOTA_APPENDS_PER_SEC` | No | --           | Per-client sustained append rate     |
+/// | `EVENTFOLD_QUOTA_BURST`     | No       | rate         | Per-client appends allowed in a burst |
+/// | `EVENTFOLD_QUOTA_MAX_IN_FLIGHT` | No   | --           | Per-client appends waiting on the writer |
+/// | `EVENTFOLD_MIN_FREE_MB`     | No       | `0`          | Reject appends below this much free disk; `0` disables |
 #[derive(Debug, Clone, PartialEq)]
 struct Config {
-    /// Path to the append-only event log file.
+    /// Data directory holding the manifest, log, and sidecar files.
     data_path: PathBuf,
     /// Socket address the gRPC server listens on.
     listen_addr: SocketAddr,
@@ -52,9 +72,31 @@ struct Config {
     /// Socket address for the Prometheus metrics HTTP endpoint.
     /// `None` disables the metrics endpoint entirely.
     metrics_listen: Option<SocketAddr>,
-    /// HS256 JWT signing secret for authenticating gRPC requests.
-    /// `None` means auth is disabled (all requests are accepted).
-    jwt_secret: Option<String>,
+    /// JWT verification keys and claim checks for authenticating gRPC
+    /// requests. `None` means auth is disabled (all requests are accepted).
+    jwt: Option<JwtConfig>,
+    /// JSON ACL policy checked against token claims. `None` allows every
+    /// authenticated call.
+    acl_file: Option<PathBuf>,
+    /// Stamp appended events with the caller's principal and address.
+    audit: bool,
+    /// Open the store in repair mode, skipping damaged ranges in the middle of
+    /// the log instead of refusing to start.
+    repair: bool,
+    /// How the writer makes appended batches durable.
+    durability: Durability,
+    /// Extent size the log file is preallocated in, in bytes. Zero disables
+    /// preallocation.
+    preallocate: u64,
+    /// Write the log through io_uring.
+    io_uring: bool,
+    /// Open the log read-only and reject appends with `FAILED_PRECONDITION`.
+    read_only: bool,
+    /// Per-client append limits. Disabled unless a limit is set.
+    quotas: QuotaConfig,
+    /// Free bytes on the data volume below which appends are rejected. Zero
+    /// disables the guard.
+    min_free: u64,
 }
 
 /// Default socket address the server listens on when `EVENTFOLD_LISTEN` is not set.
@@ -69,12 +111,370 @@ const DEFAULT_DEDUP_CAPACITY: usize = 65536;
 /// Default metrics listen address when `EVENTFOLD_METRICS_LISTEN` is not set.
 const DEFAULT_METRICS_LISTEN_ADDR: &str = "[::]:9090";
 
+/// Default fsync period in `interval` durability mode when
+/// `EVENTFOLD_SYNC_INTERVAL_MS` is not set.
+const DEFAULT_SYNC_INTERVAL_MS: u64 = 100;
+
+/// Default log preallocation extent when `EVENTFOLD_PREALLOCATE_MB` is not set.
+const DEFAULT_PREALLOCATE_MB: u64 = 64;
+
+/// How often JWT key files are checked for changes.
+const JWT_KEY_RELOAD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often TLS certificate files are checked for changes.
+const TLS_CERT_RELOAD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often the disk guard samples free space on the data volume.
+const DISK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Settings accepted by the server. Each one is read from the environment
+/// variable `EVENTFOLD_<KEY>`, from `<key>` in the config file, and from
+/// `--set <key>=<value>`. In the config file a table joins its name to the
+/// keys inside it, so `[tls] cert = ...` is the same as `tls_cert = ...`.
+const SETTINGS: &[&str] = &[
+    "data",
+    "listen",
+    "broker_capacity",
+    "dedup_capacity",
+    "tls_cert",
+    "tls_key",
+    "tls_ca",
+    "metrics_listen",
+    "jwt_secret",
+    "jwt_public_keys",
+    "jwt_algorithm",
+    "jwt_jwks",
+    "jwt_issuer",
+    "jwt_audience",
+    "jwt_leeway_secs",
+    "acl_file",
+    "audit",
+    "repair",
+    "durability",
+    "sync_interval_ms",
+    "preallocate_mb",
+    "io_uring",
+    "read_only",
+    "quota_appends_per_sec",
+    "quota_burst",
+    "quota_max_in_flight",
+    "min_free_mb",
+];
+
+/// Settings that hold comma-separated lists. A config file may give them as
+/// an array of strings instead.
+const LIST_SETTINGS: &[&str] = &["jwt_public_keys", "jwt_issuer", "jwt_audience"];
+
+/// Placeholder printed by `--print-config` in place of secret values.
+const REDACTED: &str = "<redacted>";
+
+/// Raw setting values keyed by environment variable name.
+///
+/// Values are layered: the config file first, then the environment, then
+/// command-line flags, each overriding the one before. They stay strings until
+/// [`Config::from_settings`] validates them, so every source goes through the
+/// same checks and error messages.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Settings {
+    values: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Environment variable name of a setting key.
+    fn var_name(key: &str) -> String {
+        format!("EVENTFOLD_{}", key.to_ascii_uppercase())
+    }
+
+    /// Load a TOML config file. Problems are added to `errors` and the rest of
+    /// the file is still loaded, so they can be reported together.
+    fn load_file(&mut self, path: &Path, errors: &mut Vec<String>) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                errors.push(format!(
+                    "failed to read config file {}: {e}",
+                    path.display()
+                ));
+                return;
+            }
+        };
+        match text.parse::<toml::Table>() {
+            Ok(table) => self.load_table("", &table, errors),
+            Err(e) => errors.push(format!(
+                "config file {} is not valid TOML: {e}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Load the values of `table`, whose keys are prefixed with `prefix`.
+    fn load_table(&mut self, prefix: &str, table: &toml::Table, errors: &mut Vec<String>) {
+        for (key, value) in table {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}_{key}")
+            };
+            let text = match value {
+                toml::Value::Table(table) => {
+                    self.load_table(&key, table, errors);
+                    continue;
+                }
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                toml::Value::Array(items) if LIST_SETTINGS.contains(&key.as_str()) => {
+                    let items: Option<Vec<&str>> = items.iter().map(toml::Value::as_str).collect();
+                    match items {
+                        Some(items) => items.join(","),
+                        None => {
+                            errors.push(format!("config key `{key}` must be an array of strings"));
+                            continue;
+                        }
+                    }
+                }
+                other => {
+                    errors.push(format!(
+                        "config key `{key}` must be a string, integer, or boolean, got: {}",
+                        other.type_str()
+                    ));
+                    continue;
+                }
+            };
+            if let Err(e) = self.set(&key, text) {
+                errors.push(e);
+            }
+        }
+    }
+
+    /// Overlay the values of `EVENTFOLD_*` environment variables.
+    fn load_env(&mut self) {
+        for key in SETTINGS {
+            let name = Settings::var_name(key);
+            if let Ok(val) = std::env::var(&name) {
+                self.values.insert(name, val);
+            }
+        }
+    }
+
+    /// Set `key`, in config file form (`tls_cert` or `tls.cert`), to `value`.
+    fn set(&mut self, key: &str, value: impl Into<String>) -> Result<(), String> {
+        let key = key.replace('.', "_");
+        if !SETTINGS.contains(&key.as_str()) {
+            return Err(format!("unknown config key `{key}`"));
+        }
+        self.values.insert(Settings::var_name(&key), value.into());
+        Ok(())
+    }
+
+    /// The value of the variable `name`, if any source set it.
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Parse the variable `name` as a `T`, described as `what` in the error.
+    fn parse<T>(&self, name: &str, what: &str) -> Result<Option<T>, String>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.get(name)
+            .map(|val| {
+                val.parse::<T>()
+                    .map_err(|e| format!("{name} is not a valid {what}: {e}"))
+            })
+            .transpose()
+    }
+
+    /// Parse a boolean flag. Unset and empty both mean `false`.
+    fn flag(&self, name: &str) -> Result<bool, String> {
+        match self.get(name) {
+            Some("true" | "1") => Ok(true),
+            Some("false" | "0" | "") | None => Ok(false),
+            Some(other) => Err(format!("{name} must be true, false, 1, or 0, got: {other}")),
+        }
+    }
+
+    /// Parse a comma-separated list. Unset and empty both mean an empty list.
+    fn list(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Parse an optional limit. Unset, empty, and `0` all mean no limit.
+    fn limit(&self, name: &str) -> Result<Option<NonZeroU32>, String> {
+        match self.get(name) {
+            Some("") | None => Ok(None),
+            Some(val) => val
+                .parse::<u32>()
+                .map(NonZeroU32::new)
+                .map_err(|e| format!("{name} is not a valid u32: {e}")),
+        }
+    }
+
+    /// The value of `name`, with the empty string treated as unset.
+    fn non_empty(&self, name: &str) -> Option<&str> {
+        self.get(name).filter(|val| !val.is_empty())
+    }
+}
+
+/// Record `result`'s error in `errors` and fall back to `fallback`, so that
+/// validation can continue and report every problem at once.
+fn check<T>(errors: &mut Vec<String>, result: Result<T, String>, fallback: T) -> T {
+    result.unwrap_or_else(|e| {
+        errors.push(e);
+        fallback
+    })
+}
+
+/// Parse the TLS file paths. Both CERT and KEY must be set together; CA is
+/// optional (enables mTLS).
+fn tls_from_settings(settings: &Settings) -> Result<Option<TlsConfig>, String> {
+    let tls_cert = settings.get("EVENTFOLD_TLS_CERT");
+    let tls_key = settings.get("EVENTFOLD_TLS_KEY");
+    let tls_ca = settings.get("EVENTFOLD_TLS_CA");
+
+    match (tls_cert, tls_key, tls_ca) {
+        // All three absent: plaintext mode.
+        (None, None, None) => Ok(None),
+        // Both cert and key present; CA enables mTLS when present.
+        (Some(cert), Some(key), ca) => Ok(Some(TlsConfig {
+            cert_path: PathBuf::from(cert),
+            key_path: PathBuf::from(key),
+            ca_path: ca.map(PathBuf::from),
+        })),
+        // Cert set, key missing.
+        (Some(_), None, _) => {
+            Err("EVENTFOLD_TLS_CERT is set but EVENTFOLD_TLS_KEY is missing".to_string())
+        }
+        // Key set, cert missing.
+        (None, Some(_), _) => {
+            Err("EVENTFOLD_TLS_KEY is set but EVENTFOLD_TLS_CERT is missing".to_string())
+        }
+        // CA set without cert and key.
+        (None, None, Some(_)) => Err("EVENTFOLD_TLS_CA is set but EVENTFOLD_TLS_CERT and \
+             EVENTFOLD_TLS_KEY are both missing"
+            .to_string()),
+    }
+}
+
+/// Parse the JWT settings. Exactly one key source may be set; the claim
+/// checks are only accepted alongside one. Empty strings are treated as unset.
+fn jwt_from_settings(settings: &Settings) -> Result<Option<JwtConfig>, String> {
+    let secret = settings.non_empty("EVENTFOLD_JWT_SECRET");
+    let public_keys = settings.list("EVENTFOLD_JWT_PUBLIC_KEYS");
+    let jwks = settings.non_empty("EVENTFOLD_JWT_JWKS");
+    let algorithm = settings.non_empty("EVENTFOLD_JWT_ALGORITHM");
+
+    if algorithm.is_some() && public_keys.is_empty() {
+        return Err("EVENTFOLD_JWT_ALGORITHM requires EVENTFOLD_JWT_PUBLIC_KEYS".to_string());
+    }
+    let keys = match (secret, public_keys.is_empty(), jwks) {
+        (None, true, None) => None,
+        (Some(secret), true, None) => Some(JwtKeySource::Secret(secret.to_string())),
+        (None, false, None) => {
+            let algorithm = match algorithm {
+                None | Some("RS256") => jsonwebtoken::Algorithm::RS256,
+                Some("ES256") => jsonwebtoken::Algorithm::ES256,
+                Some("EdDSA") => jsonwebtoken::Algorithm::EdDSA,
+                Some(other) => {
+                    return Err(format!(
+                        "EVENTFOLD_JWT_ALGORITHM must be RS256, ES256, or EdDSA, got: {other}"
+                    ));
+                }
+            };
+            Some(JwtKeySource::PublicKeys {
+                paths: public_keys.into_iter().map(PathBuf::from).collect(),
+                algorithm,
+            })
+        }
+        (None, true, Some(jwks)) => Some(JwtKeySource::Jwks(PathBuf::from(jwks))),
+        _ => {
+            return Err(
+                "only one of EVENTFOLD_JWT_SECRET, EVENTFOLD_JWT_PUBLIC_KEYS, and \
+                 EVENTFOLD_JWT_JWKS may be set"
+                    .to_string(),
+            );
+        }
+    };
+
+    let issuers = settings.list("EVENTFOLD_JWT_ISSUER");
+    let audiences = settings.list("EVENTFOLD_JWT_AUDIENCE");
+    let leeway = match settings.non_empty("EVENTFOLD_JWT_LEEWAY_SECS") {
+        Some(val) => Some(
+            val.parse::<u64>()
+                .map_err(|e| format!("EVENTFOLD_JWT_LEEWAY_SECS is not a valid u64: {e}"))?,
+        ),
+        None => None,
+    };
+    let Some(keys) = keys else {
+        if !issuers.is_empty() || !audiences.is_empty() || leeway.is_some() {
+            return Err("EVENTFOLD_JWT_ISSUER, EVENTFOLD_JWT_AUDIENCE, and \
+                 EVENTFOLD_JWT_LEEWAY_SECS require a JWT key source"
+                .to_string());
+        }
+        return Ok(None);
+    };
+    Ok(Some(JwtConfig {
+        issuers,
+        audiences,
+        leeway: std::time::Duration::from_secs(leeway.unwrap_or(0)),
+        ..JwtConfig::new(keys)
+    }))
+}
+
+/// Parse the durability mode. The interval is only read in `interval` mode so
+/// a stray value cannot break the other modes.
+fn durability_from_settings(settings: &Settings) -> Result<Durability, String> {
+    match settings.get("EVENTFOLD_DURABILITY") {
+        Some("fsync-per-batch" | "") | None => Ok(Durability::FsyncPerBatch),
+        Some("fdatasync") => Ok(Durability::Fdatasync),
+        Some("interval") => {
+            let ms = match settings.get("EVENTFOLD_SYNC_INTERVAL_MS") {
+                Some(val) => val
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|ms| *ms > 0)
+                    .ok_or_else(|| {
+                        format!("EVENTFOLD_SYNC_INTERVAL_MS must be a positive integer, got: {val}")
+                    })?,
+                None => DEFAULT_SYNC_INTERVAL_MS,
+            };
+            Ok(Durability::Interval(std::time::Duration::from_millis(ms)))
+        }
+        Some("none") => Ok(Durability::None),
+        Some(other) => Err(format!(
+            "EVENTFOLD_DURABILITY must be fsync-per-batch, fdatasync, interval, or none, \
+             got: {other}"
+        )),
+    }
+}
+
+/// Parse a size in MiB and convert it to bytes.
+fn mebibytes(settings: &Settings, name: &str, default: u64) -> Result<u64, String> {
+    let mb = match settings.get(name) {
+        Some("") => 0,
+        _ => settings.parse::<u64>(name, "u64")?.unwrap_or(default),
+    };
+    mb.checked_mul(1024 * 1024)
+        .ok_or_else(|| format!("{name} is too large"))
+}
+
 impl Config {
-    /// Parse server configuration from environment variables.
+    /// Validate merged settings into a server configuration.
+    ///
+    /// Every variable is checked even after a problem is found, so all errors
+    /// are returned together.
     ///
-    /// # Environment Variables
+    /// # Settings
     ///
-    /// * `EVENTFOLD_DATA` (required) - Path to the append-only log file.
+    /// * `EVENTFOLD_DATA` (required) - Data directory. A path to a log file from the
+    ///   single-file layout is migrated into a directory at the same path.
     /// * `EVENTFOLD_LISTEN` (optional) - Socket address to listen on. Defaults to `[::]:2113`.
     /// * `EVENTFOLD_BROKER_CAPACITY` (optional) - Broadcast channel buffer size. Defaults to
     ///   `4096`.
@@ -82,10 +482,45 @@ impl Config {
     ///   `65536`.
     /// * `EVENTFOLD_METRICS_LISTEN` (optional) - Metrics HTTP address. Defaults to `[::]:9090`.
     ///   Set to `""` to disable.
+    /// * `EVENTFOLD_JWT_SECRET` (optional) - HS256 shared secret. Enables JWT auth.
+    /// * `EVENTFOLD_JWT_PUBLIC_KEYS` (optional) - Comma-separated PEM public key paths. Enables
+    ///   JWT auth; a token is accepted if any key verifies it.
+    /// * `EVENTFOLD_JWT_ALGORITHM` (optional) - Algorithm for the PEM keys: `RS256`, `ES256`,
+    ///   or `EdDSA`. Defaults to `RS256`.
+    /// * `EVENTFOLD_JWT_JWKS` (optional) - JWKS file path. Enables JWT auth; tokens are matched
+    ///   to keys by `kid`.
+    /// * `EVENTFOLD_JWT_ISSUER` / `EVENTFOLD_JWT_AUDIENCE` (optional) - Comma-separated
+    ///   accepted `iss` / `aud` values. Unset skips the check.
+    /// * `EVENTFOLD_JWT_LEEWAY_SECS` (optional) - Clock skew allowed when checking `exp`.
+    ///   Defaults to `0`.
+    /// * `EVENTFOLD_ACL_FILE` (optional) - JSON ACL policy granting RPCs to token roles.
+    ///   Requires JWT auth.
+    /// * `EVENTFOLD_AUDIT` (optional) - `true`/`1` records the authenticated principal and
+    ///   client address on every appended event. Defaults to disabled.
+    /// * `EVENTFOLD_REPAIR` (optional) - `true`/`1` enables repair mode, `false`/`0`/`""`
+    ///   disables it. Defaults to disabled.
+    /// * `EVENTFOLD_DURABILITY` (optional) - `fsync-per-batch`, `fdatasync`, `interval`, or
+    ///   `none`. Defaults to `fsync-per-batch`.
+    /// * `EVENTFOLD_SYNC_INTERVAL_MS` (optional) - Fsync period in `interval` mode. Defaults to
+    ///   `100`. Ignored by the other modes.
+    /// * `EVENTFOLD_PREALLOCATE_MB` (optional) - Extent size, in MiB, the log file is grown in.
+    ///   Defaults to `64`; `0` disables preallocation.
+    /// * `EVENTFOLD_IO_URING` (optional) - `true`/`1` writes the log through io_uring. Defaults
+    ///   to disabled.
+    /// * `EVENTFOLD_READ_ONLY` (optional) - `true`/`1` serves an existing log without ever
+    ///   writing to it. Defaults to disabled.
+    /// * `EVENTFOLD_QUOTA_APPENDS_PER_SEC` (optional) - Sustained appends per second allowed
+    ///   per client. Unset or `0` disables rate limiting.
+    /// * `EVENTFOLD_QUOTA_BURST` (optional) - Appends per client allowed back to back above
+    ///   the sustained rate. Defaults to the rate.
+    /// * `EVENTFOLD_QUOTA_MAX_IN_FLIGHT` (optional) - Appends per client waiting on the writer
+    ///   at once. Unset or `0` disables the cap.
+    /// * `EVENTFOLD_MIN_FREE_MB` (optional) - Free space, in MiB, on the data volume below
+    ///   which appends are rejected. Defaults to `0`, which disables the guard.
     ///
     /// # Errors
     ///
-    /// Returns `Err(String)` if:
+    /// Returns one message per problem if:
     /// - `EVENTFOLD_DATA` is not set
     /// - `EVENTFOLD_LISTEN` is set but not a valid `SocketAddr`
     /// - `EVENTFOLD_BROKER_CAPACITY` is set but not a valid `usize`
@@ -93,99 +528,132 @@ impl Config {
     /// - `EVENTFOLD_METRICS_LISTEN` is set to a non-empty invalid `SocketAddr` string
     /// - `EVENTFOLD_TLS_CERT` is set without `EVENTFOLD_TLS_KEY` (or vice versa)
     /// - `EVENTFOLD_TLS_CA` is set without both `EVENTFOLD_TLS_CERT` and `EVENTFOLD_TLS_KEY`
-    fn from_env() -> Result<Config, String> {
-        let data_path = std::env::var("EVENTFOLD_DATA")
-            .map(PathBuf::from)
-            .map_err(|_| "EVENTFOLD_DATA environment variable is required".to_string())?;
-
-        let listen_addr = match std::env::var("EVENTFOLD_LISTEN") {
-            Ok(val) => val
-                .parse::<SocketAddr>()
-                .map_err(|e| format!("EVENTFOLD_LISTEN is not a valid socket address: {e}"))?,
-            Err(_) => DEFAULT_LISTEN_ADDR
-                .parse::<SocketAddr>()
-                .expect("default listen address is valid"),
-        };
-
-        let broker_capacity = match std::env::var("EVENTFOLD_BROKER_CAPACITY") {
-            Ok(val) => val
-                .parse::<usize>()
-                .map_err(|e| format!("EVENTFOLD_BROKER_CAPACITY is not a valid usize: {e}"))?,
-            Err(_) => DEFAULT_BROKER_CAPACITY,
-        };
-
-        let dedup_capacity = match std::env::var("EVENTFOLD_DEDUP_CAPACITY") {
-            Ok(val) => {
-                let raw: usize = val
-                    .parse()
-                    .map_err(|e| format!("EVENTFOLD_DEDUP_CAPACITY is not a valid usize: {e}"))?;
-                NonZeroUsize::new(raw)
-                    .ok_or_else(|| "EVENTFOLD_DEDUP_CAPACITY must be nonzero".to_string())?
+    /// - more than one of `EVENTFOLD_JWT_SECRET`, `EVENTFOLD_JWT_PUBLIC_KEYS`, and
+    ///   `EVENTFOLD_JWT_JWKS` is set
+    /// - `EVENTFOLD_JWT_ALGORITHM` is set without `EVENTFOLD_JWT_PUBLIC_KEYS`, or to an
+    ///   unsupported algorithm
+    /// - `EVENTFOLD_JWT_ISSUER`, `EVENTFOLD_JWT_AUDIENCE`, or `EVENTFOLD_JWT_LEEWAY_SECS` is
+    ///   set without a key source, or the leeway is not a valid `u64`
+    /// - `EVENTFOLD_ACL_FILE` is set without JWT auth
+    /// - `EVENTFOLD_AUDIT` is set to anything other than `true`, `false`, `1`, `0`, or `""`
+    /// - `EVENTFOLD_REPAIR` is set to anything other than `true`, `false`, `1`, `0`, or `""`
+    /// - `EVENTFOLD_DURABILITY` is set to an unknown mode
+    /// - `EVENTFOLD_SYNC_INTERVAL_MS` is set but not a positive integer
+    /// - `EVENTFOLD_PREALLOCATE_MB` is set but not a valid `u64`
+    /// - `EVENTFOLD_IO_URING` is set to anything other than `true`, `false`, `1`, `0`, or `""`
+    /// - `EVENTFOLD_READ_ONLY` is set to anything other than `true`, `false`, `1`, `0`, or `""`
+    /// - `EVENTFOLD_QUOTA_APPENDS_PER_SEC`, `EVENTFOLD_QUOTA_BURST`, or
+    ///   `EVENTFOLD_QUOTA_MAX_IN_FLIGHT` is set but not a valid `u32`
+    /// - `EVENTFOLD_MIN_FREE_MB` is set but not a valid `u64`
+    fn from_settings(settings: &Settings) -> Result<Config, Vec<String>> {
+        let mut errors = Vec::new();
+
+        let data_path = match settings.get("EVENTFOLD_DATA") {
+            Some(val) => PathBuf::from(val),
+            None => {
+                errors.push(
+                    "EVENTFOLD_DATA is required: set the variable, `data` in the config file, \
+                     or --data"
+                        .to_string(),
+                );
+                PathBuf::new()
             }
-            Err(_) => NonZeroUsize::new(DEFAULT_DEDUP_CAPACITY)
-                .expect("default dedup capacity is nonzero"),
         };
 
-        // Parse optional metrics listen address. Empty string disables metrics.
-        let metrics_listen = match std::env::var("EVENTFOLD_METRICS_LISTEN") {
-            Ok(val) if val.is_empty() => None,
-            Ok(val) => Some(val.parse::<SocketAddr>().map_err(|e| {
-                format!("EVENTFOLD_METRICS_LISTEN is not a valid socket address: {e}")
-            })?),
-            Err(_) => Some(
-                DEFAULT_METRICS_LISTEN_ADDR
-                    .parse::<SocketAddr>()
-                    .expect("default metrics listen address is valid"),
-            ),
-        };
+        let default_listen = DEFAULT_LISTEN_ADDR
+            .parse::<SocketAddr>()
+            .expect("default listen address is valid");
+        let listen_addr = check(
+            &mut errors,
+            settings.parse("EVENTFOLD_LISTEN", "socket address"),
+            None,
+        )
+        .unwrap_or(default_listen);
+
+        let broker_capacity = check(
+            &mut errors,
+            settings.parse("EVENTFOLD_BROKER_CAPACITY", "usize"),
+            None,
+        )
+        .unwrap_or(DEFAULT_BROKER_CAPACITY);
+
+        let default_dedup =
+            NonZeroUsize::new(DEFAULT_DEDUP_CAPACITY).expect("default dedup capacity is nonzero");
+        let dedup_capacity = check(
+            &mut errors,
+            settings
+                .parse::<usize>("EVENTFOLD_DEDUP_CAPACITY", "usize")
+                .and_then(|raw| match raw {
+                    Some(raw) => NonZeroUsize::new(raw)
+                        .ok_or_else(|| "EVENTFOLD_DEDUP_CAPACITY must be nonzero".to_string()),
+                    None => Ok(default_dedup),
+                }),
+            default_dedup,
+        );
 
-        // Parse optional TLS configuration from environment variables.
-        // Both CERT and KEY must be set together; CA is optional (enables mTLS).
-        let tls_cert = std::env::var("EVENTFOLD_TLS_CERT").ok();
-        let tls_key = std::env::var("EVENTFOLD_TLS_KEY").ok();
-        let tls_ca = std::env::var("EVENTFOLD_TLS_CA").ok();
-
-        let tls = match (tls_cert, tls_key, tls_ca) {
-            // All three absent: plaintext mode.
-            (None, None, None) => None,
-            // Both cert and key present, CA absent: TLS without mTLS.
-            (Some(cert), Some(key), None) => Some(TlsConfig {
-                cert_path: PathBuf::from(cert),
-                key_path: PathBuf::from(key),
-                ca_path: None,
-            }),
-            // All three present: TLS with mTLS.
-            (Some(cert), Some(key), Some(ca)) => Some(TlsConfig {
-                cert_path: PathBuf::from(cert),
-                key_path: PathBuf::from(key),
-                ca_path: Some(PathBuf::from(ca)),
+        // Parse optional metrics listen address. Empty string disables metrics.
+        let metrics_listen = match settings.get("EVENTFOLD_METRICS_LISTEN") {
+            Some("") => None,
+            _ => check(
+                &mut errors,
+                settings.parse("EVENTFOLD_METRICS_LISTEN", "socket address"),
+                None,
+            )
+            .or_else(|| {
+                Some(
+                    DEFAULT_METRICS_LISTEN_ADDR
+                        .parse::<SocketAddr>()
+                        .expect("default metrics listen address is valid"),
+                )
             }),
-            // Cert set, key missing.
-            (Some(_), None, _) => {
-                return Err(
-                    "EVENTFOLD_TLS_CERT is set but EVENTFOLD_TLS_KEY is missing".to_string()
-                );
-            }
-            // Key set, cert missing.
-            (None, Some(_), _) => {
-                return Err(
-                    "EVENTFOLD_TLS_KEY is set but EVENTFOLD_TLS_CERT is missing".to_string()
-                );
-            }
-            // CA set without cert and key.
-            (None, None, Some(_)) => {
-                return Err("EVENTFOLD_TLS_CA is set but EVENTFOLD_TLS_CERT and \
-                     EVENTFOLD_TLS_KEY are both missing"
-                    .to_string());
-            }
         };
 
-        // Parse optional JWT secret. Empty string is treated as unset.
-        let jwt_secret = match std::env::var("EVENTFOLD_JWT_SECRET") {
-            Ok(val) if !val.is_empty() => Some(val),
-            _ => None,
+        let tls = check(&mut errors, tls_from_settings(settings), None);
+
+        let jwt = check(&mut errors, jwt_from_settings(settings), None);
+        let acl_file = settings.non_empty("EVENTFOLD_ACL_FILE").map(PathBuf::from);
+        if acl_file.is_some() && jwt.is_none() {
+            errors.push("EVENTFOLD_ACL_FILE requires JWT auth to be configured".to_string());
+        }
+        let audit = check(&mut errors, settings.flag("EVENTFOLD_AUDIT"), false);
+        let repair = check(&mut errors, settings.flag("EVENTFOLD_REPAIR"), false);
+
+        let durability = check(
+            &mut errors,
+            durability_from_settings(settings),
+            Durability::FsyncPerBatch,
+        );
+        let preallocate = check(
+            &mut errors,
+            mebibytes(settings, "EVENTFOLD_PREALLOCATE_MB", DEFAULT_PREALLOCATE_MB),
+            0,
+        );
+        let io_uring = check(&mut errors, settings.flag("EVENTFOLD_IO_URING"), false);
+        let read_only = check(&mut errors, settings.flag("EVENTFOLD_READ_ONLY"), false);
+
+        let quotas = QuotaConfig {
+            appends_per_sec: check(
+                &mut errors,
+                settings.limit("EVENTFOLD_QUOTA_APPENDS_PER_SEC"),
+                None,
+            ),
+            burst: check(&mut errors, settings.limit("EVENTFOLD_QUOTA_BURST"), None),
+            max_in_flight: check(
+                &mut errors,
+                settings.limit("EVENTFOLD_QUOTA_MAX_IN_FLIGHT"),
+                None,
+            ),
         };
 
+        let min_free = check(
+            &mut errors,
+            mebibytes(settings, "EVENTFOLD_MIN_FREE_MB", 0),
+            0,
+        );
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
         Ok(Config {
             data_path,
             listen_addr,
@@ -193,9 +661,413 @@ impl Config {
             dedup_capacity,
             tls,
             metrics_listen,
-            jwt_secret,
+            jwt,
+            acl_file,
+            audit,
+            repair,
+            durability,
+            preallocate,
+            io_uring,
+            read_only,
+            quotas,
+            min_free,
         })
     }
+
+    /// Render the configuration as a TOML config file, including defaults,
+    /// with secrets replaced by `<redacted>`. Loading the output as a config
+    /// file yields the same configuration, apart from redacted values.
+    fn to_toml(&self) -> String {
+        fn path(path: &Path) -> toml::Value {
+            toml::Value::String(path.display().to_string())
+        }
+        fn int(value: u64) -> toml::Value {
+            toml::Value::Integer(i64::try_from(value).unwrap_or(i64::MAX))
+        }
+        fn strings(items: impl IntoIterator<Item = String>) -> toml::Value {
+            toml::Value::Array(items.into_iter().map(toml::Value::String).collect())
+        }
+
+        let mib = 1024 * 1024;
+        let mut entries: Vec<(&str, toml::Value)> = vec![
+            ("data", path(&self.data_path)),
+            ("listen", self.listen_addr.to_string().into()),
+            ("broker_capacity", int(self.broker_capacity as u64)),
+            ("dedup_capacity", int(self.dedup_capacity.get() as u64)),
+            (
+                "metrics_listen",
+                self.metrics_listen
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_default()
+                    .into(),
+            ),
+        ];
+        if let Some(tls) = &self.tls {
+            entries.push(("tls_cert", path(&tls.cert_path)));
+            entries.push(("tls_key", path(&tls.key_path)));
+            if let Some(ca) = &tls.ca_path {
+                entries.push(("tls_ca", path(ca)));
+            }
+        }
+        if let Some(jwt) = &self.jwt {
+            match &jwt.keys {
+                JwtKeySource::Secret(_) => entries.push(("jwt_secret", REDACTED.into())),
+                JwtKeySource::PublicKeys { paths, algorithm } => {
+                    entries.push((
+                        "jwt_public_keys",
+                        strings(paths.iter().map(|p| p.display().to_string())),
+                    ));
+                    entries.push(("jwt_algorithm", format!("{algorithm:?}").into()));
+                }
+                JwtKeySource::Jwks(jwks) => entries.push(("jwt_jwks", path(jwks))),
+            }
+            if !jwt.issuers.is_empty() {
+                entries.push(("jwt_issuer", strings(jwt.issuers.iter().cloned())));
+            }
+            if !jwt.audiences.is_empty() {
+                entries.push(("jwt_audience", strings(jwt.audiences.iter().cloned())));
+            }
+            entries.push(("jwt_leeway_secs", int(jwt.leeway.as_secs())));
+        }
+        if let Some(acl) = &self.acl_file {
+            entries.push(("acl_file", path(acl)));
+        }
+        entries.push(("audit", self.audit.into()));
+        entries.push(("repair", self.repair.into()));
+        entries.push(("durability", self.durability.name().into()));
+        if let Durability::Interval(interval) = self.durability {
+            entries.push(("sync_interval_ms", int(interval.as_millis() as u64)));
+        }
+        entries.push(("preallocate_mb", int(self.preallocate / mib)));
+        entries.push(("io_uring", self.io_uring.into()));
+        entries.push(("read_only", self.read_only.into()));
+        let limits = [
+            ("quota_appends_per_sec", self.quotas.appends_per_sec),
+            ("quota_burst", self.quotas.burst),
+            ("quota_max_in_flight", self.quotas.max_in_flight),
+        ];
+        for (key, limit) in limits {
+            if let Some(limit) = limit {
+                entries.push((key, int(u64::from(limit.get()))));
+            }
+        }
+        entries.push(("min_free_mb", int(self.min_free / mib)));
+
+        entries
+            .into_iter()
+            .map(|(key, value)| format!("{key} = {value}\n"))
+            .collect()
+    }
+}
+
+/// EventfoldDB server and offline log tools.
+///
+/// Without a subcommand the server is started, as with `serve`.
+#[derive(Debug, clap::Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+/// Subcommands of the `eventfold-db` binary.
+///
+/// The offline ones work directly on a data directory or log file through the
+/// library. `import` and `compact` write to it and fail while a server holds
+/// the log; the others only read and are safe to run next to a server.
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Start the gRPC server.
+    Serve(ServeArgs),
+    /// Check every batch of a log and report torn tails, mid-file damage,
+    /// and holes.
+    ///
+    /// Exits with status 1 if it finds any of them.
+    Verify(LogArgs),
+    /// Decode the raw batches and records of a log, with byte offsets.
+    Dump(DumpArgs),
+    /// Print event, stream, and size statistics for a log.
+    Stats(LogArgs),
+    /// Write the events of a log as NDJSON or length-delimited protobuf.
+    Export(ExportArgs),
+    /// Load exported events into a data directory, creating it if needed.
+    Import(ImportArgs),
+    /// Copy a log to a new file, verified by opening the copy.
+    Backup(BackupArgs),
+    /// Shrink a log file to its last complete batch, releasing preallocated
+    /// space.
+    ///
+    /// Needs exclusive access, so it fails while a server has the log open.
+    /// Refuses a log with anything but a log end marker or zeroed space past
+    /// its last batch. The data directory or log file must already exist; a
+    /// single-file log is compacted in place rather than migrated.
+    Compact(LogArgs),
+}
+
+/// Settings are read from the config file given with `--config`, then from
+/// `EVENTFOLD_*` environment variables, then from flags, each overriding the
+/// one before.
+#[derive(Debug, Default, clap::Args)]
+struct ServeArgs {
+    /// TOML config file. Keys are the variable names without the `EVENTFOLD_`
+    /// prefix, in lowercase.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Print the effective configuration, with secrets redacted, and exit.
+    #[arg(long)]
+    print_config: bool,
+
+    /// Data directory (`data`).
+    #[arg(long, value_name = "PATH")]
+    data: Option<String>,
+
+    /// Socket address to listen on (`listen`).
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<String>,
+
+    /// Serve an existing log without writing to it (`read_only`).
+    #[arg(long)]
+    read_only: bool,
+
+    /// Set any config key, e.g. `--set durability=fdatasync`. May be repeated.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+}
+
+/// The log an offline subcommand works on.
+#[derive(Debug, clap::Args)]
+struct LogArgs {
+    /// Data directory or log file.
+    #[arg(value_name = "DATA", env = "EVENTFOLD_DATA")]
+    data: PathBuf,
+}
+
+/// Arguments of `export`.
+#[derive(Debug, clap::Args)]
+struct ExportArgs {
+    #[command(flatten)]
+    log: LogArgs,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = FormatArg::Ndjson)]
+    format: FormatArg,
+
+    /// File to write to instead of standard output.
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// First global position to export.
+    #[arg(long, value_name = "POSITION", default_value_t = 0)]
+    from: u64,
+
+    /// Global position to stop before.
+    #[arg(long, value_name = "POSITION")]
+    to: Option<u64>,
+
+    /// Only export this stream. May be repeated.
+    #[arg(long = "stream", value_name = "UUID")]
+    streams: Vec<Uuid>,
+}
+
+/// Arguments of `dump`.
+#[derive(Debug, clap::Args)]
+struct DumpArgs {
+    #[command(flatten)]
+    log: LogArgs,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = DumpFormatArg::Text)]
+    format: DumpFormatArg,
+
+    /// How to print metadata and payloads. Bytes that do not fit fall back
+    /// from `json` to `utf8` to `hex`.
+    #[arg(long, value_enum, default_value_t = PayloadArg::Json)]
+    payload: PayloadArg,
+
+    /// Only print this stream. May be repeated.
+    #[arg(long = "stream", value_name = "UUID")]
+    streams: Vec<Uuid>,
+
+    /// Only print this event type. May be repeated.
+    #[arg(long = "type", value_name = "TYPE")]
+    event_types: Vec<String>,
+
+    /// First global position to print.
+    #[arg(long, value_name = "POSITION", default_value_t = 0)]
+    from: u64,
+
+    /// Global position to stop before.
+    #[arg(long, value_name = "POSITION")]
+    to: Option<u64>,
+
+    /// Earliest `recorded_at` to print, in Unix milliseconds.
+    #[arg(long, value_name = "MILLIS")]
+    since: Option<u64>,
+
+    /// `recorded_at` to stop before, in Unix milliseconds.
+    #[arg(long, value_name = "MILLIS")]
+    until: Option<u64>,
+}
+
+/// Arguments of `import`.
+#[derive(Debug, clap::Args)]
+struct ImportArgs {
+    #[command(flatten)]
+    log: LogArgs,
+
+    /// Input format.
+    #[arg(long, value_enum, default_value_t = FormatArg::Ndjson)]
+    format: FormatArg,
+
+    /// File to read from instead of standard input.
+    #[arg(long, short, value_name = "PATH")]
+    input: Option<PathBuf>,
+
+    /// How imported events get their positions.
+    #[arg(long, value_enum, default_value_t = ModeArg::Preserve)]
+    mode: ModeArg,
+}
+
+/// Arguments of `backup`.
+#[derive(Debug, clap::Args)]
+struct BackupArgs {
+    #[command(flatten)]
+    log: LogArgs,
+
+    /// Path of the backup log file. Must not exist.
+    #[arg(value_name = "DEST")]
+    dest: PathBuf,
+
+    /// Only copy events below this global position, rounded down to a batch
+    /// boundary.
+    #[arg(long, value_name = "POSITION")]
+    to_position: Option<u64>,
+}
+
+/// Command-line names of [`ExportFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum FormatArg {
+    /// Newline-delimited JSON.
+    Ndjson,
+    /// Length-delimited protobuf `RecordedEvent` messages.
+    Protobuf,
+}
+
+impl From<FormatArg> for ExportFormat {
+    fn from(format: FormatArg) -> ExportFormat {
+        match format {
+            FormatArg::Ndjson => ExportFormat::Ndjson,
+            FormatArg::Protobuf => ExportFormat::Protobuf,
+        }
+    }
+}
+
+/// Command-line names of [`DumpFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum DumpFormatArg {
+    /// Human-readable lines.
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl From<DumpFormatArg> for DumpFormat {
+    fn from(format: DumpFormatArg) -> DumpFormat {
+        match format {
+            DumpFormatArg::Text => DumpFormat::Text,
+            DumpFormatArg::Json => DumpFormat::JsonLines,
+        }
+    }
+}
+
+/// Command-line names of [`PayloadFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum PayloadArg {
+    /// Parsed JSON.
+    Json,
+    /// UTF-8 text.
+    Utf8,
+    /// Lowercase hex.
+    Hex,
+}
+
+impl From<PayloadArg> for PayloadFormat {
+    fn from(payload: PayloadArg) -> PayloadFormat {
+        match payload {
+            PayloadArg::Json => PayloadFormat::Json,
+            PayloadArg::Utf8 => PayloadFormat::Utf8,
+            PayloadArg::Hex => PayloadFormat::Hex,
+        }
+    }
+}
+
+/// Command-line names of [`ImportMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ModeArg {
+    /// Keep original positions; the target must be empty.
+    Preserve,
+    /// Append every event at the end of the target, skipping known IDs.
+    Reappend,
+}
+
+impl From<ModeArg> for ImportMode {
+    fn from(mode: ModeArg) -> ImportMode {
+        match mode {
+            ModeArg::Preserve => ImportMode::PreservePositions,
+            ModeArg::Reappend => ImportMode::Reappend,
+        }
+    }
+}
+
+impl ServeArgs {
+    /// Merge the config file, the environment, and the flags, and validate the
+    /// result. Problems from every stage are returned together.
+    fn config(&self) -> Result<Config, Vec<String>> {
+        let mut errors = Vec::new();
+        let mut settings = Settings::default();
+        if let Some(path) = &self.config {
+            settings.load_file(path, &mut errors);
+        }
+        settings.load_env();
+
+        if let Some(data) = &self.data {
+            settings
+                .values
+                .insert("EVENTFOLD_DATA".to_string(), data.clone());
+        }
+        if let Some(listen) = &self.listen {
+            settings
+                .values
+                .insert("EVENTFOLD_LISTEN".to_string(), listen.clone());
+        }
+        if self.read_only {
+            settings
+                .values
+                .insert("EVENTFOLD_READ_ONLY".to_string(), "true".to_string());
+        }
+        for pair in &self.set {
+            let result = match pair.split_once('=') {
+                Some((key, value)) => settings.set(key.trim(), value),
+                None => Err(format!("--set expects KEY=VALUE, got: {pair}")),
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+
+        match Config::from_settings(&settings) {
+            Ok(config) if errors.is_empty() => Ok(config),
+            Ok(_) => Err(errors),
+            Err(invalid) => {
+                errors.extend(invalid);
+                Err(errors)
+            }
+        }
+    }
 }
 
 /// Initialize the global `tracing` subscriber with an `EnvFilter`.
@@ -203,14 +1075,22 @@ impl Config {
 /// Reads the `RUST_LOG` environment variable to configure log level filtering. If `RUST_LOG`
 /// is not set, defaults to `"info"`. Uses `try_init()` so that repeated calls (e.g., across
 /// tests in the same process) do not panic -- the second call is a silent no-op.
-fn init_tracing() {
+///
+/// Logs go to standard output unless `to_stderr` is set, which the offline
+/// subcommands use so their output can be piped.
+fn init_tracing(to_stderr: bool) {
     use tracing_subscriber::EnvFilter;
 
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
 
     // `try_init` returns Err if a global subscriber is already set. We ignore that
     // error because test processes may call init_tracing() from multiple tests.
-    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+    let _ = if to_stderr {
+        subscriber.with_writer(std::io::stderr).try_init()
+    } else {
+        subscriber.try_init()
+    };
 }
 
 /// Waits for a shutdown signal: SIGINT on all platforms, plus SIGTERM on Unix.
@@ -235,132 +1115,609 @@ async fn shutdown_signal() {
     }
 }
 
-#[tokio::main]
-async fn main() {
-    // 1. Initialize tracing.
-    init_tracing();
+/// Health service name that reports whether appends are accepted, separately
+/// from the server as a whole (`""`) and `eventfold.EventStore`.
+const WRITES_HEALTH_SERVICE: &str = "eventfold.EventStore.Append";
 
-    // 2. Read configuration from environment variables.
-    let config = match Config::from_env() {
-        Ok(config) => config,
-        Err(msg) => {
-            eprintln!("{msg}");
-            std::process::exit(1);
+/// Keeps the health service in step with the writer and the disk guard.
+///
+/// While free disk space is below the low watermark, only
+/// [`WRITES_HEALTH_SERVICE`] is NOT_SERVING: appends are rejected, but reads
+/// keep working, so the server stays in load balancer rotation. Once the
+/// writer has failed, the server and every service are NOT_SERVING for good.
+/// Returns after a writer failure, or when the writer exits while healthy.
+async fn track_health(
+    mut writer: tokio::sync::watch::Receiver<WriterStatus>,
+    mut disk_low: Option<tokio::sync::watch::Receiver<bool>>,
+    reporter: tonic_health::server::HealthReporter,
+) {
+    let mut writable = true;
+    loop {
+        let writer_failed = *writer.borrow_and_update() != WriterStatus::Healthy;
+        let low = disk_low.as_mut().is_some_and(|rx| *rx.borrow_and_update());
+        if writable != (!writer_failed && !low) {
+            writable = !writable;
+            let status = if writable {
+                tonic_health::ServingStatus::Serving
+            } else {
+                tonic_health::ServingStatus::NotServing
+            };
+            reporter
+                .set_service_status(WRITES_HEALTH_SERVICE, status)
+                .await;
         }
-    };
-
-    // 3. Log configuration values.
-    tracing::info!(data_path = %config.data_path.display(), "Data path");
-    tracing::info!(listen_addr = %config.listen_addr, "Listen address");
-    tracing::info!(broker_capacity = config.broker_capacity, "Broker capacity");
-
-    // 4. Open the Store. Log recovered event and stream counts.
-    let store = match Store::open(&config.data_path) {
-        Ok(store) => store,
-        Err(e) => {
-            tracing::error!(error = %e, "Failed to open store");
-            std::process::exit(1);
+        if writer_failed {
+            reporter
+                .set_service_status("", tonic_health::ServingStatus::NotServing)
+                .await;
+            reporter
+                .set_not_serving::<EventStoreServer<EventfoldService>>()
+                .await;
+            return;
         }
-    };
 
-    // Read event and stream counts from the recovered log before spawning the writer.
-    {
-        let log_arc = store.log();
-        let log = log_arc
-            .read()
-            .expect("EventLog RwLock poisoned during startup");
-        tracing::info!(events = log.events.len(), "Recovered events");
-        tracing::info!(streams = log.streams.len(), "Recovered streams");
+        let disk_changed = async {
+            match disk_low.as_mut() {
+                Some(rx) => rx.changed().await.is_ok(),
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            changed = writer.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+            alive = disk_changed => {
+                if !alive {
+                    disk_low = None;
+                }
+            }
+        }
     }
+}
 
-    // 5. Create the Broker.
-    let broker = Broker::new(config.broker_capacity);
+/// Open the log of `data`, a data directory or a single log file, without
+/// writing to it. Recovery checks the CRC of every batch.
+fn open_log_read_only(data: &Path) -> Result<Store, Error> {
+    let data_dir = DataDir::open_read_only(data)?;
+    Store::open_read_only(&data_dir.log_path())
+}
 
-    // 6. Spawn the writer task.
-    tracing::info!(dedup_capacity = %config.dedup_capacity, "Dedup capacity");
-    let (writer_handle, read_index, join_handle) =
-        spawn_writer(store, 64, broker.clone(), config.dedup_capacity);
+/// Read the log file at `path` from byte offset `from` to its end.
+fn read_tail(path: &Path, from: u64) -> Result<Vec<u8>, Error> {
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(from))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok(tail)
+}
 
-    // 7. Install the Prometheus metrics recorder.
-    let metrics_handle = match eventfold_db::metrics::install_recorder() {
-        Ok(handle) => handle,
-        Err(e) => {
-            tracing::error!(error = %e, "Failed to install metrics recorder");
-            std::process::exit(1);
-        }
+/// Returns how many bytes of `tail`, the file past the last complete batch,
+/// are damage rather than a log end marker followed by zeroed preallocated
+/// space, or 0 if there is none.
+fn damaged_tail_len(tail: &[u8]) -> usize {
+    let free = match codec::decode_log_end(tail) {
+        Ok(DecodeOutcome::Complete { consumed, .. }) => &tail[consumed..],
+        _ => tail,
     };
+    if free.iter().all(|b| *b == 0) {
+        return 0;
+    }
+    tail.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1)
+}
 
-    // 8. Optionally start the metrics HTTP server.
-    let metrics_join_handle = if let Some(addr) = config.metrics_listen {
-        Some(eventfold_db::metrics::serve_metrics(metrics_handle, addr))
-    } else {
-        tracing::info!("Metrics endpoint disabled");
-        None
+/// `verify`: open the log, which checks every batch, and report what a
+/// writable open would change.
+///
+/// Recovery runs in repair mode so it reads past mid-file damage, and
+/// read-only so it records none of it. Returns `false` if the log has a torn
+/// tail, mid-file damage, or holes.
+fn verify(args: &LogArgs) -> Result<bool, Error> {
+    let data_dir = DataDir::open_read_only(&args.data)?;
+    let log_path = data_dir.log_path();
+    let options = StoreOptions {
+        repair: true,
+        read_only: true,
+        ..StoreOptions::default()
     };
+    let store = Store::open_with_options(&log_path, &options)?;
+    let recorded = store::read_holes(&store::holes_path(&log_path))?;
+    let log = store.log();
+    let log = log.read().expect("EventLog RwLock poisoned");
+
+    // Read-only recovery stops at a torn final batch instead of truncating
+    // it. Anything past the end but a log end marker or zeroed preallocated
+    // space is one, unless a valid batch follows it.
+    let tail = read_tail(&log_path, log.log_len)?;
+    let damaged = damaged_tail_len(&tail);
+
+    println!(
+        "{}: {} events in {} streams, {} bytes",
+        log_path.display(),
+        log.events.len(),
+        log.streams.len(),
+        log.log_len
+    );
+    for hole in &log.holes {
+        if recorded.contains(hole) {
+            println!(
+                "hole: positions {}..{} are missing (bytes {}..{})",
+                hole.first_position,
+                hole.first_position + hole.missing_count,
+                hole.start_offset,
+                hole.end_offset
+            );
+        } else {
+            println!(
+                "mid-file damage: bytes {}..{} are corrupt, losing positions {}..{}; valid \
+                 batches resume at offset {} (a writable open fails without --repair)",
+                hole.start_offset,
+                hole.end_offset,
+                hole.first_position,
+                hole.first_position + hole.missing_count,
+                hole.end_offset
+            );
+        }
+    }
+    if damaged > 0 {
+        match store::next_valid_batch(&tail, 0) {
+            Some((offset, position)) => println!(
+                "mid-file damage: bytes {}..{} are corrupt; a valid batch at offset {} \
+                 starts at position {position}",
+                log.log_len,
+                log.log_len + offset as u64,
+                log.log_len + offset as u64
+            ),
+            None => println!(
+                "torn tail: {damaged} bytes at offset {} will be truncated by the next \
+                 writable open",
+                log.log_len
+            ),
+        }
+    }
+    Ok(damaged == 0 && log.holes.is_empty())
+}
 
-    // 9. Build the EventfoldService and health reporter.
-    let service = EventfoldService::new(writer_handle.clone(), read_index, broker);
-    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+/// `dump`: decode the log file batch by batch, without recovering it.
+fn dump(args: &DumpArgs) -> Result<(), Error> {
+    let data_dir = DataDir::open_read_only(&args.log.data)?;
+    let filter = DumpFilter {
+        streams: (!args.streams.is_empty()).then(|| args.streams.iter().copied().collect()),
+        event_types: (!args.event_types.is_empty())
+            .then(|| args.event_types.iter().cloned().collect()),
+        from_position: args.from,
+        to_position: args.to,
+        since: args.since,
+        until: args.until,
+    };
+    let out = std::io::BufWriter::new(std::io::stdout().lock());
+    dump::dump_file(
+        &data_dir.log_path(),
+        &filter,
+        args.format.into(),
+        args.payload.into(),
+        out,
+    )?;
+    Ok(())
+}
 
-    // 10. Log JWT auth status before building the server.
-    if config.jwt_secret.is_none() {
-        tracing::warn!(
-            "JWT auth is disabled -- all requests will be accepted without authentication"
-        );
+/// `stats`: print counts and sizes for the log.
+fn stats(args: &LogArgs) -> Result<(), Error> {
+    let data_dir = DataDir::open_read_only(&args.data)?;
+    let log_path = data_dir.log_path();
+    let store = Store::open_read_only(&log_path)?;
+    let file_len = std::fs::metadata(&log_path)?.len();
+    let log = store.log();
+    let log = log.read().expect("EventLog RwLock poisoned");
+
+    let mut event_types: BTreeMap<&str, u64> = BTreeMap::new();
+    for event in &log.events {
+        *event_types.entry(event.event_type.as_str()).or_default() += 1;
+    }
+    let missing: u64 = log.holes.iter().map(|h| h.missing_count).sum();
+
+    println!("log: {}", log_path.display());
+    println!("events: {}", log.events.len());
+    println!("streams: {}", log.streams.len());
+    println!("next_position: {}", log.next_position());
+    println!("holes: {} ({missing} missing positions)", log.holes.len());
+    println!("log_bytes: {}", log.log_len);
+    println!("file_bytes: {file_len}");
+    if let (Some(first), Some(last)) = (log.events.first(), log.events.last()) {
+        println!("first_recorded_at: {}", first.recorded_at);
+        println!("last_recorded_at: {}", last.recorded_at);
     }
+    for (event_type, count) in event_types {
+        println!("event_type: {event_type} {count}");
+    }
+    Ok(())
+}
 
-    // 11. Build the tonic Server, optionally with TLS.
-    let mut builder = tonic::transport::Server::builder();
+/// `export`: write the selected events to a file or standard output.
+fn export_log(args: &ExportArgs) -> Result<(), Error> {
+    let store = open_log_read_only(&args.log.data)?;
+    let read_index = ReadIndex::new(store.log());
+    let filter = ExportFilter {
+        from_position: args.from,
+        to_position: args.to,
+        streams: (!args.streams.is_empty()).then(|| args.streams.iter().copied().collect()),
+    };
+    let written = match &args.output {
+        Some(path) => export::export(
+            &read_index,
+            &filter,
+            args.format.into(),
+            std::io::BufWriter::new(std::fs::File::create(path)?),
+        )?,
+        None => export::export(
+            &read_index,
+            &filter,
+            args.format.into(),
+            std::io::BufWriter::new(std::io::stdout().lock()),
+        )?,
+    };
+    eprintln!("exported {written} events");
+    Ok(())
+}
 
-    if let Some(ref tls) = config.tls {
-        let cert = tokio::fs::read(&tls.cert_path).await.unwrap_or_else(|e| {
-            tracing::error!(
-                path = %tls.cert_path.display(),
-                error = %e,
-                "Failed to read TLS certificate"
-            );
+/// `import`: load exported events into a data directory.
+fn import_log(args: &ImportArgs) -> Result<(), Error> {
+    let data_dir = DataDir::open(&args.log.data)?;
+    let mut store = Store::open(&data_dir.log_path())?;
+    let summary = match &args.input {
+        Some(path) => export::import(
+            &mut store,
+            std::io::BufReader::new(std::fs::File::open(path)?),
+            args.format.into(),
+            args.mode.into(),
+        )?,
+        None => export::import(
+            &mut store,
+            std::io::stdin().lock(),
+            args.format.into(),
+            args.mode.into(),
+        )?,
+    };
+    println!(
+        "imported {} events, skipped {} already present",
+        summary.imported, summary.skipped
+    );
+    Ok(())
+}
+
+/// `backup`: copy the log to a new file and verify the copy.
+fn backup_log(args: &BackupArgs) -> Result<(), Error> {
+    let store = open_log_read_only(&args.log.data)?;
+    let source = store
+        .backup_source()
+        .expect("file store has a backup source");
+    let snapshot = source.backup_to_path(&args.dest, args.to_position)?;
+    println!(
+        "backed up {} bytes up to position {} to {}",
+        snapshot.len,
+        snapshot.next_position,
+        args.dest.display()
+    );
+    Ok(())
+}
+
+/// `compact`: truncate the log file to the end of its last complete batch.
+///
+/// Only a log end marker and zeroed preallocated space may follow that
+/// batch. Anything else is refused without touching the file, since cutting
+/// it off could lose data, so the writable open that takes the log lock has
+/// nothing to truncate and only the preallocated tail is released.
+fn compact(args: &LogArgs) -> Result<(), Error> {
+    // Only resolves the log path: a missing path is an error rather than a
+    // new data directory, and a single-file log is not migrated.
+    let data_dir = DataDir::open_read_only(&args.data)?;
+    let log_path = data_dir.log_path();
+    let log_len = Store::open_read_only(&log_path)?.log_file_len()?;
+    let damaged = damaged_tail_len(&read_tail(&log_path, log_len)?);
+    if damaged > 0 {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{damaged} bytes past the last batch at offset {log_len} are not \
+                 preallocated space; run `eventfold-db verify` on the log"
+            ),
+        )));
+    }
+
+    let store = Store::open(&log_path)?;
+    if store.log_file_len()? != log_len {
+        return Err(Error::Io(std::io::Error::other(
+            "log changed while compacting; run `eventfold-db verify` on the log",
+        )));
+    }
+    let file = std::fs::OpenOptions::new().write(true).open(&log_path)?;
+    let file_len = file.metadata()?.len();
+    if file_len > log_len {
+        file.set_len(log_len)?;
+        file.sync_all()?;
+    }
+    drop(store);
+    println!(
+        "{}: {log_len} bytes, released {}",
+        log_path.display(),
+        file_len.saturating_sub(log_len)
+    );
+    Ok(())
+}
+
+/// Run an offline subcommand, exiting with status 1 on failure.
+fn run_offline(command: &Command) {
+    let result = match command {
+        Command::Serve(_) => unreachable!("serve is not an offline command"),
+        Command::Verify(args) => verify(args).map(|intact| {
+            if !intact {
+                eprintln!("error: {} is damaged", args.data.display());
+                std::process::exit(1);
+            }
+        }),
+        Command::Dump(args) => dump(args),
+        Command::Stats(args) => stats(args),
+        Command::Export(args) => export_log(args),
+        Command::Import(args) => import_log(args),
+        Command::Backup(args) => backup_log(args),
+        Command::Compact(args) => compact(args),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    match cli.command {
+        None => serve(cli.serve).await,
+        Some(Command::Serve(args)) => serve(args).await,
+        Some(command) => {
+            // Keep standard output for the command's own output.
+            init_tracing(true);
+            run_offline(&command);
+        }
+    }
+}
+
+/// Start the server and run it until a shutdown signal.
+async fn serve(args: ServeArgs) {
+    // 1. Initialize tracing.
+    init_tracing(false);
+
+    // 2. Read configuration from the config file, environment, and flags.
+    let config = match args.config() {
+        Ok(config) => config,
+        Err(errors) => {
+            for msg in errors {
+                eprintln!("{msg}");
+            }
             std::process::exit(1);
-        });
+        }
+    };
+    if args.print_config {
+        print!("{}", config.to_toml());
+        return;
+    }
 
-        let key = tokio::fs::read(&tls.key_path).await.unwrap_or_else(|e| {
-            tracing::error!(
-                path = %tls.key_path.display(),
-                error = %e,
-                "Failed to read TLS private key"
+    // 3. Log configuration values.
+    tracing::info!(data_dir = %config.data_path.display(), "Data directory");
+    tracing::info!(listen_addr = %config.listen_addr, "Listen address");
+    tracing::info!(broker_capacity = config.broker_capacity, "Broker capacity");
+
+    // 4. Open the Store. Log recovered event and stream counts.
+    if config.repair {
+        tracing::warn!("Repair mode enabled -- damaged log ranges will be skipped");
+    }
+    if config.durability == Durability::FsyncPerBatch {
+        tracing::info!(durability = %config.durability, "Durability mode");
+    } else {
+        tracing::warn!(
+            durability = %config.durability,
+            "Relaxed durability mode -- acknowledged appends may be lost on a crash"
+        );
+    }
+    let options = StoreOptions {
+        repair: config.repair,
+        durability: config.durability,
+        preallocate: config.preallocate,
+        io_uring: config.io_uring,
+        read_only: config.read_only,
+    };
+    if config.read_only {
+        tracing::warn!("Read-only mode -- the log will not be modified and appends are rejected");
+    }
+    tracing::info!(
+        preallocate_bytes = config.preallocate,
+        io_uring = config.io_uring,
+        "Log write path"
+    );
+    let data_dir = if config.read_only {
+        DataDir::open_read_only(&config.data_path)
+    } else {
+        DataDir::open(&config.data_path)
+    };
+    let data_dir = match data_dir {
+        Ok(data_dir) => data_dir,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to open data directory");
+            std::process::exit(1);
+        }
+    };
+    if data_dir.migrated() {
+        tracing::info!(
+            log_path = %data_dir.log_path().display(),
+            "Migrated single-file layout into data directory"
+        );
+    }
+    let store = match Store::open_with_options(&data_dir.log_path(), &options) {
+        Ok(store) => store,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to open store");
+            std::process::exit(1);
+        }
+    };
+
+    // Read event and stream counts from the recovered log before spawning the writer.
+    {
+        let log_arc = store.log();
+        let log = log_arc
+            .read()
+            .expect("EventLog RwLock poisoned during startup");
+        tracing::info!(events = log.events.len(), "Recovered events");
+        tracing::info!(streams = log.streams.len(), "Recovered streams");
+        for hole in &log.holes {
+            tracing::warn!(
+                first_position = hole.first_position,
+                missing_count = hole.missing_count,
+                "Log has a hole -- these global positions are permanently missing"
             );
+        }
+    }
+
+    // Grab a backup handle before the writer task takes ownership of the store.
+    let backup_source = store
+        .backup_source()
+        .expect("file store has a backup source");
+
+    // 5. Create the Broker.
+    let broker = Broker::new(config.broker_capacity);
+
+    // 6. Spawn the writer task.
+    tracing::info!(dedup_capacity = %config.dedup_capacity, "Dedup capacity");
+    let (writer_handle, read_index, join_handle) =
+        spawn_writer(store, 64, broker.clone(), config.dedup_capacity);
+
+    // 7. Install the Prometheus metrics recorder.
+    let metrics_handle = match eventfold_db::metrics::install_recorder() {
+        Ok(handle) => handle,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to install metrics recorder");
             std::process::exit(1);
-        });
+        }
+    };
+
+    // Report holes left by repair-mode recovery. These never change while the
+    // server runs, so they are set once here.
+    let holes = read_index.holes();
+    metrics::gauge!("eventfold_log_holes").set(holes.len() as f64);
+    metrics::gauge!("eventfold_missing_positions")
+        .set(holes.iter().map(|h| h.missing_count).sum::<u64>() as f64);
+    metrics::gauge!("eventfold_durability_mode", "mode" => config.durability.name()).set(1.0);
+    // Configured quota limits; zero means the limit is disabled.
+    let limit = |l: Option<NonZeroU32>| l.map_or(0.0, |l| f64::from(l.get()));
+    metrics::gauge!("eventfold_quota_appends_per_sec").set(limit(config.quotas.appends_per_sec));
+    metrics::gauge!("eventfold_quota_burst")
+        .set(limit(config.quotas.burst.or(config.quotas.appends_per_sec)));
+    metrics::gauge!("eventfold_quota_max_in_flight").set(limit(config.quotas.max_in_flight));
 
-        let identity = tonic::transport::Identity::from_pem(cert, key);
-        let mut tls_config = tonic::transport::ServerTlsConfig::new().identity(identity);
+    // 8. Optionally start the metrics HTTP server.
+    let metrics_join_handle = if let Some(addr) = config.metrics_listen {
+        Some(eventfold_db::metrics::serve_metrics(metrics_handle, addr))
+    } else {
+        tracing::info!("Metrics endpoint disabled");
+        None
+    };
 
-        if let Some(ref ca_path) = tls.ca_path {
-            let ca = tokio::fs::read(ca_path).await.unwrap_or_else(|e| {
-                tracing::error!(
-                    path = %ca_path.display(),
-                    error = %e,
-                    "Failed to read TLS CA certificate"
+    // 9. Build the EventfoldService and health reporter.
+    let mut service =
+        EventfoldService::new(writer_handle.clone(), read_index, broker).with_backup(backup_source);
+    if config.quotas.is_enabled() {
+        tracing::info!(
+            appends_per_sec = ?config.quotas.appends_per_sec,
+            burst = ?config.quotas.burst,
+            max_in_flight = ?config.quotas.max_in_flight,
+            "Per-client append quotas enabled"
+        );
+        service = service.with_quotas(Quotas::new(config.quotas));
+    }
+    metrics::gauge!("eventfold_disk_min_free_bytes").set(config.min_free as f64);
+    let disk_guard = match NonZeroU64::new(config.min_free) {
+        Some(min_free) if !config.read_only => match DiskGuard::new(data_dir.root(), min_free) {
+            Ok(guard) => {
+                tracing::info!(
+                    min_free_bytes = config.min_free,
+                    free_bytes = guard.free(),
+                    "Disk space guard enabled"
                 );
+                Some(guard)
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to read free disk space");
                 std::process::exit(1);
-            });
-            let ca_cert = tonic::transport::Certificate::from_pem(ca);
-            tls_config = tls_config.client_ca_root(ca_cert);
-            tracing::info!("mTLS enabled");
+            }
+        },
+        _ => None,
+    };
+    let disk_monitor = disk_guard
+        .as_ref()
+        .map(|guard| guard.spawn_monitor(DISK_CHECK_INTERVAL));
+    if let Some(guard) = &disk_guard {
+        service = service.with_disk_guard(guard.clone());
+    }
+    if let Some(path) = &config.acl_file {
+        let policy = AclPolicy::load(path).unwrap_or_else(|e| {
+            tracing::error!(path = %path.display(), error = %e, "Failed to load ACL policy");
+            std::process::exit(1);
+        });
+        tracing::info!(
+            path = %path.display(),
+            rules = policy.rules.len(),
+            "ACL policy enabled"
+        );
+        service = service.with_acl(policy);
+    }
+    if config.audit {
+        if config.jwt.is_none() {
+            tracing::warn!(
+                "Auditing appends without JWT auth -- only client addresses are recorded"
+            );
         } else {
-            tracing::info!("TLS enabled");
+            tracing::info!("Auditing appends");
         }
+        service = service.with_audit();
+    }
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
 
-        builder = builder.tls_config(tls_config).unwrap_or_else(|e| {
+    // 10. Log JWT auth status before building the server.
+    if config.jwt.is_none() {
+        tracing::warn!(
+            "JWT auth is disabled -- all requests will be accepted without authentication"
+        );
+    }
+
+    // 11. Build the tonic Server. TLS is terminated in front of it so that
+    // certificates can be swapped without a restart.
+    let mut builder = tonic::transport::Server::builder();
+    let tls = config.tls.clone().map(|files| {
+        let tls = ReloadableTls::load(files).unwrap_or_else(|e| {
             tracing::error!(error = %e, "Failed to configure TLS");
             std::process::exit(1);
         });
-    }
+        if tls.is_mutual() {
+            tracing::info!("mTLS enabled");
+        } else {
+            tracing::info!("TLS enabled");
+        }
+        tls
+    });
+    let tls_reload = tls
+        .as_ref()
+        .map(|tls| tls.spawn_reload(TLS_CERT_RELOAD_INTERVAL));
 
     // 12. Add services, conditionally wrapping with JWT interceptor.
     let router = builder.add_service(health_service);
-    let server = match config.jwt_secret {
-        Some(ref secret) => {
-            let interceptor = JwtInterceptor::new(secret);
+    let mut jwt_reload = None;
+    let server = match config.jwt {
+        Some(ref jwt) => {
+            let interceptor = JwtInterceptor::from_config(jwt.clone()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to load JWT keys");
+                std::process::exit(1);
+            });
+            jwt_reload = interceptor.spawn_reload(JWT_KEY_RELOAD_INTERVAL);
             let svc = InterceptedService::new(EventStoreServer::new(service), interceptor);
             router.add_service(svc)
         }
@@ -383,8 +1740,6 @@ async fn main() {
         .local_addr()
         .expect("bound listener should have a local address");
 
-    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
-
     // 14. Log the actual bound address.
     tracing::info!("Server listening on {addr}");
 
@@ -395,25 +1750,50 @@ async fn main() {
     health_reporter
         .set_service_status("", tonic_health::ServingStatus::Serving)
         .await;
+    health_reporter
+        .set_service_status(WRITES_HEALTH_SERVICE, tonic_health::ServingStatus::Serving)
+        .await;
+    tokio::spawn(track_health(
+        writer_handle.watch_status(),
+        disk_guard.as_ref().map(DiskGuard::watch_low),
+        health_reporter.clone(),
+    ));
 
     // 16-17. Serve until shutdown signal, then clean up.
     // The shutdown future transitions health status to NOT_SERVING before the
     // server begins draining connections.
-    server
-        .serve_with_incoming_shutdown(incoming, async {
-            shutdown_signal().await;
-            health_reporter
-                .set_service_status("", tonic_health::ServingStatus::NotServing)
-                .await;
-            health_reporter
-                .set_not_serving::<EventStoreServer<EventfoldService>>()
-                .await;
-        })
-        .await
-        .unwrap_or_else(|e| {
-            tracing::error!(error = %e, "Server error");
-            std::process::exit(1);
-        });
+    let shutdown = async {
+        shutdown_signal().await;
+        health_reporter
+            .set_service_status("", tonic_health::ServingStatus::NotServing)
+            .await;
+        health_reporter
+            .set_not_serving::<EventStoreServer<EventfoldService>>()
+            .await;
+        health_reporter
+            .set_service_status(
+                WRITES_HEALTH_SERVICE,
+                tonic_health::ServingStatus::NotServing,
+            )
+            .await;
+    };
+    let served = match &tls {
+        Some(tls) => {
+            server
+                .serve_with_incoming_shutdown(tls.incoming(listener), shutdown)
+                .await
+        }
+        None => {
+            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+            server
+                .serve_with_incoming_shutdown(incoming, shutdown)
+                .await
+        }
+    };
+    served.unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Server error");
+        std::process::exit(1);
+    });
 
     // Shutdown sequence: log, abort metrics server, drop writer handle, await writer task.
     tracing::info!("Shutting down");
@@ -421,6 +1801,15 @@ async fn main() {
     if let Some(handle) = metrics_join_handle {
         handle.abort();
     }
+    if let Some(handle) = disk_monitor {
+        handle.abort();
+    }
+    if let Some(handle) = jwt_reload {
+        handle.abort();
+    }
+    if let Some(handle) = tls_reload {
+        handle.abort();
+    }
 
     drop(writer_handle);
     join_handle
@@ -433,6 +1822,16 @@ mod tests {
     use super::*;
     use serial_test::serial;
 
+    impl Config {
+        /// Validate the `EVENTFOLD_*` environment variables alone, with the
+        /// errors joined into one message.
+        fn from_env() -> Result<Config, String> {
+            let mut settings = Settings::default();
+            settings.load_env();
+            Config::from_settings(&settings).map_err(|errors| errors.join("\n"))
+        }
+    }
+
     /// Clear all TLS-related environment variables so they do not leak between tests.
     fn clear_tls_env() {
         // SAFETY: serial test -- no concurrent env mutation.
@@ -451,6 +1850,49 @@ mod tests {
     fn clear_jwt_env() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::remove_var("EVENTFOLD_JWT_SECRET") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_PUBLIC_KEYS") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_ALGORITHM") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_JWKS") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_ISSUER") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_AUDIENCE") };
+        unsafe { std::env::remove_var("EVENTFOLD_JWT_LEEWAY_SECS") };
+        unsafe { std::env::remove_var("EVENTFOLD_ACL_FILE") };
+        unsafe { std::env::remove_var("EVENTFOLD_AUDIT") };
+    }
+
+    /// Clear the durability environment variables so they do not leak between tests.
+    fn clear_durability_env() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::remove_var("EVENTFOLD_DURABILITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_SYNC_INTERVAL_MS") };
+    }
+
+    /// Clear the write-path environment variables so they do not leak between tests.
+    fn clear_write_path_env() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::remove_var("EVENTFOLD_PREALLOCATE_MB") };
+        unsafe { std::env::remove_var("EVENTFOLD_IO_URING") };
+        unsafe { std::env::remove_var("EVENTFOLD_READ_ONLY") };
+    }
+
+    /// Clear the quota environment variables so they do not leak between tests.
+    fn clear_quota_env() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::remove_var("EVENTFOLD_QUOTA_APPENDS_PER_SEC") };
+        unsafe { std::env::remove_var("EVENTFOLD_QUOTA_BURST") };
+        unsafe { std::env::remove_var("EVENTFOLD_QUOTA_MAX_IN_FLIGHT") };
+    }
+
+    /// Clear the disk guard environment variable so it does not leak between tests.
+    fn clear_disk_env() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::remove_var("EVENTFOLD_MIN_FREE_MB") };
+    }
+
+    /// Clear the repair-mode environment variable so it does not leak between tests.
+    fn clear_repair_env() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::remove_var("EVENTFOLD_REPAIR") };
     }
 
     #[test]
@@ -551,7 +1993,7 @@ mod tests {
     fn init_tracing_does_not_panic() {
         // init_tracing() should be safe to call. The global subscriber may already
         // be set by another test, so we accept try_init failure silently.
-        init_tracing();
+        init_tracing(false);
     }
 
     #[test]
@@ -618,254 +2060,816 @@ mod tests {
 
     #[test]
     #[serial]
-    fn from_env_tls_key_without_cert_returns_err() {
+    fn from_env_tls_key_without_cert_returns_err() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_TLS_CERT") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
+        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let result = Config::from_env();
+        assert!(result.is_err(), "expected Err when CERT is missing");
+        let msg = result.unwrap_err();
+        assert!(
+            msg.contains("EVENTFOLD_TLS_CERT"),
+            "error should mention EVENTFOLD_TLS_CERT, got: {msg}"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_tls_cert_without_key_returns_err() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
+        unsafe { std::env::remove_var("EVENTFOLD_TLS_KEY") };
+        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let result = Config::from_env();
+        assert!(result.is_err(), "expected Err when KEY is missing");
+        let msg = result.unwrap_err();
+        assert!(
+            msg.contains("EVENTFOLD_TLS_KEY"),
+            "error should mention EVENTFOLD_TLS_KEY, got: {msg}"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_tls_cert_key_and_ca() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_CA", "/tmp/ca.crt") };
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed with cert, key, and CA");
+        assert_eq!(
+            config.tls,
+            Some(TlsConfig {
+                cert_path: PathBuf::from("/tmp/c.crt"),
+                key_path: PathBuf::from("/tmp/k.key"),
+                ca_path: Some(PathBuf::from("/tmp/ca.crt")),
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_tls_cert_and_key_without_ca() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
+        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
+        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed with cert and key");
+        assert_eq!(
+            config.tls,
+            Some(TlsConfig {
+                cert_path: PathBuf::from("/tmp/c.crt"),
+                key_path: PathBuf::from("/tmp/k.key"),
+                ca_path: None,
+            })
+        );
+    }
+
+    #[test]
+    fn binary_exits_nonzero_when_tls_cert_file_missing() {
+        // When EVENTFOLD_TLS_CERT and EVENTFOLD_TLS_KEY point to nonexistent files,
+        // the binary should log an error (via tracing::error!) and exit non-zero.
+        // We only assert on exit status because tracing output may not be fully
+        // flushed before process::exit(1) terminates the process.
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let data_path = dir.path().join("data");
+
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--bin", "eventfold-db", "--quiet"])
+            .env("EVENTFOLD_DATA", data_path.as_os_str())
+            .env("EVENTFOLD_LISTEN", "[::1]:0")
+            .env("EVENTFOLD_TLS_CERT", "/tmp/nonexistent-cert.pem")
+            .env("EVENTFOLD_TLS_KEY", "/tmp/nonexistent-key.pem")
+            .env_remove("EVENTFOLD_TLS_CA")
+            .env_remove("EVENTFOLD_BROKER_CAPACITY")
+            .env_remove("EVENTFOLD_METRICS_LISTEN")
+            .output()
+            .expect("failed to execute cargo run");
+
+        assert!(
+            !output.status.success(),
+            "expected non-zero exit when TLS cert file does not exist"
+        );
+    }
+
+    #[tokio::test]
+    async fn health_reporter_can_set_serving_and_not_serving() {
+        // Verify that tonic-health dependency is available and the API works
+        // for both the empty-string service name and the typed service name.
+        use eventfold_db::proto::event_store_server::EventStoreServer;
+
+        let (reporter, _service) = tonic_health::server::health_reporter();
+
+        // Set serving for both service names (same pattern as main.rs wiring).
+        reporter
+            .set_serving::<EventStoreServer<EventfoldService>>()
+            .await;
+        reporter
+            .set_service_status("", tonic_health::ServingStatus::Serving)
+            .await;
+
+        // Transition to NOT_SERVING (same pattern as shutdown path).
+        reporter
+            .set_service_status("", tonic_health::ServingStatus::NotServing)
+            .await;
+        reporter
+            .set_not_serving::<EventStoreServer<EventfoldService>>()
+            .await;
+    }
+
+    /// Query the health status a reporter currently publishes for `service`.
+    async fn health_of(
+        reporter: &tonic_health::server::HealthReporter,
+        service: &str,
+    ) -> tonic_health::pb::health_check_response::ServingStatus {
+        use tonic_health::pb::health_server::Health;
+
+        let health = tonic_health::server::HealthService::from_health_reporter(reporter.clone());
+        let response = health
+            .check(tonic::Request::new(tonic_health::pb::HealthCheckRequest {
+                service: service.to_string(),
+            }))
+            .await
+            .expect("service status should be set");
+        response.into_inner().status()
+    }
+
+    /// Poll until `service` reports `expected`.
+    async fn wait_for_health(
+        reporter: &tonic_health::server::HealthReporter,
+        service: &str,
+        expected: tonic_health::pb::health_check_response::ServingStatus,
+    ) {
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while health_of(reporter, service).await != expected {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{service:?} should become {expected:?}"));
+    }
+
+    #[tokio::test]
+    async fn track_health_marks_not_serving_once_writer_fails() {
+        use tonic_health::pb::health_check_response::ServingStatus;
+
+        let (reporter, _service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("", tonic_health::ServingStatus::Serving)
+            .await;
+        let (tx, rx) = tokio::sync::watch::channel(WriterStatus::Healthy);
+        let task = tokio::spawn(track_health(rx, None, reporter.clone()));
+
+        tokio::task::yield_now().await;
+        assert!(!task.is_finished(), "healthy writer should not be reported");
+        assert_eq!(health_of(&reporter, "").await, ServingStatus::Serving);
+
+        tx.send(WriterStatus::Failed("EIO".to_string()))
+            .expect("receiver should be alive");
+        tokio::time::timeout(std::time::Duration::from_secs(5), task)
+            .await
+            .expect("tracker should finish after failure")
+            .expect("tracker should not panic");
+        assert_eq!(health_of(&reporter, "").await, ServingStatus::NotServing);
+        assert_eq!(
+            health_of(&reporter, "eventfold.EventStore").await,
+            ServingStatus::NotServing
+        );
+        assert_eq!(
+            health_of(&reporter, WRITES_HEALTH_SERVICE).await,
+            ServingStatus::NotServing
+        );
+    }
+
+    #[tokio::test]
+    async fn track_health_reports_low_disk_space_on_writes_only() {
+        use tonic_health::pb::health_check_response::ServingStatus;
+
+        let (reporter, _service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("", tonic_health::ServingStatus::Serving)
+            .await;
+        reporter
+            .set_service_status(WRITES_HEALTH_SERVICE, tonic_health::ServingStatus::Serving)
+            .await;
+        let (_writer_tx, writer_rx) = tokio::sync::watch::channel(WriterStatus::Healthy);
+        let (disk_tx, disk_rx) = tokio::sync::watch::channel(false);
+        let task = tokio::spawn(track_health(writer_rx, Some(disk_rx), reporter.clone()));
+
+        disk_tx.send(true).expect("receiver should be alive");
+        wait_for_health(&reporter, WRITES_HEALTH_SERVICE, ServingStatus::NotServing).await;
+        // Reads still work, so the server as a whole stays in rotation.
+        assert_eq!(health_of(&reporter, "").await, ServingStatus::Serving);
+
+        disk_tx.send(false).expect("receiver should be alive");
+        wait_for_health(&reporter, WRITES_HEALTH_SERVICE, ServingStatus::Serving).await;
+        assert_eq!(health_of(&reporter, "").await, ServingStatus::Serving);
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn track_health_returns_when_writer_exits_healthy() {
+        let (reporter, _service) = tonic_health::server::health_reporter();
+        let (tx, rx) = tokio::sync::watch::channel(WriterStatus::Healthy);
+        drop(tx);
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            track_health(rx, None, reporter),
+        )
+        .await
+        .expect("tracker should return once the writer is gone");
+    }
+
+    #[test]
+    fn binary_exits_nonzero_without_eventfold_data() {
+        // Run the binary via `cargo run` without EVENTFOLD_DATA. The binary should
+        // print an error to stderr mentioning EVENTFOLD_DATA and exit non-zero.
+        // We clear inherited env vars so the test is deterministic.
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--bin", "eventfold-db", "--quiet"])
+            .env_remove("EVENTFOLD_DATA")
+            .env_remove("EVENTFOLD_LISTEN")
+            .env_remove("EVENTFOLD_BROKER_CAPACITY")
+            .env_remove("EVENTFOLD_TLS_CERT")
+            .env_remove("EVENTFOLD_TLS_KEY")
+            .env_remove("EVENTFOLD_TLS_CA")
+            .env_remove("EVENTFOLD_METRICS_LISTEN")
+            .output()
+            .expect("failed to execute cargo run");
+
+        assert!(
+            !output.status.success(),
+            "expected non-zero exit when EVENTFOLD_DATA is unset"
+        );
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("EVENTFOLD_DATA"),
+            "stderr should mention EVENTFOLD_DATA, got: {stderr}"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn binary_print_config_exits_with_redacted_config() {
+        // `--print-config` prints the merged settings and exits before opening
+        // the data directory, which does not exist here.
+        clear_all_env();
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--bin", "eventfold-db", "--quiet", "--"])
+            .args(["--print-config", "--data", "/nonexistent/eventfold"])
+            .args(["--set", "jwt_secret=hunter2"])
+            .output()
+            .expect("failed to execute cargo run");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            stdout.contains(r#"data = "/nonexistent/eventfold""#),
+            "got: {stdout}"
+        );
+        assert!(stdout.contains(REDACTED), "got: {stdout}");
+        assert!(!stdout.contains("hunter2"), "secret leaked: {stdout}");
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_metrics_listen_default() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(
+            config.metrics_listen,
+            Some("[::]:9090".parse::<SocketAddr>().unwrap())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_metrics_listen_empty_string_gives_none() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "") };
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(config.metrics_listen, None);
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_metrics_listen_custom_addr() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "127.0.0.1:19090") };
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(
+            config.metrics_listen,
+            Some("127.0.0.1:19090".parse::<SocketAddr>().unwrap())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_secret_set() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_JWT_SECRET", "mysecret") };
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(
+            config.jwt,
+            Some(JwtConfig::new(JwtKeySource::Secret("mysecret".to_string())))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_secret_unset() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(config.jwt, None);
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_secret_empty_string() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_JWT_SECRET", "") };
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(config.jwt, None);
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_public_keys_and_claims() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_JWT_PUBLIC_KEYS", "/keys/a.pem, /keys/b.pem") };
+        unsafe { std::env::set_var("EVENTFOLD_JWT_ALGORITHM", "ES256") };
+        unsafe { std::env::set_var("EVENTFOLD_JWT_ISSUER", "https://idp.example") };
+        unsafe { std::env::set_var("EVENTFOLD_JWT_AUDIENCE", "eventfold,admin") };
+        unsafe { std::env::set_var("EVENTFOLD_JWT_LEEWAY_SECS", "30") };
+
+        let config = Config::from_env().expect("should succeed");
+        clear_jwt_env();
+        let jwt = config.jwt.expect("JWT auth should be enabled");
+        assert_eq!(
+            jwt.keys,
+            JwtKeySource::PublicKeys {
+                paths: vec![PathBuf::from("/keys/a.pem"), PathBuf::from("/keys/b.pem")],
+                algorithm: jsonwebtoken::Algorithm::ES256,
+            }
+        );
+        assert_eq!(jwt.issuers, vec!["https://idp.example".to_string()]);
+        assert_eq!(
+            jwt.audiences,
+            vec!["eventfold".to_string(), "admin".to_string()]
+        );
+        assert_eq!(jwt.leeway, std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_jwks() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_JWT_JWKS", "/keys/jwks.json") };
+
+        let config = Config::from_env().expect("should succeed");
+        clear_jwt_env();
+        assert_eq!(
+            config.jwt,
+            Some(JwtConfig::new(JwtKeySource::Jwks(PathBuf::from(
+                "/keys/jwks.json"
+            ))))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_jwt_invalid_combinations_return_err() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+
+        let cases: [(&[(&str, &str)], &str); 4] = [
+            (
+                &[
+                    ("EVENTFOLD_JWT_SECRET", "s"),
+                    ("EVENTFOLD_JWT_JWKS", "/keys/jwks.json"),
+                ],
+                "only one of",
+            ),
+            (
+                &[("EVENTFOLD_JWT_ALGORITHM", "ES256")],
+                "EVENTFOLD_JWT_ALGORITHM",
+            ),
+            (
+                &[
+                    ("EVENTFOLD_JWT_PUBLIC_KEYS", "/keys/a.pem"),
+                    ("EVENTFOLD_JWT_ALGORITHM", "HS256"),
+                ],
+                "EVENTFOLD_JWT_ALGORITHM",
+            ),
+            (
+                &[("EVENTFOLD_JWT_AUDIENCE", "eventfold")],
+                "require a JWT key source",
+            ),
+        ];
+        for (vars, expected) in cases {
+            clear_jwt_env();
+            for (name, value) in vars {
+                unsafe { std::env::set_var(name, value) };
+            }
+            let result = Config::from_env();
+            clear_jwt_env();
+            let msg = result.expect_err("expected Err");
+            assert!(msg.contains(expected), "expected {expected:?} in: {msg}");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_acl_file_requires_jwt() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_ACL_FILE", "/etc/eventfold/acl.json") };
+
+        let msg = Config::from_env().expect_err("ACL without auth should fail");
+        assert!(msg.contains("EVENTFOLD_ACL_FILE"), "got: {msg}");
+
+        unsafe { std::env::set_var("EVENTFOLD_JWT_SECRET", "s") };
+        let config = Config::from_env().expect("should succeed");
+        clear_jwt_env();
+        assert_eq!(
+            config.acl_file,
+            Some(PathBuf::from("/etc/eventfold/acl.json"))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_audit_flag() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert!(!config.audit);
+
+        unsafe { std::env::set_var("EVENTFOLD_AUDIT", "1") };
+        let config = Config::from_env().expect("should succeed");
+        assert!(config.audit);
+
+        unsafe { std::env::set_var("EVENTFOLD_AUDIT", "sometimes") };
+        let msg = Config::from_env().expect_err("invalid flag should fail");
+        clear_jwt_env();
+        assert!(msg.contains("EVENTFOLD_AUDIT"), "got: {msg}");
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_metrics_listen_invalid_addr() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
+        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
+        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
+        clear_tls_env();
+        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "not-an-addr") };
+        clear_jwt_env();
+
+        let result = Config::from_env();
+        assert!(
+            result.is_err(),
+            "expected Err for invalid metrics listen address"
+        );
+        let msg = result.unwrap_err();
+        assert!(
+            msg.contains("EVENTFOLD_METRICS_LISTEN"),
+            "error should mention EVENTFOLD_METRICS_LISTEN, got: {msg}"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_repair_defaults_to_false() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        clear_repair_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert!(!config.repair);
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_repair_true() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_REPAIR", "true") };
+
+        let config = Config::from_env().expect("should succeed");
+        assert!(config.repair);
+        clear_repair_env();
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_repair_invalid_returns_err() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_TLS_CERT") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
-        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_REPAIR", "yes please") };
 
         let result = Config::from_env();
-        assert!(result.is_err(), "expected Err when CERT is missing");
-        let msg = result.unwrap_err();
+        clear_repair_env();
+        let msg = result.expect_err("expected Err for invalid EVENTFOLD_REPAIR");
         assert!(
-            msg.contains("EVENTFOLD_TLS_CERT"),
-            "error should mention EVENTFOLD_TLS_CERT, got: {msg}"
+            msg.contains("EVENTFOLD_REPAIR"),
+            "error should mention EVENTFOLD_REPAIR, got: {msg}"
         );
     }
 
     #[test]
     #[serial]
-    fn from_env_tls_cert_without_key_returns_err() {
+    fn from_env_durability_defaults_to_fsync_per_batch() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
-        unsafe { std::env::remove_var("EVENTFOLD_TLS_KEY") };
-        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
+        clear_durability_env();
 
-        let result = Config::from_env();
-        assert!(result.is_err(), "expected Err when KEY is missing");
-        let msg = result.unwrap_err();
-        assert!(
-            msg.contains("EVENTFOLD_TLS_KEY"),
-            "error should mention EVENTFOLD_TLS_KEY, got: {msg}"
-        );
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(config.durability, Durability::FsyncPerBatch);
     }
 
     #[test]
     #[serial]
-    fn from_env_tls_cert_key_and_ca() {
+    fn from_env_durability_parses_each_mode() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_CA", "/tmp/ca.crt") };
+        clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
-
-        let config = Config::from_env().expect("should succeed with cert, key, and CA");
-        assert_eq!(
-            config.tls,
-            Some(TlsConfig {
-                cert_path: PathBuf::from("/tmp/c.crt"),
-                key_path: PathBuf::from("/tmp/k.key"),
-                ca_path: Some(PathBuf::from("/tmp/ca.crt")),
-            })
-        );
+        clear_durability_env();
+
+        for (raw, expected) in [
+            ("fsync-per-batch", Durability::FsyncPerBatch),
+            ("fdatasync", Durability::Fdatasync),
+            (
+                "interval",
+                Durability::Interval(std::time::Duration::from_millis(100)),
+            ),
+            ("none", Durability::None),
+        ] {
+            unsafe { std::env::set_var("EVENTFOLD_DURABILITY", raw) };
+            let config = Config::from_env().expect("should succeed");
+            assert_eq!(config.durability, expected, "mode {raw}");
+        }
+        clear_durability_env();
     }
 
     #[test]
     #[serial]
-    fn from_env_tls_cert_and_key_without_ca() {
+    fn from_env_durability_interval_reads_sync_interval() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_CERT", "/tmp/c.crt") };
-        unsafe { std::env::set_var("EVENTFOLD_TLS_KEY", "/tmp/k.key") };
-        unsafe { std::env::remove_var("EVENTFOLD_TLS_CA") };
+        clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_DURABILITY", "interval") };
+        unsafe { std::env::set_var("EVENTFOLD_SYNC_INTERVAL_MS", "250") };
 
-        let config = Config::from_env().expect("should succeed with cert and key");
+        let config = Config::from_env().expect("should succeed");
+        clear_durability_env();
         assert_eq!(
-            config.tls,
-            Some(TlsConfig {
-                cert_path: PathBuf::from("/tmp/c.crt"),
-                key_path: PathBuf::from("/tmp/k.key"),
-                ca_path: None,
-            })
+            config.durability,
+            Durability::Interval(std::time::Duration::from_millis(250))
         );
     }
 
     #[test]
-    fn binary_exits_nonzero_when_tls_cert_file_missing() {
-        // When EVENTFOLD_TLS_CERT and EVENTFOLD_TLS_KEY point to nonexistent files,
-        // the binary should log an error (via tracing::error!) and exit non-zero.
-        // We only assert on exit status because tracing output may not be fully
-        // flushed before process::exit(1) terminates the process.
-        let dir = tempfile::tempdir().expect("failed to create tempdir");
-        let data_path = dir.path().join("events.log");
-
-        let output = std::process::Command::new("cargo")
-            .args(["run", "--bin", "eventfold-db", "--quiet"])
-            .env("EVENTFOLD_DATA", data_path.as_os_str())
-            .env("EVENTFOLD_LISTEN", "[::1]:0")
-            .env("EVENTFOLD_TLS_CERT", "/tmp/nonexistent-cert.pem")
-            .env("EVENTFOLD_TLS_KEY", "/tmp/nonexistent-key.pem")
-            .env_remove("EVENTFOLD_TLS_CA")
-            .env_remove("EVENTFOLD_BROKER_CAPACITY")
-            .env_remove("EVENTFOLD_METRICS_LISTEN")
-            .output()
-            .expect("failed to execute cargo run");
+    #[serial]
+    fn from_env_durability_zero_interval_returns_err() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_DURABILITY", "interval") };
+        unsafe { std::env::set_var("EVENTFOLD_SYNC_INTERVAL_MS", "0") };
 
+        let result = Config::from_env();
+        clear_durability_env();
+        let msg = result.expect_err("expected Err for zero sync interval");
         assert!(
-            !output.status.success(),
-            "expected non-zero exit when TLS cert file does not exist"
+            msg.contains("EVENTFOLD_SYNC_INTERVAL_MS"),
+            "error should mention EVENTFOLD_SYNC_INTERVAL_MS, got: {msg}"
         );
     }
 
-    #[tokio::test]
-    async fn health_reporter_can_set_serving_and_not_serving() {
-        // Verify that tonic-health dependency is available and the API works
-        // for both the empty-string service name and the typed service name.
-        use eventfold_db::proto::event_store_server::EventStoreServer;
-
-        let (reporter, _service) = tonic_health::server::health_reporter();
-
-        // Set serving for both service names (same pattern as main.rs wiring).
-        reporter
-            .set_serving::<EventStoreServer<EventfoldService>>()
-            .await;
-        reporter
-            .set_service_status("", tonic_health::ServingStatus::Serving)
-            .await;
-
-        // Transition to NOT_SERVING (same pattern as shutdown path).
-        reporter
-            .set_service_status("", tonic_health::ServingStatus::NotServing)
-            .await;
-        reporter
-            .set_not_serving::<EventStoreServer<EventfoldService>>()
-            .await;
-    }
-
     #[test]
-    fn binary_exits_nonzero_without_eventfold_data() {
-        // Run the binary via `cargo run` without EVENTFOLD_DATA. The binary should
-        // print an error to stderr mentioning EVENTFOLD_DATA and exit non-zero.
-        // We clear inherited env vars so the test is deterministic.
-        let output = std::process::Command::new("cargo")
-            .args(["run", "--bin", "eventfold-db", "--quiet"])
-            .env_remove("EVENTFOLD_DATA")
-            .env_remove("EVENTFOLD_LISTEN")
-            .env_remove("EVENTFOLD_BROKER_CAPACITY")
-            .env_remove("EVENTFOLD_TLS_CERT")
-            .env_remove("EVENTFOLD_TLS_KEY")
-            .env_remove("EVENTFOLD_TLS_CA")
-            .env_remove("EVENTFOLD_METRICS_LISTEN")
-            .output()
-            .expect("failed to execute cargo run");
-
-        assert!(
-            !output.status.success(),
-            "expected non-zero exit when EVENTFOLD_DATA is unset"
-        );
+    #[serial]
+    fn from_env_durability_invalid_returns_err() {
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        clear_tls_env();
+        clear_metrics_env();
+        clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_DURABILITY", "eventually") };
 
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let result = Config::from_env();
+        clear_durability_env();
+        let msg = result.expect_err("expected Err for invalid EVENTFOLD_DURABILITY");
         assert!(
-            stderr.contains("EVENTFOLD_DATA"),
-            "stderr should mention EVENTFOLD_DATA, got: {stderr}"
+            msg.contains("EVENTFOLD_DURABILITY"),
+            "error should mention EVENTFOLD_DURABILITY, got: {msg}"
         );
     }
 
     #[test]
     #[serial]
-    fn from_env_metrics_listen_default() {
+    fn from_env_write_path_defaults() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
+        clear_write_path_env();
 
         let config = Config::from_env().expect("should succeed");
-        assert_eq!(
-            config.metrics_listen,
-            Some("[::]:9090".parse::<SocketAddr>().unwrap())
-        );
+        assert_eq!(config.preallocate, 64 * 1024 * 1024);
+        assert!(!config.io_uring);
     }
 
     #[test]
     #[serial]
-    fn from_env_metrics_listen_empty_string_gives_none() {
+    fn from_env_preallocate_zero_disables() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
-        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "") };
+        clear_metrics_env();
         clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_PREALLOCATE_MB", "0") };
+        unsafe { std::env::set_var("EVENTFOLD_IO_URING", "1") };
 
         let config = Config::from_env().expect("should succeed");
-        assert_eq!(config.metrics_listen, None);
+        clear_write_path_env();
+        assert_eq!(config.preallocate, 0);
+        assert!(config.io_uring);
     }
 
     #[test]
     #[serial]
-    fn from_env_metrics_listen_custom_addr() {
+    fn from_env_preallocate_invalid_returns_err() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
-        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "127.0.0.1:19090") };
+        clear_metrics_env();
         clear_jwt_env();
+        unsafe { std::env::set_var("EVENTFOLD_PREALLOCATE_MB", "lots") };
 
-        let config = Config::from_env().expect("should succeed");
-        assert_eq!(
-            config.metrics_listen,
-            Some("127.0.0.1:19090".parse::<SocketAddr>().unwrap())
+        let result = Config::from_env();
+        clear_write_path_env();
+        let msg = result.expect_err("expected Err for invalid EVENTFOLD_PREALLOCATE_MB");
+        assert!(
+            msg.contains("EVENTFOLD_PREALLOCATE_MB"),
+            "error should mention EVENTFOLD_PREALLOCATE_MB, got: {msg}"
         );
     }
 
     #[test]
     #[serial]
-    fn from_env_jwt_secret_set() {
+    fn from_env_read_only_flag() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
-        unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
-        unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
-        unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
         clear_metrics_env();
-        unsafe { std::env::set_var("EVENTFOLD_JWT_SECRET", "mysecret") };
+        clear_jwt_env();
+        clear_write_path_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert!(!config.read_only);
 
+        unsafe { std::env::set_var("EVENTFOLD_READ_ONLY", "true") };
         let config = Config::from_env().expect("should succeed");
-        assert_eq!(config.jwt_secret, Some("mysecret".to_string()));
+        clear_write_path_env();
+        assert!(config.read_only);
     }
 
     #[test]
     #[serial]
-    fn from_env_jwt_secret_unset() {
+    fn from_env_quotas() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
         unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
@@ -874,14 +2878,31 @@ mod tests {
         clear_tls_env();
         clear_metrics_env();
         clear_jwt_env();
+        clear_quota_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert!(!config.quotas.is_enabled());
 
+        unsafe { std::env::set_var("EVENTFOLD_QUOTA_APPENDS_PER_SEC", "50") };
+        unsafe { std::env::set_var("EVENTFOLD_QUOTA_MAX_IN_FLIGHT", "0") };
         let config = Config::from_env().expect("should succeed");
-        assert_eq!(config.jwt_secret, None);
+        assert_eq!(config.quotas.appends_per_sec, NonZeroU32::new(50));
+        assert_eq!(config.quotas.burst, None);
+        assert_eq!(config.quotas.max_in_flight, None);
+
+        unsafe { std::env::set_var("EVENTFOLD_QUOTA_BURST", "many") };
+        let result = Config::from_env();
+        clear_quota_env();
+        let msg = result.expect_err("expected Err for invalid EVENTFOLD_QUOTA_BURST");
+        assert!(
+            msg.contains("EVENTFOLD_QUOTA_BURST"),
+            "error should mention EVENTFOLD_QUOTA_BURST, got: {msg}"
+        );
     }
 
     #[test]
     #[serial]
-    fn from_env_jwt_secret_empty_string() {
+    fn from_env_min_free() {
         // SAFETY: serial test -- no concurrent env mutation.
         unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
         unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
@@ -889,33 +2910,238 @@ mod tests {
         unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
         clear_metrics_env();
-        unsafe { std::env::set_var("EVENTFOLD_JWT_SECRET", "") };
+        clear_jwt_env();
+        clear_disk_env();
+
+        let config = Config::from_env().expect("should succeed");
+        assert_eq!(config.min_free, 0);
 
+        unsafe { std::env::set_var("EVENTFOLD_MIN_FREE_MB", "512") };
         let config = Config::from_env().expect("should succeed");
-        assert_eq!(config.jwt_secret, None);
+        assert_eq!(config.min_free, 512 * 1024 * 1024);
+
+        unsafe { std::env::set_var("EVENTFOLD_MIN_FREE_MB", "plenty") };
+        let result = Config::from_env();
+        clear_disk_env();
+        let msg = result.expect_err("expected Err for invalid EVENTFOLD_MIN_FREE_MB");
+        assert!(
+            msg.contains("EVENTFOLD_MIN_FREE_MB"),
+            "error should mention EVENTFOLD_MIN_FREE_MB, got: {msg}"
+        );
     }
 
-    #[test]
-    #[serial]
-    fn from_env_metrics_listen_invalid_addr() {
+    /// Clear every `EVENTFOLD_*` setting so only the config file and flags
+    /// under test apply.
+    fn clear_all_env() {
         // SAFETY: serial test -- no concurrent env mutation.
-        unsafe { std::env::set_var("EVENTFOLD_DATA", "/tmp/x") };
+        unsafe { std::env::remove_var("EVENTFOLD_DATA") };
         unsafe { std::env::remove_var("EVENTFOLD_LISTEN") };
         unsafe { std::env::remove_var("EVENTFOLD_BROKER_CAPACITY") };
         unsafe { std::env::remove_var("EVENTFOLD_DEDUP_CAPACITY") };
         clear_tls_env();
-        unsafe { std::env::set_var("EVENTFOLD_METRICS_LISTEN", "not-an-addr") };
+        clear_metrics_env();
         clear_jwt_env();
+        clear_durability_env();
+        clear_write_path_env();
+        clear_quota_env();
+        clear_disk_env();
+        clear_repair_env();
+    }
 
-        let result = Config::from_env();
+    /// Parse `args` (after the binary name) and build the configuration.
+    fn cli_config(args: &[&str]) -> Result<Config, Vec<String>> {
+        let cli = Cli::try_parse_from(std::iter::once("eventfold-db").chain(args.iter().copied()))
+            .expect("arguments should parse");
+        assert!(cli.command.is_none(), "server flags need no subcommand");
+        cli.serve.config()
+    }
+
+    /// Write `contents` to a config file in a fresh tempdir.
+    fn write_config_file(contents: &str) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("eventfold.toml");
+        std::fs::write(&path, contents).expect("write config file");
+        let path = path.to_str().expect("tempdir paths are UTF-8").to_string();
+        (dir, path)
+    }
+
+    #[test]
+    #[serial]
+    fn config_file_flattens_tables_and_arrays() {
+        clear_all_env();
+        let (_dir, path) = write_config_file(
+            r#"
+data = "/var/lib/eventfold"
+broker_capacity = 16
+audit = true
+
+[tls]
+cert = "/etc/eventfold/server.pem"
+key = "/etc/eventfold/server.key"
+
+[jwt]
+public_keys = ["/etc/eventfold/old.pem", "/etc/eventfold/new.pem"]
+issuer = ["https://idp.example.com"]
+"#,
+        );
+
+        let config = cli_config(&["--config", &path]).expect("config file should load");
+        assert_eq!(config.data_path, PathBuf::from("/var/lib/eventfold"));
+        assert_eq!(config.broker_capacity, 16);
+        assert!(config.audit);
+        let tls = config.tls.expect("TLS should be configured");
+        assert_eq!(tls.cert_path, PathBuf::from("/etc/eventfold/server.pem"));
+        assert_eq!(tls.key_path, PathBuf::from("/etc/eventfold/server.key"));
+        let jwt = config.jwt.expect("JWT should be configured");
+        assert_eq!(
+            jwt.keys,
+            JwtKeySource::PublicKeys {
+                paths: vec![
+                    PathBuf::from("/etc/eventfold/old.pem"),
+                    PathBuf::from("/etc/eventfold/new.pem"),
+                ],
+                algorithm: jsonwebtoken::Algorithm::RS256,
+            }
+        );
+        assert_eq!(jwt.issuers, vec!["https://idp.example.com".to_string()]);
+    }
+
+    #[test]
+    #[serial]
+    fn env_and_flags_override_config_file() {
+        clear_all_env();
+        let (_dir, path) = write_config_file(
+            r#"
+data = "/from/file"
+listen = "127.0.0.1:1000"
+broker_capacity = 16
+durability = "fdatasync"
+"#,
+        );
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_LISTEN", "127.0.0.1:2000") };
+        unsafe { std::env::set_var("EVENTFOLD_BROKER_CAPACITY", "32") };
+
+        let result = cli_config(&[
+            "--config",
+            &path,
+            "--listen",
+            "127.0.0.1:3000",
+            "--read-only",
+            "--set",
+            "durability=none",
+        ]);
+        clear_all_env();
+        let config = result.expect("merged config should be valid");
+
+        assert_eq!(config.data_path, PathBuf::from("/from/file"));
+        assert_eq!(config.broker_capacity, 32, "environment overrides the file");
+        assert_eq!(
+            config.listen_addr,
+            "127.0.0.1:3000".parse::<SocketAddr>().expect("valid addr"),
+            "flags override the environment"
+        );
+        assert_eq!(config.durability, Durability::None);
+        assert!(config.read_only);
+    }
+
+    #[test]
+    #[serial]
+    fn config_errors_are_reported_together() {
+        clear_all_env();
+        let (_dir, path) = write_config_file(
+            r#"
+listne = "[::]:2113"
+broker_capacity = 1.5
+
+[tls]
+cert = "/etc/eventfold/server.pem"
+"#,
+        );
+        // SAFETY: serial test -- no concurrent env mutation.
+        unsafe { std::env::set_var("EVENTFOLD_REPAIR", "maybe") };
+
+        let result = cli_config(&["--config", &path, "--set", "bogus=1", "--set", "audit"]);
+        clear_all_env();
+        let errors = result.expect_err("invalid settings should fail");
+
+        for expected in [
+            "`listne`",
+            "`broker_capacity`",
+            "EVENTFOLD_TLS_CERT is set but EVENTFOLD_TLS_KEY is missing",
+            "EVENTFOLD_REPAIR",
+            "`bogus`",
+            "--set expects KEY=VALUE",
+            "EVENTFOLD_DATA",
+        ] {
+            assert!(
+                errors.iter().any(|e| e.contains(expected)),
+                "expected {expected:?} in: {errors:?}"
+            );
+        }
+        assert_eq!(errors.len(), 7, "one error per problem: {errors:?}");
+    }
+
+    #[test]
+    #[serial]
+    fn unreadable_or_invalid_config_file_returns_err() {
+        clear_all_env();
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let missing = dir.path().join("missing.toml");
+        let errors = cli_config(&["--config", missing.to_str().expect("UTF-8")])
+            .expect_err("missing file should fail");
         assert!(
-            result.is_err(),
-            "expected Err for invalid metrics listen address"
+            errors[0].contains("failed to read config file"),
+            "got: {errors:?}"
         );
-        let msg = result.unwrap_err();
+
+        let (_dir, path) = write_config_file("data = ");
+        let errors = cli_config(&["--config", &path, "--data", "/tmp/x"])
+            .expect_err("invalid TOML should fail");
+        assert_eq!(errors.len(), 1, "got: {errors:?}");
+        assert!(errors[0].contains("not valid TOML"), "got: {errors:?}");
+    }
+
+    #[test]
+    #[serial]
+    fn print_config_redacts_secrets_and_round_trips() {
+        clear_all_env();
+        let result = cli_config(&[
+            "--data",
+            "/var/lib/eventfold",
+            "--set",
+            "jwt_secret=hunter2",
+            "--set",
+            "jwt.audience=orders,billing",
+            "--set",
+            "tls.cert=/etc/eventfold/server.pem",
+            "--set",
+            "tls.key=/etc/eventfold/server.key",
+            "--set",
+            "durability=interval",
+            "--set",
+            "sync_interval_ms=250",
+            "--set",
+            "quota_burst=20",
+            "--set",
+            "metrics_listen=",
+        ]);
+        let config = result.expect("config should be valid");
+
+        let printed = config.to_toml();
+        assert!(!printed.contains("hunter2"), "secret leaked: {printed}");
         assert!(
-            msg.contains("EVENTFOLD_METRICS_LISTEN"),
-            "error should mention EVENTFOLD_METRICS_LISTEN, got: {msg}"
+            printed.contains(r#"jwt_secret = "<redacted>""#),
+            "got: {printed}"
         );
+
+        // The output is a valid config file describing the same configuration.
+        let (_dir, path) = write_config_file(&printed);
+        let reloaded = cli_config(&["--config", &path]).expect("printed config should load");
+        let mut expected = config;
+        if let Some(jwt) = expected.jwt.as_mut() {
+            jwt.keys = JwtKeySource::Secret(REDACTED.to_string());
+        }
+        assert_eq!(reloaded, expected);
     }
 }
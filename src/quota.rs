@@ -0,0 +1,382 @@
+//! Per-client append quotas.
+//!
+//! The writer channel is shared by every client, so one client sending appends
+//! as fast as it can fills the channel and delays everyone else. [`Quotas`]
+//! checks each append against two per-client limits before it is enqueued:
+//!
+//! - a token-bucket rate limit (sustained appends per second plus a burst),
+//! - a cap on appends waiting on the writer at once.
+//!
+//! Clients are identified by their JWT subject when auth is enabled, and by
+//! peer IP address otherwise (see [`ClientKey`]). A rejected append is never
+//! enqueued; the service answers it with `RESOURCE_EXHAUSTED` and a retry hint.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use metrics::{counter, gauge};
+
+/// Number of tracked clients above which idle entries are pruned.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Per-client limits. The default disables both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QuotaConfig {
+    /// Sustained appends per second per client. `None` disables rate limiting.
+    pub appends_per_sec: Option<NonZeroU32>,
+    /// Appends a client may make back to back before the sustained rate
+    /// applies. `None` uses `appends_per_sec`.
+    pub burst: Option<NonZeroU32>,
+    /// Appends per client waiting on the writer at once. `None` disables the
+    /// cap.
+    pub max_in_flight: Option<NonZeroU32>,
+}
+
+impl QuotaConfig {
+    /// Whether any limit is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.appends_per_sec.is_some() || self.max_in_flight.is_some()
+    }
+
+    /// Token bucket capacity: the burst, or one second's worth of appends.
+    fn bucket_capacity(&self) -> Option<f64> {
+        let rate = self.appends_per_sec?;
+        Some(f64::from(self.burst.unwrap_or(rate).get()))
+    }
+}
+
+/// Identity a quota is charged to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// The `sub` claim of the client's JWT.
+    Subject(String),
+    /// The client's IP address, used when the request is unauthenticated.
+    Peer(IpAddr),
+    /// Neither is known, e.g. a request that did not arrive over TCP. All
+    /// such requests share one quota.
+    Unknown,
+}
+
+impl ClientKey {
+    /// Identify the client that sent `request`.
+    pub fn from_request<T>(request: &tonic::Request<T>) -> ClientKey {
+        if let Some(subject) = request
+            .extensions()
+            .get::<crate::auth::AuthenticatedSubject>()
+        {
+            return ClientKey::Subject(subject.0.clone());
+        }
+        match request.remote_addr() {
+            Some(addr) => ClientKey::Peer(addr.ip()),
+            None => ClientKey::Unknown,
+        }
+    }
+}
+
+/// Which limit rejected an append.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaLimit {
+    /// The client exceeded its appends-per-second rate.
+    Rate,
+    /// The client already has the maximum number of appends in flight.
+    InFlight,
+}
+
+impl QuotaLimit {
+    /// Label value used in metrics.
+    pub fn name(self) -> &'static str {
+        match self {
+            QuotaLimit::Rate => "rate",
+            QuotaLimit::InFlight => "in_flight",
+        }
+    }
+}
+
+/// An append rejected by a quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaRejection {
+    /// The limit that was hit.
+    pub limit: QuotaLimit,
+    /// How long the client should wait before retrying.
+    pub retry_after: Duration,
+}
+
+impl QuotaRejection {
+    /// Convert to a `RESOURCE_EXHAUSTED` status. The retry hint is carried in
+    /// the message and in the standard `grpc-retry-pushback-ms` trailer.
+    pub fn to_status(self) -> tonic::Status {
+        let retry_ms = self.retry_after.as_millis().max(1);
+        let what = match self.limit {
+            QuotaLimit::Rate => "append rate limit exceeded",
+            QuotaLimit::InFlight => "too many appends in flight",
+        };
+        let mut status =
+            tonic::Status::resource_exhausted(format!("{what}; retry after {retry_ms} ms"));
+        status.metadata_mut().insert(
+            "grpc-retry-pushback-ms",
+            retry_ms
+                .to_string()
+                .parse()
+                .expect("digits are valid metadata"),
+        );
+        status
+    }
+}
+
+/// Quota state of one client.
+#[derive(Debug)]
+struct ClientState {
+    /// Tokens left in the rate bucket.
+    tokens: f64,
+    /// When `tokens` was last refilled.
+    refilled_at: Instant,
+    /// Appends admitted but not yet answered.
+    in_flight: u32,
+}
+
+/// Shared per-client quota tracker. Cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Quotas {
+    config: QuotaConfig,
+    clients: Arc<Mutex<HashMap<ClientKey, ClientState>>>,
+}
+
+impl Quotas {
+    /// Create a tracker enforcing `config`.
+    pub fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The limits being enforced.
+    pub fn config(&self) -> &QuotaConfig {
+        &self.config
+    }
+
+    /// Admit one append for `key`, or reject it.
+    ///
+    /// The returned permit counts against the client's in-flight cap until it
+    /// is dropped, so hold it until the writer has answered.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QuotaRejection`] if the client is over its rate or
+    /// in-flight limit. A rejected append consumes no quota.
+    pub fn acquire(&self, key: ClientKey) -> Result<QuotaPermit, QuotaRejection> {
+        self.acquire_at(key, Instant::now())
+    }
+
+    /// [`Quotas::acquire`] with an explicit clock reading.
+    fn acquire_at(&self, key: ClientKey, now: Instant) -> Result<QuotaPermit, QuotaRejection> {
+        let result = self.admit(&key, now);
+        match result {
+            Ok(()) => Ok(QuotaPermit {
+                quotas: self.clone(),
+                key,
+            }),
+            Err(rejection) => {
+                counter!("eventfold_quota_rejections_total", "limit" => rejection.limit.name())
+                    .increment(1);
+                Err(rejection)
+            }
+        }
+    }
+
+    fn admit(&self, key: &ClientKey, now: Instant) -> Result<(), QuotaRejection> {
+        let mut clients = self.clients.lock().expect("quota map poisoned");
+        if clients.len() >= PRUNE_THRESHOLD {
+            self.prune(&mut clients, now);
+        }
+        let capacity = self.config.bucket_capacity();
+        let state = clients.entry(key.clone()).or_insert_with(|| ClientState {
+            tokens: capacity.unwrap_or(0.0),
+            refilled_at: now,
+            in_flight: 0,
+        });
+
+        if let Some(max) = self.config.max_in_flight {
+            if state.in_flight >= max.get() {
+                // Any in-flight append should finish within a writer batch or
+                // two; suggest a short pause rather than a precise one.
+                return Err(QuotaRejection {
+                    limit: QuotaLimit::InFlight,
+                    retry_after: Duration::from_millis(10),
+                });
+            }
+        }
+
+        if let (Some(rate), Some(capacity)) = (self.config.appends_per_sec, capacity) {
+            let rate = f64::from(rate.get());
+            let elapsed = now.saturating_duration_since(state.refilled_at);
+            state.tokens = (state.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+            state.refilled_at = now;
+            if state.tokens < 1.0 {
+                return Err(QuotaRejection {
+                    limit: QuotaLimit::Rate,
+                    retry_after: Duration::from_secs_f64((1.0 - state.tokens) / rate),
+                });
+            }
+            state.tokens -= 1.0;
+        }
+
+        state.in_flight += 1;
+        gauge!("eventfold_quota_clients").set(clients.len() as f64);
+        Ok(())
+    }
+
+    /// Drop clients with nothing in flight and a full bucket; recreating them
+    /// later yields the same state.
+    fn prune(&self, clients: &mut HashMap<ClientKey, ClientState>, now: Instant) {
+        let capacity = self.config.bucket_capacity();
+        let rate = self
+            .config
+            .appends_per_sec
+            .map_or(0.0, |r| f64::from(r.get()));
+        clients.retain(|_, state| {
+            let refilled = state.tokens
+                + now
+                    .saturating_duration_since(state.refilled_at)
+                    .as_secs_f64()
+                    * rate;
+            state.in_flight > 0 || capacity.is_some_and(|cap| refilled < cap)
+        });
+    }
+
+    fn release(&self, key: &ClientKey) {
+        let mut clients = self.clients.lock().expect("quota map poisoned");
+        if let Some(state) = clients.get_mut(key) {
+            state.in_flight = state.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// An admitted append. Releases its in-flight slot when dropped.
+#[derive(Debug)]
+pub struct QuotaPermit {
+    quotas: Quotas,
+    key: ClientKey,
+}
+
+impl Drop for QuotaPermit {
+    fn drop(&mut self) {
+        self.quotas.release(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rate: Option<u32>, burst: Option<u32>, in_flight: Option<u32>) -> QuotaConfig {
+        QuotaConfig {
+            appends_per_sec: rate.and_then(NonZeroU32::new),
+            burst: burst.and_then(NonZeroU32::new),
+            max_in_flight: in_flight.and_then(NonZeroU32::new),
+        }
+    }
+
+    fn subject(name: &str) -> ClientKey {
+        ClientKey::Subject(name.to_string())
+    }
+
+    #[test]
+    fn rate_limit_allows_burst_then_rejects_with_retry_hint() {
+        let quotas = Quotas::new(config(Some(10), Some(3), None));
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            drop(quotas.acquire_at(subject("a"), now).expect("within burst"));
+        }
+        let rejection = quotas
+            .acquire_at(subject("a"), now)
+            .expect_err("burst exhausted");
+
+        assert_eq!(rejection.limit, QuotaLimit::Rate);
+        assert_eq!(rejection.retry_after, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn rate_limit_refills_over_time() {
+        let quotas = Quotas::new(config(Some(10), Some(1), None));
+        let now = Instant::now();
+
+        drop(quotas.acquire_at(subject("a"), now).expect("first append"));
+        assert!(quotas.acquire_at(subject("a"), now).is_err());
+        drop(
+            quotas
+                .acquire_at(subject("a"), now + Duration::from_millis(100))
+                .expect("token refilled"),
+        );
+    }
+
+    #[test]
+    fn clients_have_independent_quotas() {
+        let quotas = Quotas::new(config(Some(1), None, None));
+        let now = Instant::now();
+
+        drop(
+            quotas
+                .acquire_at(subject("noisy"), now)
+                .expect("first append"),
+        );
+        assert!(quotas.acquire_at(subject("noisy"), now).is_err());
+        drop(
+            quotas
+                .acquire_at(subject("quiet"), now)
+                .expect("other client is unaffected"),
+        );
+    }
+
+    #[test]
+    fn in_flight_cap_is_released_on_drop() {
+        let quotas = Quotas::new(config(None, None, Some(2)));
+        let now = Instant::now();
+
+        let first = quotas.acquire_at(subject("a"), now).expect("slot 1");
+        let _second = quotas.acquire_at(subject("a"), now).expect("slot 2");
+        let rejection = quotas
+            .acquire_at(subject("a"), now)
+            .expect_err("cap reached");
+        assert_eq!(rejection.limit, QuotaLimit::InFlight);
+
+        drop(first);
+        quotas
+            .acquire_at(subject("a"), now)
+            .expect("slot freed by drop");
+    }
+
+    #[test]
+    fn rejection_status_carries_retry_pushback() {
+        let status = QuotaRejection {
+            limit: QuotaLimit::Rate,
+            retry_after: Duration::from_millis(250),
+        }
+        .to_status();
+
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(status.message().contains("250 ms"), "{}", status.message());
+        assert_eq!(
+            status
+                .metadata()
+                .get("grpc-retry-pushback-ms")
+                .and_then(|v| v.to_str().ok()),
+            Some("250")
+        );
+    }
+
+    #[test]
+    fn client_key_prefers_subject_over_peer() {
+        let mut request = tonic::Request::new(());
+        assert_eq!(ClientKey::from_request(&request), ClientKey::Unknown);
+
+        request
+            .extensions_mut()
+            .insert(crate::auth::AuthenticatedSubject("svc".into()));
+        assert_eq!(ClientKey::from_request(&request), subject("svc"));
+    }
+}
@@ -10,7 +10,7 @@ use uuid::Uuid;
 
 use crate::error::Error;
 use crate::store::EventLog;
-use crate::types::{RecordedEvent, StreamInfo};
+use crate::types::{LogHole, RecordedEvent, StreamInfo};
 
 /// Shared, read-only handle to the in-memory event log.
 ///
@@ -54,32 +54,57 @@ impl ReadIndex {
     /// `Some(version)` if the stream exists, `None` otherwise.
     pub fn stream_version(&self, stream_id: &Uuid) -> Option<u64> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        log.streams
-            .get(stream_id)
-            .map(|positions| positions.len() as u64 - 1)
+        log.stream_version(stream_id)
     }
 
     /// Returns the next global position (i.e., `events.len()` as `u64`).
     ///
     /// If the log is empty, returns 0. This is the position that the next
-    /// appended event would receive.
+    /// appended event would receive. Positions lost to holes are counted.
     ///
     /// # Returns
     ///
-    /// The number of events in the global log.
+    /// The number of global positions assigned so far.
     pub fn global_position(&self) -> u64 {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        log.events.len() as u64
+        log.next_position()
+    }
+
+    /// Returns the first global position recorded at or after `timestamp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - Unix epoch milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// The global position, or the next position to be assigned if every
+    /// event is older. See [`EventLog::position_at`].
+    pub fn position_at(&self, timestamp: u64) -> u64 {
+        let log = self.log.read().expect("EventLog RwLock poisoned");
+        log.position_at(timestamp)
+    }
+
+    /// Returns the ranges of global positions lost to mid-file corruption and
+    /// skipped by repair-mode recovery.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<LogHole>` in ascending position order. Empty for an undamaged
+    /// log.
+    pub fn holes(&self) -> Vec<LogHole> {
+        let log = self.log.read().expect("EventLog RwLock poisoned");
+        log.holes.clone()
     }
 
     /// Return metadata for all known streams, sorted lexicographically by stream
     /// ID string (UUID hyphenated lowercase).
     ///
     /// Acquires a single `RwLock` read guard for the entire operation. The method
-    /// iterates only `EventLog::streams` (the `HashMap<Uuid, Vec<u64>>`); it never
-    /// accesses `EventLog::events` or any event payload, metadata, or event-type
-    /// fields. This makes the operation O(s) where s is the number of distinct
-    /// streams.
+    /// iterates `EventLog::streams` (the `HashMap<Uuid, Vec<u64>>`) and looks up
+    /// only the last event of each stream to read its version; it never clones
+    /// any event payload, metadata, or event-type fields. This makes the
+    /// operation O(s) where s is the number of distinct streams.
     ///
     /// # Returns
     ///
@@ -91,18 +116,19 @@ impl ReadIndex {
             .streams
             .iter()
             .map(|(id, positions)| {
-                // Safety: any stream in `EventLog::streams` has at least one
-                // position. Stream entries are created on first append and never
-                // removed, so `positions.len()` is always >= 1. This guarantees
-                // the subtraction does not underflow.
+                // Repair mode can drop some of a stream's events, so the
+                // position count may be lower than the latest version + 1;
+                // read the version from the stream's last event instead. Any
+                // stream in `EventLog::streams` has at least one position, so
+                // the lookup always finds it.
                 StreamInfo {
                     stream_id: *id,
                     event_count: positions.len() as u64,
-                    latest_version: positions.len() as u64 - 1,
+                    latest_version: log.stream_version(id).unwrap_or(0),
                 }
             })
             .collect();
-        streams.sort_by(|a, b| a.stream_id.to_string().cmp(&b.stream_id.to_string()));
+        streams.sort_by_key(|s| s.stream_id.to_string());
         streams
     }
 
@@ -131,19 +157,7 @@ impl ReadIndex {
         max_count: u64,
     ) -> Result<Vec<RecordedEvent>, Error> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        let positions = log
-            .streams
-            .get(&stream_id)
-            .ok_or(Error::StreamNotFound { stream_id })?;
-
-        let stream_len = positions.len() as u64;
-        let start = from_version.min(stream_len);
-        let end = from_version.saturating_add(max_count).min(stream_len);
-
-        Ok(positions[start as usize..end as usize]
-            .iter()
-            .map(|&global_pos| log.events[global_pos as usize].clone())
-            .collect())
+        log.read_stream(stream_id, from_version, max_count)
     }
 
     /// Read events from the global log starting at a given position.
@@ -162,10 +176,7 @@ impl ReadIndex {
     /// A `Vec` of `RecordedEvent` in global position order.
     pub fn read_all(&self, from_position: u64, max_count: u64) -> Vec<RecordedEvent> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        let len = log.events.len() as u64;
-        let start = from_position.min(len);
-        let end = from_position.saturating_add(max_count).min(len);
-        log.events[start as usize..end as usize].to_vec()
+        log.read_all(from_position, max_count)
     }
 }
 
@@ -214,6 +225,9 @@ mod tests {
         let log = Arc::new(RwLock::new(EventLog {
             events: Vec::new(),
             streams: HashMap::new(),
+            holes: Vec::new(),
+            log_len: 0,
+            time_index: Vec::new(),
         }));
         let index = ReadIndex::new(log);
         let cloned = index.clone();
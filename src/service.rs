@@ -9,18 +9,26 @@
 // Suppressing at module level since all conversion helpers share this pattern.
 #![allow(clippy::result_large_err)]
 
+use std::sync::Arc;
+
 use bytes::Bytes;
 use metrics::{counter, gauge};
 use uuid::Uuid;
 
 use futures_core::Stream;
 
+use crate::acl::{AclPolicy, Operation};
+use crate::auth::{AuthenticatedClaims, AuthenticatedSubject};
+use crate::backup::BackupSource;
 use crate::broker::Broker;
+use crate::disk::DiskGuard;
 use crate::error::Error;
 use crate::proto;
+use crate::quota::{ClientKey, QuotaRejection, Quotas};
 use crate::reader::ReadIndex;
 use crate::types::{
-    ExpectedVersion, ProposedEvent, RecordedEvent, StreamInfo, SubscriptionMessage,
+    AuditInfo, ExpectedVersion, LogHole, ProposedEvent, RecordedEvent, StreamInfo,
+    SubscriptionMessage,
 };
 use crate::writer::WriterHandle;
 
@@ -31,6 +39,13 @@ use crate::writer::WriterHandle;
 /// - `writer` -- handle to submit append requests to the single writer task.
 /// - `read_index` -- shared, read-only view of the in-memory event log.
 /// - `broker` -- broadcast channel for live subscription events.
+///
+/// The `Backup` RPC additionally needs a [`BackupSource`], attached with
+/// [`EventfoldService::with_backup`]. Without one it returns `UNIMPLEMENTED`.
+/// Per-client append quotas are enabled with [`EventfoldService::with_quotas`],
+/// and the low-disk-space guard with [`EventfoldService::with_disk_guard`].
+/// [`EventfoldService::with_acl`] checks every call against an ACL policy, and
+/// [`EventfoldService::with_audit`] records who made each append.
 pub struct EventfoldService {
     /// Handle for submitting append requests to the writer task.
     pub writer: WriterHandle,
@@ -38,6 +53,16 @@ pub struct EventfoldService {
     pub read_index: ReadIndex,
     /// Broadcast broker for live event subscriptions.
     pub broker: Broker,
+    /// Source for online backups. `None` disables the `Backup` RPC.
+    pub backup: Option<BackupSource>,
+    /// Per-client append quotas. `None` admits every append.
+    pub quotas: Option<Quotas>,
+    /// Low-disk-space guard. `None` admits appends regardless of free space.
+    pub disk: Option<DiskGuard>,
+    /// Authorization policy. `None` allows every call that reaches the service.
+    pub acl: Option<Arc<AclPolicy>>,
+    /// Whether appends are stamped with the caller's principal and address.
+    pub audit: bool,
 }
 
 impl EventfoldService {
@@ -53,8 +78,95 @@ impl EventfoldService {
             writer,
             read_index,
             broker,
+            backup: None,
+            quotas: None,
+            disk: None,
+            acl: None,
+            audit: false,
         }
     }
+
+    /// Enable the `Backup` RPC, serving copies from `source`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Backup handle for the store the writer task owns.
+    pub fn with_backup(mut self, source: BackupSource) -> Self {
+        self.backup = Some(source);
+        self
+    }
+
+    /// Check every append against per-client `quotas` before it is enqueued.
+    ///
+    /// # Arguments
+    ///
+    /// * `quotas` - Tracker holding the configured limits.
+    pub fn with_quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = Some(quotas);
+        self
+    }
+
+    /// Reject appends while `guard` reports free space below its low
+    /// watermark.
+    ///
+    /// # Arguments
+    ///
+    /// * `guard` - Guard sampling the data volume.
+    pub fn with_disk_guard(mut self, guard: DiskGuard) -> Self {
+        self.disk = Some(guard);
+        self
+    }
+
+    /// Check every call against `policy`, using the claims the JWT
+    /// interceptor attached to the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Rules granting operations to roles.
+    pub fn with_acl(mut self, policy: AclPolicy) -> Self {
+        self.acl = Some(Arc::new(policy));
+        self
+    }
+
+    /// Stamp every appended event with the authenticated principal and the
+    /// client address, persisting them with the event.
+    pub fn with_audit(mut self) -> Self {
+        self.audit = true;
+        self
+    }
+
+    /// The audit information to record for `request`, if auditing is on.
+    fn audit_info<T>(&self, request: &tonic::Request<T>) -> Option<AuditInfo> {
+        if !self.audit {
+            return None;
+        }
+        let principal = request
+            .extensions()
+            .get::<AuthenticatedSubject>()
+            .map(|subject| truncate_utf8(&subject.0, MAX_AUDIT_PRINCIPAL_LEN).to_string());
+        Some(AuditInfo {
+            principal,
+            client_addr: request.remote_addr().map(|addr| addr.to_string()),
+        })
+    }
+
+    /// Check `operation` on `stream` against the ACL policy, if there is one.
+    fn authorize<T>(
+        &self,
+        request: &tonic::Request<T>,
+        operation: Operation,
+        stream: Option<Uuid>,
+    ) -> Result<(), tonic::Status> {
+        let Some(acl) = &self.acl else {
+            return Ok(());
+        };
+        acl.check(
+            request.extensions().get::<AuthenticatedClaims>(),
+            operation,
+            stream,
+        )
+        .map_err(error_to_status)
+    }
 }
 
 /// Type alias for the server-streaming response used by subscription RPCs.
@@ -66,21 +178,83 @@ type SubscriptionStream = std::pin::Pin<
     Box<dyn futures_core::Stream<Item = Result<proto::SubscribeResponse, tonic::Status>> + Send>,
 >;
 
+/// Server-streaming response type for the `Backup` RPC.
+type BackupStream = std::pin::Pin<
+    Box<dyn futures_core::Stream<Item = Result<proto::BackupChunk, tonic::Status>> + Send>,
+>;
+
+/// Maximum number of log bytes sent in a single `BackupChunk`.
+const BACKUP_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Longest principal recorded by auditing, in bytes. Longer subjects are
+/// truncated so that they cannot push an event over `MAX_EVENT_SIZE`.
+const MAX_AUDIT_PRINCIPAL_LEN: usize = 256;
+
+/// How much earlier than the caller's `grpc-timeout` the writer deadline
+/// expires, at most. tonic starts its own timer for the header before the
+/// handler runs and answers `CANCELLED` when it fires, so the writer has to
+/// give up first for `DEADLINE_EXCEEDED` to reach the client.
+const DEADLINE_MARGIN: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// The longest prefix of `s` that is at most `max` bytes and ends on a
+/// character boundary.
+fn truncate_utf8(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Where a global read or subscription starts: `from_position`, moved forward
+/// to the first event recorded at `from_timestamp` when one is given.
+fn start_position(read_index: &ReadIndex, from_position: u64, from_timestamp: Option<u64>) -> u64 {
+    from_timestamp.map_or(from_position, |ts| {
+        from_position.max(read_index.position_at(ts))
+    })
+}
+
 #[tonic::async_trait]
 impl proto::event_store_server::EventStore for EventfoldService {
     /// Append events to a stream with optimistic concurrency.
     ///
     /// Validates `stream_id`, `expected_version`, non-empty `events`, and each
     /// `event_id`; delegates to the writer task; returns positions on success.
+    /// A `grpc-timeout` header becomes the writer deadline, and an append not
+    /// finished when it expires fails with `DEADLINE_EXCEEDED`. The message
+    /// tells the two cases apart: an append still queued was not written, while
+    /// one the writer already started may yet commit, so its outcome is unknown.
+    /// With quotas enabled, an append over the client's limits fails with
+    /// `RESOURCE_EXHAUSTED` before it reaches the writer, as does any append
+    /// while the disk guard reports low free space. With auditing enabled, the
+    /// caller's principal and address are recorded on every event.
     async fn append(
         &self,
         request: tonic::Request<proto::AppendRequest>,
     ) -> Result<tonic::Response<proto::AppendResponse>, tonic::Status> {
+        // The caller's deadline bounds how long the append may wait for the
+        // writer.
+        let deadline = grpc_deadline(request.metadata())?;
+        let stream_id = parse_uuid(&request.get_ref().stream_id, "stream_id")?;
+        self.authorize(&request, Operation::Append, Some(stream_id))?;
+        let audit = self.audit_info(&request);
+        if let Some(disk) = &self.disk {
+            disk.check().map_err(error_to_status)?;
+        }
+        // Held until the writer answers, so it counts as in flight.
+        let _permit = match &self.quotas {
+            Some(quotas) => Some(
+                quotas
+                    .acquire(ClientKey::from_request(&request))
+                    .map_err(QuotaRejection::to_status)?,
+            ),
+            None => None,
+        };
         let req = request.into_inner();
 
-        // Validate stream_id.
-        let stream_id = parse_uuid(&req.stream_id, "stream_id")?;
-
         // Validate expected_version.
         let expected_version = proto_to_expected_version(req.expected_version)?;
 
@@ -99,7 +273,7 @@ impl proto::event_store_server::EventStore for EventfoldService {
         // Delegate to the writer task.
         let recorded = self
             .writer
-            .append(stream_id, expected_version, events)
+            .append_audited(stream_id, expected_version, events, deadline, audit)
             .await
             .map_err(error_to_status)?;
 
@@ -123,10 +297,10 @@ impl proto::event_store_server::EventStore for EventfoldService {
         request: tonic::Request<proto::ReadStreamRequest>,
     ) -> Result<tonic::Response<proto::ReadStreamResponse>, tonic::Status> {
         counter!("eventfold_reads_total", "rpc" => "read_stream").increment(1);
+        let stream_id = parse_uuid(&request.get_ref().stream_id, "stream_id")?;
+        self.authorize(&request, Operation::ReadStream, Some(stream_id))?;
         let req = request.into_inner();
 
-        let stream_id = parse_uuid(&req.stream_id, "stream_id")?;
-
         let events = self
             .read_index
             .read_stream(stream_id, req.from_version, req.max_count)
@@ -141,14 +315,18 @@ impl proto::event_store_server::EventStore for EventfoldService {
     /// Read events from the global log.
     ///
     /// Delegates to the read index (infallible); returns all matching events.
+    /// With `from_timestamp`, starts no earlier than the first event recorded
+    /// at that time.
     async fn read_all(
         &self,
         request: tonic::Request<proto::ReadAllRequest>,
     ) -> Result<tonic::Response<proto::ReadAllResponse>, tonic::Status> {
         counter!("eventfold_reads_total", "rpc" => "read_all").increment(1);
+        self.authorize(&request, Operation::ReadAll, None)?;
         let req = request.into_inner();
 
-        let events = self.read_index.read_all(req.from_position, req.max_count);
+        let from_position = start_position(&self.read_index, req.from_position, req.from_timestamp);
+        let events = self.read_index.read_all(from_position, req.max_count);
 
         let proto_events = events.iter().map(recorded_to_proto).collect();
         Ok(tonic::Response::new(proto::ReadAllResponse {
@@ -166,6 +344,7 @@ impl proto::event_store_server::EventStore for EventfoldService {
         &self,
         request: tonic::Request<proto::SubscribeAllRequest>,
     ) -> Result<tonic::Response<Self::SubscribeAllStream>, tonic::Status> {
+        self.authorize(&request, Operation::SubscribeAll, None)?;
         let req = request.into_inner();
 
         // Clone owned handles so the returned stream is `'static` (not borrowing
@@ -178,7 +357,9 @@ impl proto::event_store_server::EventStore for EventfoldService {
             // when the client disconnects mid-stream).
             let _guard = SubscriptionGauge::new();
 
-            let inner = crate::subscribe_all(read_index, &broker, req.from_position).await;
+            let from_position =
+                start_position(&read_index, req.from_position, req.from_timestamp);
+            let inner = crate::subscribe_all(read_index, &broker, from_position).await;
             tokio::pin!(inner);
 
             loop {
@@ -224,10 +405,10 @@ impl proto::event_store_server::EventStore for EventfoldService {
         &self,
         request: tonic::Request<proto::SubscribeStreamRequest>,
     ) -> Result<tonic::Response<Self::SubscribeStreamStream>, tonic::Status> {
+        let stream_id = parse_uuid(&request.get_ref().stream_id, "stream_id")?;
+        self.authorize(&request, Operation::SubscribeStream, Some(stream_id))?;
         let req = request.into_inner();
 
-        let stream_id = parse_uuid(&req.stream_id, "stream_id")?;
-
         // Clone owned handles so the returned stream is `'static`.
         let read_index = self.read_index.clone();
         let broker = self.broker.clone();
@@ -280,8 +461,9 @@ impl proto::event_store_server::EventStore for EventfoldService {
     /// Returns an empty list on an empty store (never an error status).
     async fn list_streams(
         &self,
-        _request: tonic::Request<proto::ListStreamsRequest>,
+        request: tonic::Request<proto::ListStreamsRequest>,
     ) -> Result<tonic::Response<proto::ListStreamsResponse>, tonic::Status> {
+        self.authorize(&request, Operation::ListStreams, None)?;
         let streams = self
             .read_index
             .list_streams()
@@ -290,6 +472,101 @@ impl proto::event_store_server::EventStore for EventfoldService {
             .collect();
         Ok(tonic::Response::new(proto::ListStreamsResponse { streams }))
     }
+
+    async fn list_holes(
+        &self,
+        request: tonic::Request<proto::ListHolesRequest>,
+    ) -> Result<tonic::Response<proto::ListHolesResponse>, tonic::Status> {
+        self.authorize(&request, Operation::ListHoles, None)?;
+        let holes = self.read_index.holes();
+        let missing_positions = holes.iter().map(|h| h.missing_count).sum();
+        let holes = holes.into_iter().map(log_hole_to_proto).collect();
+        Ok(tonic::Response::new(proto::ListHolesResponse {
+            holes,
+            missing_positions,
+        }))
+    }
+
+    /// Find the first global position recorded at or after a timestamp.
+    ///
+    /// Answered from the in-memory timestamp index in O(log n).
+    async fn get_position_at(
+        &self,
+        request: tonic::Request<proto::GetPositionAtRequest>,
+    ) -> Result<tonic::Response<proto::GetPositionAtResponse>, tonic::Status> {
+        counter!("eventfold_reads_total", "rpc" => "get_position_at").increment(1);
+        self.authorize(&request, Operation::GetPositionAt, None)?;
+        let position = self.read_index.position_at(request.get_ref().timestamp);
+        Ok(tonic::Response::new(proto::GetPositionAtResponse {
+            position,
+        }))
+    }
+
+    type BackupStream = BackupStream;
+
+    /// Stream a consistent copy of the log.
+    ///
+    /// Picks the cut up front, then streams exactly that many bytes of the log
+    /// file in `BACKUP_CHUNK_SIZE` chunks followed by a `BackupSummary`.
+    /// Appends continue while the copy streams; they land past the cut.
+    async fn backup(
+        &self,
+        request: tonic::Request<proto::BackupRequest>,
+    ) -> Result<tonic::Response<Self::BackupStream>, tonic::Status> {
+        self.authorize(&request, Operation::Backup, None)?;
+        let Some(source) = self.backup.clone() else {
+            return Err(tonic::Status::unimplemented(
+                "backups are not enabled on this server",
+            ));
+        };
+        let to_position = request.into_inner().to_position;
+
+        // Cutting at a position may scan the log file; keep that off the
+        // async runtime.
+        let snapshot = {
+            let source = source.clone();
+            tokio::task::spawn_blocking(move || source.snapshot(to_position))
+                .await
+                .map_err(|e| tonic::Status::internal(format!("backup task failed: {e}")))?
+                .map_err(error_to_status)?
+        };
+        counter!("eventfold_backups_total").increment(1);
+
+        let stream = async_stream::stream! {
+            use tokio::io::AsyncReadExt;
+
+            let file = match tokio::fs::File::open(source.path()).await {
+                Ok(file) => file,
+                Err(e) => {
+                    yield Err(error_to_status(Error::Io(e)));
+                    return;
+                }
+            };
+            let mut reader = file.take(snapshot.len);
+            let mut buf = vec![0u8; BACKUP_CHUNK_SIZE];
+            loop {
+                match reader.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(n) => yield Ok(proto::BackupChunk {
+                        content: Some(proto::backup_chunk::Content::Data(buf[..n].to_vec())),
+                    }),
+                    Err(e) => {
+                        yield Err(error_to_status(Error::Io(e)));
+                        return;
+                    }
+                }
+            }
+            yield Ok(proto::BackupChunk {
+                content: Some(proto::backup_chunk::Content::Summary(proto::BackupSummary {
+                    size_bytes: snapshot.len,
+                    next_position: snapshot.next_position,
+                    holes: snapshot.holes.into_iter().map(log_hole_to_proto).collect(),
+                })),
+            });
+        };
+
+        Ok(tonic::Response::new(Box::pin(stream)))
+    }
 }
 
 /// RAII guard that increments the `eventfold_subscriptions_active` gauge on
@@ -329,6 +606,7 @@ impl Drop for SubscriptionGauge {
 /// | `InvalidHeader`        | `DATA_LOSS`          |
 /// | `EventTooLarge`        | `INVALID_ARGUMENT`   |
 /// | `InvalidArgument`      | `INVALID_ARGUMENT`   |
+/// | `SubscriptionLagged`   | `ABORTED`            |
 pub fn error_to_status(err: Error) -> tonic::Status {
     let message = err.to_string();
     match err {
@@ -339,6 +617,17 @@ pub fn error_to_status(err: Error) -> tonic::Status {
         Error::InvalidHeader(_) => tonic::Status::data_loss(message),
         Error::EventTooLarge { .. } => tonic::Status::invalid_argument(message),
         Error::InvalidArgument(_) => tonic::Status::invalid_argument(message),
+        Error::ReadOnly => tonic::Status::failed_precondition(message),
+        Error::LogLocked { .. } => tonic::Status::unavailable(message),
+        Error::InvalidDataDir { .. } => tonic::Status::internal(message),
+        Error::DeadlineExceeded | Error::DeadlineExceededInFlight => {
+            tonic::Status::deadline_exceeded(message)
+        }
+        Error::WriterFailed(_) => tonic::Status::unavailable(message),
+        Error::DiskSpaceLow { .. } => tonic::Status::resource_exhausted(message),
+        Error::InvalidKeyFile { .. } => tonic::Status::internal(message),
+        Error::PermissionDenied(_) => tonic::Status::permission_denied(message),
+        Error::SubscriptionLagged => tonic::Status::aborted(message),
     }
 }
 
@@ -362,6 +651,54 @@ pub fn parse_uuid(s: &str, field_name: &str) -> Result<Uuid, tonic::Status> {
         .map_err(|e| tonic::Status::invalid_argument(format!("invalid {field_name}: {e}")))
 }
 
+/// Turn the `grpc-timeout` request header into an absolute writer deadline.
+///
+/// The header is 1-8 ASCII digits followed by a unit: `H` (hours), `M`
+/// (minutes), `S` (seconds), `m` (milliseconds), `u` (microseconds), or `n`
+/// (nanoseconds). The deadline is measured from when this is called and
+/// shortened by 20 ms, or a quarter of the timeout if that is less, so it
+/// expires before tonic's own timer for the same header.
+///
+/// # Arguments
+///
+/// * `metadata` - The request metadata.
+///
+/// # Returns
+///
+/// `None` if the client set no deadline.
+///
+/// # Errors
+///
+/// Returns `tonic::Status` with `INVALID_ARGUMENT` if the header is malformed.
+pub fn grpc_deadline(
+    metadata: &tonic::metadata::MetadataMap,
+) -> Result<Option<tokio::time::Instant>, tonic::Status> {
+    let Some(value) = metadata.get("grpc-timeout") else {
+        return Ok(None);
+    };
+    let invalid = || tonic::Status::invalid_argument("malformed grpc-timeout header");
+    let value = value.to_str().map_err(|_| invalid())?;
+    if value.len() < 2 || value.len() > 9 {
+        return Err(invalid());
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let amount: u64 = digits.parse().map_err(|_| invalid())?;
+    let timeout = match unit {
+        "H" => std::time::Duration::from_secs(amount * 3600),
+        "M" => std::time::Duration::from_secs(amount * 60),
+        "S" => std::time::Duration::from_secs(amount),
+        "m" => std::time::Duration::from_millis(amount),
+        "u" => std::time::Duration::from_micros(amount),
+        "n" => std::time::Duration::from_nanos(amount),
+        _ => return Err(invalid()),
+    };
+    let margin = DEADLINE_MARGIN.min(timeout / 4);
+    Ok(Some(tokio::time::Instant::now() + (timeout - margin)))
+}
+
 /// Convert a protobuf `ExpectedVersion` to the domain [`ExpectedVersion`] type.
 ///
 /// Returns `INVALID_ARGUMENT` if the outer `Option` is `None` (field not set)
@@ -396,7 +733,8 @@ pub fn proto_to_expected_version(
 /// Convert a domain [`RecordedEvent`] to the protobuf `RecordedEvent` type.
 ///
 /// UUIDs are serialized as hyphenated lowercase strings. `Bytes` fields are
-/// converted to `Vec<u8>`.
+/// converted to `Vec<u8>`. Unknown audit fields become empty strings, and
+/// `audited` records whether the event carries [`AuditInfo`] at all.
 ///
 /// # Arguments
 ///
@@ -415,6 +753,17 @@ pub fn recorded_to_proto(e: &RecordedEvent) -> proto::RecordedEvent {
         metadata: e.metadata.to_vec(),
         payload: e.payload.to_vec(),
         recorded_at: e.recorded_at,
+        principal: e
+            .audit
+            .as_ref()
+            .and_then(|a| a.principal.clone())
+            .unwrap_or_default(),
+        client_addr: e
+            .audit
+            .as_ref()
+            .and_then(|a| a.client_addr.clone())
+            .unwrap_or_default(),
+        audited: e.audit.is_some(),
     }
 }
 
@@ -437,6 +786,24 @@ pub fn stream_info_to_proto(s: StreamInfo) -> proto::StreamInfo {
     }
 }
 
+/// Convert a domain [`LogHole`] to the protobuf `LogHole` type.
+///
+/// # Arguments
+///
+/// * `h` - The domain `LogHole` to convert.
+///
+/// # Returns
+///
+/// The corresponding protobuf `LogHole`.
+pub fn log_hole_to_proto(h: LogHole) -> proto::LogHole {
+    proto::LogHole {
+        first_position: h.first_position,
+        missing_count: h.missing_count,
+        start_offset: h.start_offset,
+        end_offset: h.end_offset,
+    }
+}
+
 /// Convert a protobuf `ProposedEvent` to the domain [`ProposedEvent`] type.
 ///
 /// Validates that `event_id` is a valid UUID string. The `event_type`, `metadata`,
@@ -540,6 +907,108 @@ mod tests {
         assert!(status.message().contains("bad field"));
     }
 
+    #[test]
+    fn error_to_status_read_only() {
+        let status = error_to_status(Error::ReadOnly);
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert!(status.message().contains("read-only"));
+    }
+
+    #[test]
+    fn error_to_status_deadline_exceeded() {
+        let status = error_to_status(Error::DeadlineExceeded);
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert!(status.message().contains("nothing was committed"));
+
+        let status = error_to_status(Error::DeadlineExceededInFlight);
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert!(status.message().contains("outcome is unknown"));
+    }
+
+    #[test]
+    fn error_to_status_writer_failed() {
+        let status = error_to_status(Error::WriterFailed("fsync failed".into()));
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert!(status.message().contains("fsync failed"));
+    }
+
+    #[test]
+    fn error_to_status_disk_space_low() {
+        let status = error_to_status(Error::DiskSpaceLow {
+            free: 1024,
+            min_free: 4096,
+        });
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(status.message().contains("low watermark"));
+    }
+
+    #[test]
+    fn error_to_status_invalid_key_file() {
+        let status = error_to_status(Error::InvalidKeyFile {
+            path: "/keys/jwks.json".into(),
+            detail: "no usable signing keys".into(),
+        });
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert!(status.message().contains("/keys/jwks.json"));
+    }
+
+    #[test]
+    fn error_to_status_permission_denied() {
+        let status = error_to_status(Error::PermissionDenied("Append not permitted".into()));
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(status.message().contains("Append not permitted"));
+    }
+
+    #[test]
+    fn error_to_status_subscription_lagged() {
+        let status = error_to_status(Error::SubscriptionLagged);
+        assert_eq!(status.code(), tonic::Code::Aborted);
+        assert!(status.message().contains("re-subscribe"));
+    }
+
+    // -- grpc_deadline tests --
+
+    #[test]
+    fn grpc_deadline_absent_is_none() {
+        let metadata = tonic::metadata::MetadataMap::new();
+        assert_eq!(grpc_deadline(&metadata).expect("should parse"), None);
+    }
+
+    #[test]
+    fn grpc_deadline_parses_units() {
+        for (header, expected) in [
+            ("2S", std::time::Duration::from_secs(2) - DEADLINE_MARGIN),
+            (
+                "1500m",
+                std::time::Duration::from_millis(1500) - DEADLINE_MARGIN,
+            ),
+            ("1M", std::time::Duration::from_secs(60) - DEADLINE_MARGIN),
+            ("1H", std::time::Duration::from_secs(3600) - DEADLINE_MARGIN),
+            // Short timeouts give up at most a quarter.
+            ("40m", std::time::Duration::from_millis(30)),
+        ] {
+            let mut metadata = tonic::metadata::MetadataMap::new();
+            metadata.insert("grpc-timeout", header.parse().expect("valid header"));
+            let before = tokio::time::Instant::now();
+            let deadline = grpc_deadline(&metadata)
+                .expect("should parse")
+                .expect("should have deadline");
+            let after = tokio::time::Instant::now();
+            assert!(deadline >= before + expected, "{header}");
+            assert!(deadline <= after + expected, "{header}");
+        }
+    }
+
+    #[test]
+    fn grpc_deadline_rejects_malformed_header() {
+        for header in ["", "S", "10", "10x", "123456789S", "-1S"] {
+            let mut metadata = tonic::metadata::MetadataMap::new();
+            metadata.insert("grpc-timeout", header.parse().expect("valid header"));
+            let status = grpc_deadline(&metadata).expect_err(header);
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{header}");
+        }
+    }
+
     // -- parse_uuid tests --
 
     #[test]
@@ -648,6 +1117,7 @@ mod tests {
             event_type: "PaymentReceived".to_string(),
             metadata: Bytes::from_static(b"meta"),
             payload: Bytes::from_static(b"payload"),
+            audit: None,
         };
 
         let proto_event = recorded_to_proto(&domain);
@@ -674,6 +1144,7 @@ mod tests {
             event_type: "TimestampTest".to_string(),
             metadata: Bytes::from_static(b"meta"),
             payload: Bytes::from_static(b"payload"),
+            audit: None,
         };
 
         let proto_event = recorded_to_proto(&domain);
@@ -684,6 +1155,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recorded_to_proto_maps_audit() {
+        let mut domain = RecordedEvent {
+            event_id: Uuid::new_v4(),
+            stream_id: Uuid::new_v4(),
+            stream_version: 0,
+            global_position: 0,
+            recorded_at: 0,
+            event_type: "AuditTest".to_string(),
+            metadata: Bytes::new(),
+            payload: Bytes::new(),
+            audit: None,
+        };
+        let proto_event = recorded_to_proto(&domain);
+        assert!(!proto_event.audited);
+        assert!(proto_event.principal.is_empty());
+
+        domain.audit = Some(AuditInfo {
+            principal: Some("svc".into()),
+            client_addr: Some("127.0.0.1:9000".into()),
+        });
+        let proto_event = recorded_to_proto(&domain);
+        assert!(proto_event.audited);
+        assert_eq!(proto_event.principal, "svc");
+        assert_eq!(proto_event.client_addr, "127.0.0.1:9000");
+    }
+
     // -- metrics tests --
 
     /// Helper: ensure the global metrics recorder is installed and return its handle.
@@ -773,6 +1271,7 @@ mod tests {
             let ra_req = tonic::Request::new(proto::ReadAllRequest {
                 from_position: 0,
                 max_count: 100,
+                from_timestamp: None,
             });
             service
                 .read_all(ra_req)
@@ -816,7 +1315,10 @@ mod tests {
             parse_metric_value(&before, "eventfold_subscriptions_active ").unwrap_or(0.0);
 
         // Start a subscribe_all call. The returned stream increments the gauge.
-        let req = tonic::Request::new(proto::SubscribeAllRequest { from_position: 0 });
+        let req = tonic::Request::new(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        });
         let response = service
             .subscribe_all(req)
             .await
@@ -873,6 +1375,131 @@ mod tests {
         assert_eq!(proto_result.latest_version, 4);
     }
 
+    #[test]
+    fn log_hole_to_proto_maps_all_fields() {
+        let hole = LogHole {
+            first_position: 7,
+            missing_count: 3,
+            start_offset: 120,
+            end_offset: 410,
+        };
+        let proto_result = log_hole_to_proto(hole);
+        assert_eq!(proto_result.first_position, 7);
+        assert_eq!(proto_result.missing_count, 3);
+        assert_eq!(proto_result.start_offset, 120);
+        assert_eq!(proto_result.end_offset, 410);
+    }
+
+    #[tokio::test]
+    async fn backup_without_source_returns_unimplemented() {
+        use crate::proto::event_store_server::EventStore;
+
+        let (service, _dir) = temp_service();
+        let result = service
+            .backup(tonic::Request::new(proto::BackupRequest {
+                to_position: None,
+            }))
+            .await;
+        match result {
+            Err(status) => assert_eq!(status.code(), tonic::Code::Unimplemented),
+            Ok(_) => panic!("expected Unimplemented without a backup source"),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_start_at_timestamp() {
+        use crate::proto::event_store_server::EventStore;
+        use futures::StreamExt;
+
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let mut store =
+            crate::store::Store::open(&dir.path().join("events.log")).expect("open should succeed");
+        for recorded_at in [1_000, 2_000, 3_000] {
+            store
+                .append(
+                    Uuid::new_v4(),
+                    ExpectedVersion::NoStream,
+                    recorded_at,
+                    vec![ProposedEvent {
+                        event_id: Uuid::new_v4(),
+                        event_type: "Tick".to_string(),
+                        metadata: Bytes::new(),
+                        payload: Bytes::new(),
+                    }],
+                )
+                .expect("append should succeed");
+        }
+        let broker = crate::broker::Broker::new(1024);
+        let dedup_cap = std::num::NonZeroUsize::new(128).expect("nonzero");
+        let (writer_handle, read_index, _join_handle) =
+            crate::writer::spawn_writer(store, 64, broker.clone(), dedup_cap);
+        let service = EventfoldService::new(writer_handle, read_index, broker);
+
+        let position = service
+            .get_position_at(tonic::Request::new(proto::GetPositionAtRequest {
+                timestamp: 1_500,
+            }))
+            .await
+            .expect("get_position_at should return Ok")
+            .into_inner()
+            .position;
+        assert_eq!(position, 1);
+
+        let read = |from_position, from_timestamp| {
+            service.read_all(tonic::Request::new(proto::ReadAllRequest {
+                from_position,
+                max_count: 10,
+                from_timestamp,
+            }))
+        };
+        let positions = |response: tonic::Response<proto::ReadAllResponse>| -> Vec<u64> {
+            response
+                .into_inner()
+                .events
+                .iter()
+                .map(|e| e.global_position)
+                .collect()
+        };
+        assert_eq!(positions(read(0, Some(2_000)).await.unwrap()), [1, 2]);
+        // The later of the two starting points wins.
+        assert_eq!(positions(read(2, Some(1_000)).await.unwrap()), [2]);
+        assert!(positions(read(0, Some(9_000)).await.unwrap()).is_empty());
+
+        let mut subscription = service
+            .subscribe_all(tonic::Request::new(proto::SubscribeAllRequest {
+                from_position: 0,
+                from_timestamp: Some(3_000),
+            }))
+            .await
+            .expect("subscribe_all should return Ok")
+            .into_inner();
+        let first = subscription
+            .next()
+            .await
+            .expect("stream should yield")
+            .expect("item should be Ok");
+        match first.content {
+            Some(proto::subscribe_response::Content::Event(event)) => {
+                assert_eq!(event.global_position, 2);
+            }
+            other => panic!("expected an event, got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn list_holes_undamaged_store_returns_empty() {
+        use crate::proto::event_store_server::EventStore;
+
+        let (service, _dir) = temp_service();
+        let response = service
+            .list_holes(tonic::Request::new(proto::ListHolesRequest {}))
+            .await
+            .expect("list_holes should return Ok")
+            .into_inner();
+        assert!(response.holes.is_empty());
+        assert_eq!(response.missing_positions, 0);
+    }
+
     #[tokio::test]
     async fn list_streams_empty_store_returns_ok_with_empty_list() {
         use crate::proto::event_store_server::EventStore;
@@ -934,6 +1561,87 @@ mod tests {
         assert_eq!(info.latest_version, 1);
     }
 
+    #[tokio::test]
+    async fn audited_append_records_principal() {
+        use crate::proto::event_store_server::EventStore;
+
+        let (service, _dir) = temp_service();
+        let service = service.with_audit();
+        let stream_id = Uuid::new_v4();
+        let mut append_req = tonic::Request::new(proto::AppendRequest {
+            stream_id: stream_id.to_string(),
+            expected_version: Some(proto::ExpectedVersion {
+                kind: Some(proto::expected_version::Kind::Any(proto::Empty {})),
+            }),
+            events: vec![proto::ProposedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                event_type: "TestEvent".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            }],
+        });
+        append_req
+            .extensions_mut()
+            .insert(AuthenticatedSubject("billing-service".into()));
+        service
+            .append(append_req)
+            .await
+            .expect("append should succeed");
+
+        let events = service
+            .read_index
+            .read_stream(stream_id, 0, 10)
+            .expect("stream should exist");
+        // No connection, so no client address.
+        assert_eq!(
+            events[0].audit,
+            Some(AuditInfo {
+                principal: Some("billing-service".into()),
+                client_addr: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn unaudited_append_records_nothing() {
+        use crate::proto::event_store_server::EventStore;
+
+        let (service, _dir) = temp_service();
+        let stream_id = Uuid::new_v4();
+        let mut append_req = tonic::Request::new(proto::AppendRequest {
+            stream_id: stream_id.to_string(),
+            expected_version: Some(proto::ExpectedVersion {
+                kind: Some(proto::expected_version::Kind::Any(proto::Empty {})),
+            }),
+            events: vec![proto::ProposedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                event_type: "TestEvent".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            }],
+        });
+        append_req
+            .extensions_mut()
+            .insert(AuthenticatedSubject("billing-service".into()));
+        service
+            .append(append_req)
+            .await
+            .expect("append should succeed");
+
+        let events = service
+            .read_index
+            .read_stream(stream_id, 0, 10)
+            .expect("stream should exist");
+        assert_eq!(events[0].audit, None);
+    }
+
+    #[test]
+    fn truncate_utf8_stops_at_char_boundary() {
+        assert_eq!(truncate_utf8("short", 256), "short");
+        // "é" is two bytes; cutting at 3 would split the second one.
+        assert_eq!(truncate_utf8("éé", 3), "é");
+    }
+
     #[tokio::test]
     #[serial]
     async fn subscribe_stream_increments_and_decrements_gauge() {
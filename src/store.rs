@@ -7,19 +7,27 @@
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use uuid::Uuid;
 
+use crate::backend::{FileBackend, MemoryBackend, StorageBackend};
+use crate::backup::BackupSource;
 use crate::codec::{self, DecodeOutcome};
 use crate::error::Error;
+use crate::lock::LogLock;
 use crate::types::{
-    ExpectedVersion, MAX_EVENT_SIZE, MAX_EVENT_TYPE_LEN, ProposedEvent, RecordedEvent,
+    AuditInfo, ExpectedVersion, LogHole, MAX_EVENT_SIZE, MAX_EVENT_TYPE_LEN, ProposedEvent,
+    RecordedEvent,
 };
 
 /// Size of the file header in bytes (magic + format version).
-const HEADER_SIZE: usize = 8;
+pub(crate) const HEADER_SIZE: usize = 8;
+
+/// Suffix appended to the log file name to form the holes sidecar path.
+const HOLES_SUFFIX: &str = ".holes";
 
 /// Check whether a valid batch header exists in `data` after byte offset `start`.
 ///
@@ -36,22 +44,284 @@ fn has_valid_batch_after(data: &[u8], start: usize) -> bool {
     false
 }
 
+/// Find the first fully valid batch in `data` after byte offset `start`.
+///
+/// Unlike `has_valid_batch_after`, which only looks for a decodable batch
+/// header, this decodes every record and the footer and verifies the batch
+/// CRC, so a stray header magic inside the damaged region is not mistaken for
+/// the resume point.
+///
+/// # Returns
+///
+/// `Some((offset, first_global_pos))` for the first valid batch, or `None` if
+/// no complete valid batch follows `start`.
+pub fn next_valid_batch(data: &[u8], start: usize) -> Option<(usize, u64)> {
+    'probe: for probe in (start + 1)..data.len() {
+        let Ok(DecodeOutcome::Complete {
+            value: header,
+            consumed,
+        }) = codec::decode_batch_header(&data[probe..])
+        else {
+            continue;
+        };
+        let mut offset = probe + consumed;
+        for _ in 0..header.record_count {
+            match codec::decode_record(&data[offset..]) {
+                Ok(DecodeOutcome::Complete { consumed, .. }) => offset += consumed,
+                _ => continue 'probe,
+            }
+        }
+        let Ok(DecodeOutcome::Complete { value: footer, .. }) =
+            codec::decode_batch_footer(&data[offset..])
+        else {
+            continue;
+        };
+        if footer.batch_crc == crc32fast::hash(&data[probe..offset]) {
+            return Some((probe, header.first_global_pos));
+        }
+    }
+    None
+}
+
+/// Returns the path of the holes sidecar file for the log at `path`.
+///
+/// The sidecar lives next to the log with `.holes` appended to the
+/// file name, e.g. `events.log.holes`.
+pub fn holes_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(HOLES_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Load the holes recorded by earlier repair-mode opens.
+///
+/// The sidecar is a text file with one hole per line:
+/// `start_offset end_offset first_position missing_count`. Blank lines and
+/// lines starting with `#` are ignored. A missing file means no holes.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the file cannot be read or a line is malformed.
+pub fn read_holes(path: &Path) -> Result<Vec<LogHole>, Error> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut holes = Vec::new();
+    for (lineno, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<u64> = line
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|_| malformed_holes(path, lineno))?;
+        let [start_offset, end_offset, first_position, missing_count] = fields[..] else {
+            return Err(malformed_holes(path, lineno));
+        };
+        holes.push(LogHole {
+            first_position,
+            missing_count,
+            start_offset,
+            end_offset,
+        });
+    }
+    holes.sort_by_key(|h| h.start_offset);
+    Ok(holes)
+}
+
+/// Build the error returned for an unparseable holes sidecar line.
+fn malformed_holes(path: &Path, lineno: usize) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("malformed hole record at {}:{}", path.display(), lineno + 1),
+    ))
+}
+
+/// Durably replace the holes sidecar with `holes`.
+///
+/// Writes to a temporary file, fsyncs it, renames it over the sidecar, and
+/// fsyncs the parent directory so the marker survives a crash.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if any write, rename, or fsync fails.
+pub(crate) fn write_holes(path: &Path, holes: &[LogHole]) -> Result<(), Error> {
+    let mut contents = String::from("# start_offset end_offset first_position missing_count\n");
+    for hole in holes {
+        contents.push_str(&format!(
+            "{} {} {} {}\n",
+            hole.start_offset, hole.end_offset, hole.first_position, hole.missing_count
+        ));
+    }
+
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    crate::data_dir::sync_parent(path)
+}
+
+/// Decode and verify the batch starting at byte offset `start` of `data`.
+///
+/// Decodes the batch header, `record_count` records, and the footer, then
+/// checks the batch CRC over the header and record bytes.
+///
+/// # Returns
+///
+/// `Ok((events, end_offset))` for a valid batch, or `Err(reason)` naming the
+/// damage: an incomplete or unrecognized header, record, or footer, or a CRC
+/// mismatch.
+///
+/// # Errors
+///
+/// Returns any decode error other than [`Error::CorruptRecord`].
+fn decode_valid_batch(
+    data: &[u8],
+    start: usize,
+) -> Result<Result<(Vec<RecordedEvent>, usize), &'static str>, Error> {
+    let mut offset = start;
+
+    // Step 1: Decode batch header.
+    let header = match codec::decode_batch_header(&data[offset..]) {
+        Ok(DecodeOutcome::Complete { value, consumed }) => {
+            offset += consumed;
+            value
+        }
+        Ok(DecodeOutcome::Incomplete) => return Ok(Err("incomplete batch header")),
+        Err(Error::CorruptRecord { .. }) => return Ok(Err("bad batch header magic")),
+        Err(e) => return Err(e),
+    };
+
+    // Step 2: Decode record_count records. The count comes from disk and may
+    // be garbage, so the vector grows with the records actually decoded.
+    let mut events = Vec::new();
+    for _ in 0..header.record_count {
+        match codec::decode_record(&data[offset..]) {
+            Ok(DecodeOutcome::Complete { value, consumed }) => {
+                offset += consumed;
+                events.push(value);
+            }
+            Ok(DecodeOutcome::Incomplete) => return Ok(Err("incomplete record")),
+            Err(Error::CorruptRecord { .. }) => return Ok(Err("corrupt record")),
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Step 3: Decode batch footer.
+    let footer = match codec::decode_batch_footer(&data[offset..]) {
+        Ok(DecodeOutcome::Complete { value, consumed }) => {
+            offset += consumed;
+            value
+        }
+        Ok(DecodeOutcome::Incomplete) => return Ok(Err("incomplete batch footer")),
+        Err(Error::CorruptRecord { .. }) => return Ok(Err("bad batch footer magic")),
+        Err(e) => return Err(e),
+    };
+
+    // Step 4: Verify batch CRC over header + record bytes.
+    let header_plus_records = &data[start..offset - codec::BATCH_FOOTER_SIZE];
+    if footer.batch_crc != crc32fast::hash(header_plus_records) {
+        return Ok(Err("batch CRC mismatch"));
+    }
+    Ok(Ok((events, offset)))
+}
+
+/// Skip the damaged batch at byte offset `start` if valid data follows it.
+///
+/// Every kind of batch damage -- a bad header magic, an unreadable or
+/// corrupt record, a bad footer, or a batch CRC mismatch -- is handled here,
+/// so all of them get the same treatment once a valid batch header follows:
+/// without `options.repair` recovery fails with [`Error::CorruptRecord`], and
+/// in repair mode the range up to the next fully valid batch is recorded as a
+/// [`LogHole`] in `known_holes` (and the sidecar, unless read-only).
+///
+/// # Returns
+///
+/// `Some(resume_offset)` to continue decoding at after a repair, or `None`
+/// if nothing valid follows, so the damage is a torn tail to truncate.
+///
+/// # Errors
+///
+/// Returns [`Error::CorruptRecord`] for mid-file damage that is not
+/// repaired, and [`Error::Io`] if the sidecar cannot be written.
+fn skip_mid_file_damage(
+    data: &[u8],
+    start: usize,
+    reason: &str,
+    log: &mut EventLog,
+    known_holes: &mut Vec<LogHole>,
+    holes_file: &Path,
+    options: &StoreOptions,
+) -> Result<Option<usize>, Error> {
+    if !has_valid_batch_after(data, start) {
+        return Ok(None);
+    }
+    let first_position = log.next_position();
+    let resume = if options.repair {
+        next_valid_batch(data, start).filter(|&(_, pos)| pos >= first_position)
+    } else {
+        None
+    };
+    let Some((resume_offset, resume_position)) = resume else {
+        return Err(Error::CorruptRecord {
+            position: first_position,
+            detail: format!(
+                "mid-file corruption ({reason}) at byte offset {start}: valid batch follows \
+                 corrupt data"
+            ),
+        });
+    };
+    let hole = LogHole {
+        first_position,
+        missing_count: resume_position - first_position,
+        start_offset: start as u64,
+        end_offset: resume_offset as u64,
+    };
+    tracing::warn!(
+        start_offset = hole.start_offset,
+        end_offset = hole.end_offset,
+        first_position = hole.first_position,
+        missing_count = hole.missing_count,
+        "repair: skipping corrupt byte range {}..{} ({reason})",
+        hole.start_offset,
+        hole.end_offset
+    );
+    known_holes.push(hole);
+    known_holes.sort_by_key(|h| h.start_offset);
+    if !options.read_only {
+        write_holes(holes_file, known_holes)?;
+    }
+    log.holes.push(hole);
+    Ok(Some(resume_offset))
+}
+
 /// Truncate the log file to a given offset, fsync, and return a `Store` with
 /// the events recovered so far.
 ///
-/// This is the common recovery path for all partial/corrupt batch scenarios:
-/// incomplete header, incomplete record, missing footer, or CRC mismatch.
+/// This is the recovery path for damage at the tail of the log, with no valid
+/// batch after it: an incomplete header, an incomplete or corrupt record, a
+/// missing or bad footer, or a CRC mismatch in the last batch.
 ///
 /// # Arguments
 ///
 /// * `path` - Path to the log file.
 /// * `truncate_to` - Byte offset to truncate the file to.
-/// * `events` - Events recovered from prior complete batches.
-/// * `streams` - Stream index recovered from prior complete batches.
+/// * `log` - Events, stream index, and holes recovered from prior complete
+///   batches.
+/// * `options` - Options the store is being opened with.
 ///
 /// # Returns
 ///
-/// A `Store` with the recovered events and the file truncated.
+/// A `Store` with the recovered events and the file truncated. In read-only
+/// mode the file is left untouched and the store ends at `truncate_to`.
 ///
 /// # Errors
 ///
@@ -59,17 +329,120 @@ fn has_valid_batch_after(data: &[u8], start: usize) -> bool {
 fn truncate_and_return(
     path: &Path,
     truncate_to: usize,
-    events: Vec<RecordedEvent>,
-    streams: HashMap<Uuid, Vec<u64>>,
+    mut log: EventLog,
+    options: &StoreOptions,
 ) -> Result<Store, Error> {
+    log.log_len = truncate_to as u64;
+    if options.read_only {
+        // Leave the torn tail on disk; the store simply ends before it.
+        let file = File::open(path)?;
+        return Store::from_parts(file, path, log, options);
+    }
+
     let file = OpenOptions::new().read(true).write(true).open(path)?;
     file.set_len(truncate_to as u64)?;
     file.sync_all()?;
 
-    Ok(Store {
-        file,
-        log: Arc::new(RwLock::new(EventLog { events, streams })),
-    })
+    Store::from_parts(file, path, log, options)
+}
+
+/// Options controlling how [`Store::open_with_options`] opens the log.
+///
+/// `StoreOptions::default()` reproduces the behavior of [`Store::open`].
+#[derive(Debug, Clone, Default)]
+pub struct StoreOptions {
+    /// Skip damaged batch ranges in the middle of the log instead of refusing
+    /// to open. Each skipped range is recorded as a [`LogHole`] in a sidecar
+    /// file next to the log, so the positions it covered are permanently
+    /// reported as missing.
+    pub repair: bool,
+    /// How each appended batch is made durable before it is acknowledged.
+    pub durability: Durability,
+    /// Grow the log file in zero-filled extents of this many bytes
+    /// (`fallocate(2)` on Linux) instead of a few KB per append. Each batch
+    /// is then followed by a log end marker recording the logical end of the
+    /// log. Zero, the default, disables preallocation.
+    pub preallocate: u64,
+    /// Write and fsync the log through io_uring. Requires Linux and the
+    /// `io-uring` cargo feature; opening fails otherwise.
+    pub io_uring: bool,
+    /// Open the log without ever writing to it: the file is opened read-only,
+    /// a torn tail is ignored instead of truncated, repairs are not persisted
+    /// to the holes sidecar, and [`Store::append`] returns
+    /// [`Error::ReadOnly`]. Opening a path that does not exist fails.
+    pub read_only: bool,
+}
+
+/// Switch a file backend to the io_uring write path.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+fn enable_io_uring(backend: FileBackend) -> Result<FileBackend, Error> {
+    Ok(backend.with_io_uring()?)
+}
+
+/// Switch a file backend to the io_uring write path.
+#[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+fn enable_io_uring(_backend: FileBackend) -> Result<FileBackend, Error> {
+    Err(Error::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "io_uring write path requires Linux and the `io-uring` feature",
+    )))
+}
+
+/// How [`Store::append`] makes a batch durable before acknowledging it.
+///
+/// Anything weaker than the default trades crash safety for throughput and is
+/// meant for development, test, and ephemeral environments. Under the relaxed
+/// modes an acknowledged batch may be lost on power failure or kernel crash,
+/// but not on a clean shutdown: the writer task calls [`Store::sync`] before
+/// it exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// `fsync(2)` (`File::sync_all`) after every batch. Data and metadata are
+    /// durable before the append is acknowledged.
+    #[default]
+    FsyncPerBatch,
+    /// `fdatasync(2)` (`File::sync_data`) after every batch. Skips flushing
+    /// metadata that is not needed to read the data back.
+    Fdatasync,
+    /// Acknowledge after the write and fsync at most once per interval. Up to
+    /// one interval of acknowledged appends can be lost on a crash.
+    Interval(Duration),
+    /// Never fsync while running; the OS writes pages back when it chooses.
+    None,
+}
+
+impl Durability {
+    /// Returns the background sync interval, if this mode needs one.
+    ///
+    /// The writer task flushes on this cadence so that batches written under
+    /// [`Durability::Interval`] reach disk even when no further appends arrive.
+    pub fn sync_interval(&self) -> Option<Duration> {
+        match self {
+            Durability::Interval(interval) => Some(*interval),
+            _ => None,
+        }
+    }
+
+    /// Returns the mode's name as used in configuration and metrics labels.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Durability::FsyncPerBatch => "fsync-per-batch",
+            Durability::Fdatasync => "fdatasync",
+            Durability::Interval(_) => "interval",
+            Durability::None => "none",
+        }
+    }
+}
+
+impl std::fmt::Display for Durability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Durability::Interval(interval) => {
+                write!(f, "interval ({}ms)", interval.as_millis())
+            }
+            other => f.write_str(other.name()),
+        }
+    }
 }
 
 /// Thread-safe, read-optimized view of the event log.
@@ -81,11 +454,156 @@ fn truncate_and_return(
 #[derive(Debug)]
 pub struct EventLog {
     /// Global event log. Append-only -- new events are pushed to the end.
-    /// Index `i` = event at global position `i`.
+    /// Index `i` = event at global position `i`, less the number of positions
+    /// lost to `holes` before it.
     pub events: Vec<RecordedEvent>,
     /// Stream index. Maps stream ID to list of global positions.
-    /// Index `j` in the vec = event at stream version `j`.
+    /// Index `j` in the vec = event at stream version `j` unless a hole removed
+    /// earlier events from the stream.
     pub streams: HashMap<Uuid, Vec<u64>>,
+    /// Ranges of global positions skipped by repair-mode recovery, in
+    /// ascending order. Empty for an undamaged log.
+    pub holes: Vec<LogHole>,
+    /// Byte length of the log file covered by `events`: the end of the last
+    /// batch that has been fsynced and indexed. Bytes past this offset may
+    /// belong to an append that is still in flight.
+    pub log_len: u64,
+    /// Timestamp index, see [`EventLog::position_at`]. One
+    /// `(recorded_at, global_position)` entry for each event whose
+    /// `recorded_at` is later than every event before it, so both fields
+    /// ascend and a binary search finds the first event at or after a time.
+    pub time_index: Vec<(u64, u64)>,
+}
+
+impl EventLog {
+    /// Returns the global position the next appended event will receive.
+    ///
+    /// Equal to `events.len()` when the log has no holes.
+    pub fn next_position(&self) -> u64 {
+        let after_events = self.events.last().map_or(0, |e| e.global_position + 1);
+        let after_holes = self
+            .holes
+            .last()
+            .map_or(0, |h| h.first_position + h.missing_count);
+        after_events.max(after_holes)
+    }
+
+    /// Returns the latest `recorded_at` in the log, or 0 if it is empty.
+    ///
+    /// Timestamps ascend along the log, so this is the floor for the next
+    /// append's timestamp.
+    pub fn latest_recorded_at(&self) -> u64 {
+        self.time_index.last().map_or(0, |&(ts, _)| ts)
+    }
+
+    /// Returns the first global position recorded at or after `timestamp`
+    /// (Unix milliseconds), or [`EventLog::next_position`] if every event is
+    /// older.
+    ///
+    /// Should the clock step back between batches, events recorded before an
+    /// earlier, later-stamped one are not indexed, so the result is the first
+    /// position from which no indexed event is older than `timestamp`.
+    pub fn position_at(&self, timestamp: u64) -> u64 {
+        let index = self.time_index.partition_point(|&(ts, _)| ts < timestamp);
+        self.time_index
+            .get(index)
+            .map_or_else(|| self.next_position(), |&(_, position)| position)
+    }
+
+    /// Add `event` to the in-memory indexes. Events must arrive in global
+    /// position order.
+    fn push(&mut self, event: RecordedEvent) {
+        self.streams
+            .entry(event.stream_id)
+            .or_default()
+            .push(event.global_position);
+        if self
+            .time_index
+            .last()
+            .is_none_or(|&(ts, _)| event.recorded_at > ts)
+        {
+            self.time_index
+                .push((event.recorded_at, event.global_position));
+        }
+        self.events.push(event);
+    }
+
+    /// Number of global positions below `position` that were lost to holes.
+    fn missing_before(&self, position: u64) -> u64 {
+        self.holes
+            .iter()
+            .map(|h| {
+                h.missing_count
+                    .min(position.saturating_sub(h.first_position))
+            })
+            .sum()
+    }
+
+    /// Index into `events` of the first event at or after `position`.
+    fn index_of(&self, position: u64) -> usize {
+        let index = position - self.missing_before(position);
+        index.min(self.events.len() as u64) as usize
+    }
+
+    /// Returns the event at `position`, or `None` if the position is in a
+    /// hole or beyond the end of the log.
+    pub fn get(&self, position: u64) -> Option<&RecordedEvent> {
+        self.events
+            .get(self.index_of(position))
+            .filter(|e| e.global_position == position)
+    }
+
+    /// Returns the current version of a stream, or `None` if the stream does
+    /// not exist.
+    pub fn stream_version(&self, stream_id: &Uuid) -> Option<u64> {
+        let &last = self.streams.get(stream_id)?.last()?;
+        self.get(last).map(|e| e.stream_version)
+    }
+
+    /// Clone up to `max_count` events starting at global position
+    /// `from_position`. Positions lost to holes are skipped.
+    pub fn read_all(&self, from_position: u64, max_count: u64) -> Vec<RecordedEvent> {
+        let start = self.index_of(from_position);
+        let end = (start as u64)
+            .saturating_add(max_count)
+            .min(self.events.len() as u64) as usize;
+        self.events[start..end].to_vec()
+    }
+
+    /// Clone up to `max_count` events of `stream_id` starting at stream
+    /// version `from_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StreamNotFound`] if the stream does not exist.
+    pub fn read_stream(
+        &self,
+        stream_id: Uuid,
+        from_version: u64,
+        max_count: u64,
+    ) -> Result<Vec<RecordedEvent>, Error> {
+        let positions = self
+            .streams
+            .get(&stream_id)
+            .ok_or(Error::StreamNotFound { stream_id })?;
+
+        // Without holes stream versions are dense, so the version is the
+        // index. With holes, a stream may have lost versions; search instead.
+        let start = if self.holes.is_empty() {
+            from_version.min(positions.len() as u64) as usize
+        } else {
+            positions
+                .partition_point(|&p| self.get(p).is_some_and(|e| e.stream_version < from_version))
+        };
+        let end = (start as u64)
+            .saturating_add(max_count)
+            .min(positions.len() as u64) as usize;
+
+        Ok(positions[start..end]
+            .iter()
+            .filter_map(|&global_pos| self.get(global_pos).cloned())
+            .collect())
+    }
 }
 
 /// Core storage engine that manages the append-only log file and in-memory index.
@@ -99,13 +617,141 @@ pub struct EventLog {
 /// disk I/O. The `Arc<RwLock<EventLog>>` can be cloned via `Store::log()` for
 /// use by `ReadIndex` handles.
 pub struct Store {
-    /// Append-only log file handle.
-    file: File,
+    /// Where encoded batches are appended.
+    backend: Box<dyn StorageBackend>,
+    /// Path the log file was opened from. `None` for in-memory stores.
+    path: Option<PathBuf>,
+    /// Whether appends are refused. See [`StoreOptions::read_only`].
+    read_only: bool,
+    /// Exclusive lock on the log file, held for the life of a writable file
+    /// store. `None` for read-only and in-memory stores.
+    _lock: Option<LogLock>,
+    /// How appends are made durable.
+    durability: Durability,
+    /// Whether batches have been written since the last fsync. Only ever set
+    /// under [`Durability::Interval`] and [`Durability::None`].
+    unsynced: bool,
+    /// When the log was last fsynced, for [`Durability::Interval`].
+    last_sync: Instant,
+    /// Set when a write or fsync fails. The state of the file is unknown from
+    /// then on, so every later append and sync is refused.
+    failure: Option<String>,
     /// Shared in-memory event log, protected by a read-write lock.
     log: Arc<RwLock<EventLog>>,
 }
 
 impl Store {
+    /// Assemble a `Store` around an open log file and its recovered index.
+    ///
+    /// The file backend writes from `log.log_len`, so any preallocated space
+    /// past the last batch is reused.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file metadata cannot be read, or if the
+    /// io_uring write path is requested but unavailable.
+    fn from_parts(
+        file: File,
+        path: &Path,
+        log: EventLog,
+        options: &StoreOptions,
+    ) -> Result<Store, Error> {
+        let backend = FileBackend::new(file, log.log_len)?.with_preallocation(options.preallocate);
+        let backend = if options.io_uring && !options.read_only {
+            enable_io_uring(backend)?
+        } else {
+            backend
+        };
+        Ok(Store {
+            backend: Box::new(backend),
+            path: Some(path.to_path_buf()),
+            read_only: options.read_only,
+            _lock: None,
+            durability: options.durability,
+            unsynced: false,
+            failure: None,
+            last_sync: Instant::now(),
+            log: Arc::new(RwLock::new(log)),
+        })
+    }
+
+    /// Open an existing log file for reading only.
+    ///
+    /// Equivalent to [`Store::open_with_options`] with
+    /// [`StoreOptions::read_only`] set. The file is never modified, so this is
+    /// safe to use on a log another process owns. Wrap [`Store::log`] in a
+    /// [`ReadIndex`](crate::reader::ReadIndex) to read it without spawning a
+    /// writer.
+    ///
+    /// # Errors
+    ///
+    /// See [`Store::open_with_options`].
+    pub fn open_read_only(path: &Path) -> Result<Store, Error> {
+        let options = StoreOptions {
+            read_only: true,
+            ..StoreOptions::default()
+        };
+        Store::open_with_options(path, &options)
+    }
+
+    /// Returns `true` if the store refuses appends.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Create an empty store backed by a [`MemoryBackend`].
+    ///
+    /// Behaves like a freshly created file store -- same validation, same
+    /// positions, same encoded bytes -- but nothing touches the filesystem and
+    /// nothing survives the process. Intended for tests and for embedders who
+    /// want the writer, broker, and gRPC service without a log file.
+    /// [`Store::backup_source`] returns `None` for an in-memory store.
+    pub fn in_memory() -> Store {
+        Store::with_backend(Box::new(MemoryBackend::new()))
+    }
+
+    /// Create an empty store writing to `backend`, which must hold only the
+    /// file header.
+    pub(crate) fn with_backend(backend: Box<dyn StorageBackend>) -> Store {
+        Store {
+            backend,
+            path: None,
+            read_only: false,
+            _lock: None,
+            durability: Durability::default(),
+            unsynced: false,
+            failure: None,
+            last_sync: Instant::now(),
+            log: Arc::new(RwLock::new(EventLog {
+                events: Vec::new(),
+                streams: HashMap::new(),
+                holes: Vec::new(),
+                log_len: HEADER_SIZE as u64,
+                time_index: Vec::new(),
+            })),
+        }
+    }
+
+    /// Open or create the event store at the given file path.
+    ///
+    /// Equivalent to [`Store::open_with_options`] with
+    /// `StoreOptions::default()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the append-only log file.
+    ///
+    /// # Returns
+    ///
+    /// A `Store` instance with the in-memory index populated from the log.
+    ///
+    /// # Errors
+    ///
+    /// See [`Store::open_with_options`].
+    pub fn open(path: &Path) -> Result<Store, Error> {
+        Store::open_with_options(path, &StoreOptions::default())
+    }
+
     /// Open or create the event store at the given file path.
     ///
     /// If the file does not exist, creates it with the 8-byte file header,
@@ -113,17 +759,37 @@ impl Store {
     /// the header and recovers all valid events from the log, rebuilding the
     /// in-memory index.
     ///
+    /// Unless opened read-only, the store holds an exclusive advisory lock on
+    /// a `<log>.lock` file for as long as it lives, so a second server pointed
+    /// at the same log fails fast instead of interleaving batches.
+    ///
     /// # Recovery behavior
     ///
     /// - **Trailing incomplete/corrupt record**: truncated from the file with a
     ///   `tracing::warn!` log. The store opens successfully with all preceding
     ///   valid events.
-    /// - **Mid-file corruption** (corrupt record followed by valid records):
-    ///   returns [`Error::CorruptRecord`]. This is unrecoverable.
+    /// - **Log end marker**: a preallocated log (see
+    ///   [`StoreOptions::preallocate`]) ends at a checksummed marker after the
+    ///   last batch. The marker must record its own offset, otherwise opening
+    ///   fails with [`Error::CorruptRecord`]; bytes after it are kept, not
+    ///   truncated, and the next append overwrites them.
+    /// - **Zeroed tail**: for logs without a marker, zero bytes after the last
+    ///   batch are preallocated space and mark the end of the log. They are
+    ///   kept too.
+    /// - **Mid-file corruption** (a bad header magic, corrupt record, bad
+    ///   footer, or batch CRC mismatch followed by a valid batch): returns
+    ///   [`Error::CorruptRecord`], leaving the file untouched, unless
+    ///   `options.repair` is set. In
+    ///   repair mode the damaged range is skipped up to the next valid batch,
+    ///   and the lost global positions are recorded as a [`LogHole`] in the
+    ///   `<log>.holes` sidecar.
+    /// - **Known holes**: ranges listed in the sidecar are skipped on every
+    ///   open, with or without repair mode.
     ///
     /// # Arguments
     ///
     /// * `path` - Path to the append-only log file.
+    /// * `options` - Open-time behavior, see [`StoreOptions`].
     ///
     /// # Returns
     ///
@@ -131,10 +797,35 @@ impl Store {
     ///
     /// # Errors
     ///
-    /// Returns [`Error::Io`] if the file cannot be created or written.
+    /// Returns [`Error::Io`] if the file cannot be created or written, or the
+    /// holes sidecar cannot be read or written.
     /// Returns [`Error::InvalidHeader`] if an existing file has a bad header.
-    /// Returns [`Error::CorruptRecord`] if mid-file corruption is detected.
-    pub fn open(path: &Path) -> Result<Store, Error> {
+    /// Returns [`Error::CorruptRecord`] if mid-file corruption is detected and
+    /// repair mode is off.
+    /// Returns [`Error::LogLocked`] if another writable store, in this or
+    /// another process, has the log open.
+    pub fn open_with_options(path: &Path, options: &StoreOptions) -> Result<Store, Error> {
+        // Lock before reading the log so a second writer fails before it can
+        // truncate anything. Read-only opens never write, so they skip it.
+        let lock = if options.read_only {
+            None
+        } else {
+            Some(LogLock::acquire(path)?)
+        };
+        let mut store = Store::recover(path, options)?;
+        store._lock = lock;
+        Ok(store)
+    }
+
+    /// Open or create the log at `path` and rebuild the in-memory index,
+    /// without taking the file lock. See [`Store::open_with_options`].
+    fn recover(path: &Path, options: &StoreOptions) -> Result<Store, Error> {
+        if !path.exists() && options.read_only {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("log file {} does not exist", path.display()),
+            )));
+        }
         if !path.exists() {
             // New file: create with read+write so append() can write later.
             let mut file = OpenOptions::new()
@@ -155,13 +846,14 @@ impl Store {
             let dir_handle = File::open(parent)?;
             dir_handle.sync_all()?;
 
-            return Ok(Store {
-                file,
-                log: Arc::new(RwLock::new(EventLog {
-                    events: Vec::new(),
-                    streams: HashMap::new(),
-                })),
-            });
+            let log = EventLog {
+                events: Vec::new(),
+                streams: HashMap::new(),
+                holes: Vec::new(),
+                log_len: HEADER_SIZE as u64,
+                time_index: Vec::new(),
+            };
+            return Store::from_parts(file, path, log, options);
         }
 
         // Existing file: read contents, validate header, recover records.
@@ -180,139 +872,101 @@ impl Store {
             .expect("slice is exactly 8 bytes");
         codec::decode_header(header)?;
 
+        // Holes persisted by earlier repair-mode opens.
+        let holes_file = holes_path(path);
+        let mut known_holes = read_holes(&holes_file)?;
+
         // Decode batches sequentially from offset HEADER_SIZE.
         // Each batch is: BatchHeader (16 bytes) + N records + BatchFooter (8 bytes).
-        let mut events = Vec::new();
-        let mut streams: HashMap<Uuid, Vec<u64>> = HashMap::new();
+        let mut log = EventLog {
+            events: Vec::new(),
+            streams: HashMap::new(),
+            holes: Vec::new(),
+            log_len: HEADER_SIZE as u64,
+            time_index: Vec::new(),
+        };
         let mut offset = HEADER_SIZE;
 
         loop {
             let remaining = &data[offset..];
-            if remaining.is_empty() {
-                break;
-            }
-
-            let batch_start_offset = offset;
-
-            // Step 1: Decode batch header.
-            let header = match codec::decode_batch_header(remaining) {
-                Ok(DecodeOutcome::Complete { value, consumed }) => {
-                    offset += consumed;
-                    value
-                }
-                Ok(DecodeOutcome::Incomplete) => {
-                    // Trailing partial header -- truncate to batch start.
-                    tracing::warn!(
-                        batch_start_offset,
-                        valid_events = events.len(),
-                        "truncating trailing partial batch header at byte offset \
-                         {batch_start_offset}"
-                    );
-                    return truncate_and_return(path, batch_start_offset, events, streams);
-                }
-                Err(Error::CorruptRecord { .. }) => {
-                    // Bad magic at this offset. Check if valid data follows.
-                    if has_valid_batch_after(&data, batch_start_offset) {
+            // A preallocated log records its logical end in a marker after
+            // the last batch; the space past it was never written. A torn
+            // marker falls through and is handled like any trailing damage.
+            if remaining.starts_with(&codec::LOG_END_MAGIC) {
+                if let Ok(DecodeOutcome::Complete { value: end, .. }) =
+                    codec::decode_log_end(remaining)
+                {
+                    if end != offset as u64 {
                         return Err(Error::CorruptRecord {
-                            position: events.len() as u64,
-                            detail: "mid-file corruption: valid batch follows \
-                                     corrupt data"
-                                .to_string(),
+                            position: log.next_position(),
+                            detail: format!(
+                                "log end marker at byte offset {offset} records end {end}"
+                            ),
                         });
                     }
-                    // Trailing corruption -- truncate.
-                    tracing::warn!(
-                        batch_start_offset,
-                        valid_events = events.len(),
-                        "truncating trailing corrupt data at byte offset \
-                         {batch_start_offset}"
-                    );
-                    return truncate_and_return(path, batch_start_offset, events, streams);
+                    break;
                 }
-                Err(e) => return Err(e),
-            };
+            }
+            // Logs without a marker, such as ones written before it existed,
+            // fall back to this: an all-zero tail is preallocated space that
+            // was never written. No batch starts with a zero byte, so this is
+            // the logical end.
+            if remaining.iter().all(|&b| b == 0) {
+                break;
+            }
 
-            // Step 2: Decode record_count records.
-            let mut batch_events = Vec::with_capacity(header.record_count as usize);
-            for _ in 0..header.record_count {
-                match codec::decode_record(&data[offset..]) {
-                    Ok(DecodeOutcome::Complete { value, consumed }) => {
-                        offset += consumed;
-                        batch_events.push(value);
-                    }
-                    Ok(DecodeOutcome::Incomplete) | Err(Error::CorruptRecord { .. }) => {
-                        // Incomplete or corrupt record within batch -- truncate
-                        // the entire batch.
-                        tracing::warn!(
-                            batch_start_offset,
-                            valid_events = events.len(),
-                            "truncating partial batch (incomplete/corrupt record) \
-                             at byte offset {batch_start_offset}"
-                        );
-                        return truncate_and_return(path, batch_start_offset, events, streams);
-                    }
-                    Err(e) => return Err(e),
-                }
+            let batch_start_offset = offset;
+
+            // A hole recorded by an earlier repair starts here: skip it.
+            if let Some(hole) = known_holes.iter().find(|h| {
+                h.start_offset == batch_start_offset as u64 && h.end_offset as usize <= data.len()
+            }) {
+                offset = hole.end_offset as usize;
+                log.holes.push(*hole);
+                continue;
             }
 
-            // Step 3: Decode batch footer.
-            let footer = match codec::decode_batch_footer(&data[offset..]) {
-                Ok(DecodeOutcome::Complete { value, consumed }) => {
-                    offset += consumed;
-                    value
+            let batch_events = match decode_valid_batch(&data, batch_start_offset)? {
+                Ok((events, end)) => {
+                    offset = end;
+                    events
                 }
-                Ok(DecodeOutcome::Incomplete) => {
-                    // Missing/incomplete footer -- truncate the entire batch.
-                    tracing::warn!(
+                Err(reason) => {
+                    if let Some(resume) = skip_mid_file_damage(
+                        &data,
                         batch_start_offset,
-                        valid_events = events.len(),
-                        "truncating partial batch (incomplete footer) at byte \
-                         offset {batch_start_offset}"
-                    );
-                    return truncate_and_return(path, batch_start_offset, events, streams);
-                }
-                Err(Error::CorruptRecord { .. }) => {
-                    // Wrong footer magic -- truncate the entire batch.
+                        reason,
+                        &mut log,
+                        &mut known_holes,
+                        &holes_file,
+                        options,
+                    )? {
+                        offset = resume;
+                        continue;
+                    }
                     tracing::warn!(
                         batch_start_offset,
-                        valid_events = events.len(),
-                        "truncating partial batch (corrupt footer magic) at byte \
-                         offset {batch_start_offset}"
+                        valid_events = log.events.len(),
+                        "truncating trailing batch ({reason}) at byte offset {batch_start_offset}"
                     );
-                    return truncate_and_return(path, batch_start_offset, events, streams);
+                    return truncate_and_return(path, batch_start_offset, log, options);
                 }
-                Err(e) => return Err(e),
             };
 
-            // Step 4: Verify batch CRC over header + record bytes.
-            let header_plus_records = &data[batch_start_offset..offset - codec::BATCH_FOOTER_SIZE];
-            let computed_crc = crc32fast::hash(header_plus_records);
-            if footer.batch_crc != computed_crc {
-                tracing::warn!(
-                    batch_start_offset,
-                    valid_events = events.len(),
-                    "truncating batch with CRC mismatch at byte offset \
-                     {batch_start_offset}"
-                );
-                return truncate_and_return(path, batch_start_offset, events, streams);
-            }
-
-            // Step 5: Batch is valid -- commit events to the in-memory index.
+            // Batch is valid -- commit events to the in-memory index.
             for event in batch_events {
-                let global_pos = event.global_position;
-                let stream_id = event.stream_id;
-                streams.entry(stream_id).or_default().push(global_pos);
-                events.push(event);
+                log.push(event);
             }
         }
 
         // All records decoded successfully. Open file for future appends.
-        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(!options.read_only)
+            .open(path)?;
+        log.log_len = offset as u64;
 
-        Ok(Store {
-            file,
-            log: Arc::new(RwLock::new(EventLog { events, streams })),
-        })
+        Store::from_parts(file, path, log, options)
     }
 
     /// Returns the current version of a stream (zero-based), or `None` if
@@ -330,22 +984,31 @@ impl Store {
     /// `Some(version)` if the stream exists, `None` otherwise.
     pub fn stream_version(&self, stream_id: &Uuid) -> Option<u64> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        log.streams
-            .get(stream_id)
-            .map(|positions| positions.len() as u64 - 1)
+        log.stream_version(stream_id)
     }
 
     /// Returns the next global position (i.e., `events.len()` as `u64`).
     ///
     /// If the log is empty, returns 0. This is the position that the next
-    /// appended event would receive.
+    /// appended event would receive. Positions lost to holes are counted.
     ///
     /// # Returns
     ///
-    /// The number of events in the global log.
+    /// The number of global positions assigned so far.
     pub fn global_position(&self) -> u64 {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        log.events.len() as u64
+        log.next_position()
+    }
+
+    /// Returns the ranges of global positions skipped by repair-mode recovery.
+    ///
+    /// # Returns
+    ///
+    /// The recorded holes in ascending position order; empty for an
+    /// undamaged log.
+    pub fn holes(&self) -> Vec<LogHole> {
+        let log = self.log.read().expect("EventLog RwLock poisoned");
+        log.holes.clone()
     }
 
     /// Read events from the global log starting at a given position.
@@ -365,10 +1028,7 @@ impl Store {
     /// vec if `from_position >= events.len()`.
     pub fn read_all(&self, from_position: u64, max_count: u64) -> Vec<RecordedEvent> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        let len = log.events.len() as u64;
-        let start = from_position.min(len);
-        let end = from_position.saturating_add(max_count).min(len);
-        log.events[start as usize..end as usize].to_vec()
+        log.read_all(from_position, max_count)
     }
 
     /// Read events from a specific stream starting at a given version.
@@ -397,19 +1057,7 @@ impl Store {
         max_count: u64,
     ) -> Result<Vec<RecordedEvent>, Error> {
         let log = self.log.read().expect("EventLog RwLock poisoned");
-        let positions = log
-            .streams
-            .get(&stream_id)
-            .ok_or(Error::StreamNotFound { stream_id })?;
-
-        let stream_len = positions.len() as u64;
-        let start = from_version.min(stream_len);
-        let end = from_version.saturating_add(max_count).min(stream_len);
-
-        Ok(positions[start as usize..end as usize]
-            .iter()
-            .map(|&global_pos| log.events[global_pos as usize].clone())
-            .collect())
+        log.read_stream(stream_id, from_version, max_count)
     }
 
     /// Append events to a stream with optimistic concurrency control.
@@ -426,7 +1074,7 @@ impl Store {
     /// * `stream_id` - UUID of the target stream.
     /// * `expected_version` - Concurrency check against current stream state.
     /// * `recorded_at` - Unix epoch milliseconds timestamp assigned to all events
-    ///   in this batch. Typically stamped by the writer task via `SystemTime::now()`.
+    ///   in this batch. Typically stamped by the writer task from its [`Clock`](crate::clock::Clock).
     /// * `proposed_events` - Events to append.
     ///
     /// # Returns
@@ -440,6 +1088,7 @@ impl Store {
     /// [`MAX_EVENT_TYPE_LEN`] bytes.
     /// Returns [`Error::EventTooLarge`] if a record exceeds [`MAX_EVENT_SIZE`].
     /// Returns [`Error::Io`] if writing to the log file fails.
+    /// Returns [`Error::ReadOnly`] if the store was opened read-only.
     pub fn append(
         &mut self,
         stream_id: Uuid,
@@ -447,33 +1096,64 @@ impl Store {
         recorded_at: u64,
         proposed_events: Vec<ProposedEvent>,
     ) -> Result<Vec<RecordedEvent>, Error> {
+        self.append_audited(
+            stream_id,
+            expected_version,
+            recorded_at,
+            proposed_events,
+            None,
+        )
+    }
+
+    /// Append events like [`Store::append`], stamping every recorded event
+    /// with `audit`.
+    ///
+    /// The audit information is persisted with each record and restored by
+    /// recovery. It counts toward the [`MAX_EVENT_SIZE`] limit.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`Store::append`].
+    pub fn append_audited(
+        &mut self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        recorded_at: u64,
+        proposed_events: Vec<ProposedEvent>,
+        audit: Option<AuditInfo>,
+    ) -> Result<Vec<RecordedEvent>, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        if let Some(cause) = &self.failure {
+            return Err(Error::WriterFailed(cause.clone()));
+        }
+
         // Step 1: Acquire a read lock to validate expected version and compute
         // starting positions. The read lock is held only for validation and
         // position computation, not during disk I/O.
         let (mut next_global, mut next_stream_version) = {
             let log = self.log.read().expect("EventLog RwLock poisoned");
-            let stream_positions = log.streams.get(&stream_id);
+            let current_version = log.stream_version(&stream_id);
 
             match expected_version {
                 ExpectedVersion::Any => {} // always passes
                 ExpectedVersion::NoStream => {
-                    if let Some(positions) = stream_positions {
-                        let actual_version = positions.len() as u64 - 1;
+                    if let Some(actual_version) = current_version {
                         return Err(Error::WrongExpectedVersion {
                             expected: "NoStream".to_string(),
                             actual: actual_version.to_string(),
                         });
                     }
                 }
-                ExpectedVersion::Exact(n) => match stream_positions {
+                ExpectedVersion::Exact(n) => match current_version {
                     None => {
                         return Err(Error::WrongExpectedVersion {
                             expected: n.to_string(),
                             actual: "NoStream".to_string(),
                         });
                     }
-                    Some(positions) => {
-                        let current_version = positions.len() as u64 - 1;
+                    Some(current_version) => {
                         if current_version != n {
                             return Err(Error::WrongExpectedVersion {
                                 expected: n.to_string(),
@@ -484,8 +1164,8 @@ impl Store {
                 },
             }
 
-            let next_global = log.events.len() as u64;
-            let next_stream_version = stream_positions.map(|p| p.len() as u64).unwrap_or(0);
+            let next_global = log.next_position();
+            let next_stream_version = current_version.map(|v| v + 1).unwrap_or(0);
             (next_global, next_stream_version)
             // Read lock dropped here.
         };
@@ -521,6 +1201,7 @@ impl Store {
                 event_type: proposed.event_type.clone(),
                 metadata: proposed.metadata.clone(),
                 payload: proposed.payload.clone(),
+                audit: audit.clone(),
             };
 
             // Validate total encoded size.
@@ -558,39 +1239,106 @@ impl Store {
         encoded_batch.extend_from_slice(&encoded_records);
         encoded_batch.extend_from_slice(&batch_footer);
 
-        // Step 4: Write the entire batch envelope to disk and fsync (no lock held).
-        use std::io::Seek;
-        self.file.seek(std::io::SeekFrom::End(0))?;
-        self.file.write_all(&encoded_batch)?;
-        self.file.sync_all()?;
+        // Step 4: Write the entire batch envelope to disk and make it durable
+        // according to the configured mode (no lock held). A failure here
+        // leaves the file in an unknown state and fails the store for good.
+        if let Err(e) = self.write_batch(&encoded_batch) {
+            return Err(self.fail(e));
+        }
 
         // Step 5: Acquire write lock to update in-memory index (after fsync).
         {
             let mut log = self.log.write().expect("EventLog RwLock poisoned");
-            let stream_entry = log.streams.entry(stream_id).or_default();
             for event in &recorded {
-                stream_entry.push(event.global_position);
+                log.push(event.clone());
             }
-            log.events.extend(recorded.clone());
+            log.log_len += encoded_batch.len() as u64;
         }
 
         Ok(recorded)
     }
 
+    /// Append an encoded batch to the backend and sync it as the durability
+    /// mode requires.
+    fn write_batch(&mut self, batch: &[u8]) -> Result<(), Error> {
+        self.backend.append(batch)?;
+        match self.durability {
+            Durability::FsyncPerBatch => self.backend.sync_all()?,
+            Durability::Fdatasync => self.backend.sync_data()?,
+            Durability::Interval(interval) => {
+                self.unsynced = true;
+                if self.last_sync.elapsed() >= interval {
+                    self.sync()?;
+                }
+            }
+            Durability::None => self.unsynced = true,
+        }
+        Ok(())
+    }
+
+    /// Record `err` as the reason the store failed, keeping the first cause,
+    /// and hand it back.
+    fn fail(&mut self, err: Error) -> Error {
+        if self.failure.is_none() {
+            self.failure = Some(err.to_string());
+        }
+        err
+    }
+
+    /// Fsync batches written since the last sync under a relaxed
+    /// [`Durability`] mode.
+    ///
+    /// A no-op when nothing is pending, which is always the case under
+    /// [`Durability::FsyncPerBatch`] and [`Durability::Fdatasync`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the fsync fails, which fails the store.
+    /// Returns [`Error::WriterFailed`] if the store has already failed.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        if let Some(cause) = &self.failure {
+            return Err(Error::WriterFailed(cause.clone()));
+        }
+        if self.unsynced {
+            if let Err(e) = self.backend.sync_all() {
+                return Err(self.fail(e.into()));
+            }
+            self.unsynced = false;
+        }
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    /// Returns why the store stopped accepting appends, if a write or fsync
+    /// has failed.
+    ///
+    /// After such a failure the log may hold a partial or unsynced batch, so
+    /// the store refuses all further appends with [`Error::WriterFailed`].
+    /// Reopening the store recovers the log from disk.
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    /// Returns the durability mode this store was opened with.
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+
     /// Returns the current byte length of the log file.
     ///
     /// Called after each successful append to update the `eventfold_log_bytes` gauge.
-    /// Uses `File::metadata()` which issues a `stat(2)` syscall without seeking.
+    /// This is the logical length of the log -- the end of the last batch --
+    /// which is smaller than the file itself when space is preallocated.
     ///
     /// # Returns
     ///
-    /// The file size in bytes.
+    /// The log size in bytes, as reported by the storage backend.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::Io`] if the metadata syscall fails.
+    /// Returns [`Error::Io`] if the backend cannot report its size.
     pub fn log_file_len(&self) -> Result<u64, Error> {
-        Ok(self.file.metadata()?.len())
+        Ok(self.backend.size()?)
     }
 
     /// Returns a clone of the shared `Arc<RwLock<EventLog>>`.
@@ -605,6 +1353,21 @@ impl Store {
     pub fn log(&self) -> Arc<RwLock<EventLog>> {
         Arc::clone(&self.log)
     }
+
+    /// Returns a handle for taking consistent backups of this store.
+    ///
+    /// The handle reads the log file through its own file descriptor and
+    /// shares the in-memory event log, so it keeps working after the `Store`
+    /// has been moved into the writer task.
+    ///
+    /// # Returns
+    ///
+    /// A [`BackupSource`] for the log file this store was opened from, or
+    /// `None` for an in-memory store.
+    pub fn backup_source(&self) -> Option<BackupSource> {
+        let path = self.path.clone()?;
+        Some(BackupSource::new(path, self.log()))
+    }
 }
 
 #[cfg(test)]
@@ -630,6 +1393,7 @@ mod tests {
             event_type: event_type.to_string(),
             metadata: Bytes::new(),
             payload: Bytes::copy_from_slice(payload),
+            audit: None,
         }
     }
 
@@ -868,11 +1632,222 @@ mod tests {
         data[HEADER_SIZE] ^= 0xFF; // corrupt first byte of batch 1 header magic
         std::fs::write(&path, &data).expect("write corrupted file");
 
-        // Reopen -- should return CorruptRecord because a valid batch 2 follows.
+        // Reopen -- should return CorruptRecord because a valid batch 2 follows.
+        match Store::open(&path) {
+            Err(Error::CorruptRecord { .. }) => {} // expected
+            Err(other) => panic!("expected CorruptRecord, got: {other:?}"),
+            Ok(_) => panic!("expected CorruptRecord error, but open() succeeded"),
+        }
+    }
+
+    /// Helper: seed a 3-batch log and corrupt the header magic of the middle
+    /// batch. Stream A has events at positions 0, 1, 3 (versions 0, 1, 2);
+    /// stream B has a single event at position 2 inside the damaged batch.
+    fn seed_file_with_damaged_middle_batch(path: &std::path::Path) -> (Uuid, Uuid) {
+        let stream_a = Uuid::new_v4();
+        let stream_b = Uuid::new_v4();
+        let batch1 = vec![make_event(0, stream_a, 0, "Evt", b"a0")];
+        let batch2 = vec![
+            make_event(1, stream_a, 1, "Evt", b"a1"),
+            make_event(2, stream_b, 0, "Evt", b"b0"),
+        ];
+        let batch3 = vec![make_event(3, stream_a, 2, "Evt", b"a2")];
+        seed_batch_file(path, &[&batch1, &batch2, &batch3]);
+
+        let batch2_offset = HEADER_SIZE + encode_batch(&batch1).len();
+        let mut data = std::fs::read(path).expect("read file");
+        data[batch2_offset] ^= 0xFF;
+        std::fs::write(path, &data).expect("write corrupted file");
+        (stream_a, stream_b)
+    }
+
+    /// Helper: seed a 3-batch log of one event each and return the byte
+    /// offset of the end of the first batch.
+    fn seed_three_single_event_batches(path: &std::path::Path) -> usize {
+        let stream = Uuid::new_v4();
+        let batch1 = vec![make_event(0, stream, 0, "Evt", b"payload0")];
+        let batch2 = vec![make_event(1, stream, 1, "Evt", b"payload1")];
+        let batch3 = vec![make_event(2, stream, 2, "Evt", b"payload2")];
+        seed_batch_file(path, &[&batch1, &batch2, &batch3]);
+        HEADER_SIZE + encode_batch(&batch1).len()
+    }
+
+    /// Flip the byte `back` bytes before the end of the first of three
+    /// batches, then check that a plain open refuses the log without
+    /// truncating it and a repair open skips only the first batch.
+    fn assert_damage_in_first_batch_is_mid_file(back: usize) {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let batch1_end = seed_three_single_event_batches(&path);
+        let mut data = std::fs::read(&path).expect("read file");
+        data[batch1_end - back] ^= 0xFF;
+        std::fs::write(&path, &data).expect("write corrupted file");
+
+        match Store::open(&path) {
+            Err(Error::CorruptRecord { position: 0, .. }) => {}
+            Err(other) => panic!("expected CorruptRecord, got: {other:?}"),
+            Ok(_) => panic!("expected CorruptRecord error, but open() succeeded"),
+        }
+        assert_eq!(std::fs::read(&path).expect("read file"), data);
+
+        let options = StoreOptions {
+            repair: true,
+            ..StoreOptions::default()
+        };
+        let store = Store::open_with_options(&path, &options).expect("repair open should succeed");
+        assert_eq!(
+            store.holes(),
+            vec![LogHole {
+                first_position: 0,
+                missing_count: 1,
+                start_offset: HEADER_SIZE as u64,
+                end_offset: batch1_end as u64,
+            }]
+        );
+        let positions: Vec<u64> = store
+            .read_all(0, 100)
+            .iter()
+            .map(|e| e.global_position)
+            .collect();
+        assert_eq!(positions, vec![1, 2]);
+        assert_eq!(std::fs::read(&path).expect("read file").len(), data.len());
+        drop(store);
+
+        // The recorded hole is skipped by later opens without repair mode.
+        let store = Store::open(&path).expect("open with a known hole should succeed");
+        assert_eq!(store.holes().len(), 1);
+        assert_eq!(store.global_position(), 3);
+    }
+
+    #[test]
+    fn record_crc_damage_mid_file_is_corruption_not_a_torn_tail() {
+        // Inside the payload, before the footer and the record's own CRC.
+        assert_damage_in_first_batch_is_mid_file(codec::BATCH_FOOTER_SIZE + 6);
+    }
+
+    #[test]
+    fn footer_magic_damage_mid_file_is_corruption_not_a_torn_tail() {
+        assert_damage_in_first_batch_is_mid_file(codec::BATCH_FOOTER_SIZE);
+    }
+
+    #[test]
+    fn batch_crc_damage_mid_file_is_corruption_not_a_torn_tail() {
+        assert_damage_in_first_batch_is_mid_file(1);
+    }
+
+    #[test]
+    fn repair_mode_skips_mid_file_corruption_and_records_hole() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let (stream_a, stream_b) = seed_file_with_damaged_middle_batch(&path);
+
+        let options = StoreOptions {
+            repair: true,
+            ..StoreOptions::default()
+        };
+        let store = Store::open_with_options(&path, &options).expect("repair open should succeed");
+
+        let holes = store.holes();
+        assert_eq!(holes.len(), 1);
+        assert_eq!(holes[0].first_position, 1);
+        assert_eq!(holes[0].missing_count, 2);
+        assert!(holes[0].start_offset < holes[0].end_offset);
+
+        // Positions keep their original numbering; the lost ones are skipped.
+        assert_eq!(store.global_position(), 4);
+        let all = store.read_all(0, 100);
+        let positions: Vec<u64> = all.iter().map(|e| e.global_position).collect();
+        assert_eq!(positions, vec![0, 3]);
+        let from_hole = store.read_all(1, 1);
+        assert_eq!(from_hole[0].global_position, 3);
+
+        // Stream A keeps its latest version; its lost version 1 is skipped.
+        assert_eq!(store.stream_version(&stream_a), Some(2));
+        let versions: Vec<u64> = store
+            .read_stream(stream_a, 0, 100)
+            .expect("read_stream should succeed")
+            .iter()
+            .map(|e| e.stream_version)
+            .collect();
+        assert_eq!(versions, vec![0, 2]);
+        let from_lost = store
+            .read_stream(stream_a, 1, 100)
+            .expect("read_stream should succeed");
+        assert_eq!(from_lost[0].stream_version, 2);
+
+        // Stream B only existed inside the damaged batch.
+        assert_eq!(store.stream_version(&stream_b), None);
+
+        // The hole marker is persisted next to the log.
+        assert!(holes_path(&path).exists());
+    }
+
+    #[test]
+    fn reopen_without_repair_skips_known_hole_and_appends_continue() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let (stream_a, _stream_b) = seed_file_with_damaged_middle_batch(&path);
+
+        let options = StoreOptions {
+            repair: true,
+            ..StoreOptions::default()
+        };
+        let mut store =
+            Store::open_with_options(&path, &options).expect("repair open should succeed");
+        let recorded = store
+            .append(
+                stream_a,
+                ExpectedVersion::Exact(2),
+                0,
+                vec![make_proposed("Evt", b"a3")],
+            )
+            .expect("append after repair should succeed");
+        assert_eq!(recorded[0].global_position, 4);
+        assert_eq!(recorded[0].stream_version, 3);
+        let expected_holes = store.holes();
+        drop(store);
+
+        // A plain open no longer refuses to start: the hole is known.
+        let store = Store::open(&path).expect("reopen should skip the known hole");
+        assert_eq!(store.holes(), expected_holes);
+        assert_eq!(store.global_position(), 5);
+        assert_eq!(store.stream_version(&stream_a), Some(3));
+        let positions: Vec<u64> = store
+            .read_all(0, 100)
+            .iter()
+            .map(|e| e.global_position)
+            .collect();
+        assert_eq!(positions, vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn repair_mode_on_clean_log_records_no_holes() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let stream = Uuid::new_v4();
+        seed_batch_file(&path, &[&[make_event(0, stream, 0, "Evt", b"x")]]);
+
+        let options = StoreOptions {
+            repair: true,
+            ..StoreOptions::default()
+        };
+        let store = Store::open_with_options(&path, &options).expect("open should succeed");
+        assert!(store.holes().is_empty());
+        assert_eq!(store.global_position(), 1);
+        assert!(!holes_path(&path).exists());
+    }
+
+    #[test]
+    fn malformed_holes_file_returns_io_error() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        seed_file(&path, &[]);
+        std::fs::write(holes_path(&path), "1 2 three\n").expect("write holes file");
+
         match Store::open(&path) {
-            Err(Error::CorruptRecord { .. }) => {} // expected
-            Err(other) => panic!("expected CorruptRecord, got: {other:?}"),
-            Ok(_) => panic!("expected CorruptRecord error, but open() succeeded"),
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+            Err(other) => panic!("expected Io error, got: {other:?}"),
+            Ok(_) => panic!("expected error for malformed holes file"),
         }
     }
 
@@ -1577,6 +2552,86 @@ mod tests {
         assert_eq!(store.global_position(), 6);
     }
 
+    #[test]
+    fn recovery_preserves_audit_info() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let stream_id = Uuid::new_v4();
+        let audit = AuditInfo {
+            principal: Some("billing-service".to_string()),
+            client_addr: Some("10.0.0.7:51234".to_string()),
+        };
+
+        {
+            let mut store = Store::open(&path).expect("open should succeed");
+            let recorded = store
+                .append_audited(
+                    stream_id,
+                    ExpectedVersion::NoStream,
+                    0,
+                    vec![make_proposed("A0", b"a0"), make_proposed("A1", b"a1")],
+                    Some(audit.clone()),
+                )
+                .expect("audited append");
+            assert!(recorded.iter().all(|e| e.audit.as_ref() == Some(&audit)));
+            store
+                .append(
+                    stream_id,
+                    ExpectedVersion::Exact(1),
+                    0,
+                    vec![make_proposed("A2", b"a2")],
+                )
+                .expect("unaudited append");
+        }
+
+        let store = Store::open(&path).expect("reopen should succeed");
+        let all = store.read_all(0, 100);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].audit.as_ref(), Some(&audit));
+        assert_eq!(all[1].audit.as_ref(), Some(&audit));
+        assert_eq!(all[2].audit, None);
+    }
+
+    #[test]
+    fn position_at_finds_first_event_at_or_after_timestamp() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let stream_id = Uuid::new_v4();
+
+        {
+            let mut store = Store::open(&path).expect("open should succeed");
+            // Batches at 100 (two events), 200, then a clock step back to 150.
+            let batches = [
+                (
+                    100,
+                    vec![make_proposed("A", b"0"), make_proposed("A", b"1")],
+                ),
+                (200, vec![make_proposed("A", b"2")]),
+                (150, vec![make_proposed("A", b"3")]),
+            ];
+            for (recorded_at, events) in batches {
+                store
+                    .append(stream_id, ExpectedVersion::Any, recorded_at, events)
+                    .expect("append should succeed");
+            }
+            let log = store.log();
+            let log = log.read().expect("EventLog RwLock poisoned");
+            assert_eq!(log.time_index, vec![(100, 0), (200, 2)]);
+        }
+
+        // Recovery rebuilds the same index.
+        let store = Store::open(&path).expect("reopen should succeed");
+        let log = store.log();
+        let log = log.read().expect("EventLog RwLock poisoned");
+        assert_eq!(log.time_index, vec![(100, 0), (200, 2)]);
+        assert_eq!(log.position_at(0), 0);
+        assert_eq!(log.position_at(100), 0);
+        assert_eq!(log.position_at(101), 2);
+        assert_eq!(log.position_at(200), 2);
+        // Nothing recorded at or after 201: the next position to be assigned.
+        assert_eq!(log.position_at(201), 4);
+    }
+
     // -- AC-3 integration: Append 3 events via Store::append(), close, append 10 garbage
     // bytes, reopen -- 3 events recovered, garbage truncated, next append at position 3.
     #[test]
@@ -2374,4 +3429,502 @@ mod tests {
             "log file should grow after append: before={before}, after={after}"
         );
     }
+
+    /// Open a fresh store at `path` with the given durability mode.
+    fn open_with_durability(path: &Path, durability: Durability) -> Store {
+        let options = StoreOptions {
+            durability,
+            ..StoreOptions::default()
+        };
+        Store::open_with_options(path, &options).expect("open should succeed")
+    }
+
+    #[test]
+    fn every_durability_mode_persists_appends_across_reopen() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        for (i, durability) in [
+            Durability::FsyncPerBatch,
+            Durability::Fdatasync,
+            Durability::Interval(Duration::from_millis(50)),
+            Durability::None,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let path = dir.path().join(format!("events-{i}.log"));
+            let mut store = open_with_durability(&path, durability);
+            assert_eq!(store.durability(), durability);
+            store
+                .append(
+                    Uuid::new_v4(),
+                    ExpectedVersion::Any,
+                    0,
+                    vec![make_proposed("A", b"{}")],
+                )
+                .expect("append should succeed");
+            store.sync().expect("sync should succeed");
+            drop(store);
+
+            let reopened = Store::open(&path).expect("reopen should succeed");
+            assert_eq!(reopened.global_position(), 1, "mode {durability}");
+        }
+    }
+
+    #[test]
+    fn per_batch_modes_never_leave_writes_unsynced() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        for (i, durability) in [Durability::FsyncPerBatch, Durability::Fdatasync]
+            .into_iter()
+            .enumerate()
+        {
+            let path = dir.path().join(format!("events-{i}.log"));
+            let mut store = open_with_durability(&path, durability);
+            store
+                .append(
+                    Uuid::new_v4(),
+                    ExpectedVersion::Any,
+                    0,
+                    vec![make_proposed("A", b"{}")],
+                )
+                .expect("append should succeed");
+            assert!(!store.unsynced, "mode {durability}");
+        }
+    }
+
+    #[test]
+    fn relaxed_modes_defer_fsync_until_sync() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        for (i, durability) in [
+            Durability::Interval(Duration::from_secs(3600)),
+            Durability::None,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let path = dir.path().join(format!("events-{i}.log"));
+            let mut store = open_with_durability(&path, durability);
+            store
+                .append(
+                    Uuid::new_v4(),
+                    ExpectedVersion::Any,
+                    0,
+                    vec![make_proposed("A", b"{}")],
+                )
+                .expect("append should succeed");
+            assert!(store.unsynced, "mode {durability}");
+
+            store.sync().expect("sync should succeed");
+            assert!(!store.unsynced, "mode {durability}");
+        }
+    }
+
+    #[test]
+    fn interval_mode_fsyncs_on_append_once_interval_elapsed() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let mut store = open_with_durability(&path, Durability::Interval(Duration::ZERO));
+
+        store
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::Any,
+                0,
+                vec![make_proposed("A", b"{}")],
+            )
+            .expect("append should succeed");
+        assert!(!store.unsynced, "elapsed interval should fsync inline");
+    }
+
+    #[test]
+    fn durability_display_and_name() {
+        assert_eq!(Durability::default(), Durability::FsyncPerBatch);
+        assert_eq!(Durability::FsyncPerBatch.to_string(), "fsync-per-batch");
+        assert_eq!(Durability::Fdatasync.to_string(), "fdatasync");
+        assert_eq!(Durability::None.to_string(), "none");
+        let interval = Durability::Interval(Duration::from_millis(100));
+        assert_eq!(interval.name(), "interval");
+        assert_eq!(interval.to_string(), "interval (100ms)");
+        assert_eq!(interval.sync_interval(), Some(Duration::from_millis(100)));
+        assert_eq!(Durability::None.sync_interval(), None);
+    }
+
+    #[test]
+    fn in_memory_store_appends_without_backup_source() {
+        let mut store = Store::in_memory();
+        assert_eq!(store.global_position(), 0);
+        assert_eq!(store.log_file_len().expect("len"), HEADER_SIZE as u64);
+
+        store
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                0,
+                vec![make_proposed("A", b"{}")],
+            )
+            .expect("append should succeed");
+
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(
+            store.log_file_len().expect("len"),
+            store.log().read().expect("lock").log_len
+        );
+        assert!(store.backup_source().is_none());
+    }
+
+    /// Open a store at `path` that preallocates in `extent`-byte extents.
+    fn open_preallocated(path: &Path, extent: u64) -> Store {
+        let options = StoreOptions {
+            preallocate: extent,
+            ..StoreOptions::default()
+        };
+        Store::open_with_options(path, &options).expect("open should succeed")
+    }
+
+    #[test]
+    fn preallocated_log_survives_reopen_and_keeps_its_extent() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let stream_id = Uuid::new_v4();
+
+        let mut store = open_preallocated(&path, 64 * 1024);
+        store
+            .append(
+                stream_id,
+                ExpectedVersion::NoStream,
+                0,
+                vec![make_proposed("A", b"{}")],
+            )
+            .expect("append should succeed");
+        let logical = store.log_file_len().expect("len");
+        drop(store);
+
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), 64 * 1024);
+
+        // Reopening stops at the end marker and keeps the allocation.
+        let mut store = open_preallocated(&path, 64 * 1024);
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(store.log_file_len().expect("len"), logical);
+        store
+            .append(
+                stream_id,
+                ExpectedVersion::Exact(0),
+                0,
+                vec![make_proposed("B", b"{}")],
+            )
+            .expect("append after reopen should succeed");
+        drop(store);
+
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), 64 * 1024);
+        let reopened = Store::open(&path).expect("plain reopen should succeed");
+        let types: Vec<String> = reopened
+            .read_all(0, 10)
+            .into_iter()
+            .map(|e| e.event_type)
+            .collect();
+        assert_eq!(types, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn zeroed_tail_is_end_of_log_not_corruption() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        // Zero-fill well past the last batch, as preallocation would.
+        let file = OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .expect("open for extend");
+        file.set_len(valid_len + 10_000).expect("extend");
+        drop(file);
+
+        let store = Store::open(&path).expect("open should succeed");
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(store.log_file_len().expect("len"), valid_len);
+        assert_eq!(
+            std::fs::metadata(&path).expect("metadata").len(),
+            valid_len + 10_000,
+            "preallocated space must not be truncated"
+        );
+    }
+
+    #[test]
+    fn preallocated_append_is_followed_by_an_end_marker() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let mut store = open_preallocated(&path, 4096);
+        store
+            .append(
+                Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                0,
+                vec![make_proposed("A", b"{}")],
+            )
+            .expect("append should succeed");
+        let logical = store.log_file_len().expect("len");
+        drop(store);
+
+        let data = std::fs::read(&path).expect("read");
+        let end = logical as usize;
+        assert_eq!(
+            &data[end..end + codec::LOG_END_SIZE],
+            &codec::encode_log_end(logical)
+        );
+
+        // Bytes past the marker are unwritten space, whatever they hold.
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .expect("open for write");
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(logical + 100))
+            .expect("seek past marker");
+        file.write_all(b"stale").expect("write stale bytes");
+        drop(file);
+
+        let store = Store::open(&path).expect("open should succeed");
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(store.log_file_len().expect("len"), logical);
+    }
+
+    #[test]
+    fn end_marker_recording_another_offset_is_corruption() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open for append");
+        file.write_all(&codec::encode_log_end(valid_len + 64))
+            .expect("write marker");
+        file.set_len(valid_len + 4096).expect("extend");
+        drop(file);
+
+        match Store::open(&path) {
+            Err(Error::CorruptRecord { position, .. }) => assert_eq!(position, 1),
+            Err(other) => panic!("expected CorruptRecord, got: {other:?}"),
+            Ok(_) => panic!("expected a mismatched end marker to be rejected"),
+        }
+    }
+
+    #[test]
+    fn torn_end_marker_is_truncated() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        let mut marker = codec::encode_log_end(valid_len);
+        marker[14] ^= 0xFF;
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open for append");
+        file.write_all(&marker).expect("write marker");
+        file.set_len(valid_len + 4096).expect("extend");
+        drop(file);
+
+        let store = Store::open(&path).expect("open should succeed");
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), valid_len);
+    }
+
+    #[test]
+    fn torn_batch_inside_preallocated_space_is_truncated() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        // A batch whose header made it to disk but whose records did not,
+        // followed by zeroed preallocated space.
+        let second = make_event(1, Uuid::new_v4(), 0, "B", b"{}");
+        let header = codec::encode_batch_header(1, 1);
+        let record = codec::encode_record(&second);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open for append");
+        file.write_all(&header).expect("write header");
+        file.write_all(&record[..record.len() / 2])
+            .expect("write torn record");
+        file.set_len(valid_len + 4096).expect("extend");
+        drop(file);
+
+        let store = Store::open(&path).expect("open should succeed");
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), valid_len);
+    }
+
+    #[test]
+    fn garbage_record_count_is_a_torn_tail_not_an_allocation() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open for append");
+        file.write_all(&codec::encode_batch_header(u32::MAX, 1))
+            .expect("write header");
+        drop(file);
+
+        let store = Store::open_read_only(&path).expect("read-only open should succeed");
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(store.log_file_len().expect("len"), valid_len);
+    }
+
+    #[test]
+    fn io_uring_option_without_feature_fails_to_open() {
+        if cfg!(all(target_os = "linux", feature = "io-uring")) {
+            return;
+        }
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let options = StoreOptions {
+            io_uring: true,
+            ..StoreOptions::default()
+        };
+        match Store::open_with_options(&dir.path().join("events.log"), &options) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::Unsupported),
+            Err(other) => panic!("expected Unsupported io error, got: {other:?}"),
+            Ok(_) => panic!("expected io_uring to be unavailable"),
+        }
+    }
+
+    #[test]
+    fn read_only_open_stops_before_torn_tail_without_truncating() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let event = make_event(0, Uuid::new_v4(), 0, "A", b"{}");
+        seed_file(&path, &[event]);
+        let valid_len = std::fs::metadata(&path).expect("metadata").len();
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open for append");
+        file.write_all(&codec::encode_batch_header(3, 1))
+            .expect("write torn header");
+        drop(file);
+        let before = std::fs::read(&path).expect("read");
+
+        let store = Store::open_read_only(&path).expect("read-only open should succeed");
+        assert!(store.is_read_only());
+        assert_eq!(store.global_position(), 1);
+        assert_eq!(store.log_file_len().expect("len"), valid_len);
+        assert_eq!(std::fs::read(&path).expect("read"), before);
+    }
+
+    #[test]
+    fn read_only_store_rejects_appends() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        drop(Store::open(&path).expect("create should succeed"));
+        let before = std::fs::read(&path).expect("read");
+
+        let mut store = Store::open_read_only(&path).expect("read-only open should succeed");
+        let result = store.append(
+            Uuid::new_v4(),
+            ExpectedVersion::Any,
+            0,
+            vec![make_proposed("A", b"{}")],
+        );
+        assert!(
+            matches!(result, Err(Error::ReadOnly)),
+            "expected ReadOnly, got: {result:?}"
+        );
+        assert_eq!(store.global_position(), 0);
+        assert_eq!(std::fs::read(&path).expect("read"), before);
+    }
+
+    #[test]
+    fn read_only_open_of_missing_file_fails_without_creating_it() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+
+        match Store::open_read_only(&path) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            Err(other) => panic!("expected NotFound, got: {other:?}"),
+            Ok(_) => panic!("expected read-only open of a missing file to fail"),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn read_only_repair_does_not_write_holes_sidecar() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        seed_file_with_damaged_middle_batch(&path);
+
+        let options = StoreOptions {
+            repair: true,
+            read_only: true,
+            ..StoreOptions::default()
+        };
+        let store = Store::open_with_options(&path, &options).expect("open should succeed");
+        assert_eq!(store.holes().len(), 1);
+        assert!(!holes_path(&path).exists());
+    }
+
+    #[test]
+    fn second_writable_open_fails_while_first_is_open() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let first = Store::open(&path).expect("first open should succeed");
+
+        match Store::open(&path) {
+            Err(Error::LogLocked { pid, .. }) => assert_eq!(pid, Some(std::process::id())),
+            Err(other) => panic!("expected LogLocked, got: {other:?}"),
+            Ok(_) => panic!("second writable open should fail"),
+        }
+
+        // A read-only open does not contend for the lock.
+        Store::open_read_only(&path).expect("read-only open should succeed");
+
+        drop(first);
+        Store::open(&path).expect("open after drop should succeed");
+    }
+    #[test]
+    fn failed_fsync_stops_further_appends() {
+        let mut store =
+            Store::with_backend(Box::new(crate::backend::FailingSyncBackend::default()));
+        assert!(store.failure().is_none());
+
+        let first = store.append(
+            Uuid::new_v4(),
+            ExpectedVersion::NoStream,
+            0,
+            vec![make_proposed("A", b"{}")],
+        );
+        assert!(matches!(first, Err(Error::Io(_))), "got: {first:?}");
+        assert!(
+            store
+                .failure()
+                .expect("store should be failed")
+                .contains("EIO")
+        );
+        assert_eq!(store.global_position(), 0);
+
+        let second = store.append(
+            Uuid::new_v4(),
+            ExpectedVersion::NoStream,
+            0,
+            vec![make_proposed("B", b"{}")],
+        );
+        match second {
+            Err(Error::WriterFailed(cause)) => assert!(cause.contains("EIO")),
+            other => panic!("expected WriterFailed, got: {other:?}"),
+        }
+        assert!(matches!(store.sync(), Err(Error::WriterFailed(_))));
+    }
 }
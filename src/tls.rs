@@ -0,0 +1,406 @@
+//! TLS for the gRPC listener with certificate hot reload.
+//!
+//! tonic's built-in TLS reads the certificate once, so rotating it means a
+//! restart that drops every open subscription. [`ReloadableTls`] instead holds
+//! the current rustls [`ServerConfig`] and hands it to each new connection as
+//! it is accepted. [`ReloadableTls::reload`] swaps in a config built from the
+//! files on disk; connections accepted earlier keep the config they were
+//! handshaken with, so long-lived streams are not interrupted.
+//!
+//! A failed reload leaves the current config in place. Reloads are counted in
+//! `eventfold_tls_reloads_total{result}`, and `eventfold_tls_reload_failed` is
+//! `1` while the last attempt failed.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use futures_core::Stream;
+use metrics::{counter, gauge};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::server::TlsStream;
+
+use crate::error::Error;
+
+/// Longest a client may take to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshaken connections waiting for the server to pick them up.
+const ACCEPT_BACKLOG: usize = 128;
+
+/// Paths of the PEM files the server's TLS config is built from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    /// PEM-encoded server certificate chain.
+    pub cert_path: PathBuf,
+    /// PEM-encoded server private key.
+    pub key_path: PathBuf,
+    /// PEM-encoded CA certificates for verifying client certificates. When
+    /// `Some`, clients must present a certificate signed by one of them (mTLS).
+    pub ca_path: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Every file the config is built from.
+    fn paths(&self) -> Vec<&Path> {
+        let mut paths = vec![self.cert_path.as_path(), self.key_path.as_path()];
+        paths.extend(self.ca_path.as_deref());
+        paths
+    }
+}
+
+/// Build a rustls server config from the files named in `files`.
+///
+/// The config negotiates HTTP/2 through ALPN, as gRPC requires.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidKeyFile`] if a file cannot be read, holds no
+/// usable PEM data, or the key does not match the certificate.
+pub fn server_config(files: &TlsConfig) -> Result<ServerConfig, Error> {
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| invalid_file(&files.cert_path, e))?;
+
+    let builder = match &files.ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(ca_path)? {
+                roots.add(cert).map_err(|e| invalid_file(ca_path, e))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| invalid_file(ca_path, e))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let certs = read_certs(&files.cert_path)?;
+    let pem = std::fs::read(&files.key_path).map_err(|e| invalid_file(&files.key_path, e))?;
+    let key = PrivateKeyDer::from_pem_slice(&pem).map_err(|e| invalid_file(&files.key_path, e))?;
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| invalid_file(&files.key_path, e))?;
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    Ok(config)
+}
+
+/// Read every certificate in the PEM file at `path`.
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let pem = std::fs::read(path).map_err(|e| invalid_file(path, e))?;
+    let certs = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid_file(path, e))?;
+    if certs.is_empty() {
+        return Err(invalid_file(path, "no certificates found"));
+    }
+    Ok(certs)
+}
+
+fn invalid_file(path: &Path, detail: impl std::fmt::Display) -> Error {
+    Error::InvalidKeyFile {
+        path: path.to_path_buf(),
+        detail: detail.to_string(),
+    }
+}
+
+/// A TLS server config that can be swapped while the server runs.
+///
+/// Cloning shares the same config.
+#[derive(Debug, Clone)]
+pub struct ReloadableTls {
+    /// Files the config is built from.
+    files: Arc<TlsConfig>,
+    /// Config handed to newly accepted connections.
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl ReloadableTls {
+    /// Build the initial config from `files`.
+    ///
+    /// # Errors
+    ///
+    /// See [`server_config`].
+    pub fn load(files: TlsConfig) -> Result<ReloadableTls, Error> {
+        let config = server_config(&files)?;
+        Ok(ReloadableTls {
+            files: Arc::new(files),
+            current: Arc::new(RwLock::new(Arc::new(config))),
+        })
+    }
+
+    /// Whether clients must present a certificate.
+    pub fn is_mutual(&self) -> bool {
+        self.files.ca_path.is_some()
+    }
+
+    /// Re-read the files and use the new config for connections accepted
+    /// from now on.
+    ///
+    /// # Errors
+    ///
+    /// See [`server_config`]. The current config stays in use.
+    pub fn reload(&self) -> Result<(), Error> {
+        match server_config(&self.files) {
+            Ok(config) => {
+                *self.current.write().expect("TLS config lock poisoned") = Arc::new(config);
+                counter!("eventfold_tls_reloads_total", "result" => "ok").increment(1);
+                gauge!("eventfold_tls_reload_failed").set(0.0);
+                Ok(())
+            }
+            Err(e) => {
+                counter!("eventfold_tls_reloads_total", "result" => "error").increment(1);
+                gauge!("eventfold_tls_reload_failed").set(1.0);
+                Err(e)
+            }
+        }
+    }
+
+    /// An acceptor using the current config.
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(
+            self.current
+                .read()
+                .expect("TLS config lock poisoned")
+                .clone(),
+        )
+    }
+
+    /// Accept connections on `listener` and complete their TLS handshakes.
+    ///
+    /// Each handshake runs in its own task with the config current at accept
+    /// time, so a slow client cannot hold up others. Failed handshakes are
+    /// logged and counted in `eventfold_tls_handshake_failures_total`; they
+    /// are not yielded. The accept loop stops when the stream is dropped.
+    pub fn incoming(
+        &self,
+        listener: TcpListener,
+    ) -> impl Stream<Item = Result<TlsStream<TcpStream>, std::io::Error>> + Send + 'static {
+        let tls = self.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(ACCEPT_BACKLOG);
+        tokio::spawn(async move {
+            loop {
+                let accepted = tokio::select! {
+                    _ = tx.closed() => return,
+                    accepted = listener.accept() => accepted,
+                };
+                let (tcp, peer) = match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        if tx.send(Err(e)).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                };
+                let _ = tcp.set_nodelay(true);
+                let acceptor = tls.acceptor();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let detail =
+                        match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(tcp)).await {
+                            Ok(Ok(stream)) => {
+                                let _ = tx.send(Ok(stream)).await;
+                                return;
+                            }
+                            Ok(Err(e)) => e.to_string(),
+                            Err(_) => "timed out".to_string(),
+                        };
+                    counter!("eventfold_tls_handshake_failures_total").increment(1);
+                    tracing::debug!(peer = %peer, error = %detail, "TLS handshake failed");
+                });
+            }
+        });
+        tokio_stream::wrappers::ReceiverStream::new(rx)
+    }
+
+    /// Spawn a task that reloads the config when the files change or the
+    /// process receives `SIGHUP`.
+    ///
+    /// The files' modification times are checked every `interval`. Reload
+    /// errors are logged and the current config stays in use; a changed file
+    /// that fails to load is retried on the next check. The task runs until
+    /// aborted.
+    pub fn spawn_reload(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let tls = self.clone();
+        let mut last_modified = self.modified();
+        tokio::spawn(async move {
+            let mut hangup = Hangup::new();
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick.tick().await;
+            loop {
+                let signalled = tokio::select! {
+                    _ = tick.tick() => false,
+                    _ = hangup.recv() => true,
+                };
+                let modified = tls.modified();
+                if !signalled && modified == last_modified {
+                    continue;
+                }
+                let trigger = if signalled { "SIGHUP" } else { "file change" };
+                match tls.reload() {
+                    Ok(()) => {
+                        last_modified = modified;
+                        tracing::info!(trigger, "Reloaded TLS certificates");
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            trigger,
+                            error = %e,
+                            "Failed to reload TLS certificates; keeping current certificates"
+                        );
+                    }
+                }
+            }
+        })
+    }
+
+    /// Modification times of the PEM files; `None` for files that cannot be
+    /// read.
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        self.files
+            .paths()
+            .into_iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+}
+
+/// Notifications of `SIGHUP`. Never fires where the signal does not exist or
+/// its handler cannot be installed.
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    #[cfg(unix)]
+    fn new() -> Hangup {
+        let signal = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .inspect_err(|e| tracing::warn!(error = %e, "Failed to install SIGHUP handler"))
+            .ok();
+        Hangup { signal }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Hangup {
+        Hangup {}
+    }
+
+    /// Wait for the next `SIGHUP`.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+            self.signal = None;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a self-signed certificate for `localhost` and its key to `dir`.
+    fn write_self_signed(dir: &Path) -> TlsConfig {
+        let key = rcgen::KeyPair::generate().expect("key generation should succeed");
+        let cert = rcgen::CertificateParams::new(vec!["localhost".into()])
+            .expect("params should be valid")
+            .self_signed(&key)
+            .expect("self-signing should succeed");
+        let files = TlsConfig {
+            cert_path: dir.join("server.pem"),
+            key_path: dir.join("server.key"),
+            ca_path: None,
+        };
+        std::fs::write(&files.cert_path, cert.pem()).expect("write cert");
+        std::fs::write(&files.key_path, key.serialize_pem()).expect("write key");
+        files
+    }
+
+    #[test]
+    fn server_config_negotiates_h2() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let files = write_self_signed(dir.path());
+        let config = server_config(&files).expect("valid files");
+        assert_eq!(config.alpn_protocols, vec![b"h2".to_vec()]);
+    }
+
+    #[test]
+    fn server_config_rejects_bad_files() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let files = write_self_signed(dir.path());
+
+        std::fs::write(&files.cert_path, "not a certificate").expect("write cert");
+        match server_config(&files) {
+            Err(Error::InvalidKeyFile { path, .. }) => assert_eq!(path, files.cert_path),
+            other => panic!("expected InvalidKeyFile, got: {other:?}"),
+        }
+
+        // A key that does not belong to the certificate.
+        let other = write_self_signed(dir.path());
+        std::fs::write(
+            &other.key_path,
+            rcgen::KeyPair::generate().expect("key").serialize_pem(),
+        )
+        .expect("write key");
+        match server_config(&other) {
+            Err(Error::InvalidKeyFile { path, .. }) => assert_eq!(path, other.key_path),
+            other => panic!("expected InvalidKeyFile, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn failed_reload_keeps_current_config() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let files = write_self_signed(dir.path());
+        let tls = ReloadableTls::load(files.clone()).expect("valid files");
+        let before = tls.current.read().expect("lock").clone();
+
+        std::fs::write(&files.key_path, "garbage").expect("write key");
+        assert!(tls.reload().is_err());
+        assert!(Arc::ptr_eq(
+            &before,
+            &tls.current.read().expect("lock").clone()
+        ));
+
+        write_self_signed(dir.path());
+        tls.reload().expect("reload should succeed");
+        assert!(!Arc::ptr_eq(
+            &before,
+            &tls.current.read().expect("lock").clone()
+        ));
+    }
+
+    #[tokio::test]
+    async fn spawn_reload_picks_up_rewritten_files() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let files = write_self_signed(dir.path());
+        let tls = ReloadableTls::load(files).expect("valid files");
+        let before = tls.current.read().expect("lock").clone();
+
+        let task = tls.spawn_reload(Duration::from_millis(10));
+        // Make sure the rewrite lands on a different modification time.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        write_self_signed(dir.path());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while Arc::ptr_eq(&before, &tls.current.read().expect("lock").clone()) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("config should be reloaded");
+        task.abort();
+    }
+}
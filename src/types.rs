@@ -62,6 +62,7 @@ pub struct ProposedEvent {
 /// * `event_type` - Event type tag.
 /// * `metadata` - Opaque metadata bytes.
 /// * `payload` - Opaque payload bytes.
+/// * `audit` - Who appended the event, when the server records it.
 #[derive(Debug, Clone, PartialEq)]
 pub struct RecordedEvent {
     /// Client-assigned unique ID.
@@ -80,6 +81,59 @@ pub struct RecordedEvent {
     pub metadata: Bytes,
     /// Opaque payload bytes.
     pub payload: Bytes,
+    /// Who appended the event. `None` unless the server was auditing appends.
+    pub audit: Option<AuditInfo>,
+}
+
+/// Positions assigned to a successful append.
+///
+/// Carries what `AppendResponse` returns over gRPC, so callers that only need
+/// to know where a batch landed do not have to read it back.
+///
+/// # Fields
+///
+/// * `first_stream_version` - Stream version of the first appended event.
+/// * `last_stream_version` - Stream version of the last appended event.
+/// * `first_global_position` - Global position of the first appended event.
+/// * `last_global_position` - Global position of the last appended event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppendResult {
+    /// Stream version of the first appended event.
+    pub first_stream_version: u64,
+    /// Stream version of the last appended event.
+    pub last_stream_version: u64,
+    /// Global position of the first appended event.
+    pub first_global_position: u64,
+    /// Global position of the last appended event.
+    pub last_global_position: u64,
+}
+
+impl AppendResult {
+    /// The positions spanned by the events of one append, or `None` if the
+    /// append recorded no events.
+    pub fn from_recorded(recorded: &[RecordedEvent]) -> Option<AppendResult> {
+        let (first, last) = (recorded.first()?, recorded.last()?);
+        Some(AppendResult {
+            first_stream_version: first.stream_version,
+            last_stream_version: last.stream_version,
+            first_global_position: first.global_position,
+            last_global_position: last.global_position,
+        })
+    }
+}
+
+/// The origin of an append, stamped on every event of the batch when the
+/// server runs with auditing enabled.
+///
+/// Both fields are server-assigned and persisted with the event, so they
+/// survive restarts and can answer "who wrote this event" after the fact.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AuditInfo {
+    /// Authenticated principal (the JWT `sub` claim), if the caller
+    /// authenticated.
+    pub principal: Option<String>,
+    /// Address of the client connection, if known.
+    pub client_addr: Option<String>,
 }
 
 /// Controls optimistic concurrency on append.
@@ -143,6 +197,32 @@ pub struct StreamInfo {
     pub latest_version: u64,
 }
 
+/// A range of global positions lost to mid-file corruption.
+///
+/// Recorded by `Store::open_with_options` when repair mode skips a damaged
+/// byte range of the log. The positions in
+/// `first_position..first_position + missing_count` were assigned before the
+/// damage occurred but can no longer be read. Holes are persisted next to the
+/// log so that later opens skip the same range without repair mode.
+///
+/// # Fields
+///
+/// * `first_position` - First global position in the lost range.
+/// * `missing_count` - Number of consecutive global positions lost.
+/// * `start_offset` - Byte offset in the log file where the damage begins.
+/// * `end_offset` - Byte offset of the first valid batch after the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogHole {
+    /// First global position in the lost range.
+    pub first_position: u64,
+    /// Number of consecutive global positions lost.
+    pub missing_count: u64,
+    /// Byte offset in the log file where the damage begins.
+    pub start_offset: u64,
+    /// Byte offset of the first valid batch after the damage.
+    pub end_offset: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +273,7 @@ mod tests {
             event_type: "PaymentReceived".to_string(),
             metadata: Bytes::from_static(b"corr-123"),
             payload: Bytes::from_static(b"{\"amount\":100}"),
+            audit: None,
         };
 
         assert_eq!(event.event_id, event_id);
@@ -215,6 +296,7 @@ mod tests {
             event_type: "Shipped".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         };
 
         let cloned = event.clone();
@@ -234,6 +316,7 @@ mod tests {
             event_type: "Created".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::new(),
+            audit: None,
         };
         let event_b = RecordedEvent {
             global_position: 1,
@@ -243,6 +326,39 @@ mod tests {
         assert_ne!(event_a, event_b);
     }
 
+    #[test]
+    fn append_result_spans_the_first_and_last_event() {
+        let first = RecordedEvent {
+            event_id: Uuid::new_v4(),
+            stream_id: Uuid::new_v4(),
+            stream_version: 3,
+            global_position: 10,
+            recorded_at: 0,
+            event_type: "Created".to_string(),
+            metadata: Bytes::new(),
+            payload: Bytes::new(),
+            audit: None,
+        };
+        let last = RecordedEvent {
+            event_id: Uuid::new_v4(),
+            stream_version: 5,
+            global_position: 12,
+            ..first.clone()
+        };
+
+        let result = AppendResult::from_recorded(&[first, last]).expect("non-empty append");
+        assert_eq!(
+            result,
+            AppendResult {
+                first_stream_version: 3,
+                last_stream_version: 5,
+                first_global_position: 10,
+                last_global_position: 12,
+            }
+        );
+        assert_eq!(AppendResult::from_recorded(&[]), None);
+    }
+
     // AC-3: ExpectedVersion variants, Copy, Debug, and equality.
 
     #[test]
@@ -306,6 +422,7 @@ mod tests {
             event_type: "TestEvent".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         };
         let msg = SubscriptionMessage::Event(std::sync::Arc::new(event));
         let debug_str = format!("{:?}", msg);
@@ -330,6 +447,7 @@ mod tests {
             event_type: "TestEvent".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         };
         let arc = std::sync::Arc::new(event);
         let msg = SubscriptionMessage::Event(arc.clone());
@@ -396,6 +514,7 @@ mod tests {
             event_type: "TimestampTest".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         };
         assert_eq!(event.recorded_at, 1_700_000_000_123);
     }
@@ -411,6 +530,7 @@ mod tests {
             event_type: "CloneTest".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::from_static(b"{}"),
+            audit: None,
         };
         let cloned = event.clone();
         assert_eq!(cloned.recorded_at, 42);
@@ -429,6 +549,7 @@ mod tests {
             event_type: "DiffTest".to_string(),
             metadata: Bytes::new(),
             payload: Bytes::new(),
+            audit: None,
         };
         let event_b = RecordedEvent {
             recorded_at: 200,
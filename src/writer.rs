@@ -6,15 +6,18 @@
 
 use std::collections::HashSet;
 use std::num::NonZeroUsize;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
 
 use metrics::{counter, gauge, histogram};
 use uuid::Uuid;
 
 use crate::broker::Broker;
+use crate::clock::{Clock, Stamper, SystemClock};
 use crate::dedup::DedupIndex;
 use crate::error::Error;
-use crate::types::{ExpectedVersion, ProposedEvent, RecordedEvent};
+use crate::types::{AuditInfo, ExpectedVersion, ProposedEvent, RecordedEvent};
 
 /// A request to append events to a stream, sent to the writer task via the mpsc channel.
 ///
@@ -26,6 +29,9 @@ use crate::types::{ExpectedVersion, ProposedEvent, RecordedEvent};
 /// * `stream_id` - UUID of the target stream.
 /// * `expected_version` - Optimistic concurrency check for the stream.
 /// * `events` - Events the client wants to append.
+/// * `deadline` - Instant after which the request must not be written.
+/// * `state` - Shared with the caller to settle who wins when the deadline
+///   passes while the request is queued.
 /// * `response_tx` - Oneshot channel for sending the result back to the caller.
 pub struct AppendRequest {
     /// UUID of the target stream.
@@ -34,10 +40,82 @@ pub struct AppendRequest {
     pub expected_version: ExpectedVersion,
     /// Events the client wants to append.
     pub events: Vec<ProposedEvent>,
+    /// Instant after which the request must not be written. `None` waits
+    /// indefinitely.
+    pub deadline: Option<tokio::time::Instant>,
+    /// Whether the writer has started on the request or the caller gave up.
+    pub state: RequestState,
+    /// Origin of the append, stamped on every recorded event. `None` when
+    /// the server is not auditing appends.
+    pub audit: Option<AuditInfo>,
     /// Oneshot channel for sending the result back to the caller.
     pub response_tx: tokio::sync::oneshot::Sender<Result<Vec<RecordedEvent>, Error>>,
 }
 
+/// Progress of an [`AppendRequest`], shared between the caller and the writer.
+///
+/// A request starts out pending. Exactly one side moves it on: the writer
+/// claims it when it starts processing it, or the caller cancels it when its
+/// deadline passes or it stops waiting. A cancelled request is never written;
+/// a claimed one is always answered with its real outcome, although the
+/// caller may no longer be listening.
+///
+/// A writer loop fed through [`WriterHandle::new`] must call
+/// [`RequestState::claim`] on each request and skip it if that fails.
+#[derive(Debug, Clone, Default)]
+pub struct RequestState(Arc<AtomicU8>);
+
+impl RequestState {
+    const PENDING: u8 = 0;
+    const CLAIMED: u8 = 1;
+    const CANCELLED: u8 = 2;
+
+    /// Mark the request as being processed. Returns `false` if the caller has
+    /// already cancelled it.
+    pub fn claim(&self) -> bool {
+        self.transition(Self::CLAIMED)
+    }
+
+    /// Withdraw the request. Returns `false` if the writer has already
+    /// claimed it.
+    pub(crate) fn cancel(&self) -> bool {
+        self.transition(Self::CANCELLED)
+    }
+
+    fn transition(&self, to: u8) -> bool {
+        self.0
+            .compare_exchange(Self::PENDING, to, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
+
+/// Cancels a request when the caller stops waiting for it, unless the writer
+/// has already claimed it.
+///
+/// A caller's future can be dropped at any await point, for example when
+/// tonic's own `grpc-timeout` handling or a client disconnect ends the call.
+/// Without this, a request still queued at that point would be written even
+/// though nobody can learn its outcome.
+struct CancelOnDrop(RequestState);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Health of the writer task, published through [`WriterHandle::watch_status`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WriterStatus {
+    /// Appends are being accepted.
+    #[default]
+    Healthy,
+    /// A write or fsync failed. Every later append is refused with
+    /// [`Error::WriterFailed`] until the server is restarted. Carries the
+    /// original error message.
+    Failed(String),
+}
+
 /// Cloneable handle for submitting append requests to the writer task.
 ///
 /// gRPC handlers hold a `WriterHandle` and call `append` to enqueue work.
@@ -50,16 +128,32 @@ pub struct AppendRequest {
 pub struct WriterHandle {
     /// Sender half of the bounded mpsc channel to the writer task.
     tx: tokio::sync::mpsc::Sender<AppendRequest>,
+    /// Health published by the writer task.
+    status: tokio::sync::watch::Receiver<WriterStatus>,
 }
 
 impl WriterHandle {
     /// Create a new `WriterHandle` from the sender half of an mpsc channel.
     ///
+    /// The handle always reports [`WriterStatus::Healthy`]; handles returned
+    /// by [`spawn_writer`] follow the writer task instead.
+    ///
     /// # Arguments
     ///
     /// * `tx` - Sender half of the bounded mpsc channel to the writer task.
     pub fn new(tx: tokio::sync::mpsc::Sender<AppendRequest>) -> Self {
-        Self { tx }
+        let (_, status) = tokio::sync::watch::channel(WriterStatus::Healthy);
+        Self { tx, status }
+    }
+
+    /// Returns the writer's current health.
+    pub fn status(&self) -> WriterStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Returns a receiver that is notified when the writer's health changes.
+    pub fn watch_status(&self) -> tokio::sync::watch::Receiver<WriterStatus> {
+        self.status.clone()
     }
 
     /// Submit an append request to the writer task and await the result.
@@ -67,6 +161,7 @@ impl WriterHandle {
     /// Creates a oneshot channel, packages the request as an `AppendRequest`,
     /// sends it over the mpsc channel, and awaits the response. If the writer
     /// task has shut down (channel closed), returns `Error::InvalidArgument`.
+    /// Waits indefinitely; see [`WriterHandle::append_with_deadline`].
     ///
     /// # Arguments
     ///
@@ -89,27 +184,114 @@ impl WriterHandle {
         expected_version: ExpectedVersion,
         events: Vec<ProposedEvent>,
     ) -> Result<Vec<RecordedEvent>, Error> {
-        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        self.append_with_deadline(stream_id, expected_version, events, None)
+            .await
+    }
+
+    /// Submit an append request that must be written before `deadline`.
+    ///
+    /// If the deadline passes while the request is waiting for channel
+    /// capacity or sitting in the channel, the request is withdrawn and the
+    /// caller gets [`Error::DeadlineExceeded`]; the writer skips withdrawn
+    /// requests, so nothing was committed. If the writer already started on the
+    /// request, the caller gets [`Error::DeadlineExceededInFlight`] at the
+    /// deadline instead, and the write may still commit. Dropping the returned
+    /// future withdraws the request the same way if the writer has not started
+    /// on it yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - UUID of the target stream.
+    /// * `expected_version` - Optimistic concurrency check.
+    /// * `events` - Events to append.
+    /// * `deadline` - Latest instant to wait for the outcome. `None` behaves
+    ///   like [`WriterHandle::append`].
+    ///
+    /// # Returns
+    ///
+    /// The recorded events with server-assigned positions on success.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::DeadlineExceeded`] if the deadline passed before the
+    ///   writer started on the request. No events were written.
+    /// - Returns [`Error::DeadlineExceededInFlight`] if the deadline passed
+    ///   while the writer was working on the request. The events may or may
+    ///   not have been written.
+    /// - Otherwise the same errors as [`WriterHandle::append`].
+    pub async fn append_with_deadline(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+        deadline: Option<tokio::time::Instant>,
+    ) -> Result<Vec<RecordedEvent>, Error> {
+        self.append_audited(stream_id, expected_version, events, deadline, None)
+            .await
+    }
+
+    /// Submit an append request like [`WriterHandle::append_with_deadline`],
+    /// stamping every recorded event with `audit`.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`WriterHandle::append_with_deadline`].
+    pub async fn append_audited(
+        &self,
+        stream_id: Uuid,
+        expected_version: ExpectedVersion,
+        events: Vec<ProposedEvent>,
+        deadline: Option<tokio::time::Instant>,
+        audit: Option<AuditInfo>,
+    ) -> Result<Vec<RecordedEvent>, Error> {
+        let closed = || Error::InvalidArgument("writer task closed".into());
+        let (response_tx, mut response_rx) = tokio::sync::oneshot::channel();
+        let state = RequestState::default();
+        let _cancel_on_drop = CancelOnDrop(state.clone());
 
         let request = AppendRequest {
             stream_id,
             expected_version,
             events,
+            deadline,
+            state: state.clone(),
+            audit,
             response_tx,
         };
 
-        // Send the request to the writer task. If the channel is closed,
-        // the writer task has shut down.
-        self.tx
-            .send(request)
-            .await
-            .map_err(|_| Error::InvalidArgument("writer task closed".into()))?;
+        let Some(deadline) = deadline else {
+            // Send the request to the writer task. If the channel is closed,
+            // the writer task has shut down.
+            self.tx.send(request).await.map_err(|_| closed())?;
 
-        // Await the response from the writer task. If the oneshot is dropped
-        // without sending, the writer task panicked or was cancelled.
-        response_rx
-            .await
-            .map_err(|_| Error::InvalidArgument("writer task closed".into()))?
+            // Await the response from the writer task. If the oneshot is dropped
+            // without sending, the writer task panicked or was cancelled.
+            return response_rx.await.map_err(|_| closed())?;
+        };
+
+        // Waiting for channel capacity counts against the deadline too. A
+        // request that never made it into the channel cannot be written.
+        match tokio::time::timeout_at(deadline, self.tx.send(request)).await {
+            Ok(sent) => sent.map_err(|_| closed())?,
+            Err(_) => {
+                counter!("eventfold_append_deadline_exceeded_total").increment(1);
+                return Err(Error::DeadlineExceeded);
+            }
+        }
+
+        match tokio::time::timeout_at(deadline, &mut response_rx).await {
+            Ok(response) => response.map_err(|_| closed())?,
+            Err(_) if state.cancel() => {
+                counter!("eventfold_append_deadline_exceeded_total").increment(1);
+                Err(Error::DeadlineExceeded)
+            }
+            // The writer is already processing the request and may still
+            // commit it.
+            Err(_) => {
+                counter!("eventfold_append_deadline_exceeded_total").increment(1);
+                Err(Error::DeadlineExceededInFlight)
+            }
+        }
     }
 }
 
@@ -164,20 +346,57 @@ fn validate_batch_unique_ids(events: &[ProposedEvent]) -> Result<(), Error> {
 /// If a response receiver has been dropped before the result is sent, a
 /// `tracing::warn!` is logged and the result is discarded.
 ///
+/// Each request is claimed before it is processed. Requests their caller has
+/// already withdrawn are skipped, and requests whose deadline has passed by
+/// the time they are dequeued are answered with
+/// [`Error::DeadlineExceeded`] without being written.
+///
+/// The first time a write or fsync fails, the store stops accepting appends
+/// and the writer publishes [`WriterStatus::Failed`] on `status`. The loop
+/// keeps running so every later request gets a clear
+/// [`Error::WriterFailed`] instead of hanging.
+///
+/// Every batch is stamped with `clock` in Unix milliseconds, but never
+/// earlier than the batch before it, starting from the latest `recorded_at`
+/// in the recovered log. A clock that stepped backwards is clamped to that
+/// floor by a [`Stamper`], so `recorded_at` never decreases along the global
+/// log.
+///
+/// Under [`Durability::Interval`](crate::store::Durability::Interval) the
+/// loop also wakes on a timer to fsync batches written since the last sync,
+/// so acknowledged appends reach disk even when the writer goes idle. Any
+/// pending writes are fsynced once more when the loop exits.
+///
 /// # Arguments
 ///
 /// * `store` - The storage engine that processes appends.
 /// * `rx` - Receiver half of the bounded mpsc channel carrying append requests.
 /// * `broker` - Broadcast broker for publishing newly appended events to subscribers.
 /// * `dedup` - Bounded LRU dedup index for idempotent append detection.
+/// * `status` - Where the writer publishes its health.
+/// * `clock` - Source of `recorded_at`.
 pub(crate) async fn run_writer(
     mut store: crate::store::Store,
     mut rx: tokio::sync::mpsc::Receiver<AppendRequest>,
     broker: Broker,
     dedup: &mut DedupIndex,
+    status: tokio::sync::watch::Sender<WriterStatus>,
+    clock: Arc<dyn Clock>,
 ) {
+    let mut stamper = {
+        let log = store.log();
+        let log = log.read().expect("EventLog RwLock poisoned");
+        Stamper::new(clock, log.latest_recorded_at())
+    };
+
+    let mut sync_tick = store.durability().sync_interval().map(|period| {
+        let mut tick = tokio::time::interval(period);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        tick
+    });
+
     // Block on the first request; exit when channel is closed.
-    while let Some(first) = rx.recv().await {
+    while let Some(first) = next_request(&mut rx, &mut sync_tick, &mut store, &status).await {
         // Drain any additional pending requests for batching.
         let mut batch = vec![first];
         while let Ok(req) = rx.try_recv() {
@@ -186,14 +405,30 @@ pub(crate) async fn run_writer(
 
         // Stamp once per batch iteration so all requests drained together
         // share the same millisecond timestamp.
-        let recorded_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system clock before Unix epoch")
-            .as_millis() as u64;
+        let recorded_at = stamper.stamp();
 
         // Process each request sequentially. Each call to store.append()
         // writes to disk, fsyncs, and updates the in-memory index.
         for req in batch {
+            // A caller whose deadline passed has withdrawn the request and
+            // already been told nothing was written.
+            if !req.state.claim() {
+                continue;
+            }
+            if req
+                .deadline
+                .is_some_and(|deadline| deadline <= tokio::time::Instant::now())
+            {
+                counter!("eventfold_append_deadline_exceeded_total").increment(1);
+                if req.response_tx.send(Err(Error::DeadlineExceeded)).is_err() {
+                    tracing::warn!(
+                        "writer: response receiver dropped for stream {}",
+                        req.stream_id
+                    );
+                }
+                continue;
+            }
+
             // Step 0: Reject batches with duplicate event IDs within the batch.
             if let Err(e) = validate_batch_unique_ids(&req.events) {
                 if req.response_tx.send(Err(e)).is_err() {
@@ -221,7 +456,13 @@ pub(crate) async fn run_writer(
 
             // Step 2: Not a dedup hit -- perform the actual append.
             let start = Instant::now();
-            let result = store.append(req.stream_id, req.expected_version, recorded_at, req.events);
+            let result = store.append_audited(
+                req.stream_id,
+                req.expected_version,
+                recorded_at,
+                req.events,
+                req.audit,
+            );
 
             // On success: update metrics, record in dedup index, then publish
             // to broker. Failed appends do not update any metric.
@@ -256,6 +497,8 @@ pub(crate) async fn run_writer(
                 broker.publish(recorded);
             }
 
+            report_failure(&store, &status);
+
             // Send the result back to the caller.
             if req.response_tx.send(result).is_err() {
                 tracing::warn!(
@@ -265,7 +508,60 @@ pub(crate) async fn run_writer(
             }
         }
     }
-    // Channel closed -- all WriterHandle senders have been dropped. Exit cleanly.
+    // Channel closed -- all WriterHandle senders have been dropped. Flush
+    // anything a relaxed durability mode left unsynced, then exit cleanly. A
+    // failed store has nothing it can safely flush.
+    if store.failure().is_none() {
+        if let Err(e) = store.sync() {
+            tracing::warn!(error = %e, "writer: final fsync on shutdown failed");
+        }
+    }
+}
+
+/// Publish [`WriterStatus::Failed`] the first time the store reports a failure.
+fn report_failure(store: &crate::store::Store, status: &tokio::sync::watch::Sender<WriterStatus>) {
+    let Some(cause) = store.failure() else {
+        return;
+    };
+    status.send_if_modified(|current| {
+        if *current != WriterStatus::Healthy {
+            return false;
+        }
+        tracing::error!(
+            error = cause,
+            "writer: fatal I/O error -- refusing appends until restart"
+        );
+        gauge!("eventfold_writer_failed").set(1.0);
+        *current = WriterStatus::Failed(cause.to_string());
+        true
+    });
+}
+
+/// Wait for the next append request, running periodic fsyncs while idle.
+///
+/// Without a sync timer this is just `rx.recv()`. With one, each tick that
+/// fires before a request arrives fsyncs the store; a failed fsync fails the
+/// store and is reported on `status`.
+async fn next_request(
+    rx: &mut tokio::sync::mpsc::Receiver<AppendRequest>,
+    sync_tick: &mut Option<tokio::time::Interval>,
+    store: &mut crate::store::Store,
+    status: &tokio::sync::watch::Sender<WriterStatus>,
+) -> Option<AppendRequest> {
+    let Some(tick) = sync_tick.as_mut() else {
+        return rx.recv().await;
+    };
+    loop {
+        tokio::select! {
+            req = rx.recv() => return req,
+            _ = tick.tick(), if store.failure().is_none() => {
+                if let Err(e) = store.sync() {
+                    tracing::warn!(error = %e, "writer: periodic fsync failed");
+                }
+                report_failure(store, status);
+            }
+        }
+    }
 }
 
 /// Spawn the writer task on the tokio runtime.
@@ -275,6 +571,11 @@ pub(crate) async fn run_writer(
 /// the store, broker, and dedup index into the spawned writer task, and returns
 /// a triple of `(WriterHandle, ReadIndex, JoinHandle<()>)`.
 ///
+/// Batches are stamped from [`SystemClock`]. `recorded_at` never decreases
+/// along the log: a reading earlier than the previous batch, or than the
+/// latest event in the recovered log, is clamped to it. Use
+/// [`spawn_writer_with_clock`] to supply another clock.
+///
 /// # Arguments
 ///
 /// * `store` - The storage engine to move into the writer task.
@@ -297,6 +598,45 @@ pub fn spawn_writer(
     WriterHandle,
     crate::reader::ReadIndex,
     tokio::task::JoinHandle<()>,
+) {
+    spawn_writer_with_clock(
+        store,
+        channel_capacity,
+        broker,
+        dedup_capacity,
+        Arc::new(SystemClock),
+    )
+}
+
+/// Spawn the writer task with the clock it stamps `recorded_at` from.
+///
+/// Identical to [`spawn_writer`], which uses [`SystemClock`], except for the
+/// clock. Pass a [`ManualClock`](crate::clock::ManualClock) to make
+/// timestamps deterministic in tests. The clock is the only input the writer
+/// does not take from its callers: it assigns no event IDs, and positions and
+/// versions are sequential.
+///
+/// # Arguments
+///
+/// * `store` - The storage engine to move into the writer task.
+/// * `channel_capacity` - Bound on the mpsc channel. Controls backpressure.
+/// * `broker` - Broadcast broker moved into the writer task for publishing events.
+/// * `dedup_capacity` - Maximum number of event IDs tracked in the dedup index.
+/// * `clock` - Wall clock read once per batch.
+///
+/// # Returns
+///
+/// The same triple as [`spawn_writer`].
+pub fn spawn_writer_with_clock(
+    store: crate::store::Store,
+    channel_capacity: usize,
+    broker: Broker,
+    dedup_capacity: NonZeroUsize,
+    clock: Arc<dyn Clock>,
+) -> (
+    WriterHandle,
+    crate::reader::ReadIndex,
+    tokio::task::JoinHandle<()>,
 ) {
     // Clone the Arc BEFORE moving store into the task.
     let log_arc = store.log();
@@ -310,10 +650,14 @@ pub fn spawn_writer(
     }
 
     let (tx, rx) = tokio::sync::mpsc::channel(channel_capacity);
-    let writer_handle = WriterHandle::new(tx);
+    let (status_tx, status_rx) = tokio::sync::watch::channel(WriterStatus::Healthy);
+    let writer_handle = WriterHandle {
+        tx,
+        status: status_rx,
+    };
 
     let join_handle = tokio::spawn(async move {
-        run_writer(store, rx, broker, &mut dedup).await;
+        run_writer(store, rx, broker, &mut dedup, status_tx, clock).await;
     });
 
     (writer_handle, read_index, join_handle)
@@ -349,6 +693,9 @@ mod tests {
             stream_id,
             expected_version,
             events: events.clone(),
+            deadline: None,
+            state: super::RequestState::default(),
+            audit: None,
             response_tx,
         };
 
@@ -800,6 +1147,9 @@ mod tests {
                 stream_id: uuid::Uuid::new_v4(),
                 expected_version: crate::types::ExpectedVersion::Any,
                 events: vec![proposed("Fill")],
+                deadline: None,
+                state: super::RequestState::default(),
+                audit: None,
                 response_tx,
             })
             .expect("first try_send should succeed (channel empty)");
@@ -810,6 +1160,9 @@ mod tests {
             stream_id: uuid::Uuid::new_v4(),
             expected_version: crate::types::ExpectedVersion::Any,
             events: vec![proposed("Block")],
+            deadline: None,
+            state: super::RequestState::default(),
+            audit: None,
             response_tx: response_tx2,
         });
 
@@ -1262,12 +1615,51 @@ mod tests {
         join_handle.await.expect("writer task should exit cleanly");
     }
 
+    #[tokio::test]
+    async fn recorded_at_comes_from_the_injected_clock() {
+        let (store, _dir) = temp_store();
+        let clock = crate::clock::ManualClock::new(1_700_000_000_000);
+        let (handle, _read_index, join_handle) = super::spawn_writer_with_clock(
+            store,
+            8,
+            crate::broker::Broker::new(64),
+            test_dedup_cap(),
+            std::sync::Arc::new(clock.clone()),
+        );
+
+        let stream_id = uuid::Uuid::new_v4();
+        let first = handle
+            .append(
+                stream_id,
+                crate::types::ExpectedVersion::NoStream,
+                vec![proposed("First")],
+            )
+            .await
+            .expect("append should succeed");
+        clock.advance(std::time::Duration::from_secs(90));
+        let second = handle
+            .append(
+                stream_id,
+                crate::types::ExpectedVersion::Exact(0),
+                vec![proposed("Second")],
+            )
+            .await
+            .expect("append should succeed");
+
+        assert_eq!(first[0].recorded_at, 1_700_000_000_000);
+        assert_eq!(second[0].recorded_at, 1_700_000_090_000);
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+    }
+
     // --- Metrics integration test (PRD 013, Ticket 2 AC 11) ---
 
     /// Parse a Prometheus counter value from rendered text output.
     ///
     /// Scans each line for `<metric_name> <value>` and returns the parsed `u64`.
     /// Returns `None` if the metric is not present or cannot be parsed.
+    #[cfg(feature = "server")]
     fn parse_counter(rendered: &str, metric_name: &str) -> Option<u64> {
         for line in rendered.lines() {
             // Skip comment lines (# TYPE, # HELP).
@@ -1296,6 +1688,7 @@ mod tests {
         None
     }
 
+    #[cfg(feature = "server")]
     #[tokio::test]
     #[serial_test::serial]
     async fn ac11_writer_metrics_appends_and_events_total() {
@@ -1353,4 +1746,275 @@ mod tests {
             "expected 3 new events, got delta {events_delta} (before={events_before}, after={events_after})"
         );
     }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn recorded_at_is_clamped_when_the_clock_goes_backwards() {
+        let _ = crate::metrics::install_recorder();
+        let metrics_handle = crate::metrics::get_installed_handle()
+            .expect("recorder should be installed after install_recorder()");
+        let clamps_before =
+            parse_counter(&metrics_handle.render(), "eventfold_clock_clamps_total").unwrap_or(0);
+
+        // A recovered log whose latest event was recorded at 5_000.
+        let (mut store, _dir) = temp_store();
+        let stream_id = uuid::Uuid::new_v4();
+        store
+            .append(
+                stream_id,
+                crate::types::ExpectedVersion::NoStream,
+                5_000,
+                vec![proposed("Recovered")],
+            )
+            .expect("append should succeed");
+
+        // The clock starts behind the log, catches up, then steps back again.
+        let clock = crate::clock::ManualClock::new(4_000);
+        let (handle, _read_index, join_handle) = super::spawn_writer_with_clock(
+            store,
+            8,
+            crate::broker::Broker::new(64),
+            test_dedup_cap(),
+            std::sync::Arc::new(clock.clone()),
+        );
+
+        let mut stamps = Vec::new();
+        for now in [4_000, 6_000, 5_500] {
+            clock.set(now);
+            let recorded = handle
+                .append(
+                    stream_id,
+                    crate::types::ExpectedVersion::Any,
+                    vec![proposed("Evt")],
+                )
+                .await
+                .expect("append should succeed");
+            stamps.push(recorded[0].recorded_at);
+        }
+        assert_eq!(stamps, [5_000, 6_000, 6_000]);
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+
+        let clamps_after = parse_counter(&metrics_handle.render(), "eventfold_clock_clamps_total")
+            .expect("eventfold_clock_clamps_total should be present in rendered metrics");
+        assert_eq!(clamps_after - clamps_before, 2);
+    }
+
+    #[tokio::test]
+    async fn interval_durability_writer_flushes_and_exits_cleanly() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("events.log");
+        let options = crate::store::StoreOptions {
+            durability: crate::store::Durability::Interval(std::time::Duration::from_millis(5)),
+            ..crate::store::StoreOptions::default()
+        };
+        let store =
+            crate::store::Store::open_with_options(&path, &options).expect("open should succeed");
+        let (handle, _read_index, join_handle) =
+            super::spawn_writer(store, 8, crate::broker::Broker::new(64), test_dedup_cap());
+
+        let stream_id = uuid::Uuid::new_v4();
+        for _ in 0..3 {
+            handle
+                .append(
+                    stream_id,
+                    crate::types::ExpectedVersion::Any,
+                    vec![proposed("Relaxed")],
+                )
+                .await
+                .expect("append should succeed");
+        }
+        // Let a few sync ticks fire while the writer is idle.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+
+        let reopened = crate::store::Store::open(&path).expect("reopen should succeed");
+        assert_eq!(reopened.global_position(), 3);
+    }
+
+    // --- Deadlines ---
+
+    #[tokio::test]
+    async fn expired_deadline_is_skipped_by_writer() {
+        let (store, _dir) = temp_store();
+        let (handle, read_index, join_handle) =
+            super::spawn_writer(store, 8, crate::broker::Broker::new(64), test_dedup_cap());
+
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        handle
+            .tx
+            .send(super::AppendRequest {
+                stream_id: uuid::Uuid::new_v4(),
+                expected_version: crate::types::ExpectedVersion::Any,
+                events: vec![proposed("Late")],
+                deadline: Some(tokio::time::Instant::now()),
+                state: super::RequestState::default(),
+                audit: None,
+                response_tx,
+            })
+            .await
+            .expect("send should succeed");
+
+        let result = response_rx.await.expect("writer should respond");
+        assert!(
+            matches!(result, Err(crate::error::Error::DeadlineExceeded)),
+            "expected DeadlineExceeded, got: {result:?}"
+        );
+        assert_eq!(read_index.global_position(), 0);
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+    }
+
+    #[tokio::test]
+    async fn caller_withdraws_request_still_queued_at_deadline() {
+        // Nobody drains the channel, standing in for a writer stuck in fsync.
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let handle = super::WriterHandle::new(tx);
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(20);
+        let result = handle
+            .append_with_deadline(
+                uuid::Uuid::new_v4(),
+                crate::types::ExpectedVersion::Any,
+                vec![proposed("Queued")],
+                Some(deadline),
+            )
+            .await;
+        assert!(
+            matches!(result, Err(crate::error::Error::DeadlineExceeded)),
+            "expected DeadlineExceeded, got: {result:?}"
+        );
+
+        // The writer must not start on a withdrawn request.
+        let req = rx.recv().await.expect("request should be queued");
+        assert!(!req.state.claim());
+    }
+
+    #[tokio::test]
+    async fn claimed_request_reports_unknown_outcome_at_deadline() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<super::AppendRequest>(8);
+        let handle = super::WriterHandle::new(tx);
+
+        // A writer that claims the request, then takes longer than the
+        // deadline to finish it.
+        let writer = tokio::spawn(async move {
+            let req = rx.recv().await.expect("request should arrive");
+            assert!(req.state.claim());
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let _ = req.response_tx.send(Ok(vec![]));
+        });
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(10);
+        let result = handle
+            .append_with_deadline(
+                uuid::Uuid::new_v4(),
+                crate::types::ExpectedVersion::Any,
+                vec![proposed("InFlight")],
+                Some(deadline),
+            )
+            .await;
+
+        assert!(
+            matches!(result, Err(crate::error::Error::DeadlineExceededInFlight)),
+            "expected DeadlineExceededInFlight, got: {result:?}"
+        );
+        writer.await.expect("writer task should not panic");
+    }
+
+    #[tokio::test]
+    async fn dropped_caller_withdraws_queued_request() {
+        // Nobody drains the channel until the caller has gone away.
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let handle = super::WriterHandle::new(tx);
+
+        let caller = tokio::spawn(async move {
+            handle
+                .append(
+                    uuid::Uuid::new_v4(),
+                    crate::types::ExpectedVersion::Any,
+                    vec![proposed("Abandoned")],
+                )
+                .await
+        });
+        let req = rx.recv().await.expect("request should be queued");
+        caller.abort();
+        let _ = caller.await;
+
+        assert!(!req.state.claim());
+    }
+
+    #[tokio::test]
+    async fn deadline_in_future_appends_normally() {
+        let (store, _dir) = temp_store();
+        let (handle, _read_index, join_handle) =
+            super::spawn_writer(store, 8, crate::broker::Broker::new(64), test_dedup_cap());
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        let recorded = handle
+            .append_with_deadline(
+                uuid::Uuid::new_v4(),
+                crate::types::ExpectedVersion::NoStream,
+                vec![proposed("OnTime")],
+                Some(deadline),
+            )
+            .await
+            .expect("append should succeed");
+        assert_eq!(recorded[0].global_position, 0);
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+    }
+
+    #[tokio::test]
+    async fn fatal_io_error_fails_writer_and_rejects_later_appends() {
+        let store = crate::store::Store::with_backend(Box::new(
+            crate::backend::FailingSyncBackend::default(),
+        ));
+        let (handle, read_index, join_handle) =
+            super::spawn_writer(store, 8, crate::broker::Broker::new(64), test_dedup_cap());
+        let mut status = handle.watch_status();
+        assert_eq!(handle.status(), super::WriterStatus::Healthy);
+
+        let first = handle
+            .append(
+                uuid::Uuid::new_v4(),
+                crate::types::ExpectedVersion::NoStream,
+                vec![proposed("Lost")],
+            )
+            .await;
+        assert!(
+            matches!(first, Err(crate::error::Error::Io(_))),
+            "got: {first:?}"
+        );
+
+        status
+            .wait_for(|s| matches!(s, super::WriterStatus::Failed(_)))
+            .await
+            .expect("writer should publish its failure");
+        match handle.status() {
+            super::WriterStatus::Failed(cause) => assert!(cause.contains("EIO")),
+            other => panic!("expected Failed, got: {other:?}"),
+        }
+
+        let second = handle
+            .append(
+                uuid::Uuid::new_v4(),
+                crate::types::ExpectedVersion::NoStream,
+                vec![proposed("Refused")],
+            )
+            .await;
+        assert!(
+            matches!(second, Err(crate::error::Error::WriterFailed(_))),
+            "got: {second:?}"
+        );
+        assert_eq!(read_index.global_position(), 0);
+
+        drop(handle);
+        join_handle.await.expect("writer task should exit cleanly");
+    }
 }
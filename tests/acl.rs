@@ -0,0 +1,176 @@
+//! Integration tests for claim-based authorization.
+//!
+//! A server with JWT auth and an ACL policy is started in-process. A
+//! `projector` may only read, and the `orders` role may only append to the
+//! streams listed in its token. Everything else must fail with
+//! `PERMISSION_DENIED` without reaching the log.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+
+use eventfold_db::auth::JwtInterceptor;
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{AclPolicy, Broker, EventfoldService, ReadIndex, Store, spawn_writer};
+use tempfile::TempDir;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+
+const SECRET: &str = "acl-secret";
+
+const POLICY: &str = r#"{
+    "rules": [
+        { "roles": ["projector"], "operations": ["ReadStream", "ReadAll", "SubscribeAll", "SubscribeStream"] },
+        { "roles": ["orders"], "operations": ["Append", "ReadStream"], "streams_claim": "streams" }
+    ]
+}"#;
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Mint an HS256 token for `sub` with extra `claims`, valid for an hour.
+fn mint_token(sub: &str, claims: serde_json::Value) -> String {
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before Unix epoch")
+        .as_secs()
+        + 3600;
+    let mut all = serde_json::json!({ "sub": sub, "exp": exp });
+    all.as_object_mut()
+        .expect("object")
+        .extend(claims.as_object().expect("object").clone());
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        &all,
+        &jsonwebtoken::EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .expect("JWT encoding should not fail in tests")
+}
+
+/// Start an authenticated server enforcing [`POLICY`].
+async fn start_acl_server() -> (EventStoreClient<Channel>, ReadIndex, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+
+    let policy = AclPolicy::from_json(POLICY).expect("valid policy");
+    let service = EventfoldService::new(writer_handle, read_index.clone(), broker).with_acl(policy);
+    let svc = InterceptedService::new(EventStoreServer::new(service), JwtInterceptor::new(SECRET));
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr: SocketAddr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let client = EventStoreClient::connect(format!("http://[::1]:{}", addr.port()))
+        .await
+        .expect("client connect should succeed");
+    (client, read_index, dir)
+}
+
+/// Attach `token` as a bearer token to `message`.
+fn authorized<T>(message: T, token: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {token}").parse().expect("valid ASCII"),
+    );
+    request
+}
+
+/// Build a single-event append to `stream_id`.
+fn append_to(stream_id: uuid::Uuid) -> proto::AppendRequest {
+    proto::AppendRequest {
+        stream_id: stream_id.to_string(),
+        expected_version: Some(proto::ExpectedVersion {
+            kind: Some(expected_version::Kind::Any(proto::Empty {})),
+        }),
+        events: vec![proto::ProposedEvent {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            event_type: "OrderPlaced".to_string(),
+            metadata: vec![],
+            payload: b"{}".to_vec(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn projector_can_read_but_not_append() {
+    let (mut client, read_index, _dir) = start_acl_server().await;
+    let token = mint_token("projector-1", serde_json::json!({ "roles": ["projector"] }));
+
+    client
+        .read_all(authorized(
+            proto::ReadAllRequest {
+                from_position: 0,
+                max_count: 10,
+                from_timestamp: None,
+            },
+            &token,
+        ))
+        .await
+        .expect("projector may read all");
+
+    let status = client
+        .append(authorized(append_to(uuid::Uuid::new_v4()), &token))
+        .await
+        .expect_err("projector may not append");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let status = client
+        .list_streams(authorized(proto::ListStreamsRequest {}, &token))
+        .await
+        .expect_err("no rule grants ListStreams");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    assert_eq!(read_index.global_position(), 0);
+}
+
+#[tokio::test]
+async fn service_can_only_append_to_its_own_streams() {
+    let (mut client, read_index, _dir) = start_acl_server().await;
+    let own = uuid::Uuid::new_v4();
+    let token = mint_token(
+        "orders-service",
+        serde_json::json!({ "roles": ["orders"], "streams": [own.to_string()] }),
+    );
+
+    client
+        .append(authorized(append_to(own), &token))
+        .await
+        .expect("append to own stream");
+    let status = client
+        .append(authorized(append_to(uuid::Uuid::new_v4()), &token))
+        .await
+        .expect_err("append to another stream");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let status = client
+        .read_all(authorized(
+            proto::ReadAllRequest {
+                from_position: 0,
+                max_count: 10,
+                from_timestamp: None,
+            },
+            &token,
+        ))
+        .await
+        .expect_err("stream-scoped rules do not grant ReadAll");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    assert_eq!(read_index.global_position(), 1);
+}
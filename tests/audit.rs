@@ -0,0 +1,147 @@
+//! Integration tests for the append audit trail.
+//!
+//! A server with JWT auth and auditing enabled is started in-process. Every
+//! appended event must carry the token's subject and the client's address,
+//! both over gRPC and in the log file, so the information survives recovery.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use eventfold_db::auth::JwtInterceptor;
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{Broker, EventfoldService, Store, spawn_writer};
+use tempfile::TempDir;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+
+const SECRET: &str = "audit-secret";
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Mint an HS256 token for `sub`, valid for an hour.
+fn mint_token(sub: &str) -> String {
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before Unix epoch")
+        .as_secs()
+        + 3600;
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        &serde_json::json!({ "sub": sub, "exp": exp }),
+        &jsonwebtoken::EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .expect("JWT encoding should not fail in tests")
+}
+
+/// Start an authenticated server that audits appends. Returns the client,
+/// the log path, and the tempdir guard.
+async fn start_audited_server() -> (EventStoreClient<Channel>, PathBuf, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let path = dir.path().join("events.log");
+    let store = Store::open(&path).expect("open should succeed");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+
+    let service = EventfoldService::new(writer_handle, read_index, broker).with_audit();
+    let svc = InterceptedService::new(EventStoreServer::new(service), JwtInterceptor::new(SECRET));
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr: SocketAddr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let client = EventStoreClient::connect(format!("http://[::1]:{}", addr.port()))
+        .await
+        .expect("client connect should succeed");
+    (client, path, dir)
+}
+
+#[tokio::test]
+async fn appended_events_record_principal_and_client_address() {
+    let (mut client, path, _dir) = start_audited_server().await;
+    let stream_id = uuid::Uuid::new_v4();
+
+    let mut request = tonic::Request::new(proto::AppendRequest {
+        stream_id: stream_id.to_string(),
+        expected_version: Some(proto::ExpectedVersion {
+            kind: Some(expected_version::Kind::NoStream(proto::Empty {})),
+        }),
+        events: vec![
+            proto::ProposedEvent {
+                event_id: uuid::Uuid::new_v4().to_string(),
+                event_type: "OrderPlaced".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            },
+            proto::ProposedEvent {
+                event_id: uuid::Uuid::new_v4().to_string(),
+                event_type: "OrderPaid".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            },
+        ],
+    });
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", mint_token("billing-service"))
+            .parse()
+            .expect("valid ASCII"),
+    );
+    client.append(request).await.expect("append should succeed");
+
+    let mut request = tonic::Request::new(proto::ReadStreamRequest {
+        stream_id: stream_id.to_string(),
+        from_version: 0,
+        max_count: 10,
+    });
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", mint_token("reader"))
+            .parse()
+            .expect("valid ASCII"),
+    );
+    let events = client
+        .read_stream(request)
+        .await
+        .expect("read should succeed")
+        .into_inner()
+        .events;
+    assert_eq!(events.len(), 2);
+    for event in &events {
+        assert!(event.audited);
+        assert_eq!(event.principal, "billing-service");
+        let client_addr: SocketAddr = event.client_addr.parse().expect("client address");
+        assert!(client_addr.ip().is_loopback(), "got: {client_addr}");
+    }
+
+    // The audit fields are in the log itself, so they survive a restart.
+    let store = Store::open_read_only(&path).expect("read-only open should succeed");
+    let recovered = store.read_all(0, 10);
+    assert_eq!(recovered.len(), 2);
+    for event in &recovered {
+        let audit = event.audit.as_ref().expect("recovered event is audited");
+        assert_eq!(audit.principal.as_deref(), Some("billing-service"));
+        assert_eq!(
+            audit.client_addr.as_deref(),
+            Some(events[0].client_addr.as_str())
+        );
+    }
+}
@@ -4,6 +4,8 @@
 //! `JwtInterceptor` wired, connects a gRPC client, and verifies that
 //! authentication is enforced (or bypassed) correctly.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
@@ -270,7 +272,10 @@ async fn auth_streaming_subscribe_all_rejected_without_token() {
         .expect("client connect should succeed");
 
     // No authorization header on the subscribe_all request.
-    let request = tonic::Request::new(proto::SubscribeAllRequest { from_position: 0 });
+    let request = tonic::Request::new(proto::SubscribeAllRequest {
+        from_position: 0,
+        from_timestamp: None,
+    });
 
     let result = client.subscribe_all(request).await;
 
@@ -326,7 +331,10 @@ async fn auth_valid_token_subscribe_all_stays_open() {
         .await
         .expect("sub client connect should succeed");
 
-    let mut sub_request = tonic::Request::new(proto::SubscribeAllRequest { from_position: 0 });
+    let mut sub_request = tonic::Request::new(proto::SubscribeAllRequest {
+        from_position: 0,
+        from_timestamp: None,
+    });
     sub_request.metadata_mut().insert(
         "authorization",
         format!("Bearer {token}").parse().expect("valid ASCII"),
@@ -0,0 +1,195 @@
+//! Integration tests for the `Backup` RPC.
+//!
+//! Each test spins up a real tonic server with a backup source attached,
+//! streams a backup over gRPC, restores it with `eventfold_db::backup::restore`,
+//! and checks the restored copy against the live server.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+
+use eventfold_db::backup::{self, Snapshot};
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, backup_chunk, expected_version};
+use eventfold_db::{Broker, EventfoldService, Store, spawn_writer};
+use tempfile::TempDir;
+use tonic::transport::Channel;
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Spin up an in-process gRPC server with backups enabled and return a
+/// connected client, the server address, and the temp directory holding the
+/// event log.
+async fn start_test_server() -> (EventStoreClient<Channel>, SocketAddr, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let path = dir.path().join("events.log");
+    let store = Store::open(&path).expect("open should succeed");
+    let backup_source = store
+        .backup_source()
+        .expect("file store has a backup source");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+
+    let service =
+        EventfoldService::new(writer_handle, read_index, broker).with_backup(backup_source);
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+
+    // Give the server a moment to start accepting connections.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let client = EventStoreClient::connect(format!("http://{addr}"))
+        .await
+        .expect("client should connect");
+
+    (client, addr, dir)
+}
+
+/// Append `count` single-event batches to a fresh stream.
+async fn append_batches(client: &mut EventStoreClient<Channel>, count: usize) {
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    for _ in 0..count {
+        client
+            .append(proto::AppendRequest {
+                stream_id: stream_id.clone(),
+                expected_version: Some(proto::ExpectedVersion {
+                    kind: Some(expected_version::Kind::Any(proto::Empty {})),
+                }),
+                events: vec![proto::ProposedEvent {
+                    event_id: uuid::Uuid::new_v4().to_string(),
+                    event_type: "Backed".to_string(),
+                    metadata: vec![],
+                    payload: b"{}".to_vec(),
+                }],
+            })
+            .await
+            .expect("append should succeed");
+    }
+}
+
+/// Call `Backup` and collect the streamed bytes and the trailing summary.
+async fn fetch_backup(
+    client: &mut EventStoreClient<Channel>,
+    to_position: Option<u64>,
+) -> (Vec<u8>, proto::BackupSummary) {
+    let mut stream = client
+        .backup(proto::BackupRequest { to_position })
+        .await
+        .expect("backup should start")
+        .into_inner();
+
+    let mut data = Vec::new();
+    let mut summary = None;
+    while let Some(chunk) = stream.message().await.expect("chunk should arrive") {
+        match chunk.content.expect("chunk has content") {
+            backup_chunk::Content::Data(bytes) => {
+                assert!(summary.is_none(), "data after summary");
+                data.extend_from_slice(&bytes);
+            }
+            backup_chunk::Content::Summary(s) => summary = Some(s),
+        }
+    }
+    (data, summary.expect("summary should be sent last"))
+}
+
+/// Convert a `BackupSummary` into the `Snapshot` that `restore` verifies against.
+fn summary_to_snapshot(summary: &proto::BackupSummary) -> Snapshot {
+    Snapshot {
+        len: summary.size_bytes,
+        next_position: summary.next_position,
+        holes: summary
+            .holes
+            .iter()
+            .map(|h| eventfold_db::LogHole {
+                first_position: h.first_position,
+                missing_count: h.missing_count,
+                start_offset: h.start_offset,
+                end_offset: h.end_offset,
+            })
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn backup_streams_restorable_copy_of_full_log() {
+    let (mut client, _addr, dir) = start_test_server().await;
+    append_batches(&mut client, 5).await;
+
+    let (data, summary) = fetch_backup(&mut client, None).await;
+    assert_eq!(summary.next_position, 5);
+    assert_eq!(data.len() as u64, summary.size_bytes);
+
+    let dest = dir.path().join("restored.log");
+    let restored = backup::restore(&dest, data.as_slice(), &summary_to_snapshot(&summary))
+        .expect("restore should verify");
+
+    let live = client
+        .read_all(proto::ReadAllRequest {
+            from_position: 0,
+            max_count: 100,
+            from_timestamp: None,
+        })
+        .await
+        .expect("read_all should succeed")
+        .into_inner()
+        .events;
+    let copied = restored.read_all(0, 100);
+    assert_eq!(copied.len(), live.len());
+    for (copy, orig) in copied.iter().zip(&live) {
+        assert_eq!(copy.event_id.to_string(), orig.event_id);
+        assert_eq!(copy.global_position, orig.global_position);
+    }
+}
+
+#[tokio::test]
+async fn backup_to_position_stops_at_that_position() {
+    let (mut client, _addr, dir) = start_test_server().await;
+    append_batches(&mut client, 5).await;
+
+    let (data, summary) = fetch_backup(&mut client, Some(3)).await;
+    assert_eq!(summary.next_position, 3);
+
+    let dest = dir.path().join("restored.log");
+    let restored = backup::restore(&dest, data.as_slice(), &summary_to_snapshot(&summary))
+        .expect("restore should verify");
+    assert_eq!(restored.global_position(), 3);
+}
+
+#[tokio::test]
+async fn backup_is_consistent_while_appends_continue() {
+    let (mut client, addr, dir) = start_test_server().await;
+    append_batches(&mut client, 20).await;
+
+    // Keep appending from a second client while the backup streams.
+    let mut writer_client = EventStoreClient::connect(format!("http://{addr}"))
+        .await
+        .expect("client should connect");
+    let appender = tokio::spawn(async move { append_batches(&mut writer_client, 50).await });
+
+    let (data, summary) = fetch_backup(&mut client, None).await;
+    appender.await.expect("appender should finish");
+
+    assert!(summary.next_position >= 20);
+    let dest = dir.path().join("restored.log");
+    let restored = backup::restore(&dest, data.as_slice(), &summary_to_snapshot(&summary))
+        .expect("restore should verify");
+    assert_eq!(restored.global_position(), summary.next_position);
+}
@@ -0,0 +1,383 @@
+//! Integration tests for the offline subcommands of the `eventfold-db` binary.
+//!
+//! Each test builds a data directory through the library, runs the binary on
+//! it, and checks both the output and the files left behind.
+
+#![cfg(feature = "server")]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use bytes::Bytes;
+use eventfold_db::codec;
+use eventfold_db::{DataDir, ExpectedVersion, ProposedEvent, Store, StoreOptions};
+use tempfile::TempDir;
+
+/// Run the binary with `args` and no `EVENTFOLD_*` settings from the test
+/// environment.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_eventfold-db"))
+        .args(args)
+        .env_remove("EVENTFOLD_DATA")
+        .output()
+        .expect("failed to run eventfold-db")
+}
+
+/// Like [`run`], but with `dir` as the working directory.
+fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_eventfold-db"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("EVENTFOLD_DATA")
+        .output()
+        .expect("failed to run eventfold-db")
+}
+
+/// Run the binary and return its standard output, failing on a non-zero exit.
+fn run_ok(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "eventfold-db {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("output is UTF-8")
+}
+
+fn event(event_type: &str) -> ProposedEvent {
+    ProposedEvent {
+        event_id: uuid::Uuid::new_v4(),
+        event_type: event_type.to_string(),
+        metadata: Bytes::new(),
+        payload: Bytes::from_static(b"{\"n\": 1}"),
+    }
+}
+
+/// Create a data directory holding three events in two streams, with the log
+/// preallocated in 1 MiB extents. Returns the tempdir guard and the data path.
+fn seeded_data_dir() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let data = dir.path().join("data");
+    let data_dir = DataDir::open(&data).expect("data dir should open");
+    let options = StoreOptions {
+        preallocate: 1024 * 1024,
+        ..StoreOptions::default()
+    };
+    let mut store =
+        Store::open_with_options(&data_dir.log_path(), &options).expect("open should succeed");
+    let orders = uuid::Uuid::new_v4();
+    store
+        .append(
+            orders,
+            ExpectedVersion::NoStream,
+            1_700_000_000_000,
+            vec![event("OrderPlaced"), event("OrderPaid")],
+        )
+        .expect("append should succeed");
+    store
+        .append(
+            uuid::Uuid::new_v4(),
+            ExpectedVersion::NoStream,
+            1_700_000_000_500,
+            vec![event("CustomerRegistered")],
+        )
+        .expect("append should succeed");
+    (dir, data)
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("tempdir paths are UTF-8")
+}
+
+#[test]
+fn stats_and_verify_report_the_log() {
+    let (_dir, data) = seeded_data_dir();
+
+    let stats = run_ok(&["stats", path_str(&data)]);
+    assert!(stats.contains("events: 3"), "got: {stats}");
+    assert!(stats.contains("streams: 2"), "got: {stats}");
+    assert!(stats.contains("event_type: OrderPlaced 1"), "got: {stats}");
+
+    let verify = run_ok(&["verify", path_str(&data)]);
+    assert!(verify.contains("3 events in 2 streams"), "got: {verify}");
+    assert!(!verify.contains("torn tail"), "got: {verify}");
+}
+
+#[test]
+fn verify_reports_a_torn_tail_without_touching_it() {
+    let (_dir, data) = seeded_data_dir();
+    let log_path = DataDir::open_read_only(&data)
+        .expect("data dir should open")
+        .log_path();
+    let log_len = Store::open_read_only(&log_path)
+        .expect("open should succeed")
+        .log_file_len()
+        .expect("log length");
+    // Overwrite the start of the preallocated space with a partial batch.
+    let mut bytes = std::fs::read(&log_path).expect("read log");
+    bytes[log_len as usize..log_len as usize + 5].copy_from_slice(b"junk!");
+    std::fs::write(&log_path, &bytes).expect("write log");
+
+    let output = run(&["verify", path_str(&data)]);
+    assert!(!output.status.success(), "a torn tail must fail verify");
+    let verify = String::from_utf8(output.stdout).expect("output is UTF-8");
+    assert!(verify.contains("torn tail"), "got: {verify}");
+    assert_eq!(std::fs::read(&log_path).expect("read log"), bytes);
+}
+
+#[test]
+fn verify_reports_mid_file_damage_and_the_data_after_it() {
+    let (_dir, data) = seeded_data_dir();
+    let log_path = DataDir::open_read_only(&data)
+        .expect("data dir should open")
+        .log_path();
+    // Rot the first event of the first batch; the second batch stays valid.
+    let mut bytes = std::fs::read(&log_path).expect("read log");
+    let at = bytes
+        .windows(b"OrderPlaced".len())
+        .position(|w| w == b"OrderPlaced")
+        .expect("event type is in the log");
+    bytes[at] ^= 0xFF;
+    std::fs::write(&log_path, &bytes).expect("write log");
+
+    let output = run(&["verify", path_str(&data)]);
+    assert!(!output.status.success(), "mid-file damage must fail verify");
+    let verify = String::from_utf8(output.stdout).expect("output is UTF-8");
+    assert!(verify.contains("1 events in 1 streams"), "got: {verify}");
+    assert!(verify.contains("mid-file damage"), "got: {verify}");
+    assert!(verify.contains("losing positions 0..2"), "got: {verify}");
+    assert!(!verify.contains("torn tail"), "got: {verify}");
+    assert_eq!(std::fs::read(&log_path).expect("read log"), bytes);
+}
+
+#[test]
+fn verify_survives_a_garbage_record_count() {
+    let (_dir, data) = seeded_data_dir();
+    let log_path = DataDir::open_read_only(&data)
+        .expect("data dir should open")
+        .log_path();
+    let log_len = Store::open_read_only(&log_path)
+        .expect("open should succeed")
+        .log_file_len()
+        .expect("log length") as usize;
+    let mut bytes = std::fs::read(&log_path).expect("read log");
+    bytes[log_len..log_len + 16].copy_from_slice(&codec::encode_batch_header(u32::MAX, 3));
+    std::fs::write(&log_path, &bytes).expect("write log");
+
+    let output = run(&["verify", path_str(&data)]);
+    assert!(!output.status.success(), "a torn tail must fail verify");
+    let verify = String::from_utf8(output.stdout).expect("output is UTF-8");
+    assert!(verify.contains("torn tail"), "got: {verify}");
+}
+
+#[test]
+fn export_then_import_reproduces_the_log() {
+    let (dir, data) = seeded_data_dir();
+    let exported = dir.path().join("events.ndjson");
+    let copy = dir.path().join("copy");
+
+    run_ok(&["export", path_str(&data), "--output", path_str(&exported)]);
+    let imported = run_ok(&["import", path_str(&copy), "--input", path_str(&exported)]);
+    assert!(imported.contains("imported 3 events"), "got: {imported}");
+
+    // Offsets and preallocated space differ between the logs; events do not.
+    let records = |data: &Path| -> Vec<String> {
+        run_ok(&["dump", path_str(data), "--format", "json"])
+            .lines()
+            .filter(|line| line.contains("\"kind\":\"record\""))
+            .map(|line| {
+                let mut record: serde_json::Value = serde_json::from_str(line).expect("JSON line");
+                record["offset"].take();
+                record.to_string()
+            })
+            .collect()
+    };
+    assert_eq!(records(&copy), records(&data));
+}
+
+#[test]
+fn dump_decodes_and_filters_raw_records() {
+    let (_dir, data) = seeded_data_dir();
+
+    let text = run_ok(&["dump", path_str(&data)]);
+    assert!(
+        text.starts_with("header offset=0 version=3\n"),
+        "got: {text}"
+    );
+    assert!(text.contains("type=OrderPaid"), "got: {text}");
+    assert!(text.contains("payload: {\"n\":1}"), "got: {text}");
+    assert!(text.contains("preallocated offset="), "got: {text}");
+
+    let json = run_ok(&[
+        "dump",
+        path_str(&data),
+        "--format",
+        "json",
+        "--payload",
+        "hex",
+        "--type",
+        "CustomerRegistered",
+        "--since",
+        "1700000000100",
+    ]);
+    let records: Vec<serde_json::Value> = json
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("JSON line"))
+        .filter(|line: &serde_json::Value| line["kind"] == "record")
+        .collect();
+    assert_eq!(records.len(), 1, "got: {json}");
+    assert_eq!(records[0]["global_position"], 2);
+    assert_eq!(records[0]["payload_encoding"], "hex");
+    assert_eq!(records[0]["recorded_at_utc"], "2023-11-14T22:13:20.500Z");
+}
+
+#[test]
+fn backup_writes_a_verified_copy() {
+    let (dir, data) = seeded_data_dir();
+    let dest = dir.path().join("backup.log");
+
+    let output = run_ok(&["backup", path_str(&data), path_str(&dest)]);
+    assert!(output.contains("up to position 3"), "got: {output}");
+
+    let store = Store::open_read_only(&dest).expect("backup should open");
+    assert_eq!(store.global_position(), 3);
+}
+
+#[test]
+fn backup_to_a_bare_relative_path_leaves_no_lock() {
+    let (dir, _data) = seeded_data_dir();
+
+    let output = run_in(dir.path(), &["backup", "data", "backup.log"]);
+    assert!(
+        output.status.success(),
+        "backup failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!dir.path().join("backup.log.lock").exists());
+
+    let store = Store::open_read_only(&dir.path().join("backup.log")).expect("backup should open");
+    assert_eq!(store.global_position(), 3);
+}
+
+#[test]
+fn backup_of_a_log_with_holes_to_a_bare_relative_path() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let log_path = dir.path().join("events.log");
+    let mut store = Store::open(&log_path).expect("open should succeed");
+    let mut batch_ends = Vec::new();
+    for recorded_at in [1_700_000_000_000, 1_700_000_000_100, 1_700_000_000_200] {
+        store
+            .append(
+                uuid::Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                recorded_at,
+                vec![event("OrderPlaced")],
+            )
+            .expect("append should succeed");
+        batch_ends.push(store.log_file_len().expect("log length") as usize);
+    }
+    drop(store);
+
+    // Rot a payload byte of the middle batch and repair it into a hole.
+    let mut bytes = std::fs::read(&log_path).expect("read log");
+    bytes[batch_ends[1] - 16] ^= 0xFF;
+    std::fs::write(&log_path, &bytes).expect("write log");
+    let options = StoreOptions {
+        repair: true,
+        ..StoreOptions::default()
+    };
+    let store = Store::open_with_options(&log_path, &options).expect("repair should succeed");
+    assert_eq!(store.holes().len(), 1);
+    drop(store);
+
+    let output = run_in(dir.path(), &["backup", "events.log", "backup.log"]);
+    assert!(
+        output.status.success(),
+        "backup failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let store = Store::open_read_only(&dir.path().join("backup.log")).expect("backup should open");
+    assert_eq!(store.holes().len(), 1);
+    assert_eq!(store.read_all(0, 10).len(), 2);
+}
+
+#[test]
+fn compact_releases_preallocated_space() {
+    let (_dir, data) = seeded_data_dir();
+    let log_path = DataDir::open_read_only(&data)
+        .expect("data dir should open")
+        .log_path();
+    let before = std::fs::metadata(&log_path).expect("log metadata").len();
+
+    run_ok(&["compact", path_str(&data)]);
+
+    let store = Store::open(&log_path).expect("compacted log should open");
+    let after = std::fs::metadata(&log_path).expect("log metadata").len();
+    assert!(after < before, "expected {after} < {before}");
+    assert_eq!(after, store.log_file_len().expect("log length"));
+    assert_eq!(store.global_position(), 3);
+}
+
+#[test]
+fn compact_refuses_a_damaged_tail_and_leaves_the_log_intact() {
+    let (_dir, data) = seeded_data_dir();
+    let log_path = DataDir::open_read_only(&data)
+        .expect("data dir should open")
+        .log_path();
+    let log_len = Store::open_read_only(&log_path)
+        .expect("open should succeed")
+        .log_file_len()
+        .expect("log length") as usize;
+    let mut bytes = std::fs::read(&log_path).expect("read log");
+    bytes[log_len..log_len + 5].copy_from_slice(b"junk!");
+    std::fs::write(&log_path, &bytes).expect("write log");
+
+    let output = run(&["compact", path_str(&data)]);
+    assert!(!output.status.success(), "compact must refuse a torn tail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("eventfold-db verify"), "got: {stderr}");
+    assert_eq!(std::fs::read(&log_path).expect("read log"), bytes);
+}
+
+#[test]
+fn offline_command_on_missing_data_fails() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    for command in ["stats", "compact"] {
+        let missing = dir.path().join("missing");
+        let output = run(&[command, path_str(&missing)]);
+        assert!(!output.status.success(), "{command} should fail");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("error:"), "{command}: {stderr}");
+        assert!(!missing.exists(), "{command} must not create the data dir");
+    }
+}
+
+#[test]
+fn compact_leaves_a_single_file_log_in_place() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let log_path = dir.path().join("events.log");
+    let options = StoreOptions {
+        preallocate: 1024 * 1024,
+        ..StoreOptions::default()
+    };
+    let mut store = Store::open_with_options(&log_path, &options).expect("open should succeed");
+    store
+        .append(
+            uuid::Uuid::new_v4(),
+            ExpectedVersion::NoStream,
+            1_700_000_000_000,
+            vec![event("OrderPlaced")],
+        )
+        .expect("append should succeed");
+    drop(store);
+
+    run_ok(&["compact", path_str(&log_path)]);
+
+    assert!(log_path.is_file(), "compact must not migrate the log");
+    let store = Store::open(&log_path).expect("compacted log should open");
+    assert_eq!(
+        std::fs::metadata(&log_path).expect("log metadata").len(),
+        store.log_file_len().expect("log length")
+    );
+    assert_eq!(store.global_position(), 1);
+}
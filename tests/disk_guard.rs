@@ -0,0 +1,111 @@
+//! Integration tests for the low-disk-space guard.
+//!
+//! A server is started in-process with a [`DiskGuard`] whose watermark is
+//! either impossible to meet or trivially met. Below the watermark appends must
+//! fail with `RESOURCE_EXHAUSTED` without reaching the log, while reads keep
+//! working.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::{NonZeroU64, NonZeroUsize};
+
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{Broker, DiskGuard, EventfoldService, ReadIndex, Store, spawn_writer};
+use tempfile::TempDir;
+use tonic::transport::Channel;
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Start a server whose disk guard rejects appends below `min_free` bytes.
+async fn start_guarded_server(
+    min_free: NonZeroU64,
+) -> (EventStoreClient<Channel>, ReadIndex, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+
+    let guard = DiskGuard::new(dir.path(), min_free).expect("guard should sample free space");
+    let service =
+        EventfoldService::new(writer_handle, read_index.clone(), broker).with_disk_guard(guard);
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr: SocketAddr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let client = EventStoreClient::connect(format!("http://[::1]:{}", addr.port()))
+        .await
+        .expect("client connect should succeed");
+    (client, read_index, dir)
+}
+
+/// Build a single-event append to a new stream.
+fn append_request() -> proto::AppendRequest {
+    proto::AppendRequest {
+        stream_id: uuid::Uuid::new_v4().to_string(),
+        expected_version: Some(proto::ExpectedVersion {
+            kind: Some(expected_version::Kind::NoStream(proto::Empty {})),
+        }),
+        events: vec![proto::ProposedEvent {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            event_type: "Guarded".to_string(),
+            metadata: vec![],
+            payload: b"{}".to_vec(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn append_below_watermark_is_rejected_and_reads_still_work() {
+    // No volume has this much free space.
+    let (mut client, read_index, _dir) = start_guarded_server(NonZeroU64::MAX).await;
+
+    let status = client
+        .append(append_request())
+        .await
+        .expect_err("append should be rejected");
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    assert!(
+        status.message().contains("low watermark"),
+        "got: {}",
+        status.message()
+    );
+    assert_eq!(read_index.global_position(), 0);
+
+    client
+        .read_all(proto::ReadAllRequest {
+            from_position: 0,
+            max_count: 10,
+            from_timestamp: None,
+        })
+        .await
+        .expect("reads are not guarded");
+}
+
+#[tokio::test]
+async fn append_above_watermark_is_admitted() {
+    let (mut client, read_index, _dir) = start_guarded_server(NonZeroU64::MIN).await;
+
+    client
+        .append(append_request())
+        .await
+        .expect("append should succeed");
+    assert_eq!(read_index.global_position(), 1);
+}
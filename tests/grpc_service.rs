@@ -3,13 +3,16 @@
 //! Each test spins up a real tonic server on an ephemeral port using
 //! `start_test_server`, connects a gRPC client, and exercises the RPCs.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
 use eventfold_db::proto::event_store_client::EventStoreClient;
 use eventfold_db::proto::event_store_server::EventStoreServer;
 use eventfold_db::proto::{self, expected_version};
-use eventfold_db::{Broker, EventfoldService, Store, spawn_writer};
+use eventfold_db::writer::WriterHandle;
+use eventfold_db::{Broker, EventfoldService, ReadIndex, Store, spawn_writer};
 use tempfile::TempDir;
 use tonic::transport::Channel;
 
@@ -379,6 +382,7 @@ async fn ac12_read_all_two_streams() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed");
@@ -414,6 +418,7 @@ async fn ac13_read_all_partial() {
         .read_all(proto::ReadAllRequest {
             from_position: 3,
             max_count: 2,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed");
@@ -434,6 +439,7 @@ async fn ac14_read_all_empty_store() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all on empty store should succeed");
@@ -463,7 +469,10 @@ async fn ac15_subscribe_all_catchup_then_live() {
 
     // SubscribeAll from position 0.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -551,7 +560,10 @@ async fn ac16_subscribe_all_from_middle() {
 
     // SubscribeAll from position 3.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 3 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 3,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -877,7 +889,10 @@ async fn ac20_subscribe_all_lag_terminates_stream() {
     // Start a subscription on the empty store. The subscription registers a broadcast
     // receiver, does catch-up (empty), emits CaughtUp, then enters live phase.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -929,8 +944,9 @@ async fn ac20_subscribe_all_lag_terminates_stream() {
                 // Stream ended (server closed it after lag error).
                 break;
             }
-            Ok(Err(_status)) => {
-                // gRPC error status from the server (lag mapped to error).
+            Ok(Err(status)) => {
+                // gRPC error status from the server (lag mapped to ABORTED).
+                assert_eq!(status.code(), tonic::Code::Aborted, "got: {status:?}");
                 break;
             }
             Err(_) => {
@@ -961,13 +977,19 @@ async fn ac21_two_concurrent_subscribe_all() {
 
     // Start two concurrent subscriptions from position 0.
     let mut sub1 = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all 1 should succeed")
         .into_inner();
 
     let mut sub2 = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all 2 should succeed")
         .into_inner();
@@ -1083,6 +1105,7 @@ async fn all_five_rpcs_smoke_test() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed")
@@ -1094,7 +1117,10 @@ async fn all_five_rpcs_smoke_test() {
 
     // 4. SubscribeAll: from position 0, collect until CaughtUp.
     let mut sub_all = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -1189,6 +1215,7 @@ async fn read_all_returns_nonzero_recorded_at() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed");
@@ -1253,7 +1280,10 @@ async fn subscribe_all_yields_nonzero_recorded_at() {
         .expect("append should succeed");
 
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -1541,3 +1571,95 @@ async fn error_codes_end_to_end() {
         "duplicate no_stream should yield FAILED_PRECONDITION"
     );
 }
+
+// -- Deadlines: grpc-timeout against a writer that never answers in time --
+
+/// Spin up a server whose appends go to `tx` instead of a writer task, so a
+/// test can stall or script the writer side.
+async fn start_server_with_writer_channel(
+    tx: tokio::sync::mpsc::Sender<eventfold_db::writer::AppendRequest>,
+) -> EventStoreClient<Channel> {
+    let read_index = ReadIndex::new(Store::in_memory().log());
+    let service = EventfoldService::new(WriterHandle::new(tx), read_index, Broker::new(64));
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    EventStoreClient::connect(format!("http://[::1]:{}", addr.port()))
+        .await
+        .expect("client connect should succeed")
+}
+
+/// An `Append` request carrying a `grpc-timeout` of `timeout`.
+fn append_with_timeout(timeout: std::time::Duration) -> tonic::Request<proto::AppendRequest> {
+    let mut request = tonic::Request::new(proto::AppendRequest {
+        stream_id: uuid::Uuid::new_v4().to_string(),
+        expected_version: no_stream(),
+        events: vec![make_proposed("Timed")],
+    });
+    request.set_timeout(timeout);
+    request
+}
+
+#[tokio::test]
+async fn grpc_timeout_withdraws_append_queued_behind_stalled_writer() {
+    // Nobody drains the channel, standing in for a writer stuck in fsync.
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let mut client = start_server_with_writer_channel(tx).await;
+
+    let status = client
+        .append(append_with_timeout(std::time::Duration::from_millis(200)))
+        .await
+        .expect_err("append should time out");
+    assert_eq!(
+        status.code(),
+        tonic::Code::DeadlineExceeded,
+        "the writer deadline must fire before tonic's own timeout: {status:?}"
+    );
+    assert!(status.message().contains("nothing was committed"));
+
+    // The stalled writer must not start on the withdrawn request.
+    let req = rx.recv().await.expect("request should be queued");
+    assert!(!req.state.claim());
+}
+
+#[tokio::test]
+async fn grpc_timeout_during_a_stalled_write_reports_an_unknown_outcome() {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<eventfold_db::writer::AppendRequest>(8);
+    let mut client = start_server_with_writer_channel(tx).await;
+
+    // A writer that starts on the request, then outlasts the deadline.
+    let writer = tokio::spawn(async move {
+        let req = rx.recv().await.expect("request should arrive");
+        assert!(req.state.claim());
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let _ = req.response_tx.send(Ok(vec![]));
+    });
+
+    let status = client
+        .append(append_with_timeout(std::time::Duration::from_millis(200)))
+        .await
+        .expect_err("append should time out");
+    // The write may still commit, so this must not claim nothing was written.
+    assert_eq!(
+        status.code(),
+        tonic::Code::DeadlineExceeded,
+        "got: {status:?}"
+    );
+    assert!(
+        status.message().contains("outcome is unknown"),
+        "got: {status:?}"
+    );
+    writer.await.expect("writer task should not panic");
+}
@@ -4,6 +4,8 @@
 //! EventStore service on an ephemeral port, then uses the tonic-health generated
 //! client to verify health check responses.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
@@ -4,6 +4,8 @@
 //! real in-process server. Covers duplicate detection, restart survival,
 //! capacity eviction, and broker silence on dedup hits.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::path::Path;
@@ -264,6 +266,7 @@ async fn dedup_no_duplicate_records_in_log() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 1000,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed")
@@ -417,6 +420,7 @@ async fn dedup_survives_restart() {
             .read_all(proto::ReadAllRequest {
                 from_position: 0,
                 max_count: 1000,
+                from_timestamp: None,
             })
             .await
             .expect("read_all should succeed")
@@ -540,7 +544,10 @@ async fn dedup_hit_does_not_publish_to_subscription() {
 
     // Start a SubscribeAll subscription before any appends.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -6,6 +6,8 @@
 //!
 //! All tests use `#[serial]` because the metrics recorder is process-global.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
@@ -370,6 +372,7 @@ async fn metrics_reads_total_labeled_correctly() {
             .read_all(proto::ReadAllRequest {
                 from_position: 0,
                 max_count: 100,
+                from_timestamp: None,
             })
             .await
             .expect("read_all should succeed");
@@ -444,7 +447,10 @@ async fn metrics_subscriptions_active_gauge() {
     // Open a SubscribeAll stream from position 0.
     let mut sub = server
         .client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -0,0 +1,140 @@
+//! Integration tests for per-client append quotas.
+//!
+//! A server with a tight rate limit and JWT auth is started in-process. Clients
+//! with different token subjects must be limited independently, and rejected
+//! appends must come back as `RESOURCE_EXHAUSTED` with a retry hint without
+//! reaching the log.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::{NonZeroU32, NonZeroUsize};
+
+use eventfold_db::auth::JwtInterceptor;
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{Broker, EventfoldService, QuotaConfig, Quotas, ReadIndex, Store, spawn_writer};
+use tempfile::TempDir;
+use tonic::service::interceptor::InterceptedService;
+
+const SECRET: &str = "quota-secret";
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Mint an HS256 token for `sub`, valid for an hour.
+fn mint_token(sub: &str) -> String {
+    #[derive(serde::Serialize)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+    }
+
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before Unix epoch")
+        .as_secs()
+        + 3600;
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        &Claims {
+            sub: sub.to_string(),
+            exp,
+        },
+        &jsonwebtoken::EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .expect("JWT encoding should not fail in tests")
+}
+
+/// Start an authenticated server allowing one append per client per minute.
+async fn start_quota_server() -> (SocketAddr, ReadIndex, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+
+    // One token per client, refilled far slower than the test runs.
+    let quotas = Quotas::new(QuotaConfig {
+        appends_per_sec: NonZeroU32::new(1),
+        burst: NonZeroU32::new(1),
+        max_in_flight: None,
+    });
+    let service =
+        EventfoldService::new(writer_handle, read_index.clone(), broker).with_quotas(quotas);
+    let svc = InterceptedService::new(EventStoreServer::new(service), JwtInterceptor::new(SECRET));
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    (addr, read_index, dir)
+}
+
+/// Append one event as `sub`.
+async fn append_as(addr: SocketAddr, sub: &str) -> Result<(), tonic::Status> {
+    let mut client = EventStoreClient::connect(format!("http://[::1]:{}", addr.port()))
+        .await
+        .expect("client connect should succeed");
+    let mut request = tonic::Request::new(proto::AppendRequest {
+        stream_id: uuid::Uuid::new_v4().to_string(),
+        expected_version: Some(proto::ExpectedVersion {
+            kind: Some(expected_version::Kind::NoStream(proto::Empty {})),
+        }),
+        events: vec![proto::ProposedEvent {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            event_type: "Quoted".to_string(),
+            metadata: vec![],
+            payload: b"{}".to_vec(),
+        }],
+    });
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", mint_token(sub))
+            .parse()
+            .expect("valid ASCII"),
+    );
+    client.append(request).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn noisy_client_is_rejected_without_affecting_others() {
+    let (addr, read_index, _dir) = start_quota_server().await;
+
+    append_as(addr, "noisy")
+        .await
+        .expect("first append should be admitted");
+    let status = append_as(addr, "noisy")
+        .await
+        .expect_err("second append should be rate limited");
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    let pushback: u64 = status
+        .metadata()
+        .get("grpc-retry-pushback-ms")
+        .expect("retry hint should be present")
+        .to_str()
+        .expect("ASCII")
+        .parse()
+        .expect("milliseconds");
+    assert!(pushback > 0 && pushback <= 1000, "pushback: {pushback}");
+
+    append_as(addr, "quiet")
+        .await
+        .expect("another client has its own quota");
+
+    // The rejected append never reached the writer.
+    assert_eq!(read_index.global_position(), 2);
+}
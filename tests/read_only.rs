@@ -0,0 +1,127 @@
+//! Integration tests for serving a store opened read-only.
+//!
+//! A log is written through a normal store, reopened with
+//! `Store::open_read_only`, and served over gRPC. Reads must work, appends
+//! must fail with `FAILED_PRECONDITION`, and the file must not change.
+
+#![cfg(feature = "server")]
+
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{
+    Broker, EventfoldService, ExpectedVersion, ProposedEvent, ReadIndex, Store, spawn_writer,
+};
+use tonic::transport::Channel;
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Write `count` events to a fresh log at `path` and close it.
+fn seed_log(path: &Path, count: usize) {
+    let mut store = Store::open(path).expect("open should succeed");
+    for _ in 0..count {
+        store
+            .append(
+                uuid::Uuid::new_v4(),
+                ExpectedVersion::NoStream,
+                0,
+                vec![ProposedEvent {
+                    event_id: uuid::Uuid::new_v4(),
+                    event_type: "Seeded".to_string(),
+                    metadata: bytes::Bytes::new(),
+                    payload: bytes::Bytes::from_static(b"{}"),
+                }],
+            )
+            .expect("append should succeed");
+    }
+}
+
+/// Serve the log at `path` read-only and return a connected client.
+async fn start_read_only_server(path: &Path) -> EventStoreClient<Channel> {
+    let store = Store::open_read_only(path).expect("read-only open should succeed");
+    let broker = Broker::new(64);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 8, broker.clone(), test_dedup_cap());
+    let service = EventfoldService::new(writer_handle, read_index, broker);
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+
+    // Give the server a moment to start accepting connections.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    EventStoreClient::connect(format!("http://{addr}"))
+        .await
+        .expect("client should connect")
+}
+
+#[tokio::test]
+async fn read_only_server_serves_reads_and_rejects_appends() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let path = dir.path().join("events.log");
+    seed_log(&path, 3);
+    let before = std::fs::read(&path).expect("read log");
+
+    let mut client = start_read_only_server(&path).await;
+
+    let events = client
+        .read_all(proto::ReadAllRequest {
+            from_position: 0,
+            max_count: 100,
+            from_timestamp: None,
+        })
+        .await
+        .expect("read_all should succeed")
+        .into_inner()
+        .events;
+    assert_eq!(events.len(), 3);
+
+    let status = client
+        .append(proto::AppendRequest {
+            stream_id: uuid::Uuid::new_v4().to_string(),
+            expected_version: Some(proto::ExpectedVersion {
+                kind: Some(expected_version::Kind::Any(proto::Empty {})),
+            }),
+            events: vec![proto::ProposedEvent {
+                event_id: uuid::Uuid::new_v4().to_string(),
+                event_type: "Rejected".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            }],
+        })
+        .await
+        .expect_err("append should be rejected");
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+    assert_eq!(std::fs::read(&path).expect("read log"), before);
+}
+
+#[test]
+fn read_index_over_read_only_store_needs_no_writer() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let path = dir.path().join("events.log");
+    seed_log(&path, 2);
+
+    let store = Store::open_read_only(&path).expect("read-only open should succeed");
+    let read_index = ReadIndex::new(store.log());
+
+    assert_eq!(read_index.global_position(), 2);
+    assert_eq!(read_index.list_streams().len(), 2);
+}
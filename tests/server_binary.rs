@@ -5,6 +5,8 @@
 //! production binary (Store, Broker, writer task, EventfoldService, tonic Server)
 //! and exercise it through a gRPC client.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::path::Path;
@@ -13,8 +15,8 @@ use eventfold_db::proto::event_store_client::EventStoreClient;
 use eventfold_db::proto::event_store_server::EventStoreServer;
 use eventfold_db::proto::{self, expected_version};
 use eventfold_db::{
-    Broker, EventfoldService, ExpectedVersion, ProposedEvent, ReadIndex, Store, WriterHandle,
-    spawn_writer,
+    Broker, DataDir, EventfoldService, ExpectedVersion, ProposedEvent, ReadIndex, Store,
+    WriterHandle, spawn_writer,
 };
 
 use tonic::transport::Channel;
@@ -161,6 +163,7 @@ async fn ac1_server_starts_and_accepts_grpc() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed on empty store");
@@ -183,7 +186,10 @@ async fn ac5_small_broker_capacity_causes_lag() {
 
     // Start a SubscribeAll subscription on the empty store.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -281,6 +287,7 @@ async fn ac6_recovery_on_restart() {
             .read_all(proto::ReadAllRequest {
                 from_position: 0,
                 max_count: 100,
+                from_timestamp: None,
             })
             .await
             .expect("read_all should succeed after recovery");
@@ -384,6 +391,7 @@ async fn ac8_end_to_end_round_trip() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all should succeed");
@@ -399,7 +407,10 @@ async fn ac8_end_to_end_round_trip() {
 
     // 6. SubscribeAll from position 0 -- collect until CaughtUp, assert 5 events.
     let mut sub = client
-        .subscribe_all(proto::SubscribeAllRequest { from_position: 0 })
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
         .await
         .expect("subscribe_all should succeed")
         .into_inner();
@@ -462,6 +473,54 @@ fn binary_exits_nonzero_without_eventfold_data() {
     );
 }
 
+// A second server on a log that is already open must refuse to start and name
+// the process holding the lock.
+
+#[test]
+fn binary_exits_nonzero_when_log_is_locked() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let root = dir.path().join("data");
+    let data_dir = DataDir::open(&root).expect("data dir open should succeed");
+    let _held = Store::open(&data_dir.log_path()).expect("open should succeed");
+
+    let mut child = std::process::Command::new("cargo")
+        .args(["run", "--bin", "eventfold-db", "--quiet"])
+        .env("EVENTFOLD_DATA", &root)
+        .env("EVENTFOLD_LISTEN", "[::1]:0")
+        .env("EVENTFOLD_METRICS_LISTEN", "")
+        .env("EVENTFOLD_PREALLOCATE_MB", "0")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to execute cargo run");
+
+    // Fail instead of hanging if the server starts anyway.
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(120);
+    while child.try_wait().expect("try_wait").is_none() {
+        if std::time::Instant::now() > deadline {
+            child.kill().expect("kill");
+            panic!("server started on a locked log");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let output = child.wait_with_output().expect("collect output");
+
+    assert!(!output.status.success(), "expected non-zero exit");
+    let logs = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        logs.contains("locked"),
+        "logs should mention the lock: {logs}"
+    );
+    assert!(
+        logs.contains(&format!("pid {}", std::process::id())),
+        "logs should name the holding pid: {logs}"
+    );
+}
+
 // -- Test AC-7: Graceful shutdown durability at the writer level --
 //
 // Verifies that events appended via WriterHandle are durable on disk after
@@ -0,0 +1,323 @@
+//! Conformance suite shared by every storage backend.
+//!
+//! Each check takes a freshly created, empty `Store` and drives it through the
+//! public API -- writer task, `ReadIndex`, broker subscriptions, and the gRPC
+//! service. The `conformance_suite!` macro instantiates every check once per
+//! backend, so the file and in-memory stores are held to identical behavior.
+
+#![cfg(feature = "server")]
+
+use std::num::NonZeroUsize;
+
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version};
+use eventfold_db::{
+    Broker, Error, EventfoldService, ExpectedVersion, ProposedEvent, Store, SubscriptionMessage,
+    spawn_writer, subscribe_all,
+};
+use futures::StreamExt;
+use tempfile::TempDir;
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// An empty store plus whatever must outlive it (the temp directory of a file
+/// store).
+struct Fixture {
+    store: Store,
+    _dir: Option<TempDir>,
+}
+
+/// Fixture factory for the default file backend.
+fn file_fixture() -> Fixture {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    Fixture {
+        store,
+        _dir: Some(dir),
+    }
+}
+
+/// Fixture factory for the in-memory backend.
+fn memory_fixture() -> Fixture {
+    Fixture {
+        store: Store::in_memory(),
+        _dir: None,
+    }
+}
+
+/// Helper: create a `ProposedEvent` with minimal fields for testing.
+fn proposed(event_type: &str) -> ProposedEvent {
+    ProposedEvent {
+        event_id: uuid::Uuid::new_v4(),
+        event_type: event_type.to_string(),
+        metadata: bytes::Bytes::new(),
+        payload: bytes::Bytes::from_static(b"{}"),
+    }
+}
+
+/// Appends through the writer are visible through the `ReadIndex`, globally
+/// and per stream, with contiguous positions and versions.
+async fn append_then_read(fixture: Fixture) {
+    let (writer, read_index, join) =
+        spawn_writer(fixture.store, 8, Broker::new(64), test_dedup_cap());
+    let stream_a = uuid::Uuid::new_v4();
+    let stream_b = uuid::Uuid::new_v4();
+
+    writer
+        .append(
+            stream_a,
+            ExpectedVersion::NoStream,
+            vec![proposed("A1"), proposed("A2")],
+        )
+        .await
+        .expect("append should succeed");
+    writer
+        .append(stream_b, ExpectedVersion::NoStream, vec![proposed("B1")])
+        .await
+        .expect("append should succeed");
+    writer
+        .append(stream_a, ExpectedVersion::Exact(1), vec![proposed("A3")])
+        .await
+        .expect("append should succeed");
+
+    let all = read_index.read_all(0, 100);
+    let positions: Vec<u64> = all.iter().map(|e| e.global_position).collect();
+    assert_eq!(positions, vec![0, 1, 2, 3]);
+    assert_eq!(read_index.global_position(), 4);
+
+    let a = read_index
+        .read_stream(stream_a, 0, 100)
+        .expect("stream A should exist");
+    let types: Vec<&str> = a.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(types, vec!["A1", "A2", "A3"]);
+    assert_eq!(read_index.stream_version(&stream_b), Some(0));
+    assert_eq!(read_index.list_streams().len(), 2);
+
+    drop(writer);
+    join.await.expect("writer task should exit cleanly");
+}
+
+/// Optimistic concurrency failures are reported and leave the log unchanged.
+async fn wrong_expected_version_is_rejected(fixture: Fixture) {
+    let (writer, read_index, join) =
+        spawn_writer(fixture.store, 8, Broker::new(64), test_dedup_cap());
+    let stream_id = uuid::Uuid::new_v4();
+
+    writer
+        .append(
+            stream_id,
+            ExpectedVersion::NoStream,
+            vec![proposed("First")],
+        )
+        .await
+        .expect("append should succeed");
+    let result = writer
+        .append(
+            stream_id,
+            ExpectedVersion::NoStream,
+            vec![proposed("Second")],
+        )
+        .await;
+
+    assert!(
+        matches!(result, Err(Error::WrongExpectedVersion { .. })),
+        "expected WrongExpectedVersion, got: {result:?}"
+    );
+    assert_eq!(read_index.global_position(), 1);
+
+    drop(writer);
+    join.await.expect("writer task should exit cleanly");
+}
+
+/// Retrying an append with the same event IDs returns the original result
+/// without writing again.
+async fn duplicate_append_is_idempotent(fixture: Fixture) {
+    let (writer, read_index, join) =
+        spawn_writer(fixture.store, 8, Broker::new(64), test_dedup_cap());
+    let stream_id = uuid::Uuid::new_v4();
+    let event = proposed("Once");
+
+    let first = writer
+        .append(stream_id, ExpectedVersion::Any, vec![event.clone()])
+        .await
+        .expect("append should succeed");
+    let retry = writer
+        .append(stream_id, ExpectedVersion::Any, vec![event])
+        .await
+        .expect("retry should succeed");
+
+    assert_eq!(first, retry);
+    assert_eq!(read_index.global_position(), 1);
+
+    drop(writer);
+    join.await.expect("writer task should exit cleanly");
+}
+
+/// Wait for the next subscription message, failing the test on timeout, end of
+/// stream, or error.
+async fn next_message(
+    sub: &mut (impl futures::Stream<Item = Result<SubscriptionMessage, Error>> + Unpin),
+) -> SubscriptionMessage {
+    tokio::time::timeout(std::time::Duration::from_secs(2), sub.next())
+        .await
+        .expect("subscription should yield in time")
+        .expect("subscription should not end")
+        .expect("subscription should not error")
+}
+
+/// Events appended after a subscription catches up are delivered live.
+async fn subscription_sees_catch_up_then_live(fixture: Fixture) {
+    let broker = Broker::new(64);
+    let (writer, read_index, join) =
+        spawn_writer(fixture.store, 8, broker.clone(), test_dedup_cap());
+    let stream_id = uuid::Uuid::new_v4();
+
+    writer
+        .append(stream_id, ExpectedVersion::Any, vec![proposed("Old")])
+        .await
+        .expect("append should succeed");
+
+    let sub = subscribe_all(read_index, &broker, 0).await;
+    tokio::pin!(sub);
+
+    match next_message(&mut sub).await {
+        SubscriptionMessage::Event(e) => assert_eq!(e.event_type, "Old"),
+        other => panic!("expected historical event, got: {other:?}"),
+    }
+    assert!(matches!(
+        next_message(&mut sub).await,
+        SubscriptionMessage::CaughtUp
+    ));
+
+    writer
+        .append(stream_id, ExpectedVersion::Any, vec![proposed("New")])
+        .await
+        .expect("append should succeed");
+    match next_message(&mut sub).await {
+        SubscriptionMessage::Event(e) => {
+            assert_eq!(e.event_type, "New");
+            assert_eq!(e.global_position, 1);
+        }
+        other => panic!("expected live event, got: {other:?}"),
+    }
+
+    drop(writer);
+    join.await.expect("writer task should exit cleanly");
+}
+
+/// The gRPC service serves appends and reads on top of the backend.
+async fn grpc_round_trip(fixture: Fixture) {
+    let broker = Broker::new(64);
+    let (writer, read_index, _join) =
+        spawn_writer(fixture.store, 8, broker.clone(), test_dedup_cap());
+    let service = EventfoldService::new(writer, read_index, broker);
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let mut client = EventStoreClient::connect(format!("http://{addr}"))
+        .await
+        .expect("client should connect");
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let resp = client
+        .append(proto::AppendRequest {
+            stream_id: stream_id.clone(),
+            expected_version: Some(proto::ExpectedVersion {
+                kind: Some(expected_version::Kind::NoStream(proto::Empty {})),
+            }),
+            events: vec![proto::ProposedEvent {
+                event_id: uuid::Uuid::new_v4().to_string(),
+                event_type: "OverGrpc".to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            }],
+        })
+        .await
+        .expect("append should succeed")
+        .into_inner();
+    assert_eq!(resp.first_global_position, 0);
+
+    let events = client
+        .read_stream(proto::ReadStreamRequest {
+            stream_id,
+            from_version: 0,
+            max_count: 10,
+        })
+        .await
+        .expect("read_stream should succeed")
+        .into_inner()
+        .events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "OverGrpc");
+}
+
+/// Instantiate every conformance check for one backend.
+macro_rules! conformance_suite {
+    ($backend:ident, $fixture:path) => {
+        mod $backend {
+            #[tokio::test]
+            async fn append_then_read() {
+                super::append_then_read($fixture()).await;
+            }
+
+            #[tokio::test]
+            async fn wrong_expected_version_is_rejected() {
+                super::wrong_expected_version_is_rejected($fixture()).await;
+            }
+
+            #[tokio::test]
+            async fn duplicate_append_is_idempotent() {
+                super::duplicate_append_is_idempotent($fixture()).await;
+            }
+
+            #[tokio::test]
+            async fn subscription_sees_catch_up_then_live() {
+                super::subscription_sees_catch_up_then_live($fixture()).await;
+            }
+
+            #[tokio::test]
+            async fn grpc_round_trip() {
+                super::grpc_round_trip($fixture()).await;
+            }
+        }
+    };
+}
+
+conformance_suite!(file, super::file_fixture);
+conformance_suite!(memory, super::memory_fixture);
+
+/// Both backends encode the same appends to the same number of bytes.
+#[test]
+fn backends_report_identical_log_sizes() {
+    let mut file = file_fixture();
+    let mut memory = memory_fixture();
+    let stream_id = uuid::Uuid::new_v4();
+    let events = vec![proposed("Sized"), proposed("Sized")];
+
+    for fixture in [&mut file, &mut memory] {
+        fixture
+            .store
+            .append(stream_id, ExpectedVersion::Any, 42, events.clone())
+            .expect("append should succeed");
+    }
+
+    assert_eq!(
+        file.store.log_file_len().expect("file size"),
+        memory.store.log_file_len().expect("memory size")
+    );
+}
@@ -4,6 +4,8 @@
 //! in-process -- no static fixture files needed. Tests verify that TLS and mTLS
 //! servers accept valid clients and reject invalid ones.
 
+#![cfg(feature = "server")]
+
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
@@ -261,6 +263,7 @@ async fn tls_client_append_and_read_all() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all over TLS should succeed");
@@ -288,6 +291,7 @@ async fn tls_plaintext_client_rejected() {
                 .read_all(proto::ReadAllRequest {
                     from_position: 0,
                     max_count: 1,
+                    from_timestamp: None,
                 })
                 .await
                 .expect_err("plaintext RPC to TLS server should fail");
@@ -334,6 +338,7 @@ async fn mtls_client_no_cert_rejected() {
                 .read_all(proto::ReadAllRequest {
                     from_position: 0,
                     max_count: 1,
+                    from_timestamp: None,
                 })
                 .await
                 .expect_err("RPC without client cert to mTLS server should fail");
@@ -387,6 +392,7 @@ async fn mtls_client_valid_cert_accepted() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all over mTLS should succeed");
@@ -443,6 +449,7 @@ async fn plaintext_server_plaintext_client_still_works() {
         .read_all(proto::ReadAllRequest {
             from_position: 0,
             max_count: 100,
+            from_timestamp: None,
         })
         .await
         .expect("read_all over plaintext should succeed");
@@ -490,6 +497,7 @@ async fn tls_wrong_ca_rejected() {
                 .read_all(proto::ReadAllRequest {
                     from_position: 0,
                     max_count: 1,
+                    from_timestamp: None,
                 })
                 .await
                 .expect_err("RPC with wrong CA should fail");
@@ -0,0 +1,203 @@
+//! Integration tests for TLS certificate hot reload.
+//!
+//! A server is started in-process behind [`ReloadableTls`] with a certificate
+//! from one CA. After the files are rewritten with a certificate from a second
+//! CA and reloaded, new clients must trust the second CA while a subscription
+//! opened before the rotation keeps receiving events.
+
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use eventfold_db::proto::event_store_client::EventStoreClient;
+use eventfold_db::proto::event_store_server::EventStoreServer;
+use eventfold_db::proto::{self, expected_version, subscribe_response};
+use eventfold_db::tls::TlsConfig;
+use eventfold_db::{Broker, EventfoldService, ReloadableTls, Store, spawn_writer};
+use tempfile::TempDir;
+use tokio_stream::StreamExt;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+
+/// Default dedup capacity for integration tests.
+fn test_dedup_cap() -> NonZeroUsize {
+    NonZeroUsize::new(128).expect("nonzero")
+}
+
+/// Write a fresh CA and a `localhost` server certificate signed by it to
+/// `files`. Returns the CA certificate PEM.
+fn write_server_cert(files: &TlsConfig) -> Vec<u8> {
+    let ca_key = rcgen::KeyPair::generate().expect("CA key generation should succeed");
+    let mut ca_params = rcgen::CertificateParams::new(vec!["EventfoldDB Test CA".into()])
+        .expect("CA params should be valid");
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca_cert = ca_params
+        .self_signed(&ca_key)
+        .expect("CA self-signing should succeed");
+
+    let server_key = rcgen::KeyPair::generate().expect("server key generation should succeed");
+    let mut server_params = rcgen::CertificateParams::new(vec!["localhost".into()])
+        .expect("server params should be valid");
+    server_params.subject_alt_names = vec![rcgen::SanType::DnsName(
+        "localhost".try_into().expect("localhost is valid DNS"),
+    )];
+    let server_cert = server_params
+        .signed_by(&server_key, &ca_cert, &ca_key)
+        .expect("server cert signing should succeed");
+
+    std::fs::write(&files.cert_path, server_cert.pem()).expect("write cert");
+    std::fs::write(&files.key_path, server_key.serialize_pem()).expect("write key");
+    ca_cert.pem().into_bytes()
+}
+
+/// Start a TLS server whose certificate can be reloaded. Returns the
+/// address, the reload handle, the TLS file paths, the first CA, and the
+/// tempdir guard.
+async fn start_reloadable_server() -> (SocketAddr, ReloadableTls, TlsConfig, Vec<u8>, TempDir) {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let files = TlsConfig {
+        cert_path: dir.path().join("server.pem"),
+        key_path: dir.path().join("server.key"),
+        ca_path: None,
+    };
+    let ca_pem = write_server_cert(&files);
+    let tls = ReloadableTls::load(files.clone()).expect("TLS files should load");
+
+    let store = Store::open(&dir.path().join("events.log")).expect("open should succeed");
+    let broker = Broker::new(1024);
+    let (writer_handle, read_index, _join_handle) =
+        spawn_writer(store, 64, broker.clone(), test_dedup_cap());
+    let service = EventfoldService::new(writer_handle, read_index, broker);
+
+    let listener = tokio::net::TcpListener::bind("[::1]:0")
+        .await
+        .expect("bind should succeed");
+    let addr = listener.local_addr().expect("should have local addr");
+    let incoming = tls.incoming(listener);
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(EventStoreServer::new(service))
+            .serve_with_incoming(incoming)
+            .await
+            .expect("TLS server should run");
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    (addr, tls, files, ca_pem, dir)
+}
+
+/// Connect a client that trusts only `ca_pem`.
+async fn connect(addr: SocketAddr, ca_pem: &[u8]) -> Result<EventStoreClient<Channel>, String> {
+    let tls_config = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(ca_pem))
+        .domain_name("localhost");
+    let channel = Channel::from_shared(format!("https://[::1]:{}", addr.port()))
+        .expect("valid URI")
+        .tls_config(tls_config)
+        .expect("TLS config should be valid")
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut client = EventStoreClient::new(channel);
+    // The handshake only completes on the first call.
+    client
+        .list_streams(proto::ListStreamsRequest {})
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+/// Append one event to a new stream through `client`.
+async fn append_one(client: &mut EventStoreClient<Channel>, event_type: &str) {
+    client
+        .append(proto::AppendRequest {
+            stream_id: uuid::Uuid::new_v4().to_string(),
+            expected_version: Some(proto::ExpectedVersion {
+                kind: Some(expected_version::Kind::NoStream(proto::Empty {})),
+            }),
+            events: vec![proto::ProposedEvent {
+                event_id: uuid::Uuid::new_v4().to_string(),
+                event_type: event_type.to_string(),
+                metadata: vec![],
+                payload: b"{}".to_vec(),
+            }],
+        })
+        .await
+        .expect("append should succeed");
+}
+
+/// Wait for the next event on a subscription, skipping `CaughtUp` markers.
+async fn next_event(
+    stream: &mut tonic::Streaming<proto::SubscribeResponse>,
+) -> proto::RecordedEvent {
+    loop {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .expect("subscription should deliver within 5s")
+            .expect("subscription should stay open")
+            .expect("subscription should not fail");
+        if let Some(subscribe_response::Content::Event(event)) = message.content {
+            return event;
+        }
+    }
+}
+
+#[tokio::test]
+async fn reload_rotates_certificate_without_dropping_subscriptions() {
+    let (addr, tls, files, old_ca, _dir) = start_reloadable_server().await;
+
+    let mut old_client = connect(addr, &old_ca)
+        .await
+        .expect("client trusting the first CA should connect");
+    let mut subscription = old_client
+        .subscribe_all(proto::SubscribeAllRequest {
+            from_position: 0,
+            from_timestamp: None,
+        })
+        .await
+        .expect("subscribe should succeed")
+        .into_inner();
+
+    let new_ca = write_server_cert(&files);
+    tls.reload().expect("reload should succeed");
+
+    // New connections get the new certificate.
+    assert!(
+        connect(addr, &old_ca).await.is_err(),
+        "a client trusting only the old CA should be rejected after rotation"
+    );
+    let mut new_client = connect(addr, &new_ca)
+        .await
+        .expect("client trusting the new CA should connect");
+
+    // The subscription opened before the rotation still delivers.
+    append_one(&mut new_client, "AfterRotation").await;
+    assert_eq!(
+        next_event(&mut subscription).await.event_type,
+        "AfterRotation"
+    );
+}
+
+#[tokio::test]
+async fn failed_reload_keeps_serving_the_current_certificate() {
+    let (addr, tls, files, ca_pem, _dir) = start_reloadable_server().await;
+
+    std::fs::write(&files.key_path, "not a key").expect("write key");
+    let err = tls
+        .reload()
+        .expect_err("reload of a broken key should fail");
+    assert!(
+        err.to_string().contains(path_str(&files.key_path)),
+        "got: {err}"
+    );
+
+    let mut client = connect(addr, &ca_pem)
+        .await
+        .expect("the current certificate should still be served");
+    append_one(&mut client, "StillServing").await;
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("tempdir paths are UTF-8")
+}